@@ -12,7 +12,7 @@ impl<T> Pair<T> {
     /// ペアを作成する．
     /// # Examples
     /// ```
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::new(1, 2);
     /// assert_eq!(1, p.x);
@@ -25,7 +25,7 @@ impl<T> Pair<T> {
     /// ペアの要素を交換したものを返す．
     /// # Examples
     /// ```
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::new(1, 2).swap();
     /// assert_eq!(2, p.x);
@@ -38,7 +38,7 @@ impl<T> Pair<T> {
     /// 指定した型にキャストする．
     /// # Examples
     /// ```
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::new(1, 2).into::<f64>();
     /// assert_eq!(1.0, p.x);
@@ -56,7 +56,7 @@ impl<T> Pair<T> {
     ///
     /// ```
     /// //Successful into
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::<i32>::new(1, 2).try_into::<u8>();
     /// assert_eq!(Ok(Pair::new(1, 2)), p);
@@ -64,7 +64,7 @@ impl<T> Pair<T> {
     ///
     /// ```
     /// //Failure due to overflow
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::<i32>::new(1000, 2).try_into::<u8>();
     /// assert!(p.is_err());
@@ -79,7 +79,7 @@ impl<T> Pair<T> {
     /// このペアの各要素に指定した操作を適用した結果をペアとして返す．
     /// # Examples
     /// ```
-    /// use data_structure::Pair;
+    /// use rustetris::data_type::Pair;
     ///
     /// let p = Pair::new(2, 5);
     /// let mapped = p.map(|e| e * e);
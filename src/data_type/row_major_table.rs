@@ -47,7 +47,7 @@ impl<T> RowMajorTable<T> {
     ///
     /// # Examples
     /// ```
-    /// use data_structure::{Table, RowMajorTable, TableSize, TableIndex};
+    /// use rustetris::data_type::{Table, RowMajorTable, TableSize, TableIndex};
     ///
     /// let v = vec![100, 200, 300, 400];
     /// let size = TableSize::new(2, 2);
@@ -75,7 +75,7 @@ impl<T> RowMajorTable<T> {
     /// 2次元配列の要素に要素数0のVec<T>が存在する場合．
     /// # Examples
     /// ```
-    /// use data_structure::{Table, RowMajorTable, TableSize, TableIndex};
+    /// use rustetris::data_type::{Table, RowMajorTable, TableSize, TableIndex};
     ///
     /// let table = RowMajorTable::from_lines(vec![vec![5, 6], vec![7, 8], vec![9, 10]]);
     /// assert_eq!(TableSize::new(2, 3), table.size());
@@ -112,7 +112,7 @@ impl<T> RowMajorTable<T> {
     /// このテーブルの各行への参照を順に返すイテレータを生成する．
     /// # Examples
     /// ```
-    /// use data_structure::RowMajorTable;
+    /// use rustetris::data_type::RowMajorTable;
     ///
     /// // create 2-row 3-column table
     /// let table = RowMajorTable::from_lines(vec![vec![0, 1, 2], vec![3, 4, 5]]);
@@ -131,7 +131,7 @@ impl<T> RowMajorTable<T> {
     /// このテーブルの各行への可変参照を順に返すイテレータを生成する．
     /// # Examples
     /// ```
-    /// use data_structure::{RowMajorTable, TableIndex};
+    /// use rustetris::data_type::{RowMajorTable, TableIndex};
     ///
     /// // create 2-row 3-column table
     /// let mut table = RowMajorTable::from_lines(vec![vec![0, 1], vec![2, 3]]);
@@ -150,7 +150,7 @@ impl<T> RowMajorTable<T> {
     /// このテーブルの各列への参照を順に返すイテレータを生成する．
     /// # Examples
     /// ```
-    /// use data_structure::RowMajorTable;
+    /// use rustetris::data_type::RowMajorTable;
     ///
     /// // create 2-row 3-column table
     /// let table = RowMajorTable::from_lines(vec![vec![0, 1, 2], vec![3, 4, 5]]);
@@ -227,21 +227,17 @@ impl<T> Index<TableIndex> for RowMajorTable<T> {
     type Output = T;
 
     fn index(&self, index: TableIndex) -> &Self::Output {
-        self.get(index).expect(&format!(
-            "Out of table. Table size: {} index: {}",
-            self.size(),
-            index
-        ))
+        let size = self.size();
+        self.get(index)
+            .unwrap_or_else(|| panic!("Out of table. Table size: {} index: {}", size, index))
     }
 }
 
 impl<T> IndexMut<TableIndex> for RowMajorTable<T> {
     fn index_mut(&mut self, index: TableIndex) -> &mut Self::Output {
         let size = self.size();
-        self.get_mut(index).expect(&format!(
-            "Out of table. Table size: {} index: {}",
-            size, index
-        ))
+        self.get_mut(index)
+            .unwrap_or_else(|| panic!("Out of table. Table size: {} index: {}", size, index))
     }
 }
 
@@ -510,7 +506,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "Table size: (2, 2) index: (2, 0)")]
     fn test_index_out_of_range_x() {
         let table = RowMajorTable::from_lines(vec![vec![0, 1], vec![2, 3]]);
         table[TableIndex::new(2, 0)];
@@ -7,6 +7,8 @@ use num_traits::{One, Signed, Zero};
 ///
 /// # Example
 /// ```
+/// use rustetris::data_type::Shake;
+///
 /// assert_eq!(vec![0, 1, -1, 2, -2], Shake::new().take(5).collect::<Vec<_>>());
 /// ```
 #[derive(Debug)]
@@ -20,6 +22,12 @@ impl<T: Zero> Shake<T> {
     }
 }
 
+impl<T: Zero> Default for Shake<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> Iterator for Shake<T>
 where
     T: Copy + Signed + Zero + One,
@@ -50,7 +58,7 @@ mod tests_shake {
     fn test_iter() {
         assert_eq!(
             vec![0, 1, -1, 2, -2],
-            Shake::new().take(5).collect::<Vec<_>>()
+            Shake::<i32>::new().take(5).collect::<Vec<_>>()
         );
     }
 }
@@ -1,13 +1,63 @@
+pub mod animation;
 mod block_queue;
-mod block_template;
+pub mod block_template;
+mod bomb_policy;
 mod cell;
+mod clear_statistics;
+mod combined_block_selector;
 mod field;
-pub mod animation;
+mod field_history;
 pub mod field_under_agent_control;
+mod framed_field;
+mod garbage;
+mod gravity;
+mod history_block_selector;
+mod level;
+mod library_block_selector;
+mod mini_field;
+mod pentomino_block_selector;
+pub mod puzzle;
+mod random_block_selector;
+mod replay;
+mod round_gallery;
+mod score;
+mod scripted_block_selector;
+mod session;
+pub mod simulate;
 pub mod single_play;
+pub mod sprint;
+mod stats_panel;
+pub mod survival;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod ultra;
+mod weighted_shape_selector;
 
 pub use block_queue::BlockQueue;
 pub use block_template::*;
+pub use bomb_policy::{BombPolicy, BombPolicySelector, TemplateBombSelector};
 pub use cell::Cell;
-pub use field::Field;
+pub use clear_statistics::ClearStatistics;
+pub use combined_block_selector::{ChainSelector, IntervalSelector};
+pub use field::{
+    CellCounts, CellRef, CellRefMut, Field, FieldParseError, FieldParseErrorKind, FieldRow,
+    FieldRowMut, PlaceError,
+};
+pub use field_history::FieldHistory;
 pub use field_under_agent_control::FieldUnderAgentControl;
+pub use framed_field::FramedField;
+pub use garbage::GarbagePattern;
+pub use gravity::Gravity;
+pub use history_block_selector::HistoryBlockSelector;
+pub use level::Level;
+pub use library_block_selector::LibraryBlockSelector;
+pub use mini_field::MiniField;
+pub use pentomino_block_selector::PentominoBlockSelector;
+pub use random_block_selector::RandomBlockSelector;
+pub use replay::{CommandRecorder, Replay};
+pub use round_gallery::{RoundGallery, RoundSummary};
+pub use score::Score;
+pub use scripted_block_selector::{ScriptEnd, ScriptedBlockSelector};
+pub use session::{CountingSelector, Session};
+pub use stats_panel::StatsPanel;
+pub use weighted_shape_selector::WeightedShapeSelector;
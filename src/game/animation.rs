@@ -1,17 +1,29 @@
+mod clear_rows;
 mod connect_bomb;
+mod countdown;
 mod drop_cell;
 mod explosion;
 mod full_row;
+mod level_up;
 mod place_block;
+mod score_popup;
 
-use super::{BlockQueue, Field};
+use super::{BlockQueue, Field, FramedField};
 use crate::geometry::*;
 use crate::graphics::*;
+pub use clear_rows::{ClearRows, ClearRowsInitResult};
+pub(crate) use connect_bomb::{connect_big_bombs, scan_new_big_bombs, NewBigBomb};
 pub use connect_bomb::{ConnectBomb, ConnectBombInitResult};
-pub use drop_cell::DropCell;
-pub use explosion::{ChainCounter, Explosion, ExplosionInitResult};
+pub use countdown::Countdown;
+pub(crate) use drop_cell::drop_floating_cells;
+pub use drop_cell::{DropCell, GravityMode};
+pub(crate) use explosion::ExplosionState;
+pub use explosion::{ChainCounter, Explosion, ExplosionInitResult, ExplosionReport};
+pub(crate) use full_row::scan_filled_rows;
 pub use full_row::FullRow;
+pub use level_up::LevelUp;
 pub use place_block::PlaceBlock;
+pub use score_popup::ScorePopup;
 
 /// アニメーション表示用のフィールドを表す．
 pub struct AnimationField {
@@ -23,14 +35,20 @@ impl AnimationField {
     pub fn new(field: Field, block_queue: BlockQueue) -> AnimationField {
         Self { field, block_queue }
     }
+
+    /// 枠の内側のフィールドの領域を描画範囲とする子キャンバスを返す．
+    /// フィールドに重ねて描画するアニメーションの表示は，フィールドの座標のままこのキャンバスに描画すればよい．
+    pub fn field_canvas<'c, C: Canvas>(&self, canvas: &'c mut C) -> ChildCanvas<'c> {
+        canvas.child(FramedField::new(&self.field).field_roi())
+    }
 }
 
 impl Drawable for AnimationField {
     fn region_size(&self) -> Movement {
         use std::cmp::max;
 
-        // フィールド用
-        let field_region_size = self.field.region_size();
+        // 枠付きのフィールド用
+        let field_region_size = FramedField::new(&self.field).region_size();
         // nextブロック用
         let queue_region_size = self.block_queue.region_size();
         // フィールドの右にnextブロック列とholdブロックを表示するので，
@@ -41,11 +59,11 @@ impl Drawable for AnimationField {
     }
 
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        let p = Pos::origin();
-        // 左上にフィールドを描画
-        self.field.draw_on_child(p, canvas);
-        // フィールドから1マス開けて，右側にNextブロックやHoldブロックを描画していく
-        let p = p + self.field.region_size().x() + right(1);
+        // 左上に枠付きのフィールドを描画
+        let framed_field = FramedField::new(&self.field);
+        framed_field.draw(canvas);
+        // 枠から1マス開けて，右側にNextブロックやHoldブロックを描画していく
+        let p = Pos::origin() + framed_field.region_size().x() + right(1);
         self.block_queue.draw_on_child(p, canvas);
     }
 }
@@ -75,7 +93,8 @@ impl AnimationFrame {
         self.end
     }
 
-    /// 次のアニメーション遷移タイミングまで処理を中断する．
+    /// 次のフレームへ進める．
+    /// フレーム間の待機は`Drawer::wait_frame()`が行う．
     /// # Returns
     /// アニメーションが終了する場合は`None`を返す．
     /// アニメーションがまだ終了しない場合は，次のフレーム`frame`を`Some(frame)`として返す．
@@ -83,7 +102,6 @@ impl AnimationFrame {
         if self.current + 1 >= self.end {
             None
         } else {
-            std::thread::sleep(std::time::Duration::from_millis(50));
             let next = Self {
                 current: self.current + 1,
                 end: self.end,
@@ -106,6 +124,22 @@ pub trait Drawer {
 
     /// 現在の描画内容を反映する．
     fn show(&mut self);
+
+    /// アニメーションの次のフレームを表示するまで待機する．
+    /// 既定では50ミリ秒待機する．
+    /// テストなどで待機したくない場合は，何もしないように実装すればよい．
+    fn wait_frame(&mut self) {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    /// アニメーションのフレーム間の待機時間を変更する．
+    /// 既定では何もしない．`wait_frame`の待機時間を変えられる描画機能は，これに従えばよい．
+    fn set_frame_duration(&mut self, _duration: std::time::Duration) {}
+
+    /// 描画に利用する設定を返す．
+    fn render_config(&self) -> RenderConfig {
+        RenderConfig::default()
+    }
 }
 
 pub enum AnimationResult<P, F> {
@@ -118,20 +152,22 @@ pub trait Animation: Sized {
     /// アニメーション終了時に出力される型．
     type Finished;
 
-    /// 次のアニメーション遷移タイミングまで待機する．
+    /// アニメーションを1フレーム進める．
     /// # Returns
     /// アニメーションが終了する場合は`AnimationResult::Finished(...)`を返す．
     /// アニメーションがまだ終了しない場合は，次のアニメーションを表す`AnimationResult::InProgress(...)`を返す．
     fn wait_next(self) -> AnimationResult<Self, Self::Finished>;
 
-    /// 現在のアニメーションを描画する．
-    fn draw<C: Canvas>(&self, canvas: &mut C);
+    /// 現在のアニメーションを，指定した描画設定に従って描画する．
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig);
 
     /// 指定したアニメーション表示機能に対して，このアニメーションが終了するまで繰り返しコンテンツを表示する．
     fn execute<D: Drawer>(mut self, drawer: &mut D) -> Self::Finished {
+        let config = drawer.render_config();
+
         // 最初の状態を描画
         drawer.clear();
-        self.draw(drawer.canvas_mut());
+        self.draw(drawer.canvas_mut(), &config);
         drawer.show();
 
         loop {
@@ -139,8 +175,9 @@ pub trait Animation: Sized {
                 AnimationResult::InProgress(next) => self = next,
                 AnimationResult::Finished(f) => break f,
             }
+            drawer.wait_frame();
             drawer.clear();
-            self.draw(drawer.canvas_mut());
+            self.draw(drawer.canvas_mut(), &config);
             drawer.show();
         }
     }
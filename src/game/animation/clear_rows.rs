@@ -0,0 +1,146 @@
+use super::*;
+use crate::graphics::Canvas;
+
+pub enum ClearRowsInitResult {
+    Clears(ClearRows),
+    Stay(AnimationField),
+}
+
+/// 揃ったラインを爆発させずに消すアニメーション．
+/// ラインを空セルにしてしばらく表示した後，その上のセルを消したライン数だけ下へずらす．
+pub struct ClearRows {
+    field: AnimationField,
+    /// 消すラインのy座標．
+    cleared_row_ys: Vec<PosY>,
+    frame: AnimationFrame,
+}
+
+impl ClearRows {
+    /// フィールドの揃ったラインをすべて消すアニメーションを返す．
+    /// 揃ったラインがない場合は，フィールドをそのまま返す．
+    pub fn try_init(field: AnimationField) -> ClearRowsInitResult {
+        let mut field = field;
+        let cleared_row_ys = field.field.clear_filled_rows();
+        if cleared_row_ys.is_empty() {
+            return ClearRowsInitResult::Stay(field);
+        }
+
+        ClearRowsInitResult::Clears(Self {
+            field,
+            cleared_row_ys,
+            frame: AnimationFrame::with_frame_count(10),
+        })
+    }
+}
+
+impl Animation for ClearRows {
+    type Finished = AnimationField;
+
+    fn wait_next(self) -> AnimationResult<Self, Self::Finished> {
+        match self.frame.wait_next() {
+            Some(next_frame) => AnimationResult::InProgress(Self {
+                frame: next_frame,
+                ..self
+            }),
+            None => {
+                let mut field = self.field;
+                field.field.remove_rows(&self.cleared_row_ys);
+                AnimationResult::Finished(field)
+            }
+        }
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig) {
+        self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
+
+        // 演出を抑える場合は点滅させない
+        if !config.reduced_motion && self.frame.current_frame() % 2 == 1 {
+            return;
+        }
+        let cleared_cell = {
+            let color = canvas.theme().cell_color(ThemeColor::Text);
+            CanvasCell::new(SquareChar::new('-', '-'), color)
+        };
+        for &y in self.cleared_row_ys.iter() {
+            for x in 0..self.field.field.width() {
+                canvas.draw_cell(Pos(PosX::right(x as i8), y), cleared_cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::t_block_selector;
+    use crate::game::{BlockQueue, Cell};
+
+    /// 最下段が揃い，その上に通常のセルとボムセルがひとつずつあるフィールドを返す．
+    fn bottom_row_filled() -> Field {
+        let mut field = Field::empty();
+        let bottom = PosY::below(field.height() as i8 - 1);
+        for (_, cell) in field
+            .iter_pos_and_cell_mut()
+            .filter(|(p, _)| p.y() == bottom)
+        {
+            *cell = Cell::Normal;
+        }
+        *field
+            .get_mut(Pos(PosX::right(2), bottom + above(1)))
+            .unwrap() = Cell::Normal;
+        *field
+            .get_mut(Pos(PosX::right(5), bottom + above(2)))
+            .unwrap() = Cell::Bomb;
+        field
+    }
+
+    #[test]
+    fn test_clear_bottom_row() {
+        let field = bottom_row_filled();
+        let bottom = PosY::below(field.height() as i8 - 1);
+        let field = AnimationField::new(field, BlockQueue::new(&mut t_block_selector()));
+
+        let mut clear_rows = match ClearRows::try_init(field) {
+            ClearRowsInitResult::Clears(clear_rows) => clear_rows,
+            ClearRowsInitResult::Stay(_) => panic!("bottom row should be cleared"),
+        };
+        // アニメーション中は揃ったラインが空になっている
+        assert!(clear_rows
+            .field
+            .field
+            .row(bottom)
+            .unwrap()
+            .iter()
+            .all(|c| c.is_empty()));
+
+        let field = loop {
+            match clear_rows.wait_next() {
+                AnimationResult::InProgress(next) => clear_rows = next,
+                AnimationResult::Finished(field) => break field,
+            }
+        };
+        // 揃ったラインが消え，その上のセルが1ライン下へずれる
+        let expected = Field::from_str_art(
+            "
+            .....*....
+            ..#.......
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, field.field);
+    }
+
+    #[test]
+    fn test_no_rows_to_clear() {
+        let mut field = bottom_row_filled();
+        let bottom = PosY::below(field.height() as i8 - 1);
+        *field.get_mut(Pos(PosX::right(4), bottom)).unwrap() = Cell::Empty;
+        let animation_field =
+            AnimationField::new(field.clone(), BlockQueue::new(&mut t_block_selector()));
+        match ClearRows::try_init(animation_field) {
+            ClearRowsInitResult::Stay(animation_field) => assert_eq!(field, animation_field.field),
+            ClearRowsInitResult::Clears(_) => panic!("no rows should be cleared"),
+        }
+    }
+}
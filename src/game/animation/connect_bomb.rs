@@ -1,6 +1,7 @@
 use super::*;
 use crate::game::Cell;
 use crate::graphics::Canvas;
+use std::collections::BTreeSet;
 
 pub enum ConnectBombInitResult {
     Connects(ConnectBomb),
@@ -10,26 +11,14 @@ pub enum ConnectBombInitResult {
 pub struct ConnectBomb {
     field: AnimationField,
     frame: ConnectionAnimationFrame,
-    new_big_bomb_upper_left_positions: Vec<Pos>,
+    new_big_bombs: Vec<NewBigBomb>,
 }
 
 impl ConnectBomb {
     pub fn new(field: AnimationField) -> ConnectBombInitResult {
-        let new_big_bomb_upper_left_positions = (0..field.field.height())
-            .flat_map(|y| {
-                (0..field.field.width())
-                    .map(move |x| Pos(PosX::right(x as i8), PosY::below(y as i8)))
-            })
-            .map(|upper_left| big_bomb_positions(upper_left))
-            .filter(|positions| {
-                positions
-                    .iter()
-                    .all(|&p| matches!(field.field.get(p), Some(&Cell::Bomb)))
-            })
-            .map(|positions| positions[0])
-            .collect::<Vec<_>>();
-
-        if new_big_bomb_upper_left_positions.is_empty() {
+        let new_big_bombs = scan_new_big_bombs(&field.field);
+
+        if new_big_bombs.is_empty() {
             ConnectBombInitResult::Stay(field)
         } else {
             let frame = ConnectionAnimationFrame::new();
@@ -37,11 +26,27 @@ impl ConnectBomb {
             let state = Self {
                 field,
                 frame,
-                new_big_bomb_upper_left_positions,
+                new_big_bombs,
             };
             ConnectBombInitResult::Connects(state)
         }
     }
+
+    /// 新しくできるデカボムの数を返す．
+    pub fn new_big_bomb_count(&self) -> usize {
+        self.new_big_bombs
+            .iter()
+            .filter(|bomb| matches!(bomb, NewBigBomb::Big(_)))
+            .count()
+    }
+
+    /// 新しくできる特大ボムの数を返す．
+    pub fn new_huge_bomb_count(&self) -> usize {
+        self.new_big_bombs
+            .iter()
+            .filter(|bomb| matches!(bomb, NewBigBomb::Huge(_)))
+            .count()
+    }
 }
 
 impl Animation for ConnectBomb {
@@ -52,15 +57,7 @@ impl Animation for ConnectBomb {
             Some(frame) => {
                 // アニメーションの遷移が「ボム連結中」になった段階で，フィールドを初めて書き換える
                 if let ConnectionAnimationFrame::Connecting(_) = frame {
-                    for &upper_left in self.new_big_bomb_upper_left_positions.iter() {
-                        let upper_right = upper_left + right(1);
-                        let lower_left = upper_left + below(1);
-                        let lower_right = upper_left + right(1) + below(1);
-                        *self.field.field.get_mut(upper_left).unwrap() = Cell::BigBombUpperLeft;
-                        *self.field.field.get_mut(upper_right).unwrap() = Cell::BigBombUpperRight;
-                        *self.field.field.get_mut(lower_left).unwrap() = Cell::BigBombLowerLeft;
-                        *self.field.field.get_mut(lower_right).unwrap() = Cell::BigBombLowerRight;
-                    }
+                    connect_big_bombs(&mut self.field.field, &self.new_big_bombs);
                 }
                 AnimationResult::InProgress(Self { frame, ..self })
             }
@@ -68,14 +65,15 @@ impl Animation for ConnectBomb {
         }
     }
 
-    fn draw<C: Canvas>(&self, canvas: &mut C) {
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig) {
         self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
 
         if let ConnectionAnimationFrame::Connecting(frame) = &self.frame {
             let canvas_cell = {
-                use Color::*;
-                let color = CanvasCellColor::new(Red, Black);
-                let c = if frame.current_frame() % 2 == 0 {
+                let color = canvas.theme().cell_color(ThemeColor::Bomb);
+                // 演出を抑える場合は文字を切り替えない
+                let c = if config.reduced_motion || frame.current_frame() % 2 == 0 {
                     'x'
                 } else {
                     '+'
@@ -83,8 +81,8 @@ impl Animation for ConnectBomb {
                 CanvasCell::new(SquareChar::new(c, c), color)
             };
 
-            for &big_bomb_upper_left in self.new_big_bomb_upper_left_positions.iter() {
-                for &pos in big_bomb_positions(big_bomb_upper_left).iter() {
+            for big_bomb in self.new_big_bombs.iter() {
+                for pos in big_bomb.positions() {
                     canvas.draw_cell(pos, canvas_cell);
                 }
             }
@@ -123,9 +121,184 @@ impl ConnectionAnimationFrame {
     }
 }
 
+/// 新しくできる，ボムが連結したボムを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NewBigBomb {
+    /// 2x2に並んだボムからできるデカボム．左上の位置を持つ．
+    Big(Pos),
+    /// 3x3に並んだボムからできる特大ボム．中央の位置を持つ．
+    Huge(Pos),
+}
+
+impl NewBigBomb {
+    /// 連結されるボムの位置を行優先順に返す．
+    fn positions(&self) -> Vec<Pos> {
+        match *self {
+            NewBigBomb::Big(upper_left) => big_bomb_positions(upper_left).to_vec(),
+            NewBigBomb::Huge(center) => huge_bomb_positions(center).to_vec(),
+        }
+    }
+}
+
+/// 新しくできるデカボムと特大ボムを返す．
+///
+/// 3x3に並んだボムを2x2より優先して連結する．
+/// 3x3の候補どうしが重なる場合は，行優先順で先にあるものを連結する．
+/// 2x2の候補は，特大ボムにするボムを含まないものだけを行優先順に返す．
+pub(crate) fn scan_new_big_bombs(field: &Field) -> Vec<NewBigBomb> {
+    let all_positions = || field.iter_pos_and_cell().map(|(p, _)| p);
+    let is_bomb = |p: Pos| matches!(field.get(p), Some(&Cell::Bomb));
+
+    // 特大ボムにするボムの位置
+    let mut connected = BTreeSet::new();
+    let mut new_big_bombs = vec![];
+    for center in all_positions() {
+        let positions = huge_bomb_positions(center);
+        if positions
+            .iter()
+            .all(|&p| is_bomb(p) && !connected.contains(&p))
+        {
+            connected.extend(positions.iter().copied());
+            new_big_bombs.push(NewBigBomb::Huge(center));
+        }
+    }
+
+    let big_bombs = all_positions()
+        .map(big_bomb_positions)
+        .filter(|positions| {
+            positions
+                .iter()
+                .all(|&p| is_bomb(p) && !connected.contains(&p))
+        })
+        .map(|positions| NewBigBomb::Big(positions[0]));
+    new_big_bombs.extend(big_bombs);
+    new_big_bombs
+}
+
+/// 指定したボムを，それぞれデカボムか特大ボムにする．
+pub(crate) fn connect_big_bombs(field: &mut Field, new_big_bombs: &[NewBigBomb]) {
+    for &big_bomb in new_big_bombs.iter() {
+        match big_bomb {
+            NewBigBomb::Big(upper_left) => {
+                let [upper_left, upper_right, lower_left, lower_right] =
+                    big_bomb_positions(upper_left);
+                *field.get_mut(upper_left).unwrap() = Cell::BigBombUpperLeft;
+                *field.get_mut(upper_right).unwrap() = Cell::BigBombUpperRight;
+                *field.get_mut(lower_left).unwrap() = Cell::BigBombLowerLeft;
+                *field.get_mut(lower_right).unwrap() = Cell::BigBombLowerRight;
+            }
+            NewBigBomb::Huge(center) => {
+                for &pos in huge_bomb_positions(center).iter() {
+                    *field.get_mut(pos).unwrap() = if pos == center {
+                        Cell::HugeBombCenter
+                    } else {
+                        Cell::HugeBombPart
+                    };
+                }
+            }
+        }
+    }
+}
+
 fn big_bomb_positions(upper_left: Pos) -> [Pos; 4] {
     let upper_right = upper_left + right(1);
     let lower_left = upper_left + below(1);
     let lower_right = upper_left + right(1) + below(1);
     [upper_left, upper_right, lower_left, lower_right]
 }
+
+/// 指定した位置を中央とする3x3の位置を，行優先順に返す．
+fn huge_bomb_positions(center: Pos) -> [Pos; 9] {
+    let upper_left = center + left(1) + above(1);
+    let mut positions = [upper_left; 9];
+    for (i, pos) in positions.iter_mut().enumerate() {
+        *pos = upper_left + right((i % 3) as i8) + below((i / 3) as i8);
+    }
+    positions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: i8, y: i8) -> Pos {
+        Pos(PosX::right(x), PosY::below(y))
+    }
+
+    /// 指定したフィールドのボムを連結した結果を返す．
+    fn connect(art: &str) -> (Vec<NewBigBomb>, Field) {
+        let mut field = Field::from_str_art(art).unwrap();
+        let new_big_bombs = scan_new_big_bombs(&field);
+        connect_big_bombs(&mut field, &new_big_bombs);
+        (new_big_bombs, field)
+    }
+
+    #[test]
+    fn test_huge_bomb() {
+        let (new_big_bombs, field) = connect(
+            "
+            .***......
+            .***......
+            .***......
+            ",
+        );
+        assert_eq!(vec![NewBigBomb::Huge(pos(2, 18))], new_big_bombs);
+        let expected = Field::from_str_art(
+            "
+            .HHH......
+            .HHH......
+            .HHH......
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, field);
+        assert_eq!(Some(&Cell::HugeBombCenter), field.get(pos(2, 18)));
+    }
+
+    #[test]
+    fn test_huge_bomb_wins_over_big_bombs() {
+        // 3x3の右に2x2が重なって並ぶ場合，3x3だけが連結され，残りのボムはそのまま残る
+        let (new_big_bombs, field) = connect(
+            "
+            ****......
+            ****......
+            ***.......
+            ",
+        );
+        assert_eq!(vec![NewBigBomb::Huge(pos(1, 18))], new_big_bombs);
+        let expected = Field::from_str_art(
+            "
+            HHH*......
+            HHH*......
+            HHH.......
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, field);
+    }
+
+    #[test]
+    fn test_overlapping_huge_bombs() {
+        // 3x3の候補が重なる場合は行優先順で先のものだけを連結し，残ったボムで2x2を連結する
+        let (new_big_bombs, field) = connect(
+            "
+            ****......
+            *****.....
+            *****.....
+            ",
+        );
+        assert_eq!(
+            vec![NewBigBomb::Huge(pos(1, 18)), NewBigBomb::Big(pos(3, 18))],
+            new_big_bombs
+        );
+        let expected = Field::from_str_art(
+            "
+            HHH*......
+            HHHBB.....
+            HHHBB.....
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, field);
+    }
+}
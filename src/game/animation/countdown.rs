@@ -0,0 +1,87 @@
+use super::*;
+use crate::graphics::Canvas;
+
+mod consts {
+    /// カウントダウンの各表示を続けるフレーム数．
+    pub const FRAMES_PER_STEP: usize = 15;
+    /// カウントダウンで順に表示する文字列．
+    pub const STEPS: [&str; 4] = ["3", "2", "1", "GO"];
+}
+
+use consts::*;
+
+/// ゲーム開始前に，フィールドの中央へ「3，2，1，GO」を順に表示するアニメーション．
+pub struct Countdown {
+    field: AnimationField,
+    frame: AnimationFrame,
+}
+
+impl Countdown {
+    pub fn new(field: AnimationField) -> Countdown {
+        Self {
+            field,
+            frame: AnimationFrame::with_frame_count(FRAMES_PER_STEP * STEPS.len()),
+        }
+    }
+
+    /// 現在のフレームで表示する文字列を返す．
+    fn current_step(&self) -> &'static str {
+        let index = self.frame.current_frame() / FRAMES_PER_STEP;
+        STEPS[std::cmp::min(index, STEPS.len() - 1)]
+    }
+}
+
+impl Animation for Countdown {
+    type Finished = AnimationField;
+
+    fn wait_next(self) -> AnimationResult<Self, Self::Finished> {
+        match self.frame.wait_next() {
+            Some(next_frame) => AnimationResult::InProgress(Self {
+                frame: next_frame,
+                ..self
+            }),
+            None => AnimationResult::Finished(self.field),
+        }
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C, _: &RenderConfig) {
+        self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
+
+        // フィールドの中央に目立つ色で描画する
+        let color = canvas.theme().inverted_color(ThemeColor::Highlight);
+        let step = ColoredStr(self.current_step(), color);
+        let y = PosY::below((self.field.field.height() / 2) as i8);
+        let row = RegionOfInterest::new(
+            Pos(PosX::origin(), y),
+            self.field.field.region_size().x() + below(1),
+        );
+        step.draw_centered_in(row, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{t_block_selector, HeadlessDrawer};
+    use crate::game::BlockQueue;
+
+    #[test]
+    fn test_frame_count() {
+        let field = AnimationField::new(Field::empty(), BlockQueue::new(&mut t_block_selector()));
+        let mut drawer = HeadlessDrawer::recording();
+        let field = Countdown::new(field).execute(&mut drawer);
+
+        // 最初の表示と，待機のたびの表示
+        assert_eq!(60, drawer.shown.len());
+        assert_eq!(59, drawer.waited_frames);
+        assert_eq!(Field::empty(), field.field);
+
+        // 15フレームごとに表示が切り替わる
+        for (i, step) in STEPS.iter().enumerate() {
+            let shown = &drawer.shown[i * FRAMES_PER_STEP..(i + 1) * FRAMES_PER_STEP];
+            assert!(shown.iter().all(|s| s.contains(step)), "{}", step);
+        }
+        assert!(!drawer.shown[0].contains("GO"));
+    }
+}
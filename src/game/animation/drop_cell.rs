@@ -4,17 +4,60 @@ use itertools::Itertools;
 use std::collections::HashSet;
 use take_if::TakeIf;
 
+/// 爆発の後にセルを落とす規則を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GravityMode {
+    /// 最下段とつながっていないセルを，つながるまで落とす．
+    #[default]
+    Sticky,
+    /// 各ラインを，その下で爆発により空になったラインの数だけ下へずらす．
+    /// 宙に浮いたセルも，下で空になったラインがなければそのまま残る．
+    Line,
+}
+
 pub struct DropCell {
     field: AnimationField,
-    floating_cell_positions: HashSet<Pos>,
+    state: DropCellState,
+}
+
+/// 次のフレームで落とすセルを表す．
+enum DropCellState {
+    /// 指定した位置の，宙に浮いたセルを1マス落とす．
+    Sticky(HashSet<Pos>),
+    /// 指定したラインのうち最も下にあるものを取り除き，その上のラインを1マス下へずらす．
+    /// ラインのy座標は最上段に近いものから順に並ぶ．
+    Line(Vec<PosY>),
 }
 
 impl DropCell {
+    /// 宙に浮いたセルを，最下段とつながるまで落とすアニメーションを返す．
     pub fn new(field: AnimationField) -> DropCell {
         let floating_cell_positions = scan_floating_cell_positions(&field.field);
         Self {
             field,
-            floating_cell_positions,
+            state: DropCellState::Sticky(floating_cell_positions),
+        }
+    }
+
+    /// 爆発で空になったラインを取り除き，その上のラインを取り除いたライン数だけずらすアニメーションを返す．
+    /// 爆発の前から空だったラインや，空でないラインの指定は無視する．
+    pub fn with_cleared_rows(field: AnimationField, cleared_rows: &[PosY]) -> DropCell {
+        let cleared_rows = cleared_rows
+            .iter()
+            .copied()
+            .filter(|&y| {
+                field
+                    .field
+                    .row(y)
+                    .is_some_and(|row| row.iter().all(|c| c.is_empty()))
+            })
+            .sorted()
+            .dedup()
+            .collect();
+        let cleared_rows = skip_rows_without_cells_above(&field.field, cleared_rows);
+        Self {
+            field,
+            state: DropCellState::Line(cleared_rows),
         }
     }
 }
@@ -23,60 +66,88 @@ impl Animation for DropCell {
     type Finished = AnimationField;
 
     fn wait_next(mut self) -> AnimationResult<Self, Self::Finished> {
-        AnimationFrame::with_frame_count(1).wait_next();
-
-        if self.floating_cell_positions.is_empty() {
-            AnimationResult::Finished(self.field)
-        } else {
-            // 下のラインにあるセルから落としていく
-            for pos in self
-                .floating_cell_positions
-                .into_iter()
-                .sorted_by_key(|pos| pos.y())
-                .rev()
-            {
-                use crate::game::Cell;
-
-                let destination = pos + below(1);
-                // 移動対象のセルは空でないはず
-                debug_assert!(matches!(
-                    self.field.field.get(pos).map(|c| c.is_empty()),
-                    Some(false)
-                ));
-                // 移動
-                *self.field.field.get_mut(destination).unwrap() =
-                    *self.field.field.get(pos).unwrap();
-                *self.field.field.get_mut(pos).unwrap() = Cell::Empty;
+        let state = match self.state {
+            DropCellState::Sticky(floating_cell_positions) => {
+                if floating_cell_positions.is_empty() {
+                    return AnimationResult::Finished(self.field);
+                }
+                drop_cells_once(&mut self.field.field, floating_cell_positions);
+                DropCellState::Sticky(scan_floating_cell_positions(&self.field.field))
+            }
+            DropCellState::Line(mut cleared_rows) => {
+                let lowest = match cleared_rows.pop() {
+                    Some(y) => y,
+                    None => return AnimationResult::Finished(self.field),
+                };
+                self.field.field.remove_rows(&[lowest]);
+                // 取り除いたラインより上にある残りのラインも，1マス下へずれる
+                let cleared_rows = cleared_rows.into_iter().map(|y| y + below(1)).collect();
+                DropCellState::Line(skip_rows_without_cells_above(
+                    &self.field.field,
+                    cleared_rows,
+                ))
             }
+        };
 
-            let floating_cell_positions = scan_floating_cell_positions(&self.field.field);
+        AnimationResult::InProgress(Self { state, ..self })
+    }
 
-            AnimationResult::InProgress(Self {
-                floating_cell_positions,
-                ..self
-            })
+    fn draw<C: Canvas>(&self, canvas: &mut C, _: &RenderConfig) {
+        self.field.draw(canvas);
+    }
+}
+
+/// 宙に浮いたセルがなくなるまで，フィールドのセルを落とす．
+pub(crate) fn drop_floating_cells(field: &mut Field) {
+    loop {
+        let floating_cell_positions = scan_floating_cell_positions(field);
+        if floating_cell_positions.is_empty() {
+            break;
         }
+        drop_cells_once(field, floating_cell_positions);
     }
+}
 
-    fn draw<C: Canvas>(&self, canvas: &mut C) {
-        self.field.draw(canvas);
+/// 上から順に並んだ取り除くラインのうち，上に空でないセルがなく取り除いても何も動かないラインを除いて返す．
+/// 下のラインほど上にあるセルが多いので，上に空でないセルがない下側のラインがあれば，それより上のラインにもない．
+fn skip_rows_without_cells_above(field: &Field, mut cleared_rows: Vec<PosY>) -> Vec<PosY> {
+    let top = field
+        .rows()
+        .find(|row| row.iter().any(|c| !c.is_empty()))
+        .map(|row| row.y());
+    match top {
+        Some(top) => cleared_rows.retain(|&y| top < y),
+        None => cleared_rows.clear(),
     }
+    cleared_rows
 }
 
-fn scan_floating_cell_positions(field: &Field) -> HashSet<Pos> {
-    let on_ground_cell_positions = scan_connection_on_ground(field);
-    let mut floating_cell_positions = HashSet::new();
+/// 指定した位置のセルを1マスずつ落とす．
+fn drop_cells_once(field: &mut Field, floating_cell_positions: HashSet<Pos>) {
+    // 下のラインにあるセルから落としていく
+    for pos in floating_cell_positions
+        .into_iter()
+        .sorted_by_key(|pos| pos.y())
+        .rev()
+    {
+        use crate::game::Cell;
 
-    for row in field.rows() {
-        for cell_ref in row.cell_refs() {
-            let pos = cell_ref.pos();
-            if !cell_ref.cell().is_empty() && !on_ground_cell_positions.contains(&pos) {
-                floating_cell_positions.insert(pos);
-            }
-        }
+        let destination = pos + below(1);
+        // 移動対象のセルは空でないはず
+        debug_assert!(matches!(field.get(pos).map(|c| c.is_empty()), Some(false)));
+        // 移動
+        *field.get_mut(destination).unwrap() = *field.get(pos).unwrap();
+        *field.get_mut(pos).unwrap() = Cell::Empty;
     }
+}
 
-    floating_cell_positions
+fn scan_floating_cell_positions(field: &Field) -> HashSet<Pos> {
+    let on_ground_cell_positions = scan_connection_on_ground(field);
+    field
+        .iter_pos_and_cell()
+        .filter(|(pos, cell)| !cell.is_empty() && !on_ground_cell_positions.contains(pos))
+        .map(|(pos, _)| pos)
+        .collect()
 }
 
 fn scan_connection_on_ground(field: &Field) -> HashSet<Pos> {
@@ -98,9 +169,122 @@ fn scan_connection(field: &Field, current_pos: Pos, connected_positions: &mut Ha
     {
         connected_positions.insert(current_pos);
         // 周囲のセルのラベル付け
-        scan_connection(field, current_pos + right(1), connected_positions);
-        scan_connection(field, current_pos + left(1), connected_positions);
-        scan_connection(field, current_pos + below(1), connected_positions);
-        scan_connection(field, current_pos + above(1), connected_positions);
+        for &neighbor in current_pos.orthogonal_neighbors().iter() {
+            scan_connection(field, neighbor, connected_positions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{t_block_selector, HeadlessDrawer};
+    use crate::game::BlockQueue;
+
+    /// 爆発で1ライン空いた下に，下段から張り出したセルの隙間があるフィールド．
+    const OVERHANG: &str = "
+        ...###....
+        ..........
+        .#........
+        ##.#######
+        ";
+
+    /// アニメーションとしてセルを落とした結果を返す．
+    /// `cleared_rows`を指定した場合は，ラインをずらして落とす．
+    fn animate(field: Field, cleared_rows: Option<&[PosY]>) -> Field {
+        let field = AnimationField::new(field, BlockQueue::new(&mut t_block_selector()));
+        let mut drawer = HeadlessDrawer::new();
+        let drop_cell = match cleared_rows {
+            Some(cleared_rows) => DropCell::with_cleared_rows(field, cleared_rows),
+            None => DropCell::new(field),
+        };
+        drop_cell.execute(&mut drawer).field
+    }
+
+    /// 最下段から数えて`n`番目(最下段は0番目)のラインのy座標を返す．
+    fn row_from_bottom(field: &Field, n: usize) -> PosY {
+        PosY::below((field.height() - 1 - n) as i8)
+    }
+
+    #[test]
+    fn test_sticky_gravity() {
+        // 宙に浮いたセルは，下段とつながるまで隙間に落ちる
+        let expected = Field::from_str_art(
+            "
+            .#.###....
+            ##.#######
+            ",
+        )
+        .unwrap();
+        let mut field = Field::from_str_art(OVERHANG).unwrap();
+        drop_floating_cells(&mut field);
+        assert_eq!(expected, field);
+        assert_eq!(
+            expected,
+            animate(Field::from_str_art(OVERHANG).unwrap(), None)
+        );
+    }
+
+    #[test]
+    fn test_line_gravity() {
+        // 空いたラインの分だけずれ，下に隙間があっても宙に浮いたまま残る
+        let expected = Field::from_str_art(
+            "
+            ...###....
+            .#........
+            ##.#######
+            ",
+        )
+        .unwrap();
+        let field = Field::from_str_art(OVERHANG).unwrap();
+        let cleared = row_from_bottom(&field, 2);
+        assert_eq!(expected, animate(field, Some(&[cleared])));
+    }
+
+    #[test]
+    fn test_line_gravity_ignores_rows_not_cleared() {
+        // 爆発の前から空だったラインの分はずらさない
+        let field = Field::from_str_art(
+            "
+            #.........
+            ..........
+            .#........
+            ..........
+            ..........
+            ..#.......
+            ",
+        )
+        .unwrap();
+        let cleared = [row_from_bottom(&field, 2), row_from_bottom(&field, 4)];
+        let expected = Field::from_str_art(
+            "
+            #.........
+            .#........
+            ..........
+            ..#.......
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, animate(field, Some(&cleared)));
+    }
+
+    #[test]
+    fn test_line_gravity_frame_count() {
+        // 下の空いたラインから1フレームにひとつずつ取り除く
+        let field = Field::from_str_art(
+            "
+            #.........
+            ..........
+            .#........
+            ..........
+            ..#.......
+            ",
+        )
+        .unwrap();
+        let cleared = [row_from_bottom(&field, 1), row_from_bottom(&field, 3)];
+        let field = AnimationField::new(field, BlockQueue::new(&mut t_block_selector()));
+        let mut drawer = HeadlessDrawer::new();
+        DropCell::with_cleared_rows(field, &cleared).execute(&mut drawer);
+        assert_eq!(2, drawer.waited_frames);
     }
 }
@@ -2,24 +2,80 @@ use super::*;
 use crate::data_type::Counter;
 use crate::game::Cell;
 use crate::graphics::Canvas;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 use std::ops::RangeFrom;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ChainCounter(Counter<usize, RangeFrom<usize>>);
+pub struct ChainCounter {
+    counter: Counter<usize, RangeFrom<usize>>,
+    /// これまでに爆発を終えた連鎖数のうち，最大のもの．
+    max_reached: usize,
+}
 
 impl ChainCounter {
     pub fn new() -> ChainCounter {
         let counter = Counter::new(0..);
-        Self(counter)
+        Self {
+            counter,
+            max_reached: 0,
+        }
     }
 
     pub fn current_chain(&self) -> usize {
-        self.0.current()
+        self.counter.current()
+    }
+
+    /// これまでに爆発を終えた連鎖数のうち，最大のものを返す．
+    /// まだ爆発が起きていない場合は0を返す．
+    pub fn max_reached(&self) -> usize {
+        self.max_reached
     }
 
+    /// 現在の連鎖数の爆発を終え，次の連鎖数へ進める．
     pub fn next(self) -> Self {
-        Self(self.0.next())
+        Self {
+            max_reached: std::cmp::max(self.max_reached, self.current_chain()),
+            counter: self.counter.next(),
+        }
+    }
+}
+
+impl Default for ChainCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 一連の爆発で消えたセルの記録を表す．
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ExplosionReport {
+    /// 爆発の各段階で消えた，空でないセルの位置．
+    /// 連鎖により爆発が続いた場合，段階ごとに要素が追加される．
+    /// 各段階の位置は`Pos`の順序(上の行から順に，同じ行では左から順)に並ぶ．
+    steps: Vec<Vec<Pos>>,
+}
+
+impl ExplosionReport {
+    /// 各段階で消えたセルの位置を指定して記録を作る．
+    #[cfg(test)]
+    pub(crate) fn from_steps(steps: Vec<Vec<Pos>>) -> ExplosionReport {
+        Self { steps }
+    }
+
+    /// 爆発の各段階で消えたセルの位置を返す．
+    pub fn steps(&self) -> &[Vec<Pos>] {
+        &self.steps
+    }
+
+    /// 爆発でセルが消えたラインのy座標を，最上段に近いものから順に返す．
+    pub fn rows(&self) -> Vec<PosY> {
+        self.steps
+            .iter()
+            .flatten()
+            .map(|pos| pos.y())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
     }
 }
 
@@ -30,138 +86,205 @@ pub enum ExplosionInitResult {
 
 pub struct Explosion {
     field: AnimationField,
+    state: ExplosionState,
+    frame: AnimationFrame,
+}
+
+impl Explosion {
+    pub fn try_init(
+        field: AnimationField,
+        filled_rows: &[PosY],
+        current_chain: ChainCounter,
+    ) -> ExplosionInitResult {
+        match ExplosionState::try_new(&field.field, filled_rows, current_chain) {
+            Some(state) => ExplosionInitResult::Explodes(Self {
+                field,
+                state,
+                frame: animation_frame(),
+            }),
+            None => ExplosionInitResult::Stay(field),
+        }
+    }
+}
+
+impl Animation for Explosion {
+    type Finished = (AnimationField, ChainCounter, ExplosionReport);
+
+    fn wait_next(self) -> AnimationResult<Self, Self::Finished> {
+        let Self {
+            mut field,
+            mut state,
+            frame,
+        } = self;
+
+        match frame.wait_next() {
+            Some(next_frame) => AnimationResult::InProgress(Self {
+                field,
+                state,
+                frame: next_frame,
+            }),
+            None => {
+                // 巻き込まれたボムがまだ爆発する場合は，次の段階のアニメーションに移る
+                if state.advance(&mut field.field) {
+                    AnimationResult::InProgress(Self {
+                        field,
+                        state,
+                        frame: animation_frame(),
+                    })
+                } else {
+                    let (next_chain, report) = state.finish();
+                    AnimationResult::Finished((field, next_chain, report))
+                }
+            }
+        }
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig) {
+        let explosion_cell = {
+            let color = canvas.theme().cell_color(ThemeColor::Highlight);
+            // 演出を抑える場合は文字を切り替えない
+            let c = if config.reduced_motion || self.frame.current_frame() % 2 == 0 {
+                'x'
+            } else {
+                '+'
+            };
+            CanvasCell::new(SquareChar::new(c, c), color)
+        };
+
+        self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
+
+        for &pos in self.state.exploded_cell_positions.iter() {
+            canvas.draw_cell(pos, explosion_cell);
+        }
+
+        // 連鎖中は，フィールドの上部中央に連鎖数を表示する
+        let chain = self.state.current_chain.current_chain();
+        if chain > 0 {
+            let color = canvas.theme().cell_color(ThemeColor::Text);
+            let chain_str = ColoredStr(format!("CHAIN x{}", chain), color);
+            let row =
+                RegionOfInterest::new(Pos::origin(), self.field.field.region_size().x() + below(1));
+            chain_str.draw_centered_in(row, canvas);
+        }
+    }
+}
+
+/// 爆発の進行状況を表す．
+///
+/// フィールドの書き換えだけを行い，描画やフレームの管理は行わない．
+/// アニメーションを伴わずに爆発を計算する場合にも利用される．
+pub(crate) struct ExplosionState {
     current_chain: ChainCounter,
     filled_row_count: usize,
     /// 爆発してはいないが，爆発に巻き込まれたボムセルの位置．
     /// 爆発の連鎖を表現するために利用される．
-    caught_bomb_positions: HashSet<Pos>,
+    ///
+    /// 実行環境によらず同じ順序で処理するため，位置の集合は行優先順に並ぶ`BTreeSet`で保持する．
+    caught_bomb_positions: BTreeSet<Pos>,
     /// 爆発に巻き込まれたセル(空，通常，ボムの全種類)の位置．
     /// 爆発アニメーションの描画に利用される．
-    exploded_cell_positions: HashSet<Pos>,
-    frame: AnimationFrame,
+    exploded_cell_positions: BTreeSet<Pos>,
+    /// これまでに消えたセルの記録．
+    report: ExplosionReport,
 }
 
-impl Explosion {
-    pub fn try_init(
-        field: AnimationField,
+impl ExplosionState {
+    /// 揃ったラインにあるボムによる爆発の，最初の段階を返す．
+    /// # Returns
+    /// 爆発に巻き込まれるセルがない場合は`None`を返す．
+    pub(crate) fn try_new(
+        field: &Field,
         filled_rows: &[PosY],
         current_chain: ChainCounter,
-    ) -> ExplosionInitResult {
+    ) -> Option<ExplosionState> {
         let filled_row_count = filled_rows.len();
         let explosion_power = ExplosionPower::new(filled_row_count, &current_chain);
 
-        let explosion_center_rows = field
-            .field
-            .rows()
-            .filter(|row| filled_rows.contains(&row.y()));
+        let explosion_center_rows = field.rows().filter(|row| filled_rows.contains(&row.y()));
         let explodable_center_cell_positions = explosion_center_rows
             .flat_map(|row| {
                 row.cell_refs()
-                    .into_iter()
                     .filter(|r| is_explodable(*r.cell()))
                     .map(|r| r.pos())
                     .collect::<Vec<_>>()
             })
-            .collect::<HashSet<_>>();
+            .collect::<BTreeSet<_>>();
 
-        let exploded_cell_positions = scan_exploded_cell_positions(
-            &field.field,
-            &explodable_center_cell_positions,
-            explosion_power,
-        );
+        let exploded_cell_positions =
+            scan_exploded_cell_positions(field, &explodable_center_cell_positions, explosion_power);
         let caught_bomb_positions = scan_caught_explosion_cell_positions(
-            &field.field,
+            field,
             &explodable_center_cell_positions,
             &exploded_cell_positions,
         );
 
         if exploded_cell_positions.is_empty() {
-            ExplosionInitResult::Stay(field)
+            None
         } else {
-            ExplosionInitResult::Explodes(Self {
-                field,
+            Some(Self {
                 current_chain,
                 filled_row_count,
                 caught_bomb_positions,
                 exploded_cell_positions,
-                frame: animation_frame(),
+                report: ExplosionReport::default(),
             })
         }
     }
-}
 
-impl Animation for Explosion {
-    type Finished = (AnimationField, ChainCounter);
-
-    fn wait_next(mut self) -> AnimationResult<Self, Self::Finished> {
-        // partial moveを防ぐためだけにclone()を使っている．他の方法を考えるのがベター．
-        match self.frame.clone().wait_next() {
-            Some(next_frame) => AnimationResult::InProgress(Self {
-                frame: next_frame,
-                ..self
-            }),
-            None => {
-                // さっき爆発に巻き込まれた非爆心ボムセルがない場合，これ以上爆発は起きないので終了
-                if self.caught_bomb_positions.is_empty() {
-                    // 爆発に巻き込まれたセルは空セルになる
-                    for &exploded_pos in self.exploded_cell_positions.iter() {
-                        if let Some(c) = self.field.field.get_mut(exploded_pos) {
-                            *c = Cell::Empty;
-                        }
-                    }
-                    AnimationResult::Finished((self.field, self.current_chain.next()))
-                } else {
-                    // さっき爆発に巻き込まれた非爆心ボムセルがまだある場合
-                    let explosion_power =
-                        ExplosionPower::new(self.filled_row_count, &self.current_chain);
-                    let explodable_center_cell_positions = &self.caught_bomb_positions;
-                    let exploded_cell_positions = scan_exploded_cell_positions(
-                        &self.field.field,
-                        &explodable_center_cell_positions,
-                        explosion_power,
-                    );
-                    let caught_bomb_positions = scan_caught_explosion_cell_positions(
-                        &self.field.field,
-                        &explodable_center_cell_positions,
-                        &exploded_cell_positions,
-                    );
-
-                    // 爆発に巻き込まれたセルは空セルになる
-                    for &exploded_pos in self.exploded_cell_positions.iter() {
-                        if let Some(c) = self.field.field.get_mut(exploded_pos) {
-                            *c = Cell::Empty;
-                        }
-                    }
-
-                    let next_state = Self {
-                        caught_bomb_positions,
-                        exploded_cell_positions,
-                        frame: animation_frame(),
-                        ..self
-                    };
-                    AnimationResult::InProgress(next_state)
-                }
-            }
+    /// 現在の段階の爆発を終え，巻き込まれたセルを空セルにする．
+    /// 巻き込まれたボムがある場合は，それらが爆発する次の段階へ進む．
+    /// 爆発を終える場合は，一部のセルだけが消えたデカボムや特大ボムの残りのセルをボムセルに戻す．
+    /// # Returns
+    /// 次の段階へ進んだ場合は`true`を返す．
+    pub(crate) fn advance(&mut self, field: &mut Field) -> bool {
+        // さっき爆発に巻き込まれた非爆心ボムセルがない場合，これ以上爆発は起きない
+        if self.caught_bomb_positions.is_empty() {
+            self.clear_exploded_cells(field);
+            field.repair_big_bombs();
+            return false;
         }
-    }
 
-    fn draw<C: Canvas>(&self, canvas: &mut C) {
-        let explosion_cell = {
-            use Color::*;
-            let color = CanvasCellColor::new(Yellow, Black);
-            let c = if self.frame.current_frame() % 2 == 0 {
-                'x'
-            } else {
-                '+'
-            };
-            CanvasCell::new(SquareChar::new(c, c), color)
-        };
+        // さっき爆発に巻き込まれた非爆心ボムセルがまだある場合
+        let explosion_power = ExplosionPower::new(self.filled_row_count, &self.current_chain);
+        let explodable_center_cell_positions = &self.caught_bomb_positions;
+        let exploded_cell_positions =
+            scan_exploded_cell_positions(field, explodable_center_cell_positions, explosion_power);
+        let caught_bomb_positions = scan_caught_explosion_cell_positions(
+            field,
+            explodable_center_cell_positions,
+            &exploded_cell_positions,
+        );
 
-        self.field.draw(canvas);
+        // 爆発に巻き込まれたセルは空セルになる
+        self.clear_exploded_cells(field);
 
-        for &pos in self.exploded_cell_positions.iter() {
-            canvas.draw_cell(pos, explosion_cell);
+        self.caught_bomb_positions = caught_bomb_positions;
+        self.exploded_cell_positions = exploded_cell_positions;
+        true
+    }
+
+    /// 爆発を終え，次の連鎖数とこれまでに消えたセルの記録を返す．
+    pub(crate) fn finish(self) -> (ChainCounter, ExplosionReport) {
+        (self.current_chain.next(), self.report)
+    }
+
+    /// 爆発に巻き込まれたセルを空セルにし，空でなかったセルの位置を記録する．
+    /// 鋼鉄のセルは消えずに残る．
+    fn clear_exploded_cells(&mut self, field: &mut Field) {
+        let mut step = vec![];
+        for &exploded_pos in self.exploded_cell_positions.iter() {
+            if let Some(c) = field.get_mut(exploded_pos) {
+                if *c == Cell::Steel {
+                    continue;
+                }
+                if !c.is_empty() {
+                    step.push(exploded_pos);
+                }
+                *c = Cell::Empty;
+            }
         }
+        self.report.steps.push(step);
     }
 }
 
@@ -181,10 +304,17 @@ fn is_explodable(cell: Cell) -> bool {
     use Cell::*;
     matches!(
         cell,
-        Bomb | BigBombUpperLeft | BigBombUpperRight | BigBombLowerLeft | BigBombLowerRight
+        Bomb | BigBombUpperLeft
+            | BigBombUpperRight
+            | BigBombLowerLeft
+            | BigBombLowerRight
+            | HugeBombCenter
+            | HugeBombPart
     )
 }
 
+/// 指定した位置のセルが爆発する場合の爆発範囲を返す．
+/// 爆発の起点とならないセルの場合や，爆発範囲の左上の座標が`Shift`で表せない場合は`None`を返す．
 fn explosion_area(
     explosion_power: ExplosionPower,
     cell: Cell,
@@ -193,13 +323,14 @@ fn explosion_area(
     use Cell::*;
 
     match cell {
-        Bomb => Some(bomb_explosion_area(explosion_power, pos)),
-        BigBombUpperLeft => Some(big_bomb_explosion_area(pos)),
+        Bomb => bomb_explosion_area(explosion_power, pos),
+        BigBombUpperLeft => big_bomb_explosion_area(pos),
+        HugeBombCenter => huge_bomb_explosion_area(pos),
         _ => None,
     }
 }
 
-fn bomb_explosion_area(explosion_power: ExplosionPower, pos: Pos) -> RegionOfInterest {
+fn bomb_explosion_area(explosion_power: ExplosionPower, pos: Pos) -> Option<RegionOfInterest> {
     let (x, y) = match explosion_power.power {
         1 => (3, 0),
         2 => (3, 1),
@@ -212,16 +343,23 @@ fn bomb_explosion_area(explosion_power: ExplosionPower, pos: Pos) -> RegionOfInt
         _ => (8, 8),
     };
 
-    let left_top = pos + left(x) + above(y);
-    let size = Movement(right(x * 2 + 1), below(y * 2 + 1));
+    area_around(pos, right(x) + below(y))
+}
 
-    RegionOfInterest::new(left_top, size)
+fn big_bomb_explosion_area(big_bomb_upper_left_pos: Pos) -> Option<RegionOfInterest> {
+    area_around(big_bomb_upper_left_pos, right(4) + below(4))
 }
 
-fn big_bomb_explosion_area(big_bomb_upper_left_pos: Pos) -> RegionOfInterest {
-    let left_top = big_bomb_upper_left_pos + left(4) + above(4);
-    let size = Movement(right(9), below(9));
-    RegionOfInterest::new(left_top, size)
+/// 特大ボムは，中央から上下左右6マスまでを巻き込む．
+fn huge_bomb_explosion_area(huge_bomb_center_pos: Pos) -> Option<RegionOfInterest> {
+    area_around(huge_bomb_center_pos, right(6) + below(6))
+}
+
+/// 指定した位置から，左右と上下へそれぞれ`reach`だけ広げた範囲を返す．
+fn area_around(center: Pos, reach: Movement) -> Option<RegionOfInterest> {
+    let left_top = center.checked_add(-reach)?;
+    let size = reach * 2 + right(1) + below(1);
+    Some(RegionOfInterest::new(left_top, size))
 }
 
 const fn animation_frame() -> AnimationFrame {
@@ -230,21 +368,24 @@ const fn animation_frame() -> AnimationFrame {
 
 fn scan_exploded_cell_positions(
     field: &Field,
-    explodable_center_cell_positions: &HashSet<Pos>,
+    explodable_center_cell_positions: &BTreeSet<Pos>,
     explosion_power: ExplosionPower,
-) -> HashSet<Pos> {
+) -> BTreeSet<Pos> {
+    // フィールドの外側の座標は，はじめから列挙しない
+    let field_roi = field.get_roi(Pos::origin());
     explodable_center_cell_positions
         .iter()
         .filter_map(|&pos| explosion_area(explosion_power, *field.get(pos).unwrap(), pos))
+        .filter_map(|roi| roi.intersect(&field_roi))
         .flat_map(|roi| roi.iter_pos())
         .collect()
 }
 
 fn scan_caught_explosion_cell_positions(
     field: &Field,
-    explodable_center_cell_positions: &HashSet<Pos>,
-    exploded_cell_positions: &HashSet<Pos>,
-) -> HashSet<Pos> {
+    explodable_center_cell_positions: &BTreeSet<Pos>,
+    exploded_cell_positions: &BTreeSet<Pos>,
+) -> BTreeSet<Pos> {
     explodable_center_cell_positions
         .symmetric_difference(exploded_cell_positions)
         .into_iter()
@@ -257,3 +398,231 @@ fn scan_caught_explosion_cell_positions(
         .map(|&pos| pos)
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::repeating_selector;
+    use crate::game::{BlockQueue, BombTag, QuadrupleBlockShape};
+
+    /// 最下段が揃っており，その中にボムセルを含むフィールドの爆発アニメーションを返す．
+    fn bottom_row_explosion() -> Explosion {
+        let mut field = Field::empty();
+        let bottom = PosY::below(field.height() as i8 - 1);
+        for (pos, cell) in field
+            .iter_pos_and_cell_mut()
+            .filter(|(p, _)| p.y() == bottom)
+        {
+            *cell = if pos.x() == PosX::right(4) {
+                Cell::Bomb
+            } else {
+                Cell::Normal
+            };
+        }
+        let block_queue = BlockQueue::new(&mut repeating_selector(
+            QuadrupleBlockShape::T,
+            BombTag::Single(0),
+        ));
+        let field = AnimationField::new(field, block_queue);
+
+        match Explosion::try_init(field, &[bottom], ChainCounter::new()) {
+            ExplosionInitResult::Explodes(explosion) => explosion,
+            ExplosionInitResult::Stay(_) => panic!("bottom row should explode"),
+        }
+    }
+
+    /// 爆発アニメーションの連続する2フレームを描画した結果を返す．
+    fn render_frame_pair(config: &RenderConfig) -> (String, String) {
+        let explosion = bottom_row_explosion();
+        let mut canvas = RootCanvas::new();
+        let mut first = String::new();
+        explosion.draw(&mut canvas, config);
+        canvas.construct_plain_string(&mut first);
+
+        let explosion = match explosion.wait_next() {
+            AnimationResult::InProgress(explosion) => explosion,
+            AnimationResult::Finished(_) => panic!("explosion should last more than one frame"),
+        };
+        let mut canvas = RootCanvas::new();
+        let mut second = String::new();
+        explosion.draw(&mut canvas, config);
+        canvas.construct_plain_string(&mut second);
+
+        (first, second)
+    }
+
+    #[test]
+    fn test_draw_alternates_glyph() {
+        let (first, second) = render_frame_pair(&RenderConfig::default());
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_draw_reduced_motion() {
+        let config = RenderConfig {
+            reduced_motion: true,
+            ..RenderConfig::default()
+        };
+        let (first, second) = render_frame_pair(&config);
+        assert_eq!(first, second);
+    }
+
+    /// 2ラインが揃い，爆発が2回連鎖するフィールドの爆発アニメーションを返す．
+    fn chain_explosion() -> Explosion {
+        let mut field = Field::empty();
+        let pos = |x: i8, y: i8| Pos(PosX::right(x), PosY::below(y));
+        for (_, cell) in field
+            .iter_pos_and_cell_mut()
+            .filter(|(p, _)| p.y() >= PosY::below(18))
+        {
+            *cell = Cell::Normal;
+        }
+        // 揃ったラインにあるボム
+        *field.get_mut(pos(4, 19)).unwrap() = Cell::Bomb;
+        *field.get_mut(pos(8, 18)).unwrap() = Cell::Bomb;
+        // 爆発に巻き込まれて連鎖するボム
+        *field.get_mut(pos(6, 17)).unwrap() = Cell::Bomb;
+        *field.get_mut(pos(3, 16)).unwrap() = Cell::Bomb;
+        *field.get_mut(pos(0, 15)).unwrap() = Cell::Normal;
+
+        let block_queue = BlockQueue::new(&mut repeating_selector(
+            QuadrupleBlockShape::T,
+            BombTag::Single(0),
+        ));
+        let field = AnimationField::new(field, block_queue);
+        let filled_rows = [PosY::below(18), PosY::below(19)];
+
+        match Explosion::try_init(field, &filled_rows, ChainCounter::new()) {
+            ExplosionInitResult::Explodes(explosion) => explosion,
+            ExplosionInitResult::Stay(_) => panic!("filled rows should explode"),
+        }
+    }
+
+    #[test]
+    fn test_scan_order_is_deterministic() {
+        let first = chain_explosion();
+        for _ in 0..100 {
+            let explosion = chain_explosion();
+            assert!(first
+                .state
+                .exploded_cell_positions
+                .iter()
+                .eq(explosion.state.exploded_cell_positions.iter()));
+            assert!(first
+                .state
+                .caught_bomb_positions
+                .iter()
+                .eq(explosion.state.caught_bomb_positions.iter()));
+        }
+    }
+
+    #[test]
+    fn test_chain_is_deterministic() {
+        let execute = || {
+            let mut explosion = chain_explosion();
+            loop {
+                match explosion.wait_next() {
+                    AnimationResult::InProgress(next) => explosion = next,
+                    AnimationResult::Finished((field, _, report)) => break (field.field, report),
+                }
+            }
+        };
+
+        let (first_field, first_report) = execute();
+        // 揃ったラインの爆発，巻き込まれたボム2つの爆発の3段階
+        assert_eq!(3, first_report.steps().len());
+        for step in first_report.steps() {
+            assert!(step.windows(2).all(|w| w[0] < w[1]));
+        }
+
+        let (second_field, second_report) = execute();
+        assert_eq!(first_report, second_report);
+        assert_eq!(first_field, second_field);
+    }
+
+    /// 爆発が終わるまでアニメーションを進め，フィールドと次の連鎖数を返す．
+    fn finish(mut explosion: Explosion) -> (AnimationField, ChainCounter) {
+        loop {
+            match explosion.wait_next() {
+                AnimationResult::InProgress(next) => explosion = next,
+                AnimationResult::Finished((field, chain, _)) => break (field, chain),
+            }
+        }
+    }
+
+    #[test]
+    fn test_chain_count_increments() {
+        let field = bottom_row_explosion();
+        assert!(!render(&field).contains("CHAIN"));
+        let (mut field, chain) = finish(field);
+        assert_eq!(1, chain.current_chain());
+        assert_eq!(0, chain.max_reached());
+
+        // 爆発で空いた最下段をもう一度埋め，次の連鎖として爆発させる
+        let bottom = PosY::below(field.field.height() as i8 - 1);
+        for x in 0..field.field.width() {
+            let pos = Pos(PosX::right(x as i8), bottom);
+            *field.field.get_mut(pos).unwrap() = if x == 4 { Cell::Bomb } else { Cell::Normal };
+        }
+        let explosion = match Explosion::try_init(field, &[bottom], chain) {
+            ExplosionInitResult::Explodes(explosion) => explosion,
+            ExplosionInitResult::Stay(_) => panic!("refilled row should explode"),
+        };
+        assert!(render(&explosion).contains("CHAIN x1"));
+        let (_, chain) = finish(explosion);
+        assert_eq!(2, chain.current_chain());
+        assert_eq!(1, chain.max_reached());
+    }
+
+    #[test]
+    fn test_explosion_area_near_shift_limit() {
+        let power = ExplosionPower::new(4, &ChainCounter::new());
+        let corner = Pos(PosX::right(Shift::MIN), PosY::below(Shift::MIN));
+        // 爆発範囲の左上が座標の下限を超える場合は，範囲を求めない
+        assert_eq!(None, explosion_area(power, Cell::Bomb, corner));
+        assert_eq!(None, explosion_area(power, Cell::BigBombUpperLeft, corner));
+        assert_eq!(None, explosion_area(power, Cell::HugeBombCenter, corner));
+
+        // 爆発範囲の右下が座標の上限を超えても，範囲を求められる
+        let corner = Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX));
+        let area = explosion_area(power, Cell::HugeBombCenter, corner).unwrap();
+        assert!(area.contains(corner));
+        assert!(area.contains(corner + left(6) + above(6)));
+    }
+
+    #[test]
+    fn test_huge_bomb_explosion_area() {
+        // 下から12ラインが埋まっており，左下の特大ボムの中央(1, 18)を含むラインが揃っている
+        let art = "##########\n".repeat(9) + &"HHH#######\n".repeat(3);
+        let field = Field::from_str_art(&art).unwrap();
+        let field = AnimationField::new(
+            field,
+            BlockQueue::new(&mut repeating_selector(
+                QuadrupleBlockShape::T,
+                BombTag::Single(0),
+            )),
+        );
+        let explosion = match Explosion::try_init(field, &[PosY::below(18)], ChainCounter::new()) {
+            ExplosionInitResult::Explodes(explosion) => explosion,
+            ExplosionInitResult::Stay(_) => panic!("huge bomb should explode"),
+        };
+        let (field, _) = finish(explosion);
+
+        // 中央から上下左右6マスまでが消え，その外側は残る
+        for y in 8..20 {
+            for x in 0..field.field.width() {
+                let cell = field.field.get(Pos(PosX::right(x as i8), PosY::below(y)));
+                let exploded = x <= 7 && y >= 12;
+                assert_eq!(exploded, cell.unwrap().is_empty(), "({}, {})", x, y);
+            }
+        }
+    }
+
+    fn render(explosion: &Explosion) -> String {
+        let mut canvas = RootCanvas::new();
+        let mut s = String::new();
+        explosion.draw(&mut canvas, &RenderConfig::default());
+        canvas.construct_plain_string(&mut s);
+        s
+    }
+}
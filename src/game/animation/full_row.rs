@@ -9,19 +9,7 @@ pub struct FullRow {
 
 impl FullRow {
     pub fn new(field: AnimationField, previous_filled_rows: &[PosY]) -> FullRow {
-        let filled_row_ys = field
-            .field
-            .rows()
-            .filter(|row| row.iter().all(|cell| !cell.is_empty()))
-            .map(|row| row.y())
-            .collect::<Vec<_>>();
-
-        // 揃ったラインが以前とまったく同一だった場合はアニメーションを表示しない．
-        let filled_row_ys = if &filled_row_ys[..] == previous_filled_rows {
-            vec![]
-        } else {
-            filled_row_ys
-        };
+        let filled_row_ys = scan_filled_rows(&field.field, previous_filled_rows);
 
         // 一ラインあたりの表示遷移フレーム*揃ったライン数+表示が遷移した後の追加表示フレーム数
         let max_frame_count = {
@@ -52,9 +40,10 @@ impl Animation for FullRow {
         }
     }
 
-    fn draw<C: Canvas>(&self, canvas: &mut C) {
+    fn draw<C: Canvas>(&self, canvas: &mut C, _: &RenderConfig) {
         // まずは普通にフィールドを描画し，これにアニメーションを上書きしていく．
         self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
 
         /*
         アニメーションは以下の流れで表示する．
@@ -73,7 +62,7 @@ impl Animation for FullRow {
             let x = PosX::right(self.field.field.width() as i8 / 2);
             let pos = Pos(x, y);
             let colored_str = {
-                let color = CanvasCellColor::new(Color::White, Color::Black);
+                let color = canvas.theme().cell_color(ThemeColor::Text);
                 ColoredStr((i + 1).to_string(), color)
             };
             colored_str.draw_on_child(pos, canvas);
@@ -83,7 +72,7 @@ impl Animation for FullRow {
         if let Some(&y) = self.filled_row_ys.get(filled_row_count) {
             for i in 0..filling_cell_count {
                 let colored_str = {
-                    let color = CanvasCellColor::new(Color::White, Color::Black);
+                    let color = canvas.theme().cell_color(ThemeColor::Text);
                     ColoredStr("--", color)
                 };
                 // 左側
@@ -98,3 +87,16 @@ impl Animation for FullRow {
         }
     }
 }
+
+/// 揃っているラインのy座標を上から順に返す．
+/// ただし揃ったラインが`previous_filled_rows`とまったく同一だった場合は，空の列を返す．
+pub(crate) fn scan_filled_rows(field: &Field, previous_filled_rows: &[PosY]) -> Vec<PosY> {
+    let filled_row_ys = field.filled_rows();
+
+    // 揃ったラインが以前とまったく同一だった場合はアニメーションを表示しない．
+    if &filled_row_ys[..] == previous_filled_rows {
+        vec![]
+    } else {
+        filled_row_ys
+    }
+}
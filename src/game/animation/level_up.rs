@@ -0,0 +1,130 @@
+use super::*;
+use crate::game::Level;
+use crate::graphics::Canvas;
+
+mod consts {
+    /// 点滅を続けるフレーム数．
+    pub const FRAME_COUNT: usize = 10;
+    /// 色を切り替えるまでのフレーム数．
+    pub const FRAMES_PER_FLASH: usize = 2;
+}
+
+use consts::*;
+
+/// レベルが上がったときに，サイドパネルのレベルの数値を2色で交互に点滅させるアニメーション．
+/// フィールドは点滅中もそのまま描画する．
+pub struct LevelUp {
+    field: AnimationField,
+    /// 上がった後のレベル．
+    level: Level,
+    /// レベルの数値を描画する位置．
+    pos: Pos,
+    frame: AnimationFrame,
+}
+
+impl LevelUp {
+    pub fn new(field: AnimationField, level: Level, pos: Pos) -> LevelUp {
+        Self {
+            field,
+            level,
+            pos,
+            frame: AnimationFrame::with_frame_count(FRAME_COUNT),
+        }
+    }
+
+    /// 現在のフレームでレベルの数値を描画する色を返す．
+    /// 演出を抑える場合は点滅させず，目立つ色のまま描画する．
+    fn current_color(&self, theme: &Theme, config: &RenderConfig) -> CanvasCellColor {
+        let highlighted = theme.inverted_color(ThemeColor::Highlight);
+        let normal = theme.cell_color(ThemeColor::Highlight);
+        if config.reduced_motion
+            || (self.frame.current_frame() / FRAMES_PER_FLASH).is_multiple_of(2)
+        {
+            highlighted
+        } else {
+            normal
+        }
+    }
+}
+
+impl Animation for LevelUp {
+    type Finished = AnimationField;
+
+    fn wait_next(self) -> AnimationResult<Self, Self::Finished> {
+        match self.frame.wait_next() {
+            Some(next_frame) => AnimationResult::InProgress(Self {
+                frame: next_frame,
+                ..self
+            }),
+            None => AnimationResult::Finished(self.field),
+        }
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig) {
+        self.field.draw(canvas);
+
+        let text = ColoredStr(
+            self.level.level_text(),
+            self.current_color(&canvas.theme(), config),
+        );
+        text.draw_on_child(self.pos, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{t_block_selector, HeadlessDrawer};
+    use crate::game::BlockQueue;
+
+    fn level_up() -> LevelUp {
+        let field = AnimationField::new(Field::empty(), BlockQueue::new(&mut t_block_selector()));
+        let pos = Pos(PosX::right(12), PosY::below(3));
+        LevelUp::new(field, Level::with_rows(10), pos)
+    }
+
+    /// アニメーションが終わるまでの各フレームで，レベルの数値を描画する色を返す．
+    fn colors(config: &RenderConfig) -> Vec<CanvasCellColor> {
+        let mut animation = level_up();
+        let mut colors = vec![];
+        loop {
+            colors.push(animation.current_color(&Theme::default(), config));
+            match animation.wait_next() {
+                AnimationResult::InProgress(next) => animation = next,
+                AnimationResult::Finished(_) => break colors,
+            }
+        }
+    }
+
+    #[test]
+    fn test_frame_count() {
+        let mut drawer = HeadlessDrawer::recording();
+        let field = level_up().execute(&mut drawer);
+
+        // 最初の表示と，待機のたびの表示
+        assert_eq!(FRAME_COUNT, drawer.shown.len());
+        assert_eq!(FRAME_COUNT - 1, drawer.waited_frames);
+        assert_eq!(Field::empty(), field.field);
+        assert!(drawer.shown.iter().all(|s| s.contains("Level    2")));
+    }
+
+    #[test]
+    fn test_flash() {
+        let colors = colors(&RenderConfig::default());
+        assert_eq!(FRAME_COUNT, colors.len());
+        // 2フレームごとに色が切り替わる
+        assert_eq!(colors[0], colors[1]);
+        assert_ne!(colors[1], colors[2]);
+        assert_eq!(colors[0], colors[4]);
+    }
+
+    #[test]
+    fn test_reduced_motion_stays() {
+        let config = RenderConfig {
+            reduced_motion: true,
+            ..RenderConfig::default()
+        };
+        let colors = colors(&config);
+        assert!(colors.iter().all(|&color| color == colors[0]));
+    }
+}
@@ -29,7 +29,7 @@ impl Animation for PlaceBlock {
         }
     }
 
-    fn draw<C: Canvas>(&self, canvas: &mut C) {
+    fn draw<C: Canvas>(&self, canvas: &mut C, _: &RenderConfig) {
         self.field.draw(canvas);
     }
 }
@@ -0,0 +1,121 @@
+use super::*;
+use crate::graphics::Canvas;
+
+/// 獲得した得点を表す文字列を，指定した位置から1フレームごとに1マスずつ浮かび上がらせるアニメーション．
+/// 文字列はキャンバスの最上段より上へは移動しない．
+pub struct ScorePopup {
+    field: AnimationField,
+    text: String,
+    /// 最初のフレームで文字列を描画する位置．
+    start: Pos,
+    frame: AnimationFrame,
+}
+
+impl ScorePopup {
+    pub fn new(field: AnimationField, text: String, start: Pos) -> ScorePopup {
+        Self {
+            field,
+            text,
+            start,
+            frame: AnimationFrame::with_frame_count(8),
+        }
+    }
+
+    /// 現在のフレームで文字列を描画する位置を返す．
+    /// 演出を抑える場合は浮かび上がらせない．
+    fn current_pos(&self, config: &RenderConfig) -> Pos {
+        if config.reduced_motion {
+            return self.start;
+        }
+        let rise = std::cmp::min(self.frame.current_frame(), i8::MAX as usize) as i8;
+        let y = std::cmp::max(self.start.y().below_shift.saturating_sub(rise), 0);
+        Pos(self.start.x(), PosY::below(y))
+    }
+}
+
+impl Animation for ScorePopup {
+    type Finished = AnimationField;
+
+    fn wait_next(self) -> AnimationResult<Self, Self::Finished> {
+        match self.frame.wait_next() {
+            Some(next_frame) => AnimationResult::InProgress(Self {
+                frame: next_frame,
+                ..self
+            }),
+            None => AnimationResult::Finished(self.field),
+        }
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C, config: &RenderConfig) {
+        self.field.draw(canvas);
+        let canvas = &mut self.field.field_canvas(canvas);
+
+        let color = canvas.theme().cell_color(ThemeColor::Highlight);
+        ColoredStr(self.text.as_str(), color).draw_on_child(self.current_pos(config), canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::t_block_selector;
+    use crate::game::BlockQueue;
+
+    fn popup(start: Pos) -> ScorePopup {
+        let field = AnimationField::new(Field::empty(), BlockQueue::new(&mut t_block_selector()));
+        ScorePopup::new(field, "+850".to_string(), start)
+    }
+
+    /// アニメーションが終わるまでの各フレームで，文字列を描画する位置を返す．
+    fn positions(mut popup: ScorePopup, config: &RenderConfig) -> Vec<Pos> {
+        let mut positions = vec![];
+        loop {
+            let mut canvas = RootCanvas::new();
+            popup.draw(&mut canvas, config);
+            positions.push(popup.current_pos(config));
+            match popup.wait_next() {
+                AnimationResult::InProgress(next) => popup = next,
+                AnimationResult::Finished(_) => break positions,
+            }
+        }
+    }
+
+    #[test]
+    fn test_rises_one_cell_per_frame() {
+        let start = Pos(PosX::right(3), PosY::below(15));
+        let positions = positions(popup(start), &RenderConfig::default());
+        assert_eq!(8, positions.len());
+        for (i, pos) in positions.into_iter().enumerate() {
+            assert_eq!(start + above(i as i8), pos);
+        }
+    }
+
+    #[test]
+    fn test_clamps_to_top_row() {
+        let start = Pos(PosX::right(3), PosY::below(2));
+        let positions = positions(popup(start), &RenderConfig::default());
+        assert_eq!(start + above(2), positions[2]);
+        assert!(positions[2..].iter().all(|pos| pos.y() == PosY::origin()));
+    }
+
+    #[test]
+    fn test_draws_text() {
+        let popup = popup(Pos(PosX::right(3), PosY::below(10)));
+        let mut canvas = RootCanvas::new();
+        popup.draw(&mut canvas, &RenderConfig::default());
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        assert!(s.contains("+850"));
+    }
+
+    #[test]
+    fn test_reduced_motion_stays() {
+        let start = Pos(PosX::right(3), PosY::below(15));
+        let config = RenderConfig {
+            reduced_motion: true,
+            ..RenderConfig::default()
+        };
+        let positions = positions(popup(start), &config);
+        assert!(positions.iter().all(|&pos| pos == start));
+    }
+}
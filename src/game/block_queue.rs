@@ -10,7 +10,7 @@ mod consts {
 use consts::*;
 
 /// Nextブロックキューを管理する．
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct NextBlockQueue {
     /// Nextブロックキュー．
     blocks: [Block; NEXT_BLOCK_NUM],
@@ -41,10 +41,19 @@ impl NextBlockQueue {
 
         popped_block
     }
+
+    /// このキューの先頭にブロックを戻す．
+    /// キューの大きさは変わらないので，末尾のブロックを取り除いて返す．
+    fn push_front(&mut self, block: Block) -> Block {
+        let removed_block = self.blocks[self.blocks.len() - 1];
+        self.blocks.rotate_right(1);
+        self.blocks[0] = block;
+        removed_block
+    }
 }
 
 /// NextブロックおよびHoldブロックを管理する．
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BlockQueue {
     /// Nextブロック．
     next_blocks: NextBlockQueue,
@@ -62,12 +71,38 @@ impl BlockQueue {
         }
     }
 
+    /// 指定したNextブロック列とHoldブロックからなるキューを返す．
+    /// # Returns
+    /// Nextブロック列の長さがキューの大きさと一致しない場合は`None`を返す．
+    pub fn with_blocks(next_blocks: &[Block], hold_block: Block) -> Option<BlockQueue> {
+        if next_blocks.len() != NEXT_BLOCK_NUM {
+            return None;
+        }
+        let mut blocks = [Block::default(); NEXT_BLOCK_NUM];
+        blocks.copy_from_slice(next_blocks);
+        Some(Self {
+            next_blocks: NextBlockQueue { blocks },
+            hold_block,
+        })
+    }
+
+    /// Nextブロック列を，次に取り出される順に返す．
+    pub fn next_blocks(&self) -> &[Block] {
+        &self.next_blocks.blocks
+    }
+
     /// Nextブロックキューからひとつブロックを取り出す．
     /// Nextブロックキューには新たなブロックが追加される．
     pub fn pop_and_fill<S: BlockSelector>(&mut self, selector: &mut S) -> Block {
         self.next_blocks.pop_and_fill(selector)
     }
 
+    /// 取り出したブロックをNextブロックキューの先頭に戻す．
+    /// キューの大きさは変わらないので，末尾のブロックを取り除いて返す．
+    pub fn push_front(&mut self, block: Block) -> Block {
+        self.next_blocks.push_front(block)
+    }
+
     /// 現在のHoldブロックを返す．
     pub fn hold_block(&self) -> Block {
         self.hold_block
@@ -80,66 +115,59 @@ impl BlockQueue {
     }
 }
 
+impl BlockQueue {
+    /// Nextブロック列を囲む枠を返す．
+    fn next_frame(&self) -> Frame<'static> {
+        let block_region_size = self.hold_block.region_size();
+        let height = block_region_size.y() * NEXT_BLOCK_NUM as Shift;
+        Frame::new(block_region_size.x() + height)
+            .expect("next blocks fit in a frame")
+            .with_title("Next")
+    }
+
+    /// Holdブロックを囲む枠を返す．
+    fn hold_frame(&self) -> Frame<'static> {
+        Frame::around(&self.hold_block)
+            .expect("hold block fits in a frame")
+            .with_title("Hold")
+    }
+}
+
+/// Nextブロック列とHoldブロックを，見出し付きの枠で囲んで上下に並べて描画する．
 impl Drawable for BlockQueue {
     fn region_size(&self) -> Movement {
-        // ブロック用
-        let block_region_size = self.next_blocks.blocks.iter().next().unwrap().region_size();
-        // フィールドの右にnextブロック列とholdブロックを表示するので，
-        let width = block_region_size.x();
-        let y = block_region_size.y();
-        // テキスト表示，Nextブロック2つ，テキスト表示，Holdブロック表示
-        let height = below(1) + y + y + below(1) + y;
-
-        width + height
+        let next_region_size = self.next_frame().region_size();
+        let hold_region_size = self.hold_frame().region_size();
+        next_region_size.x() + next_region_size.y() + hold_region_size.y()
     }
 
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        let p = Pos::origin();
-        // Nextブロック列であることを示すテキスト
-        let s = ColoredStr("Next", CanvasCellColor::new(Color::White, Color::Black));
-        s.draw_on_child(p, canvas);
-        let mut p = p + s.region_size().y();
+        let next_frame = self.next_frame();
+        next_frame.draw(canvas);
         // nextブロック
+        let mut p = Frame::INNER_LEFT_TOP;
         for next_block in self.next_blocks.blocks.iter() {
-            let size = next_block.region_size();
             next_block.draw_on_child(p, canvas);
-            p = p + size.y();
+            p = p + next_block.region_size().y();
         }
-        // Holdブロックであることを示すテキスト
-        let s = ColoredStr("Hold", CanvasCellColor::new(Color::White, Color::Black));
-        s.draw_on_child(p, canvas);
-        let p = p + s.region_size().y();
-        // Holdブロック
-        self.hold_block.draw_on_child(p, canvas);
+        // Nextブロック列の枠の下にHoldブロック
+        let p = Pos::origin() + next_frame.region_size().y();
+        let hold_frame = self.hold_frame();
+        hold_frame.draw_with(&self.hold_block, &mut canvas.child(hold_frame.get_roi(p)));
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::super::QuadrupleBlockShape::*;
-    use super::super::{BlockShape, BombTag};
+    use super::super::QuadrupleBlockShape;
+    use super::super::{BombTag, ScriptEnd, ScriptedBlockSelector};
     use super::*;
 
-    struct QuadrupleBlockGenerator {
-        current_index: usize,
-    }
-
-    impl BlockSelector for QuadrupleBlockGenerator {
-        fn select_block_shape(&mut self) -> BlockShape {
-            let shapes = [O, J, L, Z, S, T, I];
-
-            let shape = shapes[self.current_index % shapes.len()];
-            self.current_index += 1;
-            shape.into()
-        }
-
-        fn select_bomb(&mut self, _: BlockShape) -> BombTag {
-            BombTag::None
-        }
-    }
-
-    fn block_generator() -> QuadrupleBlockGenerator {
-        QuadrupleBlockGenerator { current_index: 0 }
+    fn block_generator() -> ScriptedBlockSelector {
+        let blocks = QuadrupleBlockShape::all()
+            .map(|shape| (shape.into(), BombTag::None))
+            .collect();
+        ScriptedBlockSelector::new(blocks, ScriptEnd::Repeat).unwrap()
     }
 
     #[test]
@@ -172,4 +200,54 @@ mod tests {
             assert_eq!(generator.generate_block(), b);
         }
     }
+
+    #[test]
+    fn test_push_front() {
+        let mut generator = block_generator();
+        let mut queue = BlockQueue::new(&mut generator);
+        let before = queue.clone();
+        let popped = queue.pop_and_fill(&mut generator);
+
+        // 取り出したブロックを戻すと，取り出しで追加されたブロックが取り除かれて元に戻る
+        let removed = queue.push_front(popped);
+        assert_eq!(before, queue);
+        // Nextブロック2つとHoldブロックの後に生成された4番目のブロック
+        let mut expected = block_generator();
+        (0..3).for_each(|_| {
+            expected.generate_block();
+        });
+        assert_eq!(expected.generate_block(), removed);
+    }
+
+    #[test]
+    fn test_draw_frames() {
+        let queue = BlockQueue::new(&mut block_generator());
+        assert_eq!(right(7) + below(19), queue.region_size());
+
+        let mut canvas = RootCanvas::new();
+        queue.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let lines = buffer.lines().collect::<Vec<_>>();
+        // Nextブロック2つ分の枠の下に，Holdブロック1つ分の枠が続く
+        assert_eq!(" +Next------+ ", &lines[0][..14]);
+        assert_eq!(" |", &lines[1][..2]);
+        assert_eq!(" +----------+ ", &lines[11][..14]);
+        assert_eq!(" +Hold------+ ", &lines[12][..14]);
+        assert_eq!(" +----------+ ", &lines[18][..14]);
+        assert!(lines[19].trim().is_empty());
+    }
+
+    #[test]
+    fn test_with_blocks() {
+        let queue = BlockQueue::new(&mut block_generator());
+        assert_eq!(
+            Some(queue.clone()),
+            BlockQueue::with_blocks(queue.next_blocks(), queue.hold_block())
+        );
+        assert_eq!(
+            None,
+            BlockQueue::with_blocks(&queue.next_blocks()[..1], queue.hold_block())
+        );
+    }
 }
@@ -1,7 +1,10 @@
 use super::Cell;
 use crate::geometry::*;
 use crate::graphics::*;
+use lazy_static::lazy_static;
+use std::collections::HashSet;
 use std::ops::Index;
+use std::sync::Mutex;
 
 mod consts {
     /// ブロックの形状を定義するテーブルの一片の長さ．
@@ -10,19 +13,97 @@ mod consts {
     pub const ROTATION_KIND: usize = 4;
 }
 
+pub use block_template::{load_from_str, ParseError, ParseErrorKind, ShapeLibrary};
 use consts::*;
 
+/// 同じ数のセルからなるブロック形状の列挙型を定義する．
+/// あわせて，すべての値を宣言順に並べた`ALL`と`all()`，および`BlockShape`への変換を実装する．
+/// 値を追加したときに`ALL`へ加え忘れないよう，値はこのマクロの呼び出しにだけ書く．
+macro_rules! shape_kind {
+    (
+        $(#[$meta:meta])*
+        $name:ident => $variant:ident {
+            $($(#[$value_meta:meta])* $value:ident,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum $name {
+            $($(#[$value_meta])* $value,)+
+        }
+
+        impl $name {
+            /// すべての形状．
+            pub const ALL: &'static [$name] = &[$($name::$value,)+];
+
+            /// すべての形状を列挙する．
+            pub fn all() -> impl Iterator<Item = $name> {
+                Self::ALL.iter().copied()
+            }
+        }
+
+        impl Into<BlockShape> for $name {
+            fn into(self) -> BlockShape {
+                BlockShape::$variant(self)
+            }
+        }
+    };
+}
+
 /// ブロックの形状を表すタグ．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// 順序は`all()`で列挙される順となり，実行時に定義した形状はあらかじめ定義された形状より後になる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BlockShape {
     Single(SingleBlockShape),
     Double(DoubleBlockShape),
     Triple(TripleBlockShape),
     Quadruple(QuadrupleBlockShape),
     Quintuple(QuintupleBlockShape),
+    Sextuple(SextupleBlockShape),
+    /// `BlockShapeBuilder`で実行時に定義した形状．
+    Custom(CustomBlockShape),
 }
 
 impl BlockShape {
+    /// あらかじめ定義されたすべての形状を，セルの少ない順に列挙する．
+    /// 実行時に定義した形状は含まない．
+    pub fn all() -> impl Iterator<Item = BlockShape> {
+        let singles = SingleBlockShape::all().map(Into::into);
+        let doubles = DoubleBlockShape::all().map(Into::into);
+        let triples = TripleBlockShape::all().map(Into::into);
+        let quadruples = QuadrupleBlockShape::all().map(Into::into);
+        let quintuples = QuintupleBlockShape::all().map(Into::into);
+        let sextuples = SextupleBlockShape::all().map(Into::into);
+        singles
+            .chain(doubles)
+            .chain(triples)
+            .chain(quadruples)
+            .chain(quintuples)
+            .chain(sextuples)
+    }
+
+    /// あらかじめ定義された形状について，`all()`で列挙される順の番号を返す．
+    /// 実行時に定義した形状の場合は`None`を返す．
+    pub fn index(&self) -> Option<usize> {
+        let (kind, index) = match *self {
+            BlockShape::Single(s) => (0, s as usize),
+            BlockShape::Double(s) => (1, s as usize),
+            BlockShape::Triple(s) => (2, s as usize),
+            BlockShape::Quadruple(s) => (3, s as usize),
+            BlockShape::Quintuple(s) => (4, s as usize),
+            BlockShape::Sextuple(s) => (5, s as usize),
+            BlockShape::Custom(_) => return None,
+        };
+        let kind_sizes = [
+            SingleBlockShape::ALL.len(),
+            DoubleBlockShape::ALL.len(),
+            TripleBlockShape::ALL.len(),
+            QuadrupleBlockShape::ALL.len(),
+            QuintupleBlockShape::ALL.len(),
+        ];
+        Some(kind_sizes[..kind].iter().sum::<usize>() + index)
+    }
+
     /// このブロック形状が，空でないセルをいくつ含むか返す．
     pub fn non_empty_cell_count(&self) -> usize {
         match self {
@@ -31,125 +112,225 @@ impl BlockShape {
             BlockShape::Triple(_) => 3,
             BlockShape::Quadruple(_) => 4,
             BlockShape::Quintuple(_) => 5,
+            BlockShape::Sextuple(_) => 6,
+            BlockShape::Custom(s) => s.cell_count,
         }
     }
 }
 
-/// 空でないセル1つからなるブロック形状．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum SingleBlockShape {
-    /// セルひとつからなる形状．
-    O,
+shape_kind! {
+    /// 空でないセル1つからなるブロック形状．
+    SingleBlockShape => Single {
+        /// セルひとつからなる形状．
+        O,
+    }
 }
 
-impl Into<BlockShape> for SingleBlockShape {
-    fn into(self) -> BlockShape {
-        BlockShape::Single(self)
+shape_kind! {
+    /// 空でないセル2つからなるブロック形状．
+    DoubleBlockShape => Double {
+        /// 2つのセルが連続している．
+        ShortI,
     }
 }
 
-/// 空でないセル2つからなるブロック形状．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum DoubleBlockShape {
-    /// 2つのセルが連続している．
-    ShortI,
+shape_kind! {
+    /// 空でないセル3つからなるブロック形状．
+    TripleBlockShape => Triple {
+        /// 3つのセルが連続している．
+        ShortI,
+        /// Lミノの長辺からセルを1つ取り除いた形状．
+        ShortL,
+        /// Jミノの長辺からセルを1つ取り除いた形状．
+        ShortJ,
+    }
 }
 
-impl Into<BlockShape> for DoubleBlockShape {
-    fn into(self) -> BlockShape {
-        BlockShape::Double(self)
+shape_kind! {
+    /// 空でないセル4つからなるブロック形状．
+    QuadrupleBlockShape => Quadruple {
+        /// Oミノ．
+        O,
+        /// Jミノ．
+        J,
+        /// Lミノ．
+        L,
+        /// Zミノ．
+        Z,
+        /// Sミノ．
+        S,
+        /// Tミノ．
+        T,
+        /// Iミノ．
+        I,
     }
 }
 
-/// 空でないセル3つからなるブロック形状．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum TripleBlockShape {
-    /// 3つのセルが連続している．
-    ShortI,
-    /// Lミノの長辺からセルを1つ取り除いた形状．
-    ShortL,
-    /// Jミノの長辺からセルを1つ取り除いた形状．
-    ShortJ,
+shape_kind! {
+    /// 空でないセル5つからなるブロック形状．
+    QuintupleBlockShape => Quintuple {
+        /// Iミノを長くした形状．
+        LongI,
+        /// Lミノの長辺先端にセルを1つ追加した形状．
+        LongL,
+        /// Jミノの長辺先端にセルを1つ追加した形状．
+        LongJ,
+        /// Lミノの短辺先端にセルを1つ追加した形状．
+        LargeL,
+        /// Jミノの短辺先端にセルを1つ追加した形状．
+        LargeJ,
+        /// 上向きのTミノの左端にセルを1つ追加した形状．
+        LongTLeft,
+        /// 上向きのTミノの右端にセルを1つ追加した形状．
+        LongTRight,
+        /// 上向きのTミノの上端にセルを1つ追加した形状．
+        LargeT,
+        /// 上向きのTミノの中央下にセルを1つ追加した形状．
+        Star,
+        /// Oミノの左上にセルを1つ追加した形状．
+        OUpperLeft,
+        /// Oミノの左下にセルを1つ追加した形状．
+        OLowerLeft,
+        /// Zミノの末尾にセルを1つ追加した形状．
+        LongZ,
+        /// Sミノの末尾にセルを1つ追加した形状．
+        LongS,
+        /// Zミノの中心にセルを1つ追加した形状．
+        LargeZ,
+        /// Sミノの中心にセルを1つ追加した形状．
+        LargeS,
+        /// 右向きのTミノとJミノを合わせた形状．
+        JT,
+        /// 右向きのTミノとLミノを合わせた形状．
+        LT,
+    }
 }
 
-impl Into<BlockShape> for TripleBlockShape {
-    fn into(self) -> BlockShape {
-        BlockShape::Triple(self)
+shape_kind! {
+    /// 空でないセル6つからなるブロック形状．
+    /// どの向きでも5x5のテーブルに収まる形状だけを定義する．
+    SextupleBlockShape => Sextuple {
+        /// 横3セル，縦2セルの長方形．
+        Rectangle,
+        /// 5セルの直線の右端の上にセルを1つ追加した形状．
+        LongL,
+        /// 5セルの直線の左端の上にセルを1つ追加した形状．
+        LongJ,
+        /// Tミノの縦棒を3セルに伸ばした形状．
+        LongT,
+        /// 十字形の下側の腕を2セルに伸ばした形状．
+        Cross,
     }
 }
 
-/// 空でないセル4つからなるブロック形状．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum QuadrupleBlockShape {
-    /// Oミノ．
-    O,
-    /// Lミノ．
-    L,
-    /// Jミノ．
-    J,
-    /// Zミノ．
-    Z,
-    /// Sミノ．
-    S,
-    /// Tミノ．
-    T,
-    /// Iミノ．
-    I,
+/// 実行時に定義したブロック形状．`BlockShapeBuilder`で生成する．
+/// ブロックを値として複製できるよう，形状定義テンプレートは`'static`な参照で保持する．
+/// テンプレートは内容ごとに一度だけ確保して共有するので，同じ形状を何度生成しても確保は増えない．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CustomBlockShape {
+    /// 向きごとの形状定義テンプレート．
+    tables: &'static CellTagTableCollection,
+    /// 空でないセルの数．
+    cell_count: usize,
 }
 
-impl Into<BlockShape> for QuadrupleBlockShape {
+impl Into<BlockShape> for CustomBlockShape {
     fn into(self) -> BlockShape {
-        BlockShape::Quadruple(self)
+        BlockShape::Custom(self)
     }
 }
 
-/// 空でないセル5つからなるブロック形状．
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum QuintupleBlockShape {
-    /// Iミノを長くした形状．
-    LongI,
-    /// Lミノの長辺先端にセルを1つ追加した形状．
-    LongL,
-    /// Jミノの長辺先端にセルを1つ追加した形状．
-    LongJ,
-    /// Lミノの短辺先端にセルを1つ追加した形状．
-    LargeL,
-    /// Jミノの短辺先端にセルを1つ追加した形状．
-    LargeJ,
-    /// 上向きのTミノの左端にセルを1つ追加した形状．
-    LongTLeft,
-    /// 上向きのTミノの右端にセルを1つ追加した形状．
-    LongTRight,
-    /// 上向きのTミノの上端にセルを1つ追加した形状．
-    LargeT,
-    /// 上向きのTミノの中央下にセルを1つ追加した形状．
-    Star,
-    /// Oミノの左上にセルを1つ追加した形状．
-    OUpperLeft,
-    /// Oミノの左下にセルを1つ追加した形状．
-    OLowerLeft,
-    /// Zミノの末尾にセルを1つ追加した形状．
-    LongZ,
-    /// Sミノの末尾にセルを1つ追加した形状．
-    LongS,
-    /// Zミノの中心にセルを1つ追加した形状．
-    LargeZ,
-    /// Sミノの中心にセルを1つ追加した形状．
-    LargeS,
-    /// 右向きのTミノとJミノを合わせた形状．
-    JT,
-    /// 右向きのTミノとLミノを合わせた形状．
-    LT,
+/// ブロック形状を実行時に定義する．
+///
+/// `Above`向きの形状を5x5のテーブルで指定すると，残りの向きはテーブルの中央のセルを中心に時計回りに回転させて生成する．
+/// 空でないセルには，`Above`向きのテーブルを行優先順に走査した順に0から始まるラベルを関連付ける．
+/// 回転時の位置補正には，Tミノなどと同じ標準的なキックを利用する．
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BlockShapeBuilder {
+    /// `Above`向きの形状．`grid[y][x]`が`true`の位置のセルは空でない．
+    grid: [[bool; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE],
+    /// テーブルの範囲外の位置を指定された場合は`true`．
+    out_of_range: bool,
 }
 
-impl Into<BlockShape> for QuintupleBlockShape {
-    fn into(self) -> BlockShape {
-        BlockShape::Quintuple(self)
+impl BlockShapeBuilder {
+    /// 空でないセルを含まないビルダーを返す．
+    pub fn new() -> BlockShapeBuilder {
+        Self::default()
+    }
+
+    /// `Above`向きの形状を表すテーブルからビルダーを返す．
+    /// `grid[y][x]`が`true`の位置のセルは空でない．
+    pub fn from_grid(grid: [[bool; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE]) -> BlockShapeBuilder {
+        Self {
+            grid,
+            out_of_range: false,
+        }
+    }
+
+    /// `Above`向きのテーブルで，左から`x`番目，上から`y`番目のセルを空でないセルにする．
+    /// テーブルの範囲外の位置を指定した場合，`build`は`None`を返す．
+    pub fn occupy(mut self, x: usize, y: usize) -> BlockShapeBuilder {
+        match self.grid.get_mut(y).and_then(|row| row.get_mut(x)) {
+            Some(occupied) => *occupied = true,
+            None => self.out_of_range = true,
+        }
+        self
+    }
+
+    /// 指定した形状を生成する．
+    /// # Returns
+    /// 空でないセルがひとつもない場合や，テーブルの範囲外の位置を指定していた場合は`None`を返す．
+    pub fn build(&self) -> Option<BlockShape> {
+        if self.out_of_range {
+            return None;
+        }
+
+        let mut above = [[CellTag::Empty; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE];
+        let mut cell_count = 0;
+        for (source_row, target_row) in self.grid.iter().zip(above.iter_mut()) {
+            for (&occupied, target) in source_row.iter().zip(target_row.iter_mut()) {
+                if occupied {
+                    *target = CellTag::Occupied(cell_count);
+                    cell_count += 1;
+                }
+            }
+        }
+        if cell_count == 0 {
+            return None;
+        }
+
+        // テーブルには`Right,Below,Left,Above`の順に格納する
+        let right = rotate_table_clockwise(&above);
+        let below = rotate_table_clockwise(&right);
+        let left = rotate_table_clockwise(&below);
+        let tables = CellTagTableCollection {
+            tables: [right, below, left, above],
+            default_bomb: None,
+        };
+        let shape = CustomBlockShape {
+            tables: intern_cell_tag_collection(tables),
+            cell_count,
+        };
+        Some(shape.into())
     }
 }
 
+/// テーブルを，中央のセルを中心に時計回りに90度回転させたテーブルを返す．
+fn rotate_table_clockwise(table: &Table<CellTag>) -> Table<CellTag> {
+    let mut rotated = [[CellTag::Empty; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE];
+    for (y, row) in table.iter().enumerate() {
+        for (x, &tag) in row.iter().enumerate() {
+            // 下向きを正とする座標系では，(x, y)は(-y, x)へ移る
+            rotated[x][BLOCK_TABLE_SIZE - 1 - y] = tag;
+        }
+    }
+    rotated
+}
+
 /// ボムセルの位置を表す．
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BombTag {
     /// ボムセルはない．すべての空でないセルは通常のセル．
     None,
@@ -157,11 +338,86 @@ pub enum BombTag {
     Single(usize),
     /// すべての空でないセルがボムセルである．
     All,
+    /// 空でないセルの中に複数のボムセルがあり，それぞれのセルは集合に含まれるラベルと関連付けられている．
+    Many(BombLabels),
+}
+
+impl BombTag {
+    /// 指定した形状のブロックに付けられるボム指定の場合は`true`を返す．
+    /// ボムセルのラベルは，形状の空でないセルの数より小さくなければならない．
+    pub fn is_valid_for(&self, shape: BlockShape) -> bool {
+        let count = shape.non_empty_cell_count();
+        match *self {
+            BombTag::Single(label) => label < count,
+            BombTag::Many(labels) => labels.iter().all(|label| label < count),
+            BombTag::None | BombTag::All => true,
+        }
+    }
+
+    /// 指定したラベルと関連付けられたセルがボムセルとなる場合は`true`を返す．
+    pub fn is_bomb(&self, label: usize) -> bool {
+        match *self {
+            BombTag::None => false,
+            BombTag::Single(j) => label == j,
+            BombTag::Many(labels) => labels.contains(label),
+            BombTag::All => true,
+        }
+    }
+}
+
+/// 複数のボムセルと関連付けるラベルの集合を表す．
+/// ブロックと同様に値として複製できるよう，ラベルの有無をビットで保持する．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BombLabels {
+    /// `i`番目のビットが立っていれば，ラベル`i`を含む．
+    bits: u32,
+}
+
+impl BombLabels {
+    /// 集合に含められるラベルの最大値．
+    pub const MAX_LABEL: usize = 31;
+
+    /// 指定したラベルからなる集合を返す．
+    /// # Returns
+    /// ラベルが重複している場合や，`MAX_LABEL`より大きいラベルがある場合は`None`を返す．
+    pub fn new(labels: &[usize]) -> Option<BombLabels> {
+        let mut bits = 0u32;
+        for &label in labels {
+            if label > Self::MAX_LABEL || bits & (1 << label) != 0 {
+                return None;
+            }
+            bits |= 1 << label;
+        }
+        Some(Self { bits })
+    }
+
+    /// 指定したラベルを含む場合は`true`を返す．
+    pub fn contains(&self, label: usize) -> bool {
+        label <= Self::MAX_LABEL && self.bits & (1 << label) != 0
+    }
+
+    /// 含むラベルの数を返す．
+    pub const fn len(&self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    /// ラベルをひとつも含まない場合は`true`を返す．
+    pub const fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// 含むラベルを昇順に列挙する．
+    pub fn iter(&self) -> impl Iterator<Item = usize> {
+        let bits = self.bits;
+        (0..=Self::MAX_LABEL).filter(move |&label| bits & (1 << label) != 0)
+    }
 }
 
 /// ブロックの方向を表す．
+/// 生成された直後のブロックは`Above`を向いている．
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
     Left,
     Below,
     Right,
@@ -169,6 +425,27 @@ enum Direction {
 }
 
 impl Direction {
+    /// すべての向き．
+    pub const ALL: [Direction; 4] = [
+        Direction::Left,
+        Direction::Below,
+        Direction::Right,
+        Direction::Above,
+    ];
+
+    /// 向きごとに定義されたテーブルにおける，この向きのインデックスを返す．
+    /// テーブルは`Right,Below,Left,Above`の順に格納される．
+    fn table_index(self) -> usize {
+        use Direction::*;
+
+        match self {
+            Right => 0,
+            Below => 1,
+            Left => 2,
+            Above => 3,
+        }
+    }
+
     fn rotate_clockwise(self) -> Direction {
         use Direction::*;
 
@@ -192,8 +469,15 @@ impl Direction {
     }
 }
 
-/// ブロック形状テンプレートに利用するタグ．
+/// ブロックの回転方向を表す．
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Clockwise,
+    Unticlockwise,
+}
+
+/// ブロック形状テンプレートに利用するタグ．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum CellTag {
     /// このセルが空でないことを表す．
     /// また，0から始まる互いに異なるラベルが関連付けられる．
@@ -211,13 +495,24 @@ pub trait BlockSelector {
     fn select_bomb(&mut self, shape: BlockShape) -> BombTag;
 
     /// ブロックを生成して返す．
+    /// # Panics on debug build
+    /// 1. `select_bomb`が，選んだ形状の空でないセルの数以上のラベルを返した場合．
     fn generate_block(&mut self) -> Block {
         let shape = self.select_block_shape();
         let bomb = self.select_bomb(shape);
-        // 形状に合致したテーブルを取得
-        let table = block_template::get_cell_tag_collection(shape);
-        // テーブルをブロックに変換
-        Block::new(table, Direction::Above, bomb)
+        Block::new(shape, Direction::Above, bomb)
+    }
+}
+
+/// 可変参照を通して，参照先の生成ルールでブロックを生成する．
+/// ゲームに渡した後も，生成ルールの状態を呼び出し側で参照できる．
+impl<S: BlockSelector + ?Sized> BlockSelector for &mut S {
+    fn select_block_shape(&mut self) -> BlockShape {
+        (**self).select_block_shape()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        (**self).select_bomb(shape)
     }
 }
 
@@ -225,34 +520,72 @@ pub trait BlockSelector {
 type Table<T> = [[T; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE];
 
 /// ブロックの方向ごとにブロックの形状を定義する．
-/// 内部は4要素の配列からなり，先頭から順に`Right,Below,Left,Above`の順に形状データが格納される．
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct CellTagTableCollection([Table<CellTag>; ROTATION_KIND]);
+/// 形状に既定のボム指定がある場合は，それも合わせて保持する．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct CellTagTableCollection {
+    /// 向きごとの形状データ．先頭から順に`Right,Below,Left,Above`の順に格納される．
+    tables: [Table<CellTag>; ROTATION_KIND],
+    /// この形状のブロックに既定で付けるボム指定．
+    default_bomb: Option<BombTag>,
+}
 
 impl Index<Direction> for CellTagTableCollection {
     type Output = Table<CellTag>;
 
     fn index(&self, d: Direction) -> &Self::Output {
-        use Direction::*;
+        &self.tables[d.table_index()]
+    }
+}
 
-        let index = match d {
-            Right => 0,
-            Below => 1,
-            Left => 2,
-            Above => 3,
-        };
-        &self.0[index]
+lazy_static! {
+    /// これまでに確保した形状定義テンプレート．
+    static ref INTERNED_CELL_TAG_COLLECTIONS: Mutex<HashSet<&'static CellTagTableCollection>> =
+        Mutex::new(HashSet::new());
+}
+
+/// 指定した形状定義テンプレートと同じ内容の，`'static`なテンプレートを返す．
+/// 同じ内容のテンプレートがすでに確保されていればそれを返し，なければ新たに確保する．
+fn intern_cell_tag_collection(tables: CellTagTableCollection) -> &'static CellTagTableCollection {
+    let mut interned = INTERNED_CELL_TAG_COLLECTIONS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    match interned.get(&tables) {
+        Some(&tables) => tables,
+        None => {
+            let tables = Box::leak(Box::new(tables));
+            interned.insert(tables);
+            tables
+        }
+    }
+}
+
+/// ブロックを回転させるときに試す位置補正(キック)を，回転前の向きと回転方向ごとに定義する．
+/// 各候補は`(右方向の移動量, 下方向の移動量)`で表され，先頭から順に試される．
+/// 内部の配列には`Right,Below,Left,Above`の順に，その向きからの回転で試す候補が格納される．
+#[derive(Debug)]
+struct KickTable {
+    clockwise: [&'static [(i8, i8)]; ROTATION_KIND],
+    unticlockwise: [&'static [(i8, i8)]; ROTATION_KIND],
+}
+
+impl KickTable {
+    /// 指定した向きから指定した方向へ回転させるときに試す候補を返す．
+    fn offsets(&self, from: Direction, rotation: Rotation) -> &'static [(i8, i8)] {
+        match rotation {
+            Rotation::Clockwise => self.clockwise[from.table_index()],
+            Rotation::Unticlockwise => self.unticlockwise[from.table_index()],
+        }
     }
 }
 
 /// ブロックを表す．
+/// ハッシュ値は`Block::state_key`から求める．
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Block {
     /// このブロックを構成するセル．
     cells: Table<Cell>,
-    /// このブロックを生成するために利用した形状テーブル．
-    /// ブロックの回転処理に利用される．
-    tables: &'static CellTagTableCollection,
+    /// このブロックの形状．
+    shape: BlockShape,
     /// このブロックの方向．
     /// ブロックの回転処理に利用される．
     direction: Direction,
@@ -262,20 +595,62 @@ pub struct Block {
 
 impl Block {
     /// ブロックを生成して返す．
-    fn new(
-        tables: &'static CellTagTableCollection,
-        direction: Direction,
-        bomb_tag: BombTag,
-    ) -> Block {
+    /// # Panics on debug build
+    /// 1. `bomb_tag`のボムセルのラベルが，`shape`の空でないセルの数以上の場合．
+    pub fn new(shape: BlockShape, direction: Direction, bomb_tag: BombTag) -> Block {
+        debug_assert!(
+            bomb_tag.is_valid_for(shape),
+            "bomb tag {:?} refers to a label out of {} cells of {:?}",
+            bomb_tag,
+            shape.non_empty_cell_count(),
+            shape
+        );
+        let tables = block_template::get_cell_tag_collection(shape);
         let cells = Self::generate_cells(tables, direction, bomb_tag);
         Self {
             cells,
-            tables,
+            shape,
             direction,
             bomb_tag,
         }
     }
 
+    /// このブロックの形状を返す．
+    pub const fn shape(&self) -> BlockShape {
+        self.shape
+    }
+
+    /// このブロックの現在の向きを返す．
+    pub const fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// このブロックのボムセルの数や位置を返す．
+    pub const fn bomb_tag(&self) -> BombTag {
+        self.bomb_tag
+    }
+
+    /// 形状，向き，ボム指定を詰めた整数を返す．
+    /// AIの探索などで，同じ状態のブロックを安く見分けるために利用する．
+    ///
+    /// 上位8ビットに`BlockShape::index`を，続く2ビットに向きを，下位22ビットにボムセルとなるラベルの集合を格納する．
+    /// キーはセルテーブルや形状定義テンプレートの参照先に依存しないので，実行のたびに同じ値となる．
+    /// 形状，向き，ボム指定が等しいブロックは等しいキーを持つ．
+    /// ただし，実行時に定義した形状はすべて同じ番号として扱い，ラベルが22以上のボムセルはキーに含めないので，
+    /// そのようなブロックでは異なる状態でもキーが一致しうる．
+    pub fn state_key(&self) -> u32 {
+        const BOMB_BITS: usize = 22;
+        const DIRECTION_BITS: usize = 2;
+
+        let shape = self.shape.index().map_or(u8::MAX as u32, |i| i as u32);
+        let direction = self.direction.table_index() as u32;
+        let cell_count = std::cmp::min(self.shape.non_empty_cell_count(), BOMB_BITS);
+        let bombs = (0..cell_count)
+            .filter(|&label| self.bomb_tag.is_bomb(label))
+            .fold(0, |bits, label| bits | 1 << label);
+        shape << (BOMB_BITS + DIRECTION_BITS) | direction << BOMB_BITS | bombs
+    }
+
     /// ブロックのセルテーブルのサイズを返す．
     /// ブロックを構成するセルは正方形状に配置されており，このメソッドはその正方形のサイズを返す．
     pub const fn cell_table_size(&self) -> usize {
@@ -298,16 +673,64 @@ impl Block {
         })
     }
 
+    /// このブロックの空でないセルをすべて含む最小の長方形領域を，セルテーブル上の座標で返す．
+    /// 空でないセルがない場合は，原点に位置する大きさ0の領域を返す．
+    pub fn occupied_extent(&self) -> RegionOfInterest {
+        let positions = self
+            .iter_pos_and_occupied_cell()
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
+        let min_x = positions.iter().map(|p| p.x()).min();
+        let min_y = positions.iter().map(|p| p.y()).min();
+        let max_x = positions.iter().map(|p| p.x()).max();
+        let max_y = positions.iter().map(|p| p.y()).max();
+        match (min_x, min_y, max_x, max_y) {
+            (Some(min_x), Some(min_y), Some(max_x), Some(max_y)) => {
+                let left_top = Pos(min_x, min_y);
+                let size = (Pos(max_x, max_y) - left_top) + right(1) + below(1);
+                RegionOfInterest::new(left_top, size)
+            }
+            _ => RegionOfInterest::new(Pos::origin(), right(0) + below(0)),
+        }
+    }
+
+    /// このブロックの空でないセルが占める列の数を返す．
+    pub fn occupied_width(&self) -> usize {
+        self.occupied_extent().size.x().as_positive_index().unwrap()
+    }
+
+    /// このブロックの空でないセルが占める行の数を返す．
+    pub fn occupied_height(&self) -> usize {
+        self.occupied_extent().size.y().as_positive_index().unwrap()
+    }
+
     /// このブロックを時計回りに90度回転させたブロックを返す．
     pub fn rotate_clockwise(&self) -> Block {
         let direction = self.direction.rotate_clockwise();
-        Self::new(self.tables, direction, self.bomb_tag)
+        Self::new(self.shape, direction, self.bomb_tag)
     }
 
     /// このブロックを反時計回りに90度回転させたブロックを返す．
     pub fn rotate_unticlockwise(&self) -> Block {
         let direction = self.direction.rotate_unticlockwise();
-        Self::new(self.tables, direction, self.bomb_tag)
+        Self::new(self.shape, direction, self.bomb_tag)
+    }
+
+    /// このブロックを指定した方向に90度回転させたブロックを返す．
+    pub fn rotate(&self, rotation: Rotation) -> Block {
+        match rotation {
+            Rotation::Clockwise => self.rotate_clockwise(),
+            Rotation::Unticlockwise => self.rotate_unticlockwise(),
+        }
+    }
+
+    /// このブロックを現在の向きから指定した方向へ回転させるときに，位置補正として試す移動量を返す．
+    /// 移動量は試すべき順に並び，先頭は常に移動なしである．
+    pub fn kick_offsets(&self, rotation: Rotation) -> impl Iterator<Item = Movement> {
+        block_template::get_kick_table(self.shape)
+            .offsets(self.direction, rotation)
+            .iter()
+            .map(|&(x, y)| right(x) + below(y))
     }
 
     /// 指定した条件に合致したセルテーブルを返す．
@@ -320,17 +743,8 @@ impl Block {
         for (source_row, target_row) in tables[direction].iter().zip(cells.iter_mut()) {
             for (&source, target) in source_row.iter().zip(target_row.iter_mut()) {
                 *target = match source {
-                    CellTag::Occupied(i) => match bomb_tag {
-                        BombTag::All => Cell::Bomb,
-                        BombTag::None => Cell::Normal,
-                        BombTag::Single(j) => {
-                            if i == j {
-                                Cell::Bomb
-                            } else {
-                                Cell::Normal
-                            }
-                        }
-                    },
+                    CellTag::Occupied(i) if bomb_tag.is_bomb(i) => Cell::Bomb,
+                    CellTag::Occupied(_) => Cell::Normal,
                     CellTag::Empty => Cell::Empty,
                 };
             }
@@ -341,8 +755,25 @@ impl Block {
 
 impl Default for Block {
     fn default() -> Self {
-        let tables = block_template::get_cell_tag_collection(SingleBlockShape::O.into());
-        Self::new(tables, Direction::Above, BombTag::None)
+        Self::new(SingleBlockShape::O.into(), Direction::Above, BombTag::None)
+    }
+}
+
+impl std::hash::Hash for Block {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u32(self.state_key());
+    }
+}
+
+/// セルテーブルの1行を1行とし，各セルを`Cell::as_ascii`の文字で表した文字列として書き出す．
+/// 回転や位置補正を調べるときに，セルテーブルを読みやすく表示するためのもの．
+impl std::fmt::Display for Block {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.iter() {
+            let line = row.iter().map(|cell| cell.as_ascii()).collect::<String>();
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
     }
 }
 
@@ -358,988 +789,713 @@ impl Drawable for Block {
     }
 }
 
-/// ブロックの形状を定義するテンプレートを詰めたモジュール．
+/// ブロック形状テンプレートの不備を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateError {
+    /// 不備のあったブロック形状．
+    pub shape: BlockShape,
+    /// 不備の内容．
+    pub kind: TemplateErrorKind,
+}
+
+/// ブロック形状テンプレートの不備の内容を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateErrorKind {
+    /// ある向きのテーブルに含まれる空でないセルの数が，形状のセル数と一致しない．
+    WrongCellCount { expected: usize, actual: usize },
+    /// ある向きのテーブルのラベルが，0から始まる重複のない連番になっていない．
+    InvalidLabels { labels: Vec<usize> },
+    /// ある向きの空でないセルの配置を時計回りに回転させても，平行移動を除いて次の向きの配置と一致しない．
+    RotationMismatch { from: Direction },
+    /// 既定のボム指定に，形状の空でないセルの数以上のラベルが含まれる．
+    InvalidDefaultBomb { bomb: BombTag },
+}
+
+/// 定義済みのすべてのブロック形状テンプレートを検査する．
+/// 検査の内容は`validate_shape`と同じ．
+/// # Returns
+/// 見つかった不備．不備がない場合は空の`Vec`を返す．
+pub fn validate_templates() -> Vec<TemplateError> {
+    BlockShape::all().flat_map(validate_shape).collect()
+}
+
+/// 指定したブロック形状の形状定義テンプレートを検査する．
+/// 各向きのテーブルについて，空でないセルの数が`BlockShape::non_empty_cell_count()`と一致し，
+/// それらのラベルが`0..n`を重複なく網羅していることを確かめる．
+/// また，各向きの空でないセルの配置を時計回りに回転させると，平行移動を除いて次の向きの配置と一致することを確かめる．
+/// 既定のボム指定がある場合は，そのラベルが形状の空でないセルの数より小さいことを確かめる．
+/// # Returns
+/// 見つかった不備．不備がない場合は空の`Vec`を返す．
+pub fn validate_shape(shape: BlockShape) -> Vec<TemplateError> {
+    let tables = block_template::get_cell_tag_collection(shape);
+    let mut errors = vec![];
+
+    for table in tables.tables.iter() {
+        let mut labels = table
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter_map(|&tag| match tag {
+                CellTag::Occupied(i) => Some(i),
+                CellTag::Empty => None,
+            })
+            .collect::<Vec<_>>();
+        labels.sort();
+
+        let expected = shape.non_empty_cell_count();
+        if labels.len() != expected {
+            let kind = TemplateErrorKind::WrongCellCount {
+                expected,
+                actual: labels.len(),
+            };
+            errors.push(TemplateError { shape, kind });
+        } else if labels.iter().enumerate().any(|(i, &label)| i != label) {
+            let kind = TemplateErrorKind::InvalidLabels { labels };
+            errors.push(TemplateError { shape, kind });
+        }
+    }
+
+    for &from in Direction::ALL.iter() {
+        let rotated = rotate_table_clockwise(&tables[from]);
+        if normalized_positions(&rotated) != normalized_positions(&tables[from.rotate_clockwise()])
+        {
+            let kind = TemplateErrorKind::RotationMismatch { from };
+            errors.push(TemplateError { shape, kind });
+        }
+    }
+
+    if let Some(bomb) = tables.default_bomb {
+        if !bomb.is_valid_for(shape) {
+            let kind = TemplateErrorKind::InvalidDefaultBomb { bomb };
+            errors.push(TemplateError { shape, kind });
+        }
+    }
+
+    errors
+}
+
+/// 指定したブロック形状に既定で付けるボム指定を返す．
+/// 既定のボム指定は，形状定義ファイルの`bomb`の行で指定する．
+/// # Returns
+/// 既定のボム指定がない形状の場合は`None`を返す．
+pub fn default_bomb(shape: BlockShape) -> Option<BombTag> {
+    block_template::get_cell_tag_collection(shape).default_bomb
+}
+
+/// テーブルの空でないセルの位置を，空でないセルが占める範囲の左上が原点となるよう平行移動して返す．
+fn normalized_positions(table: &Table<CellTag>) -> Vec<(usize, usize)> {
+    let positions = table
+        .iter()
+        .enumerate()
+        .flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, &tag)| tag != CellTag::Empty)
+                .map(move |(x, _)| (x, y))
+        })
+        .collect::<Vec<_>>();
+    let min_x = positions.iter().map(|&(x, _)| x).min().unwrap_or(0);
+    let min_y = positions.iter().map(|&(_, y)| y).min().unwrap_or(0);
+    positions
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect()
+}
+
+/// ブロックの形状を定義するテンプレートを読み込むモジュール．
+/// 組み込みの形状は，埋め込んだ形状定義ファイル`shapes.txt`から読み込む．
 mod block_template {
-    use super::BlockShape;
-    use super::CellTag;
-    use super::CellTagTableCollection;
-    use super::{
-        DoubleBlockShape, QuadrupleBlockShape, QuintupleBlockShape, SingleBlockShape,
-        TripleBlockShape,
-    };
+    use super::consts::{BLOCK_TABLE_SIZE, ROTATION_KIND};
+    use super::{rotate_table_clockwise, Table};
+    use super::{validate_shape, TemplateErrorKind};
+    use super::{BlockShape, CellTag, CellTagTableCollection, CustomBlockShape, Direction};
+    use super::{BombLabels, BombTag, KickTable, QuadrupleBlockShape, QuintupleBlockShape};
     use lazy_static::lazy_static;
     use std::collections::HashMap;
+    use std::io;
+    use std::path::Path;
+
+    /// 組み込みのブロック形状を定義したファイルの内容．
+    const DEFAULT_SHAPES: &str = include_str!("shapes.txt");
+
+    /// 形状定義ファイルに書ける，向きの名前と向きの組．
+    const DIRECTION_NAMES: [(&str, Direction); ROTATION_KIND] = [
+        ("right", Direction::Right),
+        ("below", Direction::Below),
+        ("left", Direction::Left),
+        ("above", Direction::Above),
+    ];
+
+    /// 形状定義ファイルから読み込んだ，名前付きのブロック形状の一覧．
+    /// 各形状は`BlockShape::Custom`として保持する．
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ShapeLibrary {
+        /// ファイルに書かれた順の，形状の名前と形状．
+        shapes: Vec<(String, BlockShape)>,
+    }
 
-    /// 文字数を短くそろえるためだけの定数．
-    const EM: CellTag = CellTag::Empty;
-    const O0: CellTag = CellTag::Occupied(0);
-    const O1: CellTag = CellTag::Occupied(1);
-    const O2: CellTag = CellTag::Occupied(2);
-    const O3: CellTag = CellTag::Occupied(3);
-    const O4: CellTag = CellTag::Occupied(4);
+    impl ShapeLibrary {
+        /// 組み込みのブロック形状の一覧を返す．
+        /// 名前は`Quadruple(T)`のように，組み込みの形状を`Debug`で書き出したものとなる．
+        pub fn builtin() -> &'static ShapeLibrary {
+            &DEFAULT_LIBRARY
+        }
 
-    lazy_static! {
-        static ref SINGLE_CELL_TAG_COLLECTION: HashMap<SingleBlockShape, CellTagTableCollection> = {
-            let mut map = HashMap::new();
-            let o = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-            map.insert(SingleBlockShape::O, o);
-            map
-        };
-        static ref DOUBLE_CELL_TAG_COLLECTION: HashMap<DoubleBlockShape, CellTagTableCollection> = {
-            let mut map = HashMap::new();
-            let short_i = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O1, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-            map.insert(DoubleBlockShape::ShortI, short_i);
-            map
-        };
-        static ref TRIPLE_CELL_TAG_COLLECTION: HashMap<TripleBlockShape, CellTagTableCollection> = {
-            let mut map = HashMap::new();
-            let short_i = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O2, O1, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let short_l = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, O2, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O1, O0, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, O2, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let short_j = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O2, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O1, O0, EM, EM],
-                    [EM, O2, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O2, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            map.insert(TripleBlockShape::ShortI, short_i);
-            map.insert(TripleBlockShape::ShortL, short_l);
-            map.insert(TripleBlockShape::ShortJ, short_j);
-
-            map
-        };
-        static ref QUADRUPLE_CELL_TAG_COLLECTION: HashMap<QuadrupleBlockShape, CellTagTableCollection> = {
-            let mut map = HashMap::new();
-
-            let o = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, O2, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, O3, EM],
-                    [EM, EM, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O1, EM],
-                    [EM, EM, O3, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let z = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, EM, O0, O2, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O1, O0, EM, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, O2, O0, EM, EM],
-                    [EM, O3, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, EM, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let s = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, O2, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, O3, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, EM, EM, EM],
-                    [EM, O2, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, O1, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let j = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, O3, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, EM, EM, EM],
-                    [EM, O2, O1, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let l = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O2, O1, O0, EM],
-                    [EM, O3, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let t = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, O3, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O2, O1, O0, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, O3, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let i = CellTagTableCollection([
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, O1, O0],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, O2, O3],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            map.insert(QuadrupleBlockShape::O, o);
-            map.insert(QuadrupleBlockShape::Z, z);
-            map.insert(QuadrupleBlockShape::S, s);
-            map.insert(QuadrupleBlockShape::J, j);
-            map.insert(QuadrupleBlockShape::L, l);
-            map.insert(QuadrupleBlockShape::T, t);
-            map.insert(QuadrupleBlockShape::I, i);
-
-            map
+        /// 指定した形状定義ファイルから読み込む．
+        /// ファイルの形式は`load_from_str`を参照．
+        pub fn load<P: AsRef<Path>>(path: P) -> io::Result<ShapeLibrary> {
+            let text = std::fs::read_to_string(path)?;
+            load_from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        }
+
+        /// 形状の数を返す．
+        pub fn len(&self) -> usize {
+            self.shapes.len()
+        }
+
+        /// 形状をひとつも含まない場合は`true`を返す．
+        pub fn is_empty(&self) -> bool {
+            self.shapes.is_empty()
+        }
+
+        /// 指定した名前の形状を返す．
+        pub fn get(&self, name: &str) -> Option<BlockShape> {
+            self.shapes
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|&(_, shape)| shape)
+        }
+
+        /// 形状の名前を，ファイルに書かれた順に列挙する．
+        pub fn names(&self) -> impl Iterator<Item = &str> + '_ {
+            self.shapes.iter().map(|(name, _)| name.as_str())
+        }
+
+        /// 形状を，ファイルに書かれた順に列挙する．
+        pub fn shapes(&self) -> impl Iterator<Item = BlockShape> + '_ {
+            self.shapes.iter().map(|&(_, shape)| shape)
+        }
+    }
+
+    /// 形状定義ファイルの読み込みに失敗した理由を表す．
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ParseError {
+        /// 問題のあった行の番号．1から始まる．
+        pub line: usize,
+        /// 問題の内容．
+        pub kind: ParseErrorKind,
+    }
+
+    /// 形状定義ファイルの問題の内容を表す．
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub enum ParseErrorKind {
+        /// 形状の名前，向きの名前，テーブルのいずれとしても解釈できない行がある．
+        UnexpectedLine(String),
+        /// 形状の名前が空．
+        EmptyName,
+        /// 同じ名前の形状が複数ある．
+        DuplicateShape(String),
+        /// ひとつの形状に同じ向きのテーブルが複数ある．
+        DuplicateDirection(Direction),
+        /// 向きの名前に続くテーブルの行数が足りない．
+        TooFewRows { actual: usize },
+        /// テーブルの行の長さが正しくない．
+        WrongRowLength { actual: usize },
+        /// テーブルに`.`と数字以外の文字がある．
+        InvalidChar(char),
+        /// ひとつのテーブルの中で，同じラベルが複数のセルに付いている．
+        DuplicateLabel(usize),
+        /// テーブルのラベルが，0から始まる連番になっていない．
+        NonContiguousLabels(Vec<usize>),
+        /// 向きによって空でないセルの数が異なる．
+        CellCountMismatch { expected: usize, actual: usize },
+        /// 空でないセルがひとつもない．
+        NoCells,
+        /// `above`のテーブルがない．
+        MissingAbove,
+        /// 指定した向きの空でないセルの配置を時計回りに回転させても，平行移動を除いて次の向きの配置と一致しない．
+        RotationMismatch(Direction),
+        /// ひとつの形状に`bomb`の行が複数ある．
+        DuplicateBomb,
+        /// `bomb`の行を，既定のボム指定として解釈できない．
+        InvalidBomb(String),
+        /// 既定のボム指定に，形状の空でないセルの数以上のラベルが含まれる．
+        InvalidDefaultBomb(BombTag),
+    }
+
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            use ParseErrorKind::*;
+
+            write!(f, "line {}: ", self.line)?;
+            match &self.kind {
+                UnexpectedLine(line) => write!(f, "unexpected line `{}`", line),
+                EmptyName => write!(f, "shape name is empty"),
+                DuplicateShape(name) => write!(f, "shape `{}` is defined twice", name),
+                DuplicateDirection(d) => write!(f, "direction {:?} is defined twice", d),
+                TooFewRows { actual } => {
+                    write!(f, "grid has {} rows, expected {}", actual, BLOCK_TABLE_SIZE)
+                }
+                WrongRowLength { actual } => write!(
+                    f,
+                    "grid row has {} cells, expected {}",
+                    actual, BLOCK_TABLE_SIZE
+                ),
+                InvalidChar(c) => write!(f, "invalid cell `{}`", c),
+                DuplicateLabel(label) => write!(f, "label {} is used twice", label),
+                NonContiguousLabels(labels) => {
+                    write!(f, "labels {:?} are not contiguous from 0", labels)
+                }
+                CellCountMismatch { expected, actual } => write!(
+                    f,
+                    "grid has {} cells, other directions have {}",
+                    actual, expected
+                ),
+                NoCells => write!(f, "shape has no cells"),
+                MissingAbove => write!(f, "shape has no `above` grid"),
+                RotationMismatch(d) => write!(
+                    f,
+                    "rotating direction {:?} clockwise does not match the next direction",
+                    d
+                ),
+                DuplicateBomb => write!(f, "default bomb is defined twice"),
+                InvalidBomb(spec) => write!(f, "invalid default bomb `{}`", spec),
+                InvalidDefaultBomb(bomb) => {
+                    write!(f, "default bomb {:?} does not fit the shape", bomb)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    /// 形状定義ファイルの内容から，ブロック形状の一覧を読み込む．
+    ///
+    /// 各形状は`shape <名前>`の行から始まり，向きの名前(`right`，`below`，`left`，`above`)の行と，それに続く5行のテーブルを並べる．
+    /// テーブルの`.`は空のセル，数字はそのラベルを持つ空でないセルを表す．
+    /// `above`のテーブルは必ず書く．
+    /// 書かなかった向きのテーブルは，`above`のテーブルをテーブルの中央のセルを中心に時計回りに回転させて生成する．
+    /// 回転の中心がずれる形状のように，生成したテーブルと異なる配置にしたい向きだけテーブルを書けばよい．
+    /// `bomb <ラベル>...`または`bomb all`の行を書くと，その形状の既定のボム指定となる(`default_bomb`を参照)．
+    /// 空行と`#`で始まる行は読み飛ばす．
+    ///
+    /// 読み込んだ形状定義テンプレートは内容ごとに共有されるので，同じ内容を何度読み込んでも確保は増えない．
+    pub fn load_from_str(text: &str) -> Result<ShapeLibrary, ParseError> {
+        let lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .collect::<Vec<_>>();
+
+        let mut shapes: Vec<(String, BlockShape)> = vec![];
+        let mut index = 0;
+        while index < lines.len() {
+            let (line_number, line) = lines[index];
+            let error = |kind| ParseError {
+                line: line_number,
+                kind,
+            };
+            let name = match line.strip_prefix("shape") {
+                Some(name) if name.is_empty() || name.starts_with(' ') => name.trim(),
+                _ => return Err(error(ParseErrorKind::UnexpectedLine(line.to_string()))),
+            };
+            if name.is_empty() {
+                return Err(error(ParseErrorKind::EmptyName));
+            }
+            if shapes.iter().any(|(n, _)| n == name) {
+                return Err(error(ParseErrorKind::DuplicateShape(name.to_string())));
+            }
+            index += 1;
+
+            // 次の形状の定義が始まるまで，向きごとのテーブルと既定のボム指定を読む
+            let mut tables: Vec<(usize, Direction, Table<CellTag>)> = vec![];
+            let mut bomb: Option<(usize, BombTag)> = None;
+            while let Some(&(line_number, line)) = lines.get(index) {
+                if line.starts_with("shape") {
+                    break;
+                }
+                let error = |kind| ParseError {
+                    line: line_number,
+                    kind,
+                };
+                if let Some(spec) = line.strip_prefix("bomb ") {
+                    if bomb.is_some() {
+                        return Err(error(ParseErrorKind::DuplicateBomb));
+                    }
+                    let tag = parse_bomb(spec)
+                        .ok_or_else(|| error(ParseErrorKind::InvalidBomb(spec.to_string())))?;
+                    bomb = Some((line_number, tag));
+                    index += 1;
+                    continue;
+                }
+                let direction = DIRECTION_NAMES
+                    .iter()
+                    .find(|(n, _)| *n == line)
+                    .map(|&(_, d)| d)
+                    .ok_or_else(|| error(ParseErrorKind::UnexpectedLine(line.to_string())))?;
+                if tables.iter().any(|&(_, d, _)| d == direction) {
+                    return Err(error(ParseErrorKind::DuplicateDirection(direction)));
+                }
+                let rows = lines[index + 1..]
+                    .iter()
+                    .take(BLOCK_TABLE_SIZE)
+                    .take_while(|(_, row)| !is_keyword(row))
+                    .copied()
+                    .collect::<Vec<_>>();
+                if rows.len() < BLOCK_TABLE_SIZE {
+                    return Err(error(ParseErrorKind::TooFewRows { actual: rows.len() }));
+                }
+                let table = parse_table(&rows)?;
+                tables.push((line_number, direction, table));
+                index += 1 + BLOCK_TABLE_SIZE;
+            }
+
+            let tables = complete_tables(line_number, tables)?;
+            let shape = CustomBlockShape {
+                cell_count: count_cells(&tables[0]),
+                tables: super::intern_cell_tag_collection(CellTagTableCollection {
+                    tables,
+                    default_bomb: bomb.map(|(_, tag)| tag),
+                }),
+            };
+            let shape = shape.into();
+            // セルの数とラベルは読み込み時に確かめたので，残る不備は回転させたときの配置の不一致と，
+            // 既定のボム指定のラベルが形状に収まらないことだけ
+            let error = validate_shape(shape)
+                .into_iter()
+                .find_map(|error| match error.kind {
+                    TemplateErrorKind::RotationMismatch { from } => {
+                        Some((line_number, ParseErrorKind::RotationMismatch(from)))
+                    }
+                    TemplateErrorKind::InvalidDefaultBomb { bomb: tag } => {
+                        let bomb_line = bomb.map_or(line_number, |(line, _)| line);
+                        Some((bomb_line, ParseErrorKind::InvalidDefaultBomb(tag)))
+                    }
+                    _ => None,
+                });
+            if let Some((line, kind)) = error {
+                return Err(ParseError { line, kind });
+            }
+            shapes.push((name.to_string(), shape));
+        }
+
+        debug_assert!(
+            shapes
+                .iter()
+                .all(|&(_, shape)| validate_shape(shape).is_empty()),
+            "loaded shapes should pass the template validation"
+        );
+        Ok(ShapeLibrary { shapes })
+    }
+
+    /// 形状の名前や向きの名前，既定のボム指定の行であれば`true`を返す．
+    fn is_keyword(line: &str) -> bool {
+        line.starts_with("shape")
+            || line.starts_with("bomb")
+            || DIRECTION_NAMES.iter().any(|&(name, _)| name == line)
+    }
+
+    /// `bomb`に続く，`all`または空白で区切ったラベルの並びを既定のボム指定として読み込む．
+    /// ラベルがひとつの場合は`BombTag::Single`，複数の場合は`BombTag::Many`となる．
+    /// # Returns
+    /// ラベルを読み込めない場合や，ラベルが重複している場合は`None`を返す．
+    fn parse_bomb(spec: &str) -> Option<BombTag> {
+        if spec.trim() == "all" {
+            return Some(BombTag::All);
+        }
+        let labels = spec
+            .split_whitespace()
+            .map(|label| label.parse().ok())
+            .collect::<Option<Vec<usize>>>()?;
+        match labels[..] {
+            [] => None,
+            [label] => Some(BombTag::Single(label)),
+            _ => BombLabels::new(&labels).map(BombTag::Many),
+        }
+    }
+
+    /// 行番号とテーブルの行の組から，テーブルを読み込む．
+    /// ラベルは，テーブルの中で重複がなく，0から始まる連番でなければならない．
+    fn parse_table(rows: &[(usize, &str)]) -> Result<Table<CellTag>, ParseError> {
+        let mut table = [[CellTag::Empty; BLOCK_TABLE_SIZE]; BLOCK_TABLE_SIZE];
+        let mut labels = vec![];
+        for (&(line_number, row), target_row) in rows.iter().zip(table.iter_mut()) {
+            let error = |kind| ParseError {
+                line: line_number,
+                kind,
+            };
+            let chars = row.chars().collect::<Vec<_>>();
+            if chars.len() != BLOCK_TABLE_SIZE {
+                return Err(error(ParseErrorKind::WrongRowLength {
+                    actual: chars.len(),
+                }));
+            }
+            for (&c, target) in chars.iter().zip(target_row.iter_mut()) {
+                *target = match c {
+                    '.' => CellTag::Empty,
+                    c => {
+                        let label = c
+                            .to_digit(10)
+                            .ok_or_else(|| error(ParseErrorKind::InvalidChar(c)))?
+                            as usize;
+                        if labels.contains(&label) {
+                            return Err(error(ParseErrorKind::DuplicateLabel(label)));
+                        }
+                        labels.push(label);
+                        CellTag::Occupied(label)
+                    }
+                };
+            }
+        }
+
+        labels.sort_unstable();
+        if labels.iter().enumerate().any(|(i, &label)| i != label) {
+            return Err(ParseError {
+                line: rows[0].0,
+                kind: ParseErrorKind::NonContiguousLabels(labels),
+            });
+        }
+        Ok(table)
+    }
+
+    /// 読み込んだ向きごとのテーブルを，`Right,Below,Left,Above`の順に並べる．
+    /// 読み込まなかった向きは，`above`のテーブルを回転させて生成する．
+    /// # Params
+    /// 1. `line_number` 形状の名前の行番号．
+    /// 1. `tables` 向きの名前の行番号，向き，テーブルの組．
+    fn complete_tables(
+        line_number: usize,
+        tables: Vec<(usize, Direction, Table<CellTag>)>,
+    ) -> Result<[Table<CellTag>; ROTATION_KIND], ParseError> {
+        let error = |kind| ParseError {
+            line: line_number,
+            kind,
         };
-        static ref QUINTUPLE_CELL_TAG_COLLECTION: HashMap<QuintupleBlockShape, CellTagTableCollection> = {
-            let mut map = HashMap::new();
-
-            let long_i = CellTagTableCollection([
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [O4, O3, O2, O1, O0],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [O0, O1, O2, O3, O4],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_l = CellTagTableCollection([
-                [
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, O1, EM],
-                    [EM, EM, EM, O2, EM],
-                    [EM, EM, EM, O3, O4],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, O1, O0],
-                    [EM, O4, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, O3, EM],
-                    [EM, EM, EM, O2, EM],
-                    [EM, EM, EM, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, O4],
-                    [EM, O0, O1, O2, O3],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_j = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, O3, EM],
-                    [EM, EM, EM, O2, EM],
-                    [EM, EM, EM, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, O4],
-                    [EM, O0, O1, O2, O3],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O3, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, O1, O0],
-                    [EM, O4, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let large_l = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O1, O0],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O4, O3, O2, EM],
-                    [EM, EM, EM, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, O3, O4],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let large_j = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, O1, O0],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O3, O4],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, O3, EM],
-                    [EM, EM, EM, O4, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, O1, EM],
-                    [EM, O4, O3, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_t_left = CellTagTableCollection([
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O2, O4, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, O3, O2, O1, O0],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, O4, O2, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [O0, O1, O2, O3, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_t_right = CellTagTableCollection([
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, O4, O2, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, O3, O2, O1, O0],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, O4, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [O0, O1, O2, O3, EM],
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let large_t = CellTagTableCollection([
-                [
-                    [EM, O0, EM, EM, EM],
-                    [EM, O1, O3, O4, EM],
-                    [EM, O2, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, O2, O1, O0, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, O2, EM],
-                    [EM, O4, O3, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let star = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, O4, O1, O3, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, O2, O1, O0, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, O3, O1, O4, EM],
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, O0, O1, O2, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let o_upper_left = CellTagTableCollection([
-                [
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, O0, O3, EM],
-                    [EM, EM, O1, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, O2, O3, O4],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O1, EM],
-                    [EM, EM, O3, O0, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O4, O3, O2, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let o_lower_left = CellTagTableCollection([
-                [
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, O3, O0, EM],
-                    [EM, EM, O2, O1, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O3, O4],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O2, EM],
-                    [EM, EM, O0, O3, EM],
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O0, O1, EM],
-                    [EM, O4, O3, O2, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_z = CellTagTableCollection([
-                [
-                    [EM, EM, O0, EM, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, O3, O2, EM, EM],
-                    [EM, O4, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O4, O3, EM, EM],
-                    [EM, EM, O2, O1, O0],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, O2, O3, EM],
-                    [EM, EM, O1, EM, EM],
-                    [EM, EM, O0, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [O0, O1, O2, EM, EM],
-                    [EM, EM, O3, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let long_s = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, EM, EM, EM],
-                    [EM, O1, O2, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [O4, O3, O2, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O2, O3, O4],
-                    [EM, O0, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let large_z = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O3, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, O3, O2, O1, EM],
-                    [EM, O4, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O4, O3, EM, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O4, EM],
-                    [EM, O1, O2, O3, EM],
-                    [EM, O0, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let large_s = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, O4, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, O0, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, EM, EM, EM],
-                    [EM, O1, O2, O3, EM],
-                    [EM, EM, EM, O4, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, O2, EM, EM],
-                    [EM, O4, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O4, EM, EM, EM],
-                    [EM, O3, O2, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let jt = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, EM, EM, EM],
-                    [EM, O1, O2, O3, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, O4, O2, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, O3, O2, O1, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, O2, O4, EM],
-                    [EM, O0, O1, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            let lt = CellTagTableCollection([
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, O1, O2, O3, EM],
-                    [EM, O0, EM, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, O0, O1, EM, EM],
-                    [EM, EM, O2, O4, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, EM, O0, EM],
-                    [EM, O3, O2, O1, EM],
-                    [EM, EM, O4, EM, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-                [
-                    [EM, EM, EM, EM, EM],
-                    [EM, EM, O3, EM, EM],
-                    [EM, O4, O2, EM, EM],
-                    [EM, EM, O1, O0, EM],
-                    [EM, EM, EM, EM, EM],
-                ],
-            ]);
-
-            map.insert(QuintupleBlockShape::LongI, long_i);
-            map.insert(QuintupleBlockShape::LongL, long_l);
-            map.insert(QuintupleBlockShape::LongJ, long_j);
-            map.insert(QuintupleBlockShape::LargeL, large_l);
-            map.insert(QuintupleBlockShape::LargeJ, large_j);
-            map.insert(QuintupleBlockShape::LongTLeft, long_t_left);
-            map.insert(QuintupleBlockShape::LongTRight, long_t_right);
-            map.insert(QuintupleBlockShape::LargeT, large_t);
-            map.insert(QuintupleBlockShape::Star, star);
-            map.insert(QuintupleBlockShape::OUpperLeft, o_upper_left);
-            map.insert(QuintupleBlockShape::OLowerLeft, o_lower_left);
-            map.insert(QuintupleBlockShape::LongZ, long_z);
-            map.insert(QuintupleBlockShape::LongS, long_s);
-            map.insert(QuintupleBlockShape::LargeZ, large_z);
-            map.insert(QuintupleBlockShape::LargeS, large_s);
-            map.insert(QuintupleBlockShape::JT, jt);
-            map.insert(QuintupleBlockShape::LT, lt);
-
-            map
+        let above = match tables.iter().find(|&&(_, d, _)| d == Direction::Above) {
+            Some(&(_, _, above)) => above,
+            None => return Err(error(ParseErrorKind::MissingAbove)),
         };
+        let right = rotate_table_clockwise(&above);
+        let below = rotate_table_clockwise(&right);
+        let left = rotate_table_clockwise(&below);
+        let mut completed = [right, below, left, above];
+        // 書いてある向きは，生成したテーブルを置き換える
+        for &(_, direction, table) in tables.iter() {
+            completed[direction.table_index()] = table;
+        }
+
+        let expected = count_cells(&completed[0]);
+        if expected == 0 {
+            return Err(error(ParseErrorKind::NoCells));
+        }
+        for (line_number, _, table) in tables {
+            let actual = count_cells(&table);
+            if actual != expected {
+                return Err(ParseError {
+                    line: line_number,
+                    kind: ParseErrorKind::CellCountMismatch { expected, actual },
+                });
+            }
+        }
+        Ok(completed)
+    }
+
+    /// テーブルに含まれる空でないセルの数を返す．
+    fn count_cells(table: &Table<CellTag>) -> usize {
+        table
+            .iter()
+            .flat_map(|row| row.iter())
+            .filter(|&&tag| tag != CellTag::Empty)
+            .count()
+    }
+
+    lazy_static! {
+        /// 組み込みのブロック形状の一覧．
+        static ref DEFAULT_LIBRARY: ShapeLibrary =
+            load_from_str(DEFAULT_SHAPES).expect("embedded shape file should be valid");
+
+        /// 組み込みのブロック形状と，その形状定義テンプレート．
+        static ref BUILTIN_CELL_TAG_COLLECTION: HashMap<BlockShape, &'static CellTagTableCollection> =
+            BlockShape::all()
+                .map(|shape| {
+                    let name = format!("{:?}", shape);
+                    let tables = match DEFAULT_LIBRARY.get(&name) {
+                        Some(BlockShape::Custom(custom)) => custom.tables,
+                        _ => panic!("embedded shape file should define {}", name),
+                    };
+                    (shape, tables)
+                })
+                .collect();
+    }
+
+    /// 定義済みのすべてのブロック形状と，その形状定義テンプレートを列挙する．
+    #[cfg(test)]
+    pub(super) fn iter_cell_tag_collections(
+    ) -> impl Iterator<Item = (BlockShape, &'static CellTagTableCollection)> {
+        BUILTIN_CELL_TAG_COLLECTION
+            .iter()
+            .map(|(&shape, &tables)| (shape, tables))
+    }
+
+    /// 回転しても位置補正を行わないキック．
+    /// 1セルのブロックやOミノのように，回転しても占める位置が変わらない形状に利用する．
+    const NO_KICKS: KickTable = KickTable {
+        clockwise: [&[(0, 0)]; 4],
+        unticlockwise: [&[(0, 0)]; 4],
+    };
+
+    /// 標準的なキック．SRSのJ,L,S,T,Zミノのものと同じ(y座標の向きのみ反転)．
+    const STANDARD_KICKS: KickTable = KickTable {
+        clockwise: [
+            // Right -> Below
+            &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+            // Below -> Left
+            &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+            // Left -> Above
+            &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+            // Above -> Right
+            &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+        ],
+        unticlockwise: [
+            // Right -> Above
+            &[(0, 0), (1, 0), (1, 1), (0, -2), (1, -2)],
+            // Below -> Right
+            &[(0, 0), (-1, 0), (-1, -1), (0, 2), (-1, 2)],
+            // Left -> Below
+            &[(0, 0), (-1, 0), (-1, 1), (0, -2), (-1, -2)],
+            // Above -> Left
+            &[(0, 0), (1, 0), (1, -1), (0, 2), (1, 2)],
+        ],
+    };
+
+    /// 細長い形状が横向きになるときに試すキック．
+    /// 横向きのIミノは中心から左へ1マス，右へ2マス広がるので，壁際では横へ2マスまで補正する．
+    const TO_HORIZONTAL_LONG_KICKS: &[(i8, i8)] = &[(0, 0), (-1, 0), (1, 0), (-2, 0), (2, 0)];
+
+    /// 細長い形状が縦向きになるときに試すキック．
+    /// 床際や天井際では縦へ2マスまで補正し，斜めには移動させない．
+    const TO_VERTICAL_LONG_KICKS: &[(i8, i8)] = &[(0, 0), (0, -1), (0, 1), (0, -2), (0, 2)];
+
+    /// Iミノのように細長い形状のキック．
+    /// SRSのIミノのキックは回転中心がセルの間にあることを前提としており，
+    /// セルを中心に回転するこのクレートのテンプレートには合わないため，縦横の補正だけを行う．
+    const LONG_KICKS: KickTable = KickTable {
+        clockwise: [
+            // Right -> Below
+            TO_HORIZONTAL_LONG_KICKS,
+            // Below -> Left
+            TO_VERTICAL_LONG_KICKS,
+            // Left -> Above
+            TO_HORIZONTAL_LONG_KICKS,
+            // Above -> Right
+            TO_VERTICAL_LONG_KICKS,
+        ],
+        unticlockwise: [
+            // Right -> Above
+            TO_HORIZONTAL_LONG_KICKS,
+            // Below -> Right
+            TO_VERTICAL_LONG_KICKS,
+            // Left -> Below
+            TO_HORIZONTAL_LONG_KICKS,
+            // Above -> Left
+            TO_VERTICAL_LONG_KICKS,
+        ],
+    };
+
+    /// 指定したブロック形状の回転に利用するキックを返す．
+    pub(super) fn get_kick_table(shape: BlockShape) -> &'static KickTable {
+        match shape {
+            BlockShape::Single(_) | BlockShape::Quadruple(QuadrupleBlockShape::O) => &NO_KICKS,
+            BlockShape::Quadruple(QuadrupleBlockShape::I)
+            | BlockShape::Quintuple(QuintupleBlockShape::LongI) => &LONG_KICKS,
+            _ => &STANDARD_KICKS,
+        }
     }
 
     /// 指定したブロック形状に対応する形状定義テンプレートを返す．
     pub(super) fn get_cell_tag_collection(shape: BlockShape) -> &'static CellTagTableCollection {
         match shape {
-            BlockShape::Single(s) => &SINGLE_CELL_TAG_COLLECTION[&s],
-            BlockShape::Double(s) => &DOUBLE_CELL_TAG_COLLECTION[&s],
-            BlockShape::Triple(s) => &TRIPLE_CELL_TAG_COLLECTION[&s],
-            BlockShape::Quadruple(s) => &QUADRUPLE_CELL_TAG_COLLECTION[&s],
-            BlockShape::Quintuple(s) => &QUINTUPLE_CELL_TAG_COLLECTION[&s],
+            BlockShape::Custom(s) => s.tables,
+            _ => BUILTIN_CELL_TAG_COLLECTION[&shape],
+        }
+    }
+}
+
+/// ブロックとその構成要素の直列化．
+///
+/// 形状は`Quadruple(T)`のような名前で書き出す．実行時に定義した形状は書き出せない．
+/// ブロックは形状，向き，ボム指定だけを書き出し，読み込むときに形状定義テンプレートからセルを組み立て直す．
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::{Block, BlockShape, BombLabels, BombTag, Direction};
+    use serde::de::Error as _;
+    use serde::ser::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for BlockShape {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                BlockShape::Custom(_) => {
+                    Err(S::Error::custom("custom block shapes cannot be serialized"))
+                }
+                shape => serializer.collect_str(&format_args!("{:?}", shape)),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BlockShape {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let name = String::deserialize(deserializer)?;
+            BlockShape::all()
+                .find(|shape| format!("{:?}", shape) == name)
+                .ok_or_else(|| D::Error::custom(format_args!("unknown block shape `{}`", name)))
+        }
+    }
+
+    /// ラベルを昇順に並べた列として書き出す．
+    impl Serialize for BombLabels {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(self.iter())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for BombLabels {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let labels = Vec::<usize>::deserialize(deserializer)?;
+            BombLabels::new(&labels)
+                .ok_or_else(|| D::Error::custom(format_args!("invalid bomb labels {:?}", labels)))
+        }
+    }
+
+    /// 書き出すブロックの内容．
+    #[derive(Serialize, Deserialize)]
+    struct BlockRepr {
+        shape: BlockShape,
+        direction: Direction,
+        bomb_tag: BombTag,
+    }
+
+    impl Serialize for Block {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let repr = BlockRepr {
+                shape: self.shape,
+                direction: self.direction,
+                bomb_tag: self.bomb_tag,
+            };
+            repr.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Block {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = BlockRepr::deserialize(deserializer)?;
+            if !repr.bomb_tag.is_valid_for(repr.shape) {
+                return Err(D::Error::custom(format_args!(
+                    "bomb tag {:?} does not fit {:?}",
+                    repr.bomb_tag, repr.shape
+                )));
+            }
+            Ok(Block::new(repr.shape, repr.direction, repr.bomb_tag))
         }
     }
 }
@@ -1348,11 +1504,370 @@ mod block_template {
 mod tests {
     use super::Cell::{Bomb, Empty, Normal};
     use super::*;
+    use crate::game::test_util::repeating_selector;
+
+    /// 自動で回転させる形状と，4つの向きをすべて書いた形状を定義した内容．
+    const SHAPE_FILE: &str = "
+        # コメントは読み飛ばす
+        shape Corner
+        above
+        .....
+        ..0..
+        ..12.
+        .....
+        .....
+
+        shape Bar
+        right
+        .....
+        ..0..
+        ..1..
+        .....
+        .....
+        below
+        .....
+        .....
+        .10..
+        .....
+        .....
+        left
+        .....
+        .....
+        ..1..
+        ..0..
+        .....
+        above
+        .....
+        .....
+        ..01.
+        .....
+        .....
+        ";
+
+    #[test]
+    fn test_load_from_str() {
+        let library = load_from_str(SHAPE_FILE).unwrap();
+        assert_eq!(2, library.len());
+        assert_eq!(vec!["Corner", "Bar"], library.names().collect::<Vec<_>>());
+        assert_eq!(None, library.get("T"));
+
+        let corner = library.get("Corner").unwrap();
+        assert_eq!(3, corner.non_empty_cell_count());
+        let block = Block::new(corner, Direction::Above, BombTag::Single(0));
+        assert_eq!([Empty, Empty, Bomb, Empty, Empty], block.cells[1]);
+        assert_eq!([Empty, Empty, Normal, Normal, Empty], block.cells[2]);
+        // 書かなかった向きは回転させて生成する
+        let block = block.rotate_clockwise();
+        assert_eq!([Empty, Empty, Normal, Bomb, Empty], block.cells[2]);
+        assert_eq!([Empty, Empty, Normal, Empty, Empty], block.cells[3]);
+
+        let bar = library.get("Bar").unwrap();
+        let block = Block::new(bar, Direction::Left, BombTag::Single(0));
+        assert_eq!([Empty, Empty, Normal, Empty, Empty], block.cells[2]);
+        assert_eq!([Empty, Empty, Bomb, Empty, Empty], block.cells[3]);
+    }
+
+    #[test]
+    fn test_builtin_library() {
+        let library = ShapeLibrary::builtin();
+        assert_eq!(BlockShape::all().count(), library.len());
+        for shape in BlockShape::all() {
+            let loaded = library.get(&format!("{:?}", shape)).unwrap();
+            assert_eq!(shape.non_empty_cell_count(), loaded.non_empty_cell_count());
+            for &direction in Direction::ALL.iter() {
+                let expected = Block::new(shape, direction, BombTag::Single(0));
+                let block = Block::new(loaded, direction, BombTag::Single(0));
+                assert_eq!(expected.cells, block.cells, "{:?}", shape);
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_tables_match_hand_written() {
+        // 回転させて生成したテーブルが，4つの向きをすべて手で書いていたころのテーブルとラベルまで一致する
+        let hand_written = load_from_str(include_str!("testdata/hand_written_shapes.txt")).unwrap();
+        assert_eq!(29, hand_written.len());
+        // 手で書いていたのは5セルまでの形状
+        for shape in BlockShape::all().filter(|s| s.non_empty_cell_count() <= 5) {
+            let expected = hand_written.get(&format!("{:?}", shape)).unwrap();
+            assert_eq!(
+                block_template::get_cell_tag_collection(expected).tables,
+                block_template::get_cell_tag_collection(shape).tables,
+                "{:?}",
+                shape
+            );
+        }
+    }
+
+    #[test]
+    fn test_override_direction() {
+        // 右向きだけ1列右にずらす
+        let text = "
+            shape Domino
+            above
+            .....
+            .....
+            ..01.
+            .....
+            .....
+            right
+            .....
+            .....
+            ...0.
+            ...1.
+            .....
+            ";
+        let library = load_from_str(text).unwrap();
+        let domino = library.get("Domino").unwrap();
+        let tables = block_template::get_cell_tag_collection(domino);
+        assert_eq!(CellTag::Occupied(0), tables[Direction::Right][2][3]);
+        assert_eq!(CellTag::Occupied(1), tables[Direction::Right][3][3]);
+        // 書かなかった向きは上向きを回転させて生成する
+        assert_eq!(CellTag::Occupied(0), tables[Direction::Below][2][2]);
+        assert_eq!(CellTag::Occupied(1), tables[Direction::Below][2][1]);
+    }
+
+    #[test]
+    fn test_load_malformed() {
+        use ParseErrorKind::*;
+
+        let grid = ".....\n..0..\n..1..\n.....\n.....\n";
+        let cases = vec![
+            // 形状の名前より前にテーブルがある
+            (
+                format!("above\n{}", grid),
+                1,
+                UnexpectedLine("above".to_string()),
+            ),
+            ("shape\n".to_string(), 1, EmptyName),
+            (
+                format!("shape A\nabove\n{}shape A\nabove\n{}", grid, grid),
+                8,
+                DuplicateShape("A".to_string()),
+            ),
+            (
+                format!("shape A\nup\n{}", grid),
+                2,
+                UnexpectedLine("up".to_string()),
+            ),
+            (
+                format!("shape A\nabove\n{}above\n{}", grid, grid),
+                8,
+                DuplicateDirection(Direction::Above),
+            ),
+            // テーブルの大きさが正しくない
+            (
+                "shape A\nabove\n.....\n..0..\n".to_string(),
+                2,
+                TooFewRows { actual: 2 },
+            ),
+            (
+                "shape A\nabove\n.....\n..0...\n.....\n.....\n.....\n".to_string(),
+                4,
+                WrongRowLength { actual: 6 },
+            ),
+            (
+                "shape A\nabove\n.....\n..0x.\n.....\n.....\n.....\n".to_string(),
+                4,
+                InvalidChar('x'),
+            ),
+            // ラベルが正しくない
+            (
+                "shape A\nabove\n.....\n..0..\n..0..\n.....\n.....\n".to_string(),
+                5,
+                DuplicateLabel(0),
+            ),
+            (
+                "shape A\nabove\n.....\n..0..\n..2..\n.....\n.....\n".to_string(),
+                3,
+                NonContiguousLabels(vec![0, 2]),
+            ),
+            (
+                "shape A\nabove\n.....\n.....\n.....\n.....\n.....\n".to_string(),
+                1,
+                NoCells,
+            ),
+            // 向きが足りない
+            (format!("shape A\nright\n{}", grid), 1, MissingAbove),
+            // 縦向きのまま回転していない
+            (
+                format!(
+                    "shape A\nright\n{}below\n{}left\n{}above\n{}",
+                    grid, grid, grid, grid
+                ),
+                1,
+                RotationMismatch(Direction::Left),
+            ),
+            (
+                format!(
+                    "shape A\nright\n{}below\n{}left\n{}above\n..0..\n.....\n.....\n.....\n.....\n",
+                    grid, grid, grid
+                ),
+                20,
+                CellCountMismatch {
+                    expected: 2,
+                    actual: 1,
+                },
+            ),
+            // 既定のボム指定が正しくない
+            (
+                format!("shape A\nbomb 0\nbomb 1\nabove\n{}", grid),
+                3,
+                DuplicateBomb,
+            ),
+            (
+                format!("shape A\nbomb x\nabove\n{}", grid),
+                2,
+                InvalidBomb("x".to_string()),
+            ),
+            (
+                format!("shape A\nbomb 1 1\nabove\n{}", grid),
+                2,
+                InvalidBomb("1 1".to_string()),
+            ),
+            (
+                format!("shape A\nabove\n{}bomb 2\n", grid),
+                8,
+                InvalidDefaultBomb(BombTag::Single(2)),
+            ),
+        ];
+        for (text, line, kind) in cases {
+            let expected = ParseError { line, kind };
+            assert_eq!(Err(expected), load_from_str(&text), "{}", text);
+        }
+        assert!(load_from_str("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        let error = load_from_str("shape A\nabove\n.....\n").unwrap_err();
+        assert_eq!("line 2: grid has 1 rows, expected 5", error.to_string());
+    }
+
+    #[test]
+    fn test_validate_templates() {
+        assert_eq!(Vec::<TemplateError>::new(), validate_templates());
+        assert!(validate_shape(asymmetric_shape()).is_empty());
+    }
+
+    #[test]
+    fn test_default_bomb() {
+        // 組み込みの形状では，十字形のペントミノだけが中央のセルにボムを持つ
+        let star = QuintupleBlockShape::Star.into();
+        assert_eq!(Some(BombTag::Single(1)), default_bomb(star));
+        let center = Block::new(star, Direction::Above, BombTag::Single(1));
+        assert_eq!(
+            Some(&Bomb),
+            center.iter_pos_and_cell().nth(12).map(|(_, c)| c)
+        );
+        assert!(BlockShape::all()
+            .filter(|&shape| shape != star)
+            .all(|shape| default_bomb(shape).is_none()));
+
+        let library = load_from_str(
+            "shape A\nabove\n.....\n..0..\n..1..\n..2..\n.....\nbomb 0 2\nshape B\nbomb all\nabove\n.....\n.....\n..0..\n.....\n.....\n",
+        )
+        .unwrap();
+        let labels = BombLabels::new(&[0, 2]).unwrap();
+        let a = library.get("A").unwrap();
+        assert_eq!(Some(BombTag::Many(labels)), default_bomb(a));
+        assert_eq!(Some(BombTag::All), default_bomb(library.get("B").unwrap()));
+        // 既定のボム指定の行は，テーブルの前後どちらに書いてもよい
+        assert_eq!(3, a.non_empty_cell_count());
+    }
+
+    #[test]
+    fn test_validate_default_bomb() {
+        let mut tables = *block_template::get_cell_tag_collection(QuadrupleBlockShape::T.into());
+        tables.default_bomb = Some(BombTag::Many(BombLabels::new(&[1, 4]).unwrap()));
+        let shape = CustomBlockShape {
+            tables: intern_cell_tag_collection(tables),
+            cell_count: 4,
+        }
+        .into();
+        let kinds = validate_shape(shape)
+            .into_iter()
+            .map(|error| error.kind)
+            .collect::<Vec<_>>();
+        let bomb = tables.default_bomb.unwrap();
+        assert_eq!(vec![TemplateErrorKind::InvalidDefaultBomb { bomb }], kinds);
+    }
+
+    #[test]
+    fn test_validate_rotation() {
+        // 下向きのテーブルを上向きのテーブルで置き換えると，その前後の回転が一致しなくなる
+        let mut tables = *block_template::get_cell_tag_collection(QuadrupleBlockShape::T.into());
+        tables.tables[Direction::Below.table_index()] = tables[Direction::Above];
+        let shape = CustomBlockShape {
+            tables: intern_cell_tag_collection(tables),
+            cell_count: 4,
+        }
+        .into();
+        let kinds = validate_shape(shape)
+            .into_iter()
+            .map(|error| error.kind)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            TemplateErrorKind::RotationMismatch {
+                from: Direction::Below,
+            },
+            TemplateErrorKind::RotationMismatch {
+                from: Direction::Right,
+            },
+        ];
+        assert_eq!(expected, kinds);
+    }
+
+    #[test]
+    fn test_all_shapes() {
+        // 大きさごとの形状の数は，定義済みの形状定義テンプレートのうちその大きさのものの数と一致する
+        let count_templates = |cell_count| {
+            block_template::iter_cell_tag_collections()
+                .filter(|(shape, _)| shape.non_empty_cell_count() == cell_count)
+                .count()
+        };
+        assert_eq!(count_templates(1), SingleBlockShape::all().count());
+        assert_eq!(count_templates(2), DoubleBlockShape::all().count());
+        assert_eq!(count_templates(3), TripleBlockShape::all().count());
+        assert_eq!(count_templates(4), QuadrupleBlockShape::all().count());
+        assert_eq!(count_templates(5), QuintupleBlockShape::all().count());
+        assert_eq!(count_templates(6), SextupleBlockShape::all().count());
+        assert_eq!(
+            block_template::iter_cell_tag_collections().count(),
+            BlockShape::all().count()
+        );
+
+        // 重複なく，セルの少ない順に並ぶ
+        let shapes = BlockShape::all().collect::<Vec<_>>();
+        let unique = shapes.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(shapes.len(), unique.len());
+        assert!(shapes
+            .windows(2)
+            .all(|w| w[0].non_empty_cell_count() <= w[1].non_empty_cell_count()));
+    }
+
+    #[test]
+    fn test_iter_cell_tag_collections() {
+        // 1 + 1 + 3 + 7 + 17 + 5種類の形状が定義されている
+        assert_eq!(34, block_template::iter_cell_tag_collections().count());
+    }
+
+    #[test]
+    fn test_all_shapes_have_templates() {
+        let mut shapes = block_template::iter_cell_tag_collections()
+            .map(|(shape, _)| shape)
+            .collect::<Vec<_>>();
+        for shape in BlockShape::all() {
+            let i = shapes.iter().position(|&s| s == shape).unwrap();
+            shapes.remove(i);
+        }
+        assert!(shapes.is_empty());
+    }
 
     #[test]
     fn test_cell_table_size() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::None,
         );
@@ -1362,7 +1877,7 @@ mod tests {
     #[test]
     fn test_cells_without_bomb() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::None,
         );
@@ -1376,7 +1891,7 @@ mod tests {
     #[test]
     fn test_cells_all_bomb() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::All,
         );
@@ -1390,7 +1905,7 @@ mod tests {
     #[test]
     fn test_cells_single_bomb() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::Single(1),
         );
@@ -1401,10 +1916,45 @@ mod tests {
         assert_eq!([Empty; 5], block.cells[4]);
     }
 
+    #[test]
+    fn test_cells_many_bombs() {
+        let block = Block::new(
+            QuadrupleBlockShape::O.into(),
+            Direction::Above,
+            BombTag::Many(BombLabels::new(&[1, 2]).unwrap()),
+        );
+        assert_eq!([Empty; 5], block.cells[0]);
+        assert_eq!([Empty, Empty, Bomb, Bomb, Empty], block.cells[1]);
+        assert_eq!([Empty, Empty, Normal, Normal, Empty], block.cells[2]);
+        assert_eq!([Empty; 5], block.cells[3]);
+        assert_eq!([Empty; 5], block.cells[4]);
+    }
+
+    #[test]
+    fn test_bomb_labels() {
+        let labels = BombLabels::new(&[3, 0]).unwrap();
+        assert_eq!(2, labels.len());
+        assert!(labels.contains(0) && labels.contains(3));
+        assert!(!labels.contains(1) && !labels.contains(100));
+        assert_eq!(vec![0, 3], labels.iter().collect::<Vec<_>>());
+        assert!(BombLabels::new(&[]).unwrap().is_empty());
+
+        // 重複したラベルや，大きすぎるラベルは指定できない
+        assert_eq!(None, BombLabels::new(&[1, 2, 1]));
+        assert_eq!(None, BombLabels::new(&[BombLabels::MAX_LABEL + 1]));
+
+        // ラベルは形状の空でないセルの数より小さくなければならない
+        let t = QuadrupleBlockShape::T.into();
+        assert!(BombTag::Many(labels).is_valid_for(t));
+        let labels = BombLabels::new(&[1, 4]).unwrap();
+        assert!(!BombTag::Many(labels).is_valid_for(t));
+        assert!(BombTag::Many(labels).is_valid_for(QuintupleBlockShape::LongI.into()));
+    }
+
     #[test]
     fn test_iter_pos_and_occupied_cell() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::Single(1),
         );
@@ -1431,7 +1981,7 @@ mod tests {
     #[test]
     fn test_rotate_clockwise() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::Single(1),
         );
@@ -1445,10 +1995,206 @@ mod tests {
         assert_eq!([Empty; 5], block.cells[4]);
     }
 
+    #[test]
+    fn test_rotate_many_bombs() {
+        let bomb_tag = BombTag::Many(BombLabels::new(&[1, 2]).unwrap());
+        let block = Block::new(QuadrupleBlockShape::O.into(), Direction::Above, bomb_tag);
+
+        let clockwise = block.rotate_clockwise();
+        assert_eq!(bomb_tag, clockwise.bomb_tag);
+        assert_eq!([Empty, Empty, Normal, Bomb, Empty], clockwise.cells[1]);
+        assert_eq!([Empty, Empty, Normal, Bomb, Empty], clockwise.cells[2]);
+
+        let unticlockwise = block.rotate_unticlockwise();
+        assert_eq!(bomb_tag, unticlockwise.bomb_tag);
+        assert_eq!([Empty, Empty, Bomb, Normal, Empty], unticlockwise.cells[1]);
+        assert_eq!([Empty, Empty, Bomb, Normal, Empty], unticlockwise.cells[2]);
+
+        // 一周すると元に戻る
+        let rotated = (0..ROTATION_KIND).fold(block, |b, _| b.rotate_clockwise());
+        assert_eq!(block, rotated);
+    }
+
+    #[test]
+    fn test_occupied_extent() {
+        // 横向きのIミノはテーブルの3行目を占める
+        let i = Block::new(
+            QuadrupleBlockShape::I.into(),
+            Direction::Above,
+            BombTag::None,
+        );
+        let expected =
+            RegionOfInterest::new(Pos(PosX::right(1), PosY::below(2)), right(4) + below(1));
+        assert_eq!(expected, i.occupied_extent());
+        assert_eq!((4, 1), (i.occupied_width(), i.occupied_height()));
+
+        // 縦向きのIミノはテーブルの3列目を占める
+        let i = i.rotate_clockwise();
+        let expected =
+            RegionOfInterest::new(Pos(PosX::right(2), PosY::below(0)), right(1) + below(4));
+        assert_eq!(expected, i.occupied_extent());
+        assert_eq!((1, 4), (i.occupied_width(), i.occupied_height()));
+
+        let o = Block::new(
+            QuadrupleBlockShape::O.into(),
+            Direction::Above,
+            BombTag::None,
+        );
+        let expected =
+            RegionOfInterest::new(Pos(PosX::right(2), PosY::below(1)), right(2) + below(2));
+        assert_eq!(expected, o.occupied_extent());
+        assert_eq!((2, 2), (o.occupied_width(), o.occupied_height()));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn test_new_bomb_label_out_of_range() {
+        // Tミノの空でないセルは4つ
+        Block::new(
+            QuadrupleBlockShape::T.into(),
+            Direction::Above,
+            BombTag::Single(7),
+        );
+    }
+
+    #[test]
+    fn test_new_single_cell_all_bomb() {
+        let block = Block::new(SingleBlockShape::O.into(), Direction::Above, BombTag::All);
+        let cells = block
+            .iter_pos_and_occupied_cell()
+            .map(|(_, &cell)| cell)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![Bomb], cells);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let bomb_tag = BombTag::Many(BombLabels::new(&[0, 3]).unwrap());
+        let block = Block::new(QuadrupleBlockShape::L.into(), Direction::Above, bomb_tag)
+            .rotate_clockwise()
+            .rotate_clockwise();
+        let json = serde_json::to_string(&block).unwrap();
+        assert_eq!(
+            r#"{"shape":"Quadruple(L)","direction":"Below","bomb_tag":{"Many":[0,3]}}"#,
+            json
+        );
+        let decoded = serde_json::from_str::<Block>(&json).unwrap();
+        assert_eq!(block, decoded);
+        assert_eq!(block.cells, decoded.cells);
+
+        // 形状に収まらないボム指定や，知らない形状は読み込めない
+        let invalid = r#"{"shape":"Quadruple(L)","direction":"Below","bomb_tag":{"Single":4}}"#;
+        assert!(serde_json::from_str::<Block>(invalid).is_err());
+        let unknown = r#"{"shape":"Quadruple(Q)","direction":"Below","bomb_tag":"None"}"#;
+        assert!(serde_json::from_str::<Block>(unknown).is_err());
+        // 実行時に定義した形状は書き出せない
+        let custom = Block::new(asymmetric_shape(), Direction::Above, BombTag::None);
+        assert!(serde_json::to_string(&custom).is_err());
+    }
+
+    #[test]
+    fn test_sextuple_fits_table() {
+        for shape in SextupleBlockShape::all() {
+            let shape = shape.into();
+            assert_eq!(6, BlockShape::non_empty_cell_count(&shape));
+            for &direction in Direction::ALL.iter() {
+                let block = Block::new(shape, direction, BombTag::Single(5));
+                // 回転してもセルがテーブルからはみ出さない
+                assert_eq!(6, block.iter_pos_and_occupied_cell().count());
+                let extent = block.occupied_extent();
+                assert!(extent.right_below().x() < PosX::right(BLOCK_TABLE_SIZE as i8));
+                assert!(extent.right_below().y() < PosY::below(BLOCK_TABLE_SIZE as i8));
+            }
+        }
+        assert!(validate_templates().is_empty());
+    }
+
+    #[test]
+    fn test_shape_index() {
+        for (i, shape) in BlockShape::all().enumerate() {
+            assert_eq!(Some(i), shape.index(), "{:?}", shape);
+        }
+        assert_eq!(None, asymmetric_shape().index());
+
+        // 順序は列挙される順と一致する
+        let mut shapes = BlockShape::all().collect::<Vec<_>>();
+        shapes.reverse();
+        shapes.sort();
+        assert_eq!(BlockShape::all().collect::<Vec<_>>(), shapes);
+    }
+
+    #[test]
+    fn test_state_key() {
+        let t = QuadrupleBlockShape::T.into();
+        let block = Block::new(t, Direction::Above, BombTag::Single(1));
+        assert_eq!(
+            block.state_key(),
+            Block::new(t, Direction::Above, BombTag::Single(1)).state_key()
+        );
+        assert_ne!(
+            block.state_key(),
+            Block::new(t, Direction::Above, BombTag::Single(2)).state_key()
+        );
+        assert_ne!(
+            block.state_key(),
+            Block::new(t, Direction::Above, BombTag::None).state_key()
+        );
+
+        // あらかじめ定義されたすべての形状と向きで，キーが重複しない
+        let mut keys = std::collections::HashSet::new();
+        for shape in BlockShape::all() {
+            let mut block = Block::new(shape, Direction::Above, BombTag::Single(0));
+            for _ in 0..ROTATION_KIND {
+                assert!(keys.insert(block.state_key()), "{:?}", block);
+                block = block.rotate_clockwise();
+            }
+        }
+
+        // ハッシュ値はキーから求める
+        let blocks = [block, block, block.rotate_clockwise()]
+            .iter()
+            .copied()
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(2, blocks.len());
+    }
+
+    #[test]
+    fn test_display() {
+        let block = Block::new(
+            QuadrupleBlockShape::T.into(),
+            Direction::Above,
+            BombTag::Single(0),
+        );
+        assert_eq!(".....\n..#..\n.*##.\n.....\n.....\n", block.to_string());
+    }
+
+    #[test]
+    fn test_accessors() {
+        // 生成したブロックは，生成ルールが選んだ形状とボム指定を返す
+        let block = repeating_selector(QuadrupleBlockShape::T, BombTag::Single(2)).generate_block();
+        let t: BlockShape = QuadrupleBlockShape::T.into();
+        assert_eq!(t, block.shape());
+        assert_eq!(BombTag::Single(2), block.bomb_tag());
+
+        // 回転しても向きだけが変わる
+        let mut rotated = block;
+        for _ in 0..ROTATION_KIND {
+            let next = rotated.rotate_clockwise();
+            assert_eq!(rotated.direction().rotate_clockwise(), next.direction());
+            assert_eq!(block.shape(), next.shape());
+            assert_eq!(block.bomb_tag(), next.bomb_tag());
+            rotated = next;
+        }
+        assert_eq!(block, rotated);
+        assert_eq!(Direction::Above, Block::default().direction());
+    }
+
     #[test]
     fn test_rotate_unticlockwise() {
         let block = Block::new(
-            block_template::get_cell_tag_collection(QuadrupleBlockShape::O.into()),
+            QuadrupleBlockShape::O.into(),
             Direction::Above,
             BombTag::Single(1),
         );
@@ -1461,4 +2207,110 @@ mod tests {
         assert_eq!([Empty; 5], block.cells[3]);
         assert_eq!([Empty; 5], block.cells[4]);
     }
+
+    /// Fペントミノのように，どの向きにも対称でない形状を返す．
+    fn asymmetric_shape() -> BlockShape {
+        BlockShapeBuilder::new()
+            .occupy(2, 1)
+            .occupy(3, 1)
+            .occupy(1, 2)
+            .occupy(2, 2)
+            .occupy(2, 3)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_custom_shape_rotation() {
+        let shape = asymmetric_shape();
+        assert_eq!(5, shape.non_empty_cell_count());
+
+        // ラベルは行優先順に付くので，ラベル1は上段の右のセル
+        let block = Block::new(shape, Direction::Above, BombTag::Single(1));
+        assert_eq!([Empty, Empty, Normal, Bomb, Empty], block.cells[1]);
+        assert_eq!([Empty, Normal, Normal, Empty, Empty], block.cells[2]);
+        assert_eq!([Empty, Empty, Normal, Empty, Empty], block.cells[3]);
+
+        let clockwise = block.rotate_clockwise();
+        assert_eq!([Empty, Empty, Normal, Empty, Empty], clockwise.cells[1]);
+        assert_eq!([Empty, Normal, Normal, Normal, Empty], clockwise.cells[2]);
+        assert_eq!([Empty, Empty, Empty, Bomb, Empty], clockwise.cells[3]);
+        assert_eq!(block, clockwise.rotate_unticlockwise());
+
+        // 4回回転させると元に戻り，途中の向きはすべて異なる
+        let mut rotated = vec![block];
+        for _ in 0..ROTATION_KIND {
+            rotated.push(rotated.last().unwrap().rotate_clockwise());
+        }
+        assert_eq!(block, rotated[ROTATION_KIND]);
+        for i in 0..ROTATION_KIND {
+            for j in i + 1..ROTATION_KIND {
+                assert_ne!(rotated[i].cells, rotated[j].cells);
+            }
+        }
+    }
+
+    #[test]
+    fn test_custom_shape_matches_template() {
+        // Tミノと同じ形を定義すると，どの向きでもテンプレートと同じ位置のセルを占める
+        let grid = [
+            [false; 5],
+            [false, false, true, false, false],
+            [false, true, true, true, false],
+            [false; 5],
+            [false; 5],
+        ];
+        let custom = BlockShapeBuilder::from_grid(grid).build().unwrap();
+        let t = QuadrupleBlockShape::T.into();
+        for &direction in Direction::ALL.iter() {
+            let expected = Block::new(t, direction, BombTag::None);
+            let block = Block::new(custom, direction, BombTag::None);
+            assert_eq!(expected.cells, block.cells, "{:?}", direction);
+        }
+
+        // 同じ形を定義した形状は等しい
+        assert_eq!(Some(custom), BlockShapeBuilder::from_grid(grid).build());
+        assert_ne!(Some(custom), BlockShapeBuilder::new().occupy(0, 0).build());
+    }
+
+    #[test]
+    fn test_custom_shape_shares_template() {
+        // 同じ形を何度定義しても，テンプレートは同じものを共有する
+        let build = || match BlockShapeBuilder::new().occupy(2, 2).occupy(2, 3).build() {
+            Some(BlockShape::Custom(custom)) => custom.tables,
+            _ => unreachable!(),
+        };
+        assert!(std::ptr::eq(build(), build()));
+
+        let library = || load_from_str("shape A\nabove\n.....\n.....\n..0..\n..1..\n.....\n");
+        let tables = |library: ShapeLibrary| match library.get("A") {
+            Some(BlockShape::Custom(custom)) => custom.tables,
+            _ => unreachable!(),
+        };
+        assert!(std::ptr::eq(
+            tables(library().unwrap()),
+            tables(library().unwrap())
+        ));
+    }
+
+    #[test]
+    fn test_invalid_custom_shape() {
+        assert_eq!(None, BlockShapeBuilder::new().build());
+        let builder = BlockShapeBuilder::new().occupy(2, 2).occupy(5, 0);
+        assert_eq!(None, builder.build());
+    }
+
+    #[test]
+    fn test_kick_offsets_start_without_movement() {
+        for (shape, _) in block_template::iter_cell_tag_collections() {
+            let mut block = Block::new(shape, Direction::Above, BombTag::None);
+            for _ in 0..ROTATION_KIND {
+                for &rotation in [Rotation::Clockwise, Rotation::Unticlockwise].iter() {
+                    let first = block.kick_offsets(rotation).next();
+                    assert_eq!(Some(right(0) + below(0)), first);
+                }
+                block = block.rotate_clockwise();
+            }
+        }
+    }
 }
@@ -0,0 +1,344 @@
+use super::{default_bomb, BlockSelector, BlockShape, BombTag};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// 最も大きいブロック形状が含む，空でないセルの数を返す．
+fn max_non_empty_cell_count() -> usize {
+    BlockShape::all()
+        .map(|shape| shape.non_empty_cell_count())
+        .max()
+        .unwrap_or(0)
+}
+
+/// ブロックにボムセルをどう含めるかを表す．
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BombPolicy {
+    /// ボムセルを含めない．
+    Never,
+    /// 指定したラベルのセルをボムセルにする．
+    /// ラベルは，生成するすべてのブロックの空でないセルの数より小さくなければならない．`is_valid_for`で確かめられる．
+    FixedLabel(usize),
+    /// 指定した確率で，無作為に選んだひとつのセルをボムセルにする．
+    RandomSingle {
+        /// ボムセルを含める確率．0以上1以下．
+        probability: f64,
+    },
+    /// すべてのセルをボムセルにする．
+    All,
+}
+
+impl BombPolicy {
+    /// このポリシーを適用できる場合は`true`を返す．
+    ///
+    /// どのブロックのセル数にも収まらないラベルや，0以上1以下でない確率は適用できない．
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            BombPolicy::Never | BombPolicy::All => true,
+            BombPolicy::FixedLabel(label) => label < max_non_empty_cell_count(),
+            BombPolicy::RandomSingle { probability } => (0.0..=1.0).contains(&probability),
+        }
+    }
+
+    /// このポリシーを指定した形状のブロックに適用できる場合は`true`を返す．
+    ///
+    /// `is_valid`の条件に加えて，ラベルが形状の空でないセルの数より小さい必要がある．
+    pub fn is_valid_for(&self, shape: BlockShape) -> bool {
+        match *self {
+            BombPolicy::FixedLabel(label) => label < shape.non_empty_cell_count(),
+            _ => self.is_valid(),
+        }
+    }
+
+    /// `never`，`all`，`fixed:<ラベル>`，`random:<確率>`のいずれかの形式の文字列からポリシーを読み込む．
+    /// # Returns
+    /// 形式が正しくない場合や，読み込んだポリシーを適用できない場合は`None`を返す．
+    pub fn parse(text: &str) -> Option<BombPolicy> {
+        let policy = match text {
+            "never" => BombPolicy::Never,
+            "all" => BombPolicy::All,
+            _ => {
+                if let Some(label) = text.strip_prefix("fixed:") {
+                    BombPolicy::FixedLabel(label.parse().ok()?)
+                } else if let Some(probability) = text.strip_prefix("random:") {
+                    BombPolicy::RandomSingle {
+                        probability: probability.parse().ok()?,
+                    }
+                } else {
+                    return None;
+                }
+            }
+        };
+        Some(policy).filter(BombPolicy::is_valid)
+    }
+}
+
+/// 別のブロック生成ルールを包み，ボムセルの指定だけをポリシーに従って決めるブロック生成ルール．
+/// ブロックの形状は包んだ生成ルールが決める．
+#[derive(Debug, Clone)]
+pub struct BombPolicySelector<S> {
+    selector: S,
+    /// 適用するポリシー．`None`の場合は包んだ生成ルールのボム指定をそのまま使う．
+    policy: Option<BombPolicy>,
+    rng: StdRng,
+}
+
+impl<S> BombPolicySelector<S> {
+    /// 指定した生成ルールを包み，無作為なシード値で初期化した生成ルールを返す．
+    /// # Returns
+    /// ポリシーを適用できない場合は`None`を返す．
+    pub fn new(selector: S, policy: BombPolicy) -> Option<BombPolicySelector<S>> {
+        Self::with_seed(selector, policy, rand::random())
+    }
+
+    /// 指定した生成ルールを包み，指定したシード値で初期化した生成ルールを返す．
+    /// 同じシード値と同じ生成ルールからは，同じボムセルの指定が得られる．
+    /// # Returns
+    /// ポリシーを適用できない場合は`None`を返す．
+    pub fn with_seed(selector: S, policy: BombPolicy, seed: u64) -> Option<BombPolicySelector<S>> {
+        if !policy.is_valid() {
+            return None;
+        }
+        Some(Self {
+            selector,
+            policy: Some(policy),
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// 指定した生成ルールを包み，そのボム指定をそのまま使う生成ルールを返す．
+    /// ポリシーを指定しない場合にも，ポリシーを指定した場合と同じ型で扱うために利用する．
+    pub fn inherit(selector: S) -> BombPolicySelector<S> {
+        Self {
+            selector,
+            policy: None,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    /// 包んでいる生成ルールを返す．
+    pub fn selector(&self) -> &S {
+        &self.selector
+    }
+
+    /// 適用しているポリシーを返す．包んだ生成ルールのボム指定をそのまま使う場合は`None`を返す．
+    pub fn policy(&self) -> Option<BombPolicy> {
+        self.policy
+    }
+}
+
+impl<S: BlockSelector> BlockSelector for BombPolicySelector<S> {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.selector.select_block_shape()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        let policy = match self.policy {
+            Some(policy) => policy,
+            None => return self.selector.select_bomb(shape),
+        };
+        let count = shape.non_empty_cell_count();
+        match policy {
+            BombPolicy::Never => BombTag::None,
+            BombPolicy::FixedLabel(label) => BombTag::Single(label),
+            BombPolicy::RandomSingle { probability } => {
+                if self.rng.gen_bool(probability) {
+                    BombTag::Single(self.rng.gen_range(0..count))
+                } else {
+                    BombTag::None
+                }
+            }
+            BombPolicy::All => BombTag::All,
+        }
+    }
+}
+
+/// 別のブロック生成ルールを包み，形状に既定のボム指定があればそれを使うブロック生成ルール．
+/// 既定のボム指定がない形状では，包んだ生成ルールのボム指定を使う．
+/// 既定のボム指定は`default_bomb`で得られる．
+///
+/// 包んだ生成ルールの状態が生成したブロックの数によらず進むよう，既定のボム指定を使う場合も包んだ生成ルールにボム指定を選ばせる．
+#[derive(Debug, Clone)]
+pub struct TemplateBombSelector<S> {
+    selector: S,
+}
+
+impl<S> TemplateBombSelector<S> {
+    /// 指定した生成ルールを包んだ生成ルールを返す．
+    pub fn new(selector: S) -> TemplateBombSelector<S> {
+        Self { selector }
+    }
+
+    /// 包んでいる生成ルールを返す．
+    pub fn selector(&self) -> &S {
+        &self.selector
+    }
+}
+
+impl<S: BlockSelector> BlockSelector for TemplateBombSelector<S> {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.selector.select_block_shape()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        let fallback = self.selector.select_bomb(shape);
+        default_bomb(shape).unwrap_or(fallback)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::RandomBlockSelector;
+    use super::super::{PentominoBlockSelector, QuadrupleBlockShape, QuintupleBlockShape};
+    use super::super::{ScriptEnd, ScriptedBlockSelector, SingleBlockShape};
+    use super::*;
+
+    fn bombs(policy: BombPolicy, count: usize) -> Vec<BombTag> {
+        let mut selector =
+            BombPolicySelector::with_seed(RandomBlockSelector::with_seed(0), policy, 0).unwrap();
+        (0..count)
+            .map(|_| {
+                let shape = selector.select_block_shape();
+                selector.select_bomb(shape)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_single_probability() {
+        let never = bombs(BombPolicy::RandomSingle { probability: 0.0 }, 100);
+        assert!(never.iter().all(|&tag| tag == BombTag::None));
+
+        let always = bombs(BombPolicy::RandomSingle { probability: 1.0 }, 100);
+        for tag in always {
+            match tag {
+                BombTag::Single(label) => assert!(label < 4),
+                _ => panic!("unexpected bomb tag: {:?}", tag),
+            }
+        }
+    }
+
+    #[test]
+    fn test_never_and_all() {
+        assert!(bombs(BombPolicy::Never, 20)
+            .iter()
+            .all(|&tag| tag == BombTag::None));
+        assert!(bombs(BombPolicy::All, 20)
+            .iter()
+            .all(|&tag| tag == BombTag::All));
+    }
+
+    #[test]
+    fn test_fixed_label() {
+        assert!(bombs(BombPolicy::FixedLabel(3), 20)
+            .iter()
+            .all(|&tag| tag == BombTag::Single(3)));
+    }
+
+    #[test]
+    fn test_is_valid_for() {
+        let single = SingleBlockShape::O.into();
+        let quadruple = QuadrupleBlockShape::T.into();
+        let quintuple = QuintupleBlockShape::Star.into();
+
+        // ラベルはブロックのセル数より小さくなければならない
+        let policy = BombPolicy::FixedLabel(4);
+        assert!(!policy.is_valid_for(single));
+        assert!(!policy.is_valid_for(quadruple));
+        assert!(policy.is_valid_for(quintuple));
+        assert!(BombPolicy::FixedLabel(0).is_valid_for(single));
+
+        // ラベルを指定しないポリシーは，形状によらず適用できる
+        assert!(BombPolicy::All.is_valid_for(single));
+        let random = BombPolicy::RandomSingle { probability: 0.5 };
+        assert!(random.is_valid_for(single));
+        let invalid = BombPolicy::RandomSingle { probability: 1.5 };
+        assert!(!invalid.is_valid_for(quintuple));
+    }
+
+    #[test]
+    fn test_invalid_policy_rejected() {
+        let selector = RandomBlockSelector::new;
+        assert!(BombPolicySelector::new(selector(), BombPolicy::FixedLabel(6)).is_none());
+        assert!(BombPolicySelector::new(selector(), BombPolicy::FixedLabel(usize::MAX)).is_none());
+        assert!(
+            BombPolicySelector::new(selector(), BombPolicy::RandomSingle { probability: 1.5 })
+                .is_none()
+        );
+        assert!(BombPolicySelector::new(
+            selector(),
+            BombPolicy::RandomSingle {
+                probability: f64::NAN
+            }
+        )
+        .is_none());
+        assert!(BombPolicySelector::new(selector(), BombPolicy::FixedLabel(5)).is_some());
+    }
+
+    #[test]
+    fn test_inherit() {
+        let mut selector = BombPolicySelector::inherit(RandomBlockSelector::with_seed(3));
+        let mut inner = RandomBlockSelector::with_seed(3);
+        assert_eq!(None, selector.policy());
+        for _ in 0..20 {
+            assert_eq!(inner.generate_block(), selector.generate_block());
+        }
+    }
+
+    #[test]
+    fn test_template_bomb() {
+        let star = QuintupleBlockShape::Star.into();
+        let t = QuadrupleBlockShape::T.into();
+        let script = vec![
+            (star, BombTag::None),
+            (t, BombTag::Single(2)),
+            (star, BombTag::All),
+        ];
+        let inner = ScriptedBlockSelector::new(script, ScriptEnd::Stop).unwrap();
+        let mut selector = TemplateBombSelector::new(inner);
+
+        // 既定のボム指定がある形状ではそれを使い，ない形状では包んだ生成ルールのボム指定を使う
+        let bombs = (0..3)
+            .map(|_| selector.generate_block().bomb_tag())
+            .collect::<Vec<_>>();
+        let expected = vec![BombTag::Single(1), BombTag::Single(2), BombTag::Single(1)];
+        assert_eq!(expected, bombs);
+        // 既定のボム指定を使った場合も，包んだ生成ルールは進む
+        assert!(selector.selector().is_exhausted());
+    }
+
+    #[test]
+    fn test_template_bomb_with_policy() {
+        let policy =
+            BombPolicySelector::new(PentominoBlockSelector::with_seed(5), BombPolicy::Never);
+        let mut selector = TemplateBombSelector::new(policy.unwrap());
+        for _ in 0..200 {
+            let block = selector.generate_block();
+            let expected = match block.shape() {
+                BlockShape::Quintuple(QuintupleBlockShape::Star) => BombTag::Single(1),
+                _ => BombTag::None,
+            };
+            assert_eq!(expected, block.bomb_tag());
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Some(BombPolicy::Never), BombPolicy::parse("never"));
+        assert_eq!(Some(BombPolicy::All), BombPolicy::parse("all"));
+        assert_eq!(
+            Some(BombPolicy::FixedLabel(2)),
+            BombPolicy::parse("fixed:2")
+        );
+        assert_eq!(
+            Some(BombPolicy::RandomSingle { probability: 0.25 }),
+            BombPolicy::parse("random:0.25")
+        );
+        assert_eq!(
+            Some(BombPolicy::FixedLabel(5)),
+            BombPolicy::parse("fixed:5")
+        );
+        assert_eq!(None, BombPolicy::parse("fixed:6"));
+        assert_eq!(None, BombPolicy::parse("random:2"));
+        assert_eq!(None, BombPolicy::parse("sometimes"));
+    }
+}
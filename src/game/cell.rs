@@ -5,11 +5,15 @@ use crate::graphics::*;
 /// セルは，ブロックを構成する最小単位である．
 /// また，フィールドに二次元格子状に配置されるものでもある．
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Cell {
     /// 空セル．
     Empty,
     /// 通常のセル．
     Normal,
+    /// 爆発に巻き込まれても消えない鋼鉄のセル．
+    /// ブロックには含まれず，せり上がりラインやパズルの盤面にだけ現れる．
+    Steel,
     /// ボムセル．
     Bomb,
     /// デカボムの左上を表すセル．
@@ -20,6 +24,10 @@ pub enum Cell {
     BigBombLowerLeft,
     /// デカボムの右下に割り当てられるセル．
     BigBombLowerRight,
+    /// 特大ボムの中央を表すセル．
+    HugeBombCenter,
+    /// 特大ボムの中央以外に割り当てられるセル．
+    HugeBombPart,
 }
 
 impl Cell {
@@ -30,39 +38,80 @@ impl Cell {
             _ => false,
         }
     }
+
+    /// このセルを左右反転したセルを返す．
+    /// デカボムを構成するセルは左右が入れ替わり，それ以外のセルはそのまま返す．
+    pub fn mirrored(&self) -> Cell {
+        use Cell::*;
+
+        match self {
+            BigBombUpperLeft => BigBombUpperRight,
+            BigBombUpperRight => BigBombUpperLeft,
+            BigBombLowerLeft => BigBombLowerRight,
+            BigBombLowerRight => BigBombLowerLeft,
+            cell => *cell,
+        }
+    }
+
+    /// デバッグ表示用に，このセルを1文字で表して返す．
+    /// 空セルは`.`，通常のセルは`#`，鋼鉄のセルは`@`，ボムセルは`*`，デカボムを構成するセルは`B`，特大ボムを構成するセルは`H`となる．
+    pub fn as_ascii(&self) -> char {
+        use Cell::*;
+
+        match self {
+            Empty => '.',
+            Normal => '#',
+            Steel => '@',
+            Bomb => '*',
+            BigBombUpperLeft | BigBombUpperRight | BigBombLowerLeft | BigBombLowerRight => 'B',
+            HugeBombCenter | HugeBombPart => 'H',
+        }
+    }
 }
 
 impl Cell {
+    /// 表示用の文字を返す．
+    /// 色を表示できない端末でもセルの種類を見分けられるように，空セル以外は種類ごとに異なる文字となる．
     fn char_for_display(&self) -> SquareChar {
         use Cell::*;
 
         match self {
             Empty => SquareChar::new(' ', '.'),
             Normal => SquareChar::new('[', ']'),
-            Bomb => SquareChar::new('[', ']'),
+            Steel => SquareChar::new('#', '#'),
+            Bomb => SquareChar::new('(', ')'),
             BigBombUpperLeft => SquareChar::new('/', '^'),
             BigBombUpperRight => SquareChar::new('^', '\\'),
             BigBombLowerLeft => SquareChar::new('\\', '_'),
             BigBombLowerRight => SquareChar::new('_', '/'),
+            HugeBombCenter => SquareChar::new('<', '>'),
+            HugeBombPart => SquareChar::new('=', '='),
         }
     }
 
-    fn color_for_display(&self) -> CanvasCellColor {
+    /// 表示する色の役割を返す．
+    /// 実際の色は，描画する際の配色が決める．
+    fn theme_color(&self) -> ThemeColor {
         use Cell::*;
-        use Color::*;
 
         match self {
-            Empty => CanvasCellColor::new(White, Black),
-            Normal => CanvasCellColor::new(Cyan, Black),
-            Bomb => CanvasCellColor::new(Red, Black),
+            Empty => ThemeColor::EmptyCell,
+            Normal => ThemeColor::NormalCell,
+            Steel => ThemeColor::SteelCell,
+            Bomb => ThemeColor::Bomb,
             BigBombUpperLeft | BigBombUpperRight | BigBombLowerLeft | BigBombLowerRight => {
-                CanvasCellColor::new(Magenta, Black)
+                ThemeColor::BigBomb
             }
+            HugeBombCenter | HugeBombPart => ThemeColor::HugeBomb,
         }
     }
 
-    fn canvas_cell(&self) -> CanvasCell {
-        CanvasCell::new(self.char_for_display(), self.color_for_display())
+    /// このセルを指定した配色で描画する際のキャンバスセルを返す．
+    pub(crate) fn canvas_cell(&self, theme: &Theme) -> CanvasCell {
+        CanvasCell::new(
+            self.char_for_display(),
+            theme.cell_color(self.theme_color()),
+        )
     }
 }
 
@@ -72,7 +121,7 @@ impl Drawable for Cell {
     }
 
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        canvas.draw_cell(Pos::origin(), self.canvas_cell());
+        canvas.draw_cell(Pos::origin(), self.canvas_cell(&canvas.theme()));
     }
 }
 
@@ -84,10 +133,86 @@ mod tests {
     fn test_is_empty() {
         assert!(Empty.is_empty());
         assert!(!Normal.is_empty());
+        assert!(!Steel.is_empty());
         assert!(!Bomb.is_empty());
         assert!(!BigBombUpperLeft.is_empty());
         assert!(!BigBombUpperRight.is_empty());
         assert!(!BigBombLowerLeft.is_empty());
         assert!(!BigBombLowerRight.is_empty());
+        assert!(!HugeBombCenter.is_empty());
+        assert!(!HugeBombPart.is_empty());
+    }
+
+    #[test]
+    fn test_mirrored() {
+        assert_eq!(BigBombUpperRight, BigBombUpperLeft.mirrored());
+        assert_eq!(BigBombUpperLeft, BigBombUpperRight.mirrored());
+        assert_eq!(BigBombLowerRight, BigBombLowerLeft.mirrored());
+        assert_eq!(BigBombLowerLeft, BigBombLowerRight.mirrored());
+        for cell in [Empty, Normal, Steel, Bomb, HugeBombCenter, HugeBombPart] {
+            assert_eq!(cell, cell.mirrored());
+        }
+    }
+
+    #[test]
+    fn test_as_ascii() {
+        assert_eq!('.', Empty.as_ascii());
+        assert_eq!('#', Normal.as_ascii());
+        assert_eq!('@', Steel.as_ascii());
+        assert_eq!('*', Bomb.as_ascii());
+        assert_eq!('B', BigBombUpperLeft.as_ascii());
+        assert_eq!('B', BigBombLowerRight.as_ascii());
+        assert_eq!('H', HugeBombCenter.as_ascii());
+        assert_eq!('H', HugeBombPart.as_ascii());
+    }
+
+    #[test]
+    fn test_distinct_display_chars() {
+        let cells = [
+            Empty,
+            Normal,
+            Steel,
+            Bomb,
+            BigBombUpperLeft,
+            BigBombUpperRight,
+            BigBombLowerLeft,
+            BigBombLowerRight,
+            HugeBombCenter,
+            HugeBombPart,
+        ];
+        for (i, a) in cells.iter().enumerate() {
+            for b in cells.iter().skip(i + 1) {
+                assert_ne!(a.char_for_display(), b.char_for_display());
+            }
+        }
+    }
+
+    #[test]
+    fn test_draw_with_theme() {
+        use crate::geometry::*;
+        use crate::graphics::*;
+
+        // セルはキャンバスの配色に合わせた色で描画される
+        let draw = |theme: Theme| {
+            let mut canvas = RootCanvas::new();
+            canvas.set_theme(theme);
+            Bomb.draw_on_child(Pos::origin() + right(1), &mut canvas);
+            let (_, cell) = canvas.iter_pos_and_cell().nth(1).unwrap();
+            cell.color
+        };
+        let theme = Theme::standard(ColorSupport::Color256);
+        assert_eq!(theme.cell_color(ThemeColor::Bomb), draw(theme));
+        assert_eq!(
+            CanvasCellColor::new(Color::Red, Color::Black),
+            draw(theme.with_support(ColorSupport::Basic))
+        );
+
+        // 配色を切り替えると，同じセルでも異なる色で描画される
+        let high_contrast = Theme::new(ThemeName::HighContrast, ColorSupport::Basic);
+        assert_eq!(
+            CanvasCellColor::new(Color::White, Color::Black),
+            draw(high_contrast)
+        );
+        assert_ne!(draw(Theme::default()), draw(high_contrast));
     }
 }
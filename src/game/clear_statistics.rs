@@ -0,0 +1,236 @@
+use super::animation::ExplosionReport;
+use super::field::consts::{HEIGHT, WIDTH};
+use crate::geometry::*;
+use crate::graphics::*;
+use itertools::Itertools;
+
+/// フィールドの各行が，爆発によって何回消されたかを数える統計を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearStatistics {
+    /// 各行が爆発に巻き込まれた回数．インデックスは行のy座標に対応する．
+    counts: [u32; HEIGHT],
+}
+
+impl ClearStatistics {
+    pub const fn new() -> ClearStatistics {
+        Self {
+            counts: [0; HEIGHT],
+        }
+    }
+
+    /// 各行が爆発に巻き込まれた回数を，上の行から順に返す．
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+
+    /// 爆発の記録を統計に反映する．
+    /// 爆発の各段階について，消えたセルをひとつ以上含む行の回数を1増やす．
+    pub fn record_explosion(&mut self, report: &ExplosionReport) {
+        for step in report.steps() {
+            let ys = step
+                .iter()
+                .filter_map(|pos| pos.y().as_positive_index())
+                .unique();
+            for y in ys {
+                if let Some(count) = self.counts.get_mut(y) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    /// ヒートマップの色の段階．回数が少ない順に並ぶ．
+    const RAMP: [ThemeColor; 5] = [
+        ThemeColor::HeatLowest,
+        ThemeColor::HeatLow,
+        ThemeColor::HeatMiddle,
+        ThemeColor::HeatHigh,
+        ThemeColor::HeatHighest,
+    ];
+
+    /// 指定した回数に対応するヒートマップの色の役割を返す．
+    /// 回数が0の場合は`None`を返す．
+    fn heat_color(&self, count: u32) -> Option<ThemeColor> {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        if count == 0 || max == 0 {
+            return None;
+        }
+        let len = Self::RAMP.len() as u32;
+        let index = (count * len - 1) / max;
+        Some(Self::RAMP[std::cmp::min(index, len - 1) as usize])
+    }
+}
+
+impl Default for ClearStatistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// フィールドの外枠と，その右に各行の回数を色付きで並べたヒートマップを描画する．
+impl Drawable for ClearStatistics {
+    fn region_size(&self) -> Movement {
+        // 左右の枠とヒートマップ
+        right(WIDTH as i8 + 3) + below(HEIGHT as i8 + 1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let outline_color = canvas.theme().cell_color(ThemeColor::Text);
+        let wall = CanvasCell::new(SquareChar::new(' ', '|'), outline_color);
+        let right_wall = CanvasCell::new(SquareChar::new('|', ' '), outline_color);
+        let floor = CanvasCell::new(SquareChar::new('-', '-'), outline_color);
+
+        for (y, &count) in self.counts.iter().enumerate() {
+            let y = PosY::below(y as i8);
+            canvas.draw_cell(Pos(PosX::origin(), y), wall);
+            canvas.draw_cell(Pos(PosX::right(WIDTH as i8 + 1), y), right_wall);
+
+            if let Some(color) = self.heat_color(count) {
+                let label = format!("{:>2}", std::cmp::min(count, 99));
+                let mut chars = label.chars();
+                let c = SquareChar::new(chars.next().unwrap(), chars.next().unwrap());
+                let cell = CanvasCell::new(c, canvas.theme().inverted_color(color));
+                canvas.draw_cell(Pos(PosX::right(WIDTH as i8 + 2), y), cell);
+            }
+        }
+        for x in 0..WIDTH + 2 {
+            canvas.draw_cell(Pos(PosX::right(x as i8), PosY::below(HEIGHT as i8)), floor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::animation::{
+        Animation, AnimationField, AnimationResult, ChainCounter, Explosion, ExplosionInitResult,
+    };
+    use crate::game::test_util::repeating_selector;
+    use crate::game::QuadrupleBlockShape;
+    use crate::game::{BlockQueue, BombTag, Cell, Field};
+
+    /// 指定した行を埋め，そのうち`bomb_x`の位置にボムを置いたフィールドを爆発させ，その記録を返す．
+    fn explode_row(y: i8, bomb_x: i8) -> ExplosionReport {
+        let mut field = Field::empty();
+        let row = PosY::below(y);
+        for (_, cell) in field.iter_pos_and_cell_mut().filter(|(p, _)| p.y() == row) {
+            *cell = Cell::Normal;
+        }
+        *field.get_mut(Pos(PosX::right(bomb_x), row)).unwrap() = Cell::Bomb;
+
+        let field = AnimationField::new(
+            field,
+            BlockQueue::new(&mut repeating_selector(
+                QuadrupleBlockShape::T,
+                BombTag::Single(0),
+            )),
+        );
+        let mut explosion = match Explosion::try_init(field, &[row], ChainCounter::new()) {
+            ExplosionInitResult::Explodes(explosion) => explosion,
+            ExplosionInitResult::Stay(_) => panic!("the row should explode"),
+        };
+        loop {
+            match explosion.wait_next() {
+                AnimationResult::InProgress(next) => explosion = next,
+                AnimationResult::Finished((_, _, report)) => break report,
+            }
+        }
+    }
+
+    #[test]
+    fn test_record_explosions() {
+        let mut statistics = ClearStatistics::new();
+
+        // 1ライン揃えたときの爆発は，揃った行だけを消す
+        statistics.record_explosion(&explode_row(19, 4));
+        statistics.record_explosion(&explode_row(19, 0));
+        statistics.record_explosion(&explode_row(10, 9));
+
+        let mut expected = [0; HEIGHT];
+        expected[19] = 2;
+        expected[10] = 1;
+        assert_eq!(&expected[..], statistics.counts());
+    }
+
+    #[test]
+    fn test_record_counts_each_row_once_per_step() {
+        let report = ExplosionReport::from_steps(vec![
+            vec![
+                Pos(PosX::right(0), PosY::below(3)),
+                Pos(PosX::right(1), PosY::below(3)),
+                Pos(PosX::right(1), PosY::below(4)),
+            ],
+            vec![Pos(PosX::right(5), PosY::below(3))],
+        ]);
+
+        let mut statistics = ClearStatistics::new();
+        statistics.record_explosion(&report);
+
+        assert_eq!(2, statistics.counts()[3]);
+        assert_eq!(1, statistics.counts()[4]);
+        assert_eq!(3, statistics.counts().iter().sum::<u32>());
+    }
+
+    #[test]
+    fn test_heat_color() {
+        let mut statistics = ClearStatistics::new();
+        assert_eq!(None, statistics.heat_color(0));
+
+        statistics.counts[19] = 10;
+        assert_eq!(None, statistics.heat_color(0));
+        assert_eq!(Some(ThemeColor::HeatLowest), statistics.heat_color(1));
+        assert_eq!(Some(ThemeColor::HeatMiddle), statistics.heat_color(5));
+        assert_eq!(Some(ThemeColor::HeatHighest), statistics.heat_color(10));
+    }
+
+    #[test]
+    fn test_heatmap_follows_theme() {
+        let mut statistics = ClearStatistics::new();
+        statistics.counts[18] = 1;
+        statistics.counts[19] = 10;
+        let heat_x = PosX::right(WIDTH as i8 + 2);
+        let draw = |theme: Theme| {
+            let mut canvas = RootCanvas::new();
+            canvas.set_theme(theme);
+            statistics.draw(&mut canvas);
+            [18, 19].map(|y| {
+                let pos = Pos(heat_x, PosY::below(y));
+                let (_, cell) = canvas.iter_pos_and_cell().find(|&(p, _)| p == pos).unwrap();
+                cell.color
+            })
+        };
+
+        let standard = draw(Theme::standard(ColorSupport::Basic));
+        assert_eq!(CanvasCellColor::new(Color::Black, Color::Blue), standard[0]);
+        assert_eq!(CanvasCellColor::new(Color::Black, Color::Red), standard[1]);
+
+        // コントラストの高い配色では，回数によらず白地に黒で描画する
+        let high_contrast = draw(Theme::high_contrast(ColorSupport::Basic));
+        for color in high_contrast {
+            assert_eq!(CanvasCellColor::new(Color::Black, Color::White), color);
+        }
+    }
+
+    #[test]
+    fn test_draw_heatmap() {
+        let mut statistics = ClearStatistics::new();
+        statistics.counts[17] = 1;
+        statistics.counts[18] = 3;
+        statistics.counts[19] = 12;
+
+        let mut canvas = RootCanvas::new();
+        statistics.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let lines = buffer
+            .lines()
+            .map(|line| line[..(WIDTH + 3) * 2].to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(" |                    |   ", lines[0]);
+        assert_eq!(" |                    |  1", lines[17]);
+        assert_eq!(" |                    |  3", lines[18]);
+        assert_eq!(" |                    | 12", lines[19]);
+        assert_eq!("------------------------  ", lines[20]);
+    }
+}
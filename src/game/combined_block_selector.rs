@@ -0,0 +1,226 @@
+use super::{BlockSelector, BlockShape, BombTag};
+
+/// 直前に形状を選んだ生成ルールを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Chosen {
+    First,
+    Second,
+}
+
+/// 最初の指定した数のブロックをひとつめの生成ルールで生成し，以降をふたつめの生成ルールで生成するブロック生成ルール．
+/// 決めておいた序盤のブロック列の後に，無作為なブロックを生成し続ける場合などに利用する．
+///
+/// ボムセルの指定は，そのブロックの形状を選んだ生成ルールが決める．
+#[derive(Debug, Clone)]
+pub struct ChainSelector<A, B> {
+    first: A,
+    second: B,
+    /// ひとつめの生成ルールで生成するブロックの数．
+    first_count: usize,
+    /// これまでに形状を選んだブロックの数．
+    generated_blocks: usize,
+    chosen: Chosen,
+}
+
+impl<A, B> ChainSelector<A, B> {
+    /// 最初の`first_count`個のブロックを`first`で，以降を`second`で生成する生成ルールを返す．
+    pub fn new(first: A, first_count: usize, second: B) -> ChainSelector<A, B> {
+        Self {
+            first,
+            second,
+            first_count,
+            generated_blocks: 0,
+            chosen: Chosen::First,
+        }
+    }
+
+    /// ひとつめの生成ルールを返す．
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// ふたつめの生成ルールを返す．
+    pub fn second(&self) -> &B {
+        &self.second
+    }
+
+    /// ふたつめの生成ルールに切り替わるまでに，ひとつめの生成ルールで生成するブロックの数を返す．
+    pub fn remaining_first(&self) -> usize {
+        self.first_count.saturating_sub(self.generated_blocks)
+    }
+}
+
+impl<A: BlockSelector, B: BlockSelector> BlockSelector for ChainSelector<A, B> {
+    fn select_block_shape(&mut self) -> BlockShape {
+        if self.generated_blocks < self.first_count {
+            self.generated_blocks += 1;
+            self.chosen = Chosen::First;
+            self.first.select_block_shape()
+        } else {
+            self.chosen = Chosen::Second;
+            self.second.select_block_shape()
+        }
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        match self.chosen {
+            Chosen::First => self.first.select_bomb(shape),
+            Chosen::Second => self.second.select_bomb(shape),
+        }
+    }
+}
+
+/// 指定した間隔ごとにふたつめの生成ルールでブロックを生成し，それ以外をひとつめの生成ルールで生成するブロック生成ルール．
+/// 間隔を`k`とすると，`k`番目，`2k`番目，...のブロックをふたつめの生成ルールで生成する．
+/// 一定の間隔でボムだけのブロックを混ぜる場合などに利用する．
+///
+/// ボムセルの指定は，そのブロックの形状を選んだ生成ルールが決める．
+/// 一方の生成ルールでブロックを生成している間，もう一方の生成ルールの状態は進まない．
+#[derive(Debug, Clone)]
+pub struct IntervalSelector<A, B> {
+    base: A,
+    interleaved: B,
+    /// ふたつめの生成ルールでブロックを生成する間隔．
+    interval: usize,
+    /// 直前にふたつめの生成ルールで生成してから，形状を選んだブロックの数．
+    generated_blocks: usize,
+    chosen: Chosen,
+}
+
+impl<A, B> IntervalSelector<A, B> {
+    /// `interval`個ごとに1個のブロックを`interleaved`で，それ以外を`base`で生成する生成ルールを返す．
+    /// # Returns
+    /// 間隔が0の場合は`None`を返す．
+    pub fn new(base: A, interval: usize, interleaved: B) -> Option<IntervalSelector<A, B>> {
+        if interval == 0 {
+            return None;
+        }
+        Some(Self {
+            base,
+            interleaved,
+            interval,
+            generated_blocks: 0,
+            chosen: Chosen::First,
+        })
+    }
+
+    /// ひとつめの生成ルールを返す．
+    pub fn base(&self) -> &A {
+        &self.base
+    }
+
+    /// 間隔ごとに使うふたつめの生成ルールを返す．
+    pub fn interleaved(&self) -> &B {
+        &self.interleaved
+    }
+
+    /// ふたつめの生成ルールでブロックを生成する間隔を返す．
+    pub const fn interval(&self) -> usize {
+        self.interval
+    }
+}
+
+impl<A: BlockSelector, B: BlockSelector> BlockSelector for IntervalSelector<A, B> {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.generated_blocks = (self.generated_blocks + 1) % self.interval;
+        if self.generated_blocks == 0 {
+            self.chosen = Chosen::Second;
+            self.interleaved.select_block_shape()
+        } else {
+            self.chosen = Chosen::First;
+            self.base.select_block_shape()
+        }
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        match self.chosen {
+            Chosen::First => self.base.select_bomb(shape),
+            Chosen::Second => self.interleaved.select_bomb(shape),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::QuadrupleBlockShape::*;
+    use super::super::{ScriptEnd, ScriptedBlockSelector, SingleBlockShape};
+    use super::*;
+
+    fn script(blocks: &[(BlockShape, BombTag)]) -> ScriptedBlockSelector {
+        ScriptedBlockSelector::new(blocks.to_vec(), ScriptEnd::Repeat).unwrap()
+    }
+
+    fn blocks<S: BlockSelector>(selector: &mut S, count: usize) -> Vec<(BlockShape, BombTag)> {
+        (0..count)
+            .map(|_| {
+                let block = selector.generate_block();
+                (block.shape(), block.bomb_tag())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chain_boundary() {
+        let a = [
+            (O.into(), BombTag::Single(0)),
+            (T.into(), BombTag::Single(3)),
+        ];
+        let b = [(I.into(), BombTag::All), (S.into(), BombTag::None)];
+        let mut selector = ChainSelector::new(script(&a), 3, script(&b));
+        assert_eq!(3, selector.remaining_first());
+
+        let generated = blocks(&mut selector, 3);
+        assert_eq!(vec![a[0], a[1], a[0]], generated);
+        assert_eq!(0, selector.remaining_first());
+        // ひとつめの生成ルールは3個生成したところで止まる
+        assert_eq!(1, selector.first().remaining());
+        assert_eq!(2, selector.second().remaining());
+
+        let generated = blocks(&mut selector, 3);
+        assert_eq!(vec![b[0], b[1], b[0]], generated);
+        assert_eq!(1, selector.first().remaining());
+    }
+
+    #[test]
+    fn test_chain_without_first() {
+        let a = [(O.into(), BombTag::None)];
+        let b = [(L.into(), BombTag::Single(1))];
+        let mut selector = ChainSelector::new(script(&a), 0, script(&b));
+        assert_eq!(vec![b[0]; 3], blocks(&mut selector, 3));
+    }
+
+    #[test]
+    fn test_interval_boundary() {
+        let a = [
+            (O.into(), BombTag::None),
+            (J.into(), BombTag::Single(1)),
+            (Z.into(), BombTag::Single(2)),
+        ];
+        let b = [
+            (SingleBlockShape::O.into(), BombTag::All),
+            (I.into(), BombTag::All),
+        ];
+        let mut selector = IntervalSelector::new(script(&a), 3, script(&b)).unwrap();
+        assert_eq!(3, selector.interval());
+
+        // 3, 6, 9番目をふたつめの生成ルールで生成し，ひとつめの生成ルールは続きから生成する
+        let expected = vec![a[0], a[1], b[0], a[2], a[0], b[1], a[1], a[2], b[0]];
+        assert_eq!(expected, blocks(&mut selector, 9));
+        assert_eq!(3, selector.base().remaining());
+        assert_eq!(1, selector.interleaved().remaining());
+    }
+
+    #[test]
+    fn test_interval_every_block() {
+        let a = [(O.into(), BombTag::None)];
+        let b = [(T.into(), BombTag::All)];
+        let mut selector = IntervalSelector::new(script(&a), 1, script(&b)).unwrap();
+        assert_eq!(vec![b[0]; 3], blocks(&mut selector, 3));
+    }
+
+    #[test]
+    fn test_zero_interval() {
+        let a = script(&[(O.into(), BombTag::None)]);
+        assert!(IntervalSelector::new(a.clone(), 0, a).is_none());
+    }
+}
@@ -1,9 +1,15 @@
-use super::Cell;
+use super::{Block, Cell, GarbagePattern};
+use crate::data_type::{RowMajorTable, Shake, Table, TableIndex, TableMut, TableSize};
 use crate::geometry::*;
 use crate::graphics::*;
-use std::ops::{Deref, DerefMut};
+use itertools::Itertools;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::{Deref, DerefMut, Range};
+use std::str::FromStr;
 
-mod consts {
+pub(super) mod consts {
+    /// 既定のフィールドの大きさ．
     pub const WIDTH: usize = 10;
     pub const HEIGHT: usize = 20;
 }
@@ -11,30 +17,88 @@ mod consts {
 use consts::*;
 
 /// セルの集合として表されるフィールド．
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// 変更した可能性のあるセルの位置を記録しており，`take_changes`で取り出せる．
+/// 記録した位置はフィールドの比較や直列化には影響しない．
+#[derive(Debug, Clone)]
 pub struct Field {
     /// 各位置に割り当てられたセル．
-    cells: [[Cell; WIDTH]; HEIGHT],
+    cells: RowMajorTable<Cell>,
+    /// 前回`take_changes`を呼んでから，変更した可能性のあるセルの位置．
+    changes: HashSet<Pos>,
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
 }
 
+impl Eq for Field {}
+
 impl Field {
-    /// 空のフィールドを返す．
+    /// 既定の大きさ(横10セル，縦20セル)の空のフィールドを返す．
     /// # Returns
     /// すべてのセルが`Cell::Empty`である`Field`．
-    pub const fn empty() -> Field {
+    pub fn empty() -> Field {
+        Self::empty_with_size(WIDTH, HEIGHT)
+    }
+
+    /// 指定した大きさの空のフィールドを返す．
+    /// # Panics
+    /// 1. 横方向，または縦方向のセル数に0が指定された場合．
+    /// 1. 横方向，または縦方向のセル数が`i8::MAX`を超える場合．
+    pub fn empty_with_size(width: usize, height: usize) -> Field {
+        assert!(width <= i8::MAX as usize);
+        assert!(height <= i8::MAX as usize);
         Self {
-            cells: [[Cell::Empty; WIDTH]; HEIGHT],
+            cells: RowMajorTable::from_fill(Cell::Empty, TableSize::new(width, height)),
+            changes: HashSet::new(),
+        }
+    }
+
+    /// 下端の指定したライン数を，穴を除いて通常のセルで埋めた既定の大きさのフィールドを返す．
+    /// それより上のラインは空とする．
+    /// # Params
+    /// 1. `rng` 穴の列を決めるための乱数生成器．
+    /// 1. `filled_rows` 埋めるライン数．フィールドの縦方向のセル数を超える場合は，すべてのラインを埋める．
+    /// 1. `holes_per_row` 各ラインの穴の数．穴の列はラインごとに無作為に選ぶ．
+    ///
+    /// 埋めたラインがそろわないよう，穴の数に0が指定された場合も各ラインに1つ以上の穴をあける．
+    /// 穴の数がフィールドの横方向のセル数を超える場合は，ライン全体を穴とする．
+    pub fn random_garbage<R: rand::Rng>(
+        rng: &mut R,
+        filled_rows: usize,
+        holes_per_row: usize,
+    ) -> Field {
+        let mut field = Field::empty();
+        let (width, height) = (field.width(), field.height());
+        let filled_rows = std::cmp::min(filled_rows, height);
+        let holes_per_row = holes_per_row.clamp(1, width);
+
+        let cells = field.cells.as_raw_slice_mut();
+        for row in cells[(height - filled_rows) * width..].chunks_mut(width) {
+            row.fill(Cell::Normal);
+            for hole in rand::seq::index::sample(rng, width, holes_per_row) {
+                row[hole] = Cell::Empty;
+            }
         }
+        field
     }
 
     /// このフィールドの横方向のセル数を返す．
-    pub const fn width(&self) -> usize {
-        WIDTH
+    pub fn width(&self) -> usize {
+        self.cells.width()
     }
 
     /// このフィールドの縦方向のセル数を返す．
-    pub const fn height(&self) -> usize {
-        HEIGHT
+    pub fn height(&self) -> usize {
+        self.cells.height()
+    }
+
+    /// このフィールドのセルがすべて空である場合は`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter_items().all(|cell| cell.is_empty())
     }
 
     /// 指定した位置のセルへの参照を返す．
@@ -42,9 +106,7 @@ impl Field {
     /// 1. 指定した位置にセルが存在する場合は`Some(cell)`を返す．
     /// 1. 指定した位置にセルが存在しない場合は`None`を返す．
     pub fn get(&self, p: Pos) -> Option<&Cell> {
-        let x = p.x().as_positive_index()?;
-        let y = p.y().as_positive_index()?;
-        self.cells.get(y).and_then(|row| row.get(x))
+        self.cells.get(p.to_table_index()?)
     }
 
     /// 指定した位置のセルへの可変参照を返す．
@@ -52,9 +114,9 @@ impl Field {
     /// 1. 指定した位置にセルが存在する場合は`Some(cell)`を返す．
     /// 1. 指定した位置にセルが存在しない場合は`None`を返す．
     pub fn get_mut(&mut self, p: Pos) -> Option<&mut Cell> {
-        let x = p.x().as_positive_index()?;
-        let y = p.y().as_positive_index()?;
-        self.cells.get_mut(y).and_then(|row| row.get_mut(x))
+        let cell = self.cells.get_mut(p.to_table_index()?)?;
+        self.changes.insert(p);
+        Some(cell)
     }
 
     /// 指定した位置のライン(同じy座標をもつセル列)を返す．
@@ -63,7 +125,7 @@ impl Field {
     /// 1. 指定した位置にラインが存在しない場合は`None`を返す．
     pub fn row(&self, y: PosY) -> Option<FieldRow<'_>> {
         match y.as_positive_index() {
-            Some(y_index) if y_index < HEIGHT => Some(FieldRow::from_y_index(self, y_index)),
+            Some(y_index) if y_index < self.height() => Some(FieldRow::from_y_index(self, y_index)),
             _ => None,
         }
     }
@@ -78,20 +140,808 @@ impl Field {
 
     /// 最上段から順にこのフィールドのラインを返す．
     pub fn rows(&self) -> impl Iterator<Item = FieldRow<'_>> + '_ {
-        (0..HEIGHT).map(move |i| FieldRow::from_y_index(self, i))
+        (0..self.height()).map(move |i| FieldRow::from_y_index(self, i))
+    }
+
+    /// 最上段から順にこのフィールドの可変ラインを返す．
+    /// すべてのセルを変更した可能性があるものとして記録する．
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = FieldRowMut<'_>> + '_ {
+        self.mark_rows_changed(0..self.height());
+        self.cells
+            .iter_row_mut()
+            .enumerate()
+            .map(|(y_index, cells)| FieldRowMut { cells, y_index })
+    }
+
+    /// 指定した位置の列(同じx座標をもつセル列)のセルを，最上段から順に返す．
+    /// 指定した位置に列が存在しない場合は，何も返さない．
+    pub fn column(&self, x: PosX) -> impl Iterator<Item = &Cell> + '_ {
+        x.as_positive_index()
+            .and_then(|x_index| self.cells.iter_column().nth(x_index))
+            .into_iter()
+            .flatten()
+    }
+
+    /// 前回このメソッドを呼んでから変更した可能性のあるセルの位置を返し，記録を空にする．
+    /// セルへの可変参照を返すメソッドを呼んだ場合や，ラインを移動した場合などに位置を記録する．
+    /// 実際にはセルの値が変わっていない位置を含む場合がある．
+    pub fn take_changes(&mut self) -> HashSet<Pos> {
+        std::mem::take(&mut self.changes)
+    }
+
+    /// 前回`take_changes`を呼んでから変更した可能性のあるセルだけをキャンバスに描画し，記録を空にする．
+    /// 前回の描画内容が残っているキャンバスに重ねて描画することを想定している．
+    pub fn draw_changes<C: Canvas>(&mut self, canvas: &mut C) {
+        for pos in self.take_changes() {
+            if let Some(cell) = self.get(pos) {
+                cell.draw_on_child(pos, canvas);
+            }
+        }
+    }
+
+    /// このフィールドのすべてのセルとその位置を列挙する．
+    /// 最上段の左端から行優先順に列挙する．描画はこの順序に依存している．
+    pub fn iter_pos_and_cell(&self) -> impl Iterator<Item = (Pos, &Cell)> + '_ {
+        let width = self.width();
+        self.cells
+            .iter_items()
+            .enumerate()
+            .map(move |(i, cell)| (index_to_pos(i, width), cell))
+    }
+
+    /// このフィールドのすべてのセルへの可変参照とその位置を列挙する．
+    /// 列挙する順序は`iter_pos_and_cell`と同じ．
+    /// すべてのセルを変更した可能性があるものとして記録する．
+    pub fn iter_pos_and_cell_mut(&mut self) -> impl Iterator<Item = (Pos, &mut Cell)> + '_ {
+        self.mark_rows_changed(0..self.height());
+        let width = self.width();
+        self.cells
+            .as_raw_slice_mut()
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, cell)| (index_to_pos(i, width), cell))
+    }
+
+    /// 指定したブロックを，セルテーブルの左上が指定した位置となるよう配置できる場合は`true`を返す．
+    /// ブロックの空でないセルがすべてフィールド内にあり，いずれもフィールドの空でないセルと重ならない場合に配置できる．
+    pub fn can_place(&self, block: &Block, block_left_top: Pos) -> bool {
+        let diff = block_left_top - Pos::origin();
+        block
+            .iter_pos_and_occupied_cell()
+            .map(|(pos, _)| pos + diff)
+            .all(|pos| self.get(pos).is_some_and(|c| c.is_empty()))
+    }
+
+    /// 指定したブロックを，セルテーブルの左上が指定した位置となるようフィールドに設置する．
+    /// # Returns
+    /// ブロックの空でないセルがフィールドの外にはみ出すか，フィールドの空でないセルと重なる場合は，
+    /// 最初に見つかったそのセルの位置を含む`Err`を返す．このときフィールドは変化しない．
+    pub fn place_block(&mut self, block: &Block, block_left_top: Pos) -> Result<(), PlaceError> {
+        let diff = block_left_top - Pos::origin();
+        for (pos, _) in block.iter_pos_and_occupied_cell() {
+            let pos = pos + diff;
+            match self.get(pos) {
+                None => return Err(PlaceError::OutOfBounds(pos)),
+                Some(c) if !c.is_empty() => return Err(PlaceError::Occupied(pos)),
+                Some(_) => {}
+            }
+        }
+        self.place_block_lenient(block, block_left_top);
+        Ok(())
+    }
+
+    /// 指定したブロックを，セルテーブルの左上が指定した位置となるようフィールドに設置する．
+    /// `place_block`と異なり，フィールドの外にはみ出すセルは捨て，フィールドの空でないセルと重なるセルは上書きする．
+    pub fn place_block_lenient(&mut self, block: &Block, block_left_top: Pos) {
+        let diff = block_left_top - Pos::origin();
+        for (pos, &cell) in block.iter_pos_and_occupied_cell() {
+            if let Some(c) = self.get_mut(pos + diff) {
+                *c = cell;
+            }
+        }
+    }
+
+    /// 指定したブロックを操作ブロックとしてフィールドに登場させる場合の，初期位置(セルテーブルの左上の座標)を返す．
+    ///
+    /// 上下方向は，ブロックの空でないセルの上端がフィールドの最上段に揃う位置から順に，セルテーブルの大きさの半分だけ下の位置までを試す．
+    /// 左右方向は，セルテーブルの中央の列がフィールドの中央の列(幅が偶数の場合は中央の右側の列)に揃う位置を最初に試し，
+    /// 続けて1列右，1列左の位置を試す．
+    /// 各段で左右の3つの位置を試してから，次の段へ進む．
+    /// # Returns
+    /// 試したいずれかの位置に配置できる場合は，最初に見つかった位置`pos`を`Some(pos)`として返す．
+    /// いずれの位置にも配置できない場合は`None`を返す．
+    pub fn appearance_pos(&self, block: &Block) -> Option<Pos> {
+        let table_size = block.cell_table_size() as i8;
+        let shift_max = table_size / 2;
+        // 空でないセルの上端の，セルテーブル上の行
+        let top = block
+            .occupied_extent()
+            .left_top
+            .y()
+            .as_positive_index()
+            .unwrap() as i8;
+        let center_x = self.width() as i8 / 2 - table_size / 2;
+        (0..=shift_max)
+            .map(|dy| dy - top)
+            .flat_map(|y| {
+                Shake::<i8>::new()
+                    .take(3)
+                    .map(move |dx| Pos::origin() + below(y) + right(center_x + dx))
+            })
+            .find(|&pos| self.can_place(block, pos))
+    }
+
+    /// 左端の列から順に，各列に積まれたセルの高さを返す．
+    /// 高さは，最下段からその列で最も上にある空でないセルまでのセル数とする．
+    /// 途中の空セルも高さに含め，空でないセルがない列の高さは0とする．
+    /// デカボムや特大ボムを構成するセルも空でないセルとして扱う．
+    pub fn column_heights(&self) -> Vec<usize> {
+        let height = self.height();
+        (0..self.width())
+            .map(|x| {
+                self.column(PosX::right(x as i8))
+                    .position(|cell| !cell.is_empty())
+                    .map_or(0, |top| height - top)
+            })
+            .collect()
+    }
+
+    /// すべての列に積まれたセルの高さの合計を返す．
+    /// 各列の高さは`column_heights`と同じ．
+    pub fn aggregate_height(&self) -> usize {
+        self.column_heights().iter().sum()
+    }
+
+    /// 隣り合う列に積まれたセルの高さの差の絶対値を合計したものを返す．
+    /// 積まれたセルの表面の凹凸が大きいほど大きくなる．各列の高さは`column_heights`と同じ．
+    pub fn bumpiness(&self) -> usize {
+        self.column_heights()
+            .iter()
+            .tuple_windows()
+            .map(|(&left, &right)| left.abs_diff(right))
+            .sum()
+    }
+
+    /// 左端の列から順に，各列の穴の数を返す．
+    /// 穴は，同じ列でそれより上に空でないセルがある空セルとする．
+    /// 列で最も上にある空でないセルより上の空セルは穴に含めない．
+    pub fn holes_by_column(&self) -> Vec<usize> {
+        (0..self.width())
+            .map(|x| {
+                self.column(PosX::right(x as i8))
+                    .skip_while(|cell| cell.is_empty())
+                    .filter(|cell| cell.is_empty())
+                    .count()
+            })
+            .collect()
+    }
+
+    /// フィールド全体の穴の数を返す．
+    /// 穴の定義は`holes_by_column`と同じ．
+    pub fn count_holes(&self) -> usize {
+        self.holes_by_column().iter().sum()
+    }
+
+    /// 各ラインを左右反転したフィールドを返す．
+    /// デカボムを構成するセルは左右を入れ替えるので，反転後も2x2の形を保つ．
+    pub fn mirrored(&self) -> Field {
+        let width = self.width();
+        let mut mirrored = Field::empty_with_size(width, self.height());
+        for (dst, src) in mirrored
+            .cells
+            .as_raw_slice_mut()
+            .chunks_mut(width)
+            .zip(self.cells.as_raw_slice().chunks(width))
+        {
+            for (d, s) in dst.iter_mut().zip(src.iter().rev()) {
+                *d = s.mirrored();
+            }
+        }
+        mirrored
+    }
+
+    /// 形がそろっていないデカボムや特大ボムのセルの位置を，最上段の左端から行優先順に返す．
+    /// デカボムは2x2の4つのセルが，特大ボムは中央のセルとそれを囲む8つのセルがそろっている必要がある．
+    /// 爆発やラインの移動で一部のセルが失われたボムの，残りのセルが該当する．
+    pub fn validate_big_bombs(&self) -> Vec<Pos> {
+        use Cell::*;
+
+        let cell = |x: usize, y: usize| self.cells.get(TableIndex::new(x, y)).copied();
+        let mut intact = HashSet::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                match cell(x, y) {
+                    Some(BigBombUpperLeft) => {
+                        let parts = [
+                            (x + 1, y, BigBombUpperRight),
+                            (x, y + 1, BigBombLowerLeft),
+                            (x + 1, y + 1, BigBombLowerRight),
+                        ];
+                        if parts
+                            .iter()
+                            .all(|&(px, py, part)| cell(px, py) == Some(part))
+                        {
+                            intact.insert((x, y));
+                            intact.extend(parts.iter().map(|&(px, py, _)| (px, py)));
+                        }
+                    }
+                    Some(HugeBombCenter) if x > 0 && y > 0 => {
+                        let around = (y - 1..=y + 1)
+                            .flat_map(|py| (x - 1..=x + 1).map(move |px| (px, py)))
+                            .filter(|&p| p != (x, y))
+                            .collect::<Vec<_>>();
+                        if around
+                            .iter()
+                            .all(|&(px, py)| cell(px, py) == Some(HugeBombPart))
+                        {
+                            intact.insert((x, y));
+                            intact.extend(around);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let width = self.width();
+        self.cells
+            .iter_items()
+            .enumerate()
+            .filter(|&(i, c)| {
+                let is_part = matches!(
+                    c,
+                    BigBombUpperLeft
+                        | BigBombUpperRight
+                        | BigBombLowerLeft
+                        | BigBombLowerRight
+                        | HugeBombCenter
+                        | HugeBombPart
+                );
+                is_part && !intact.contains(&(i % width, i / width))
+            })
+            .map(|(i, _)| index_to_pos(i, width))
+            .collect()
+    }
+
+    /// 形がそろっていないデカボムや特大ボムのセルを，それぞれボムセルに戻す．
+    /// 戻すセルは`validate_big_bombs`が返す位置のセル．
+    pub fn repair_big_bombs(&mut self) {
+        for pos in self.validate_big_bombs() {
+            if let Some(cell) = self.get_mut(pos) {
+                *cell = Cell::Bomb;
+            }
+        }
+    }
+
+    /// フィールドのセルを種類ごとに数えて返す．
+    pub fn count_cells(&self) -> CellCounts {
+        let mut counts = CellCounts::default();
+        for cell in self.cells.iter_items() {
+            match cell {
+                Cell::Empty => counts.empty += 1,
+                Cell::Normal => counts.normal += 1,
+                Cell::Steel => counts.steel += 1,
+                Cell::Bomb => counts.bomb += 1,
+                Cell::BigBombUpperLeft
+                | Cell::BigBombUpperRight
+                | Cell::BigBombLowerLeft
+                | Cell::BigBombLowerRight => counts.big_bomb_part += 1,
+                Cell::HugeBombCenter | Cell::HugeBombPart => counts.huge_bomb_part += 1,
+            }
+        }
+        counts
+    }
+
+    /// フィールドの下端にせり上がりライン(お邪魔ライン)を挿入する．
+    /// 既存のセルは挿入したライン数だけ上へ移動し，フィールド上端からはみ出たセルは失われる．
+    /// はみ出たことで分断されたデカボムや特大ボムの残りのセルは，それぞれボムセルに戻す．
+    /// # Params
+    /// 1. `hole_columns` 挿入する各ラインの穴の列．上のラインから順に指定する．
+    ///    各ラインは，穴の列のセルが空で，それ以外のセルが通常のセルとなる．
+    ///    穴の列がフィールドの外を指す場合は右端の列を穴とし，挿入したラインがそろわないようにする．
+    ///
+    /// # Returns
+    /// 空でないセルがフィールド上端からはみ出た場合は`true`を返す．
+    pub fn insert_garbage_rows(&mut self, hole_columns: &[usize]) -> bool {
+        self.insert_garbage_rows_with(hole_columns, Cell::Normal)
+    }
+
+    /// 穴以外を指定したセルで埋めたせり上がりラインを挿入する．
+    /// 爆発で消えないせり上がりラインを作る場合は，`Cell::Steel`を指定する．
+    /// その他は`insert_garbage_rows`と同様．
+    pub fn insert_garbage_rows_with(&mut self, hole_columns: &[usize], cell: Cell) -> bool {
+        let width = self.width();
+        let height = self.height();
+        let rows = std::cmp::min(hole_columns.len(), height);
+        let hole_columns = &hole_columns[hole_columns.len() - rows..];
+
+        self.mark_rows_changed(0..height);
+        let cells = self.cells.as_raw_slice_mut();
+        let overflowed = cells[..rows * width].iter().any(|cell| !cell.is_empty());
+
+        cells.rotate_left(rows * width);
+        for (row, &hole) in cells[(height - rows) * width..]
+            .chunks_mut(width)
+            .zip(hole_columns)
+        {
+            let hole = std::cmp::min(hole, width.saturating_sub(1));
+            for (x, c) in row.iter_mut().enumerate() {
+                *c = if x == hole { Cell::Empty } else { cell };
+            }
+        }
+        self.repair_big_bombs();
+
+        overflowed
+    }
+
+    /// 指定したラインを取り除き，それより上にあるラインを1ライン下へ移動する．
+    /// フィールド上端の空いたラインは空セルで埋める．
+    /// 取り除いたラインで分断されたデカボムや特大ボムの残りのセルは，それぞれボムセルに戻す．
+    /// フィールドの外を指すy座標は無視する．
+    pub fn remove_row(&mut self, y: PosY) {
+        self.remove_rows(&[y]);
+    }
+
+    /// 指定した範囲のラインを，指定したライン数だけまとめて下へ移動する．
+    /// 移動先にあったラインは上書きされ，フィールド下端からはみ出したラインは失われる．
+    /// 範囲の上端から移動したライン数ぶんの，移動後に空いたラインは空セルで埋める．
+    /// 移動によって分断されたデカボムや特大ボムのセルは，それぞれボムセルに戻す．
+    /// 範囲のうちフィールドの外を指す部分は無視する．
+    pub fn shift_rows_down(&mut self, ys: Range<PosY>, amount: usize) {
+        let width = self.width();
+        let height = self.height();
+        let clamp = |y: PosY| std::cmp::min(std::cmp::max(y.below_shift, 0) as usize, height);
+        let (start, end) = (clamp(ys.start), clamp(ys.end));
+        if start >= end || amount == 0 {
+            return;
+        }
+
+        self.mark_rows_changed(start..std::cmp::min(end + amount, height));
+        let cells = self.cells.as_raw_slice_mut();
+        // 下のラインから順に移動すれば，まだ移動していないラインを上書きしない
+        for y_index in (start..end).rev() {
+            let destination = y_index + amount;
+            if destination < height {
+                cells.copy_within(y_index * width..(y_index + 1) * width, destination * width);
+            }
+        }
+        let vacated_end = std::cmp::min(start + amount, end);
+        cells[start * width..vacated_end * width].fill(Cell::Empty);
+
+        self.repair_big_bombs();
+    }
+
+    /// 指定したラインを取り除き，それより上にあるセルを取り除いたライン数だけ下へ移動する．
+    /// フィールド上端の空いたラインは空セルで埋める．
+    /// 取り除いたラインで分断されたデカボムや特大ボムの残りのセルは，それぞれボムセルに戻す．
+    /// フィールドの外を指すy座標は無視する．
+    pub fn remove_rows(&mut self, ys: &[PosY]) {
+        let width = self.width();
+        let height = self.height();
+
+        // 上のラインから順に取り除けば，残りのラインの位置は変わらない
+        let y_indices = ys
+            .iter()
+            .filter_map(|y| y.as_positive_index())
+            .filter(|&y_index| y_index < height)
+            .sorted()
+            .dedup()
+            .collect::<Vec<_>>();
+        if let Some(&lowest) = y_indices.last() {
+            self.mark_rows_changed(0..lowest + 1);
+        }
+        let cells = self.cells.as_raw_slice_mut();
+        for y_index in y_indices {
+            let shifted = &mut cells[..(y_index + 1) * width];
+            shifted.rotate_right(width);
+            shifted[..width].fill(Cell::Empty);
+        }
+
+        self.repair_big_bombs();
+    }
+
+    /// 空セルを含まないライン(揃ったライン)のy座標を，最上段に近いものから順に返す．
+    pub fn filled_rows(&self) -> Vec<PosY> {
+        self.rows()
+            .filter(|row| row.iter().all(|cell| !cell.is_empty()))
+            .map(|row| row.y())
+            .collect()
+    }
+
+    /// 空セルを含まないラインをすべて空セルにする．
+    /// ほかのセルは移動しない．空けたラインより上のセルを下へ移動するには`remove_rows`を利用する．
+    /// # Returns
+    /// 空セルにしたラインのy座標．最上段に近いものから順に並べる．
+    pub fn clear_filled_rows(&mut self) -> Vec<PosY> {
+        let filled_row_ys = self.filled_rows();
+        for &y in filled_row_ys.iter() {
+            if let Some(mut row) = self.row_mut(y) {
+                row.fill(Cell::Empty);
+            }
+        }
+        filled_row_ys
+    }
+
+    /// 指定したパターンに従って，フィールドの下端にせり上がりラインを挿入する．
+    /// 詳細は`insert_garbage_rows`を参照．
+    pub fn insert_garbage<R: rand::Rng>(
+        &mut self,
+        rows: usize,
+        pattern: GarbagePattern,
+        rng: &mut R,
+    ) -> bool {
+        let hole_columns = pattern.hole_columns(rows, self.width(), rng);
+        self.insert_garbage_rows(&hole_columns)
+    }
+
+    /// このフィールドと指定したフィールドとで異なるセルを列挙する．
+    /// # Returns
+    /// `(位置, このフィールドのセル, 指定したフィールドのセル)`を，最上段の左端から行優先順に並べたもの．
+    pub fn diff(&self, other: &Field) -> Vec<(Pos, Cell, Cell)> {
+        self.rows()
+            .zip(other.rows())
+            .flat_map(|(row, other_row)| {
+                let y = row.y();
+                row.iter()
+                    .zip(other_row.iter())
+                    .enumerate()
+                    .filter(|(_, (cell, other_cell))| cell != other_cell)
+                    .map(move |(x, (&cell, &other_cell))| {
+                        (Pos(PosX::right(x as i8), y), cell, other_cell)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// このフィールドと指定したフィールドとの差分を，人が読みやすい文字列として返す．
+    ///
+    /// 異なるセルを含むラインだけを，`y=行番号: このフィールド | 指定したフィールド | 差分`の形式で1行ずつ出力する．
+    /// 差分の欄では，異なるセルが`!`で示される．
+    /// 各セルの表記は`Cell::as_ascii`に従う．
+    pub fn format_diff(&self, other: &Field) -> String {
+        let mut s = String::new();
+
+        for (row, other_row) in self.rows().zip(other.rows()) {
+            if row[..] == other_row[..] {
+                continue;
+            }
+            let left = row.iter().map(|c| c.as_ascii()).collect::<String>();
+            let right = other_row.iter().map(|c| c.as_ascii()).collect::<String>();
+            let marks = row
+                .iter()
+                .zip(other_row.iter())
+                .map(|(c, other_c)| if c == other_c { '.' } else { '!' })
+                .collect::<String>();
+            s.push_str(&format!(
+                "y={:2}: {} | {} | {}\n",
+                row.y().below_shift,
+                left,
+                right,
+                marks
+            ));
+        }
+
+        s
+    }
+
+    /// このフィールドを，最上段から順に1ラインを1行とした文字列として返す．
+    /// 各セルの表記は`Cell::as_ascii`に従う．
+    pub fn to_str_art(&self) -> String {
+        let mut s = String::new();
+        for row in self.rows() {
+            s.extend(row.iter().map(|c| c.as_ascii()));
+            s.push('\n');
+        }
+        s
+    }
+
+    /// `to_str_art`の形式の文字列からフィールドを構築する．
+    ///
+    /// フィールドは既定の大きさとなる．
+    /// 空行と各行の前後の空白は無視され，与えた行はフィールドの最下段に揃えて配置される．
+    /// 与えなかった上側のラインは空になる．
+    /// `B`は左上から行優先順に2x2のデカボムとして，`H`は同様に3x3の特大ボムとして解釈される．
+    /// # Returns
+    /// 行数や行の長さが合わない場合や，デカボムや特大ボムを構成できない`B`や`H`がある場合は`None`を返す．
+    pub fn from_str_art(art: &str) -> Option<Field> {
+        Self::from_art_lines(&art_lines(art), WIDTH, HEIGHT).ok()
+    }
+
+    /// 空行を除いた行から，指定した大きさのフィールドを構築する．
+    /// 行はフィールドの最下段に揃えて配置し，与えなかった上側のラインは空にする．
+    /// # Params
+    /// 1. `lines` 行番号と，行の文字の組．
+    fn from_art_lines(
+        lines: &[(usize, Vec<char>)],
+        width: usize,
+        height: usize,
+    ) -> Result<Field, FieldParseError> {
+        let first_line = lines.first().map_or(1, |&(line, _)| line);
+        if width == 0 || height == 0 || width > i8::MAX as usize || height > i8::MAX as usize {
+            return Err(FieldParseError {
+                line: first_line,
+                kind: FieldParseErrorKind::TooLarge { width, height },
+            });
+        }
+        if lines.len() > height {
+            return Err(FieldParseError {
+                line: first_line,
+                kind: FieldParseErrorKind::TooLarge {
+                    width,
+                    height: lines.len(),
+                },
+            });
+        }
+        if let Some((line, chars)) = lines.iter().find(|(_, chars)| chars.len() != width) {
+            return Err(FieldParseError {
+                line: *line,
+                kind: FieldParseErrorKind::RaggedLine {
+                    expected: width,
+                    actual: chars.len(),
+                },
+            });
+        }
+
+        // 最下段に揃えたときの，各ラインの文字と行番号
+        let offset = height - lines.len();
+        let mut chars = vec![vec!['.'; width]; height];
+        for (row, (_, line)) in chars[offset..].iter_mut().zip(lines) {
+            row.copy_from_slice(line);
+        }
+        let line_of = |y: usize| lines[y.saturating_sub(offset)].0;
+
+        let mut field = Field::empty_with_size(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let index = TableIndex::new(x, y);
+                if !field.cells[index].is_empty() {
+                    continue;
+                }
+                let error = |kind| FieldParseError {
+                    line: line_of(y),
+                    kind,
+                };
+                field.cells[index] = match chars[y][x] {
+                    '.' => Cell::Empty,
+                    '#' => Cell::Normal,
+                    '@' => Cell::Steel,
+                    '*' => Cell::Bomb,
+                    'B' => {
+                        // 右，下，右下もデカボムの一部でなければならない
+                        let is_big_bomb = |y: usize, x: usize| {
+                            y < height
+                                && x < width
+                                && chars[y][x] == 'B'
+                                && field.cells[TableIndex::new(x, y)].is_empty()
+                        };
+                        if !(is_big_bomb(y, x + 1)
+                            && is_big_bomb(y + 1, x)
+                            && is_big_bomb(y + 1, x + 1))
+                        {
+                            return Err(error(FieldParseErrorKind::MalformedBigBomb));
+                        }
+                        field.cells[TableIndex::new(x + 1, y)] = Cell::BigBombUpperRight;
+                        field.cells[TableIndex::new(x, y + 1)] = Cell::BigBombLowerLeft;
+                        field.cells[TableIndex::new(x + 1, y + 1)] = Cell::BigBombLowerRight;
+                        Cell::BigBombUpperLeft
+                    }
+                    'H' => {
+                        // 左上から3x3のセルがすべて特大ボムの一部でなければならない
+                        let is_huge_bomb = |y: usize, x: usize| {
+                            y < height
+                                && x < width
+                                && chars[y][x] == 'H'
+                                && field.cells[TableIndex::new(x, y)].is_empty()
+                        };
+                        if !(0..3).all(|dy| (0..3).all(|dx| is_huge_bomb(y + dy, x + dx))) {
+                            return Err(error(FieldParseErrorKind::MalformedHugeBomb));
+                        }
+                        for dy in 0..3 {
+                            for dx in 0..3 {
+                                field.cells[TableIndex::new(x + dx, y + dy)] = Cell::HugeBombPart;
+                            }
+                        }
+                        field.cells[TableIndex::new(x + 1, y + 1)] = Cell::HugeBombCenter;
+                        Cell::HugeBombPart
+                    }
+                    c => return Err(error(FieldParseErrorKind::UnknownChar(c))),
+                };
+            }
+        }
+
+        Ok(field)
+    }
+}
+
+/// 行優先順に並べたセルのインデックスを，フィールド上の位置に変換する．
+fn index_to_pos(index: usize, width: usize) -> Pos {
+    Pos(
+        PosX::right((index % width) as i8),
+        PosY::below((index / width) as i8),
+    )
+}
+
+/// 文字列の各行の前後の空白を取り除き，空行を除いた行を，1から始まる行番号とともに返す．
+fn art_lines(art: &str) -> Vec<(usize, Vec<char>)> {
+    art.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| (i, line.chars().collect()))
+        .collect()
+}
+
+/// 1ラインを1行とし，各セルを`Cell::as_ascii`の文字で表した文字列として書き出す．
+/// `to_str_art`と同じ内容になる．
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_str_art())
+    }
+}
+
+/// `Display`で書き出した文字列からフィールドを構築する．
+///
+/// フィールドの横のセル数は最初の行の長さ，縦のセル数は行の数となる．
+/// 空行と各行の前後の空白は無視する．
+/// `B`と`H`の解釈は`Field::from_str_art`と同じ．
+impl FromStr for Field {
+    type Err = FieldParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = art_lines(s);
+        let width = match lines.first() {
+            Some((_, chars)) => chars.len(),
+            None => {
+                return Err(FieldParseError {
+                    line: 1,
+                    kind: FieldParseErrorKind::NoRows,
+                })
+            }
+        };
+        Self::from_art_lines(&lines, width, lines.len())
+    }
+}
+
+/// フィールドのセルを種類ごとに数えた結果．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct CellCounts {
+    /// 空セルの数．
+    pub empty: usize,
+    /// 通常のセルの数．
+    pub normal: usize,
+    /// 鋼鉄のセルの数．
+    pub steel: usize,
+    /// ボムセルの数．
+    pub bomb: usize,
+    /// デカボムを構成するセルの数．
+    pub big_bomb_part: usize,
+    /// 特大ボムを構成するセルの数．
+    pub huge_bomb_part: usize,
+}
+
+impl CellCounts {
+    /// 空でないセルの数を返す．
+    pub const fn occupied(&self) -> usize {
+        self.normal + self.steel + self.bomb + self.big_bomb_part + self.huge_bomb_part
+    }
+
+    /// 数えたセルの総数を返す．
+    pub const fn total(&self) -> usize {
+        self.empty + self.occupied()
+    }
+
+    /// 空でないセルがひとつもない場合は`true`を返す．
+    pub const fn is_empty_field(&self) -> bool {
+        self.occupied() == 0
+    }
+}
+
+/// ブロックをフィールドに設置できなかった理由を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    /// ブロックの空でないセルがフィールドの外にはみ出す．はみ出したセルの位置をもつ．
+    OutOfBounds(Pos),
+    /// ブロックの空でないセルがフィールドの空でないセルと重なる．重なったセルの位置をもつ．
+    Occupied(Pos),
+}
+
+impl PlaceError {
+    /// ブロックのセルがフィールドの上端からはみ出したために設置できなかった場合は`true`を返す．
+    pub fn is_above_top(&self) -> bool {
+        match *self {
+            PlaceError::OutOfBounds(pos) => pos.y() < PosY::origin(),
+            PlaceError::Occupied(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PlaceError::OutOfBounds(pos) => write!(f, "cell at {} is out of the field", pos),
+            PlaceError::Occupied(pos) => write!(f, "cell at {} is already occupied", pos),
+        }
+    }
+}
+
+impl std::error::Error for PlaceError {}
+
+/// フィールドの文字列表現を読み込めなかった理由と，その行番号を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldParseError {
+    /// 問題のあった行の，1から始まる行番号．
+    pub line: usize,
+    pub kind: FieldParseErrorKind,
+}
+
+/// フィールドの文字列表現を読み込めなかった理由を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldParseErrorKind {
+    /// 空でない行がひとつもない．
+    NoRows,
+    /// フィールドの大きさが0であるか，上限を超える．
+    TooLarge { width: usize, height: usize },
+    /// 行の長さが，フィールドの横のセル数と異なる．
+    RaggedLine { expected: usize, actual: usize },
+    /// セルとして解釈できない文字がある．
+    UnknownChar(char),
+    /// デカボムを構成できない`B`がある．
+    MalformedBigBomb,
+    /// 特大ボムを構成できない`H`がある．
+    MalformedHugeBomb,
+}
+
+impl fmt::Display for FieldParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use FieldParseErrorKind::*;
+
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            NoRows => write!(f, "field has no rows"),
+            TooLarge { width, height } => write!(f, "invalid field size {}x{}", width, height),
+            RaggedLine { expected, actual } => {
+                write!(f, "row has {} cells, expected {}", actual, expected)
+            }
+            UnknownChar(c) => write!(f, "unknown cell `{}`", c),
+            MalformedBigBomb => write!(f, "`B` does not form a 2x2 big bomb"),
+            MalformedHugeBomb => write!(f, "`H` does not form a 3x3 huge bomb"),
+        }
+    }
+}
+
+impl std::error::Error for FieldParseError {}
+
+impl Field {
+    /// 指定した行番号の範囲にあるラインのセルを，すべて変更した可能性があるものとして記録する．
+    fn mark_rows_changed(&mut self, y_indices: Range<usize>) {
+        let width = self.width();
+        for y_index in y_indices {
+            let y = PosY::below(y_index as i8);
+            self.changes
+                .extend((0..width).map(|x_index| Pos(PosX::right(x_index as i8), y)));
+        }
+    }
+
+    /// 指定した行番号のラインのセルを返す．
+    fn row_cells(&self, y_index: usize) -> &[Cell] {
+        let width = self.width();
+        let start = y_index * width;
+        &self.cells.as_raw_slice()[start..start + width]
     }
 }
 
 impl Drawable for Field {
     fn region_size(&self) -> Movement {
-        right(WIDTH as i8) + below(HEIGHT as i8)
+        Movement::from(self.cells.size())
     }
 
+    /// 上から`RootCanvas::MAX_HEIGHT`本までのラインを描画する．
+    /// キャンバスからはみ出すセルは描画されない．
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        for (y, row) in self.rows().enumerate() {
-            for (x, cell) in row.iter().enumerate() {
-                let pos = Pos(PosX::right(x as i8), PosY::below(y as i8));
-                cell.draw_on_child(pos, canvas);
+        for row in self.rows().take(RootCanvas::MAX_HEIGHT) {
+            for cell_ref in row.cell_refs() {
+                cell_ref.cell().draw_on_child(cell_ref.pos(), canvas);
             }
         }
     }
@@ -108,21 +958,20 @@ impl<'f> FieldRow<'f> {
         PosY::origin() + below(self.y_index as i8)
     }
 
-    pub const fn width(&self) -> usize {
+    pub fn width(&self) -> usize {
         self.field.width()
     }
 
-    pub fn cell_refs(&self) -> impl IntoIterator<Item = FieldCellRef<'_>> + '_ {
+    /// このラインのセルを，フィールド上の位置とともに左端から順に列挙する．
+    pub fn cell_refs(&self) -> impl Iterator<Item = CellRef<'_>> + '_ {
         let y = self.y();
         self.iter()
             .enumerate()
-            .map(|(x, cell)| (PosX::right(x as i8), cell))
-            .map(move |(x, cell)| (Pos(x, y), cell))
-            .map(|(pos, cell)| FieldCellRef::new(cell, pos))
+            .map(move |(x, cell)| CellRef::new(cell, Pos(PosX::right(x as i8), y)))
     }
 
     fn from_y_index(field: &'f Field, y_index: usize) -> FieldRow<'f> {
-        debug_assert!(y_index < HEIGHT);
+        debug_assert!(y_index < field.height());
         Self { field, y_index }
     }
 }
@@ -131,20 +980,27 @@ impl Deref for FieldRow<'_> {
     type Target = [Cell];
 
     fn deref(&self) -> &Self::Target {
-        &self.field.cells[self.y_index]
+        self.field.row_cells(self.y_index)
     }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct FieldRowMut<'f> {
-    field: &'f mut Field,
+    cells: &'f mut [Cell],
     y_index: usize,
 }
 
 impl<'f> FieldRowMut<'f> {
+    /// ラインのすべてのセルを変更した可能性があるものとして記録する．
     pub fn new(field: &'f mut Field, y: PosY) -> Option<FieldRowMut<'f>> {
         match y.as_positive_index() {
-            Some(y_index) if y_index < HEIGHT => Some(Self { field, y_index }),
+            Some(y_index) if y_index < field.height() => {
+                field.mark_rows_changed(y_index..y_index + 1);
+                let width = field.width();
+                let start = y_index * width;
+                let cells = &mut field.cells.as_raw_slice_mut()[start..start + width];
+                Some(Self { cells, y_index })
+            }
             _ => None,
         }
     }
@@ -152,56 +1008,222 @@ impl<'f> FieldRowMut<'f> {
     pub fn y(&self) -> PosY {
         PosY::origin() + below(self.y_index as i8)
     }
+
+    /// このラインのセルへの可変参照を，フィールド上の位置とともに左端から順に列挙する．
+    pub fn cell_refs_mut(&mut self) -> impl Iterator<Item = CellRefMut<'_>> + '_ {
+        let y = self.y();
+        self.iter_mut()
+            .enumerate()
+            .map(move |(x, cell)| CellRefMut::new(cell, Pos(PosX::right(x as i8), y)))
+    }
 }
 
 impl Deref for FieldRowMut<'_> {
     type Target = [Cell];
 
     fn deref(&self) -> &Self::Target {
-        &self.field.cells[self.y_index]
+        self.cells
     }
 }
 
 impl DerefMut for FieldRowMut<'_> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.field.cells[self.y_index]
+        self.cells
     }
 }
 
+/// フィールド上のセルへの参照と，そのセルの位置．
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct FieldCellRef<'c> {
-    cell: &'c Cell,
+pub struct CellRef<'f> {
+    cell: &'f Cell,
     pos: Pos,
 }
 
-impl<'c> FieldCellRef<'c> {
-    const fn new(cell: &'c Cell, pos: Pos) -> FieldCellRef<'c> {
+impl<'f> CellRef<'f> {
+    const fn new(cell: &'f Cell, pos: Pos) -> CellRef<'f> {
         Self { cell, pos }
     }
 
+    /// 参照しているセルを返す．
     pub const fn cell(&self) -> &Cell {
         self.cell
     }
 
+    /// 参照しているセルのフィールド上の位置を返す．
+    pub const fn pos(&self) -> Pos {
+        self.pos
+    }
+}
+
+/// フィールド上のセルへの可変参照と，そのセルの位置．
+#[derive(Debug, PartialEq, Eq)]
+pub struct CellRefMut<'f> {
+    cell: &'f mut Cell,
+    pos: Pos,
+}
+
+impl<'f> CellRefMut<'f> {
+    fn new(cell: &'f mut Cell, pos: Pos) -> CellRefMut<'f> {
+        Self { cell, pos }
+    }
+
+    /// 参照しているセルを返す．
+    pub fn cell(&self) -> &Cell {
+        self.cell
+    }
+
+    /// 参照しているセルへの可変参照を返す．
+    pub fn cell_mut(&mut self) -> &mut Cell {
+        self.cell
+    }
+
+    /// 参照しているセルのフィールド上の位置を返す．
     pub const fn pos(&self) -> Pos {
         self.pos
     }
 }
 
+/// フィールドの直列化．
+/// 横と縦のセル数と，セルを行優先順に並べた列を書き出す．
+#[cfg(feature = "serde")]
+mod serialization {
+    use super::{Cell, Field, RowMajorTable, TableSize};
+    use serde::de::Error as _;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize)]
+    struct FieldRef<'a> {
+        width: usize,
+        height: usize,
+        cells: &'a [Cell],
+    }
+
+    #[derive(Deserialize)]
+    struct FieldRepr {
+        width: usize,
+        height: usize,
+        cells: Vec<Cell>,
+    }
+
+    impl Serialize for Field {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let repr = FieldRef {
+                width: self.width(),
+                height: self.height(),
+                cells: self.cells.as_raw_slice(),
+            };
+            repr.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Field {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let FieldRepr {
+                width,
+                height,
+                cells,
+            } = FieldRepr::deserialize(deserializer)?;
+            let max = i8::MAX as usize;
+            if width == 0 || height == 0 || width > max || height > max {
+                return Err(D::Error::custom(format_args!(
+                    "invalid field size {}x{}",
+                    width, height
+                )));
+            }
+            if cells.len() != width * height {
+                return Err(D::Error::custom(format_args!(
+                    "field of {}x{} needs {} cells, got {}",
+                    width,
+                    height,
+                    width * height,
+                    cells.len()
+                )));
+            }
+            let cells = RowMajorTable::from_vec(cells, TableSize::new(width, height));
+            Ok(Field {
+                cells,
+                changes: Default::default(),
+            })
+        }
+    }
+}
+
+/// 2つのフィールドが等しいことを検査し，異なる場合は差分を表示してpanicする．
+#[cfg(test)]
+pub(crate) fn assert_field_eq(expected: &Field, actual: &Field) {
+    if expected != actual {
+        panic!(
+            "fields differ (expected | actual | diff):\n{}",
+            expected.format_diff(actual)
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::{BlockShape, BlockShapeBuilder, BombTag, Direction};
+    use super::super::{QuadrupleBlockShape, QuintupleBlockShape, SingleBlockShape};
     use super::*;
 
     #[test]
     fn test_empty() {
         let field = Field::empty();
-        for row in field.cells.iter() {
-            for &cell in row.iter() {
-                assert_eq!(Cell::Empty, cell);
-            }
+        for &cell in field.cells.iter_items() {
+            assert_eq!(Cell::Empty, cell);
         }
     }
 
+    #[test]
+    fn test_is_empty() {
+        let mut field = Field::empty_with_size(3, 2);
+        assert!(field.is_empty());
+        *field.get_mut(Pos::origin() + right(2) + below(1)).unwrap() = Cell::Bomb;
+        assert!(!field.is_empty());
+    }
+
+    #[test]
+    fn test_empty_with_size() {
+        let field = Field::empty_with_size(6, 30);
+        assert_eq!(6, field.width());
+        assert_eq!(30, field.height());
+        assert_eq!(30, field.rows().count());
+        assert!(field.rows().all(|row| row.len() == 6 && row.width() == 6));
+        assert_eq!(right(6) + below(30), field.region_size());
+
+        assert!(field.get(Pos::origin() + right(5) + below(29)).is_some());
+        assert!(field.get(Pos::origin() + right(6)).is_none());
+        assert!(field.get(Pos::origin() + below(30)).is_none());
+        assert!(field.row(PosY::below(29)).is_some());
+        assert!(field.row(PosY::below(30)).is_none());
+    }
+
+    #[test]
+    fn test_row_mut_with_size() {
+        let mut field = Field::empty_with_size(4, 3);
+        field.row_mut(PosY::below(1)).unwrap()[3] = Cell::Bomb;
+        assert_eq!(
+            Some(&Cell::Bomb),
+            field.get(Pos(PosX::right(3), PosY::below(1)))
+        );
+        assert_eq!("....\n...*\n....\n", field.to_str_art());
+
+        assert!(!field.insert_garbage_rows(&[0]));
+        assert_eq!("...*\n....\n.###\n", field.to_str_art());
+    }
+
+    #[test]
+    fn test_draw_clips_tall_field() {
+        let mut field = Field::empty_with_size(3, 100);
+        *field.get_mut(Pos::origin() + below(99)).unwrap() = Cell::Normal;
+        *field.get_mut(Pos::origin()).unwrap() = Cell::Bomb;
+        let mut canvas = RootCanvas::new();
+        field.draw(&mut canvas);
+
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        assert_eq!(canvas.output_size().1, buffer.lines().count());
+    }
+
     #[test]
     fn test_width() {
         let field = Field::empty();
@@ -265,6 +1287,8 @@ mod tests {
         let lower_right = p + right(WIDTH as i8 - 1) + below(HEIGHT as i8 - 1);
         *field.get_mut(lower_right).unwrap() = Cell::BigBombUpperRight;
         assert_eq!(Some(&Cell::BigBombUpperRight), field.get(lower_right));
+        // 書き換えた4隅以外のセルは変化していないはず
+        assert_eq!(4, Field::empty().diff(&field).len());
 
         // 正のx方向にはみ出た座標
         let outer_positive_x = p + right(WIDTH as i8);
@@ -310,11 +1334,16 @@ mod tests {
                 *cell = Cell::Bomb;
             }
         }
-        assert!(field
-            .row(PosY::origin())
-            .unwrap()
-            .iter()
-            .all(|&cell| cell == Cell::Bomb));
+        let expected = {
+            let mut expected = Field::empty();
+            for x in 0..WIDTH {
+                *expected
+                    .get_mut(Pos(PosX::right(x as i8), PosY::origin()))
+                    .unwrap() = Cell::Bomb;
+            }
+            expected
+        };
+        assert_field_eq(&expected, &field);
 
         assert!(field.row_mut(PosY::origin() + above(1)).is_none());
         assert!(field.row_mut(PosY::below(HEIGHT as i8)).is_none());
@@ -333,4 +1362,1111 @@ mod tests {
             assert_eq!(row2, row);
         }
     }
+
+    #[test]
+    fn test_rows_mut() {
+        let mut field = Field::empty();
+        let mut count = 0;
+        for (i, mut row) in field.rows_mut().enumerate() {
+            assert_eq!(PosY::below(i as i8), row.y());
+            assert_eq!(WIDTH, row.len());
+            // 各ラインの，上から数えた行番号の列をボムセルにする
+            if let Some(cell) = row.get_mut(i) {
+                *cell = Cell::Bomb;
+            }
+            count += 1;
+        }
+        assert_eq!(HEIGHT, count);
+        assert_eq!(WIDTH * HEIGHT, field.take_changes().len());
+
+        for (pos, &cell) in field.iter_pos_and_cell() {
+            let expected = if pos.x().right_shift == pos.y().below_shift {
+                Cell::Bomb
+            } else {
+                Cell::Empty
+            };
+            assert_eq!(expected, cell);
+        }
+    }
+
+    #[test]
+    fn test_column() {
+        let field = Field::from_str_art(
+            "
+            .#........
+            ##........
+            .*.......@
+            ",
+        )
+        .unwrap();
+        let empty_rows = HEIGHT - 3;
+
+        let column = field.column(PosX::right(1)).copied().collect::<Vec<_>>();
+        assert_eq!(HEIGHT, column.len());
+        assert!(column[..empty_rows].iter().all(|c| c.is_empty()));
+        assert_eq!(
+            &[Cell::Normal, Cell::Normal, Cell::Bomb],
+            &column[empty_rows..]
+        );
+
+        let column = field
+            .column(PosX::right(WIDTH as i8 - 1))
+            .collect::<Vec<_>>();
+        assert_eq!(Some(&&Cell::Steel), column.last());
+
+        // 範囲外の列は何も返さない
+        assert_eq!(0, field.column(PosX::right(-1)).count());
+        assert_eq!(0, field.column(PosX::right(WIDTH as i8)).count());
+    }
+
+    #[test]
+    fn test_take_changes() {
+        let mut field = Field::empty();
+        assert!(field.take_changes().is_empty());
+
+        let positions = [
+            Pos(PosX::origin(), PosY::origin()),
+            Pos(PosX::right(4), PosY::below(7)),
+            Pos(PosX::right(WIDTH as i8 - 1), PosY::below(HEIGHT as i8 - 1)),
+        ];
+        for &pos in positions.iter() {
+            *field.get_mut(pos).unwrap() = Cell::Normal;
+        }
+        // フィールドの外を指す位置は記録しない
+        assert!(field
+            .get_mut(Pos(PosX::right(-1), PosY::origin()))
+            .is_none());
+
+        let expected = positions.iter().copied().collect::<HashSet<_>>();
+        assert_eq!(expected, field.take_changes());
+        assert!(field.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_take_changes_on_rows() {
+        let mut field = Field::from_str_art(
+            "
+            ..........
+            ##.#######
+            .....#....
+            ",
+        )
+        .unwrap();
+        // 読み込んだ直後は何も記録していない
+        assert!(field.take_changes().is_empty());
+
+        // 取り除いたラインと，それより上のラインを記録する
+        let y = PosY::below(HEIGHT as i8 - 2);
+        field.remove_row(y);
+        let changes = field.take_changes();
+        assert_eq!(WIDTH * (HEIGHT - 1), changes.len());
+        assert!(changes.iter().all(|pos| pos.y() <= y));
+
+        field.row_mut(y).unwrap()[0] = Cell::Bomb;
+        let expected = (0..WIDTH)
+            .map(|x| Pos(PosX::right(x as i8), y))
+            .collect::<HashSet<_>>();
+        assert_eq!(expected, field.take_changes());
+
+        // 記録した位置はフィールドの比較に影響しない
+        let mut other = field.clone();
+        other.get_mut(Pos::origin());
+        assert_field_eq(&field, &other);
+    }
+
+    #[test]
+    fn test_cell_refs() {
+        let field = Field::from_str_art(
+            "
+            ..#.*.....
+            ##########
+            ",
+        )
+        .unwrap();
+        for row in field.rows() {
+            let cell_refs = row.cell_refs().collect::<Vec<_>>();
+            assert_eq!(WIDTH, cell_refs.len());
+            for (x, cell_ref) in cell_refs.into_iter().enumerate() {
+                let pos = Pos(PosX::right(x as i8), row.y());
+                assert_eq!(pos, cell_ref.pos());
+                assert_eq!(field.get(pos), Some(cell_ref.cell()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cell_refs_mut() {
+        let mut field = Field::empty();
+        let y = PosY::below(3);
+        {
+            let mut row = field.row_mut(y).unwrap();
+            for (x, mut cell_ref) in row.cell_refs_mut().enumerate() {
+                assert_eq!(Pos(PosX::right(x as i8), y), cell_ref.pos());
+                assert_eq!(&Cell::Empty, cell_ref.cell());
+                if x % 2 == 0 {
+                    *cell_ref.cell_mut() = Cell::Bomb;
+                }
+            }
+        }
+        let row = field.row(y).unwrap();
+        for cell_ref in row.cell_refs() {
+            let expected = if cell_ref.pos().0.right_shift % 2 == 0 {
+                Cell::Bomb
+            } else {
+                Cell::Empty
+            };
+            assert_eq!(&expected, cell_ref.cell());
+        }
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let field = Field::empty();
+        assert!(field.diff(&field.clone()).is_empty());
+        assert!(field.format_diff(&field.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_one_cell() {
+        let field = Field::empty();
+        let mut other = Field::empty();
+        let pos = Pos(PosX::right(3), PosY::below(17));
+        *other.get_mut(pos).unwrap() = Cell::Bomb;
+
+        assert_eq!(vec![(pos, Cell::Empty, Cell::Bomb)], field.diff(&other));
+        assert_eq!(
+            "y=17: .......... | ...*...... | ...!......\n",
+            field.format_diff(&other)
+        );
+    }
+
+    #[test]
+    fn test_str_art_round_trip() {
+        let art = "
+            HHH.......
+            HHHBB.....
+            HHHBB.....
+            *.#...#.@#
+        ";
+        let field = Field::from_str_art(art).unwrap();
+
+        let bottom = PosY::below(HEIGHT as i8 - 1);
+        assert_eq!(Some(&Cell::Bomb), field.get(Pos(PosX::origin(), bottom)));
+        assert_eq!(
+            Some(&Cell::BigBombUpperLeft),
+            field.get(Pos(PosX::right(3), bottom + above(2)))
+        );
+        assert_eq!(
+            Some(&Cell::BigBombLowerRight),
+            field.get(Pos(PosX::right(4), bottom + above(1)))
+        );
+        assert_eq!(
+            Some(&Cell::HugeBombCenter),
+            field.get(Pos(PosX::right(1), bottom + above(2)))
+        );
+        assert_eq!(
+            Some(&Cell::HugeBombPart),
+            field.get(Pos(PosX::right(2), bottom + above(1)))
+        );
+
+        let str_art = field.to_str_art();
+        assert_eq!(HEIGHT, str_art.lines().count());
+        assert_eq!(Some("*.#...#.@#"), str_art.lines().last());
+        assert_eq!(Some(field), Field::from_str_art(&str_art));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let field = Field::from_str_art(
+            "
+            HHH.......
+            HHHBB.....
+            HHHBB.....
+            *.#...#.@#
+        ",
+        )
+        .unwrap();
+        let json = serde_json::to_string(&field).unwrap();
+        assert!(json.starts_with(r#"{"width":10,"height":20,"cells":["Empty","#));
+        let decoded = serde_json::from_str::<Field>(&json).unwrap();
+        assert_field_eq(&field, &decoded);
+
+        // セルの数が大きさと合わない
+        let short = r#"{"width":2,"height":2,"cells":["Empty","Normal","Bomb"]}"#;
+        assert!(serde_json::from_str::<Field>(short).is_err());
+        let empty = r#"{"width":0,"height":2,"cells":[]}"#;
+        assert!(serde_json::from_str::<Field>(empty).is_err());
+    }
+
+    #[test]
+    fn test_from_str_display_round_trip() {
+        let art = "
+            ....
+            .HHH
+            #HHH
+            *HHH
+            BB.@
+            BB##
+        ";
+        let field = art.parse::<Field>().unwrap();
+        assert_eq!((4, 6), (field.width(), field.height()));
+        assert_eq!(
+            Some(&Cell::HugeBombCenter),
+            field.get(Pos(PosX::right(2), PosY::below(2)))
+        );
+        assert_eq!(
+            Some(&Cell::BigBombLowerRight),
+            field.get(Pos(PosX::right(1), PosY::below(5)))
+        );
+
+        let text = field.to_string();
+        assert_eq!("....\n.HHH\n#HHH\n*HHH\nBB.@\nBB##\n", text);
+        assert_eq!(Ok(field), text.parse());
+    }
+
+    #[test]
+    fn test_from_str_invalid() {
+        use FieldParseErrorKind::*;
+
+        let cases = [
+            ("", 1, NoRows),
+            (
+                "\n...\n....\n",
+                3,
+                RaggedLine {
+                    expected: 3,
+                    actual: 4,
+                },
+            ),
+            ("...\n.x.\n", 2, UnknownChar('x')),
+            ("B..\nB..\n", 1, MalformedBigBomb),
+        ];
+        for &(text, line, ref kind) in cases.iter() {
+            let expected = FieldParseError {
+                line,
+                kind: kind.clone(),
+            };
+            assert_eq!(Err(expected), text.parse::<Field>(), "{}", text);
+        }
+        let error = "..\n.?\n".parse::<Field>().unwrap_err();
+        assert_eq!("line 2: unknown cell `?`", error.to_string());
+    }
+
+    #[test]
+    fn test_from_str_art_invalid() {
+        // 行の長さが合わない
+        assert_eq!(None, Field::from_str_art("..."));
+        // 未知の文字
+        assert_eq!(None, Field::from_str_art("....x....."));
+        // デカボムを構成できない
+        assert_eq!(None, Field::from_str_art("....BB....\n....B....."));
+        // 特大ボムを構成できない
+        assert_eq!(
+            None,
+            Field::from_str_art("....HHH...\n....HHH...\n....HH....")
+        );
+        // 行数が多すぎる
+        assert_eq!(
+            None,
+            Field::from_str_art(&"..........\n".repeat(HEIGHT + 1))
+        );
+    }
+
+    #[test]
+    fn test_insert_garbage_rows() {
+        let mut field = Field::empty();
+        let top = Pos(PosX::right(4), PosY::below(HEIGHT as i8 - 1));
+        *field.get_mut(top).unwrap() = Cell::Bomb;
+
+        assert!(!field.insert_garbage_rows(&[2, 7]));
+
+        // 既存のセルは2ライン上へ移動する
+        assert_eq!(Some(&Cell::Bomb), field.get(top + above(2)));
+        // 挿入したラインは穴の列以外が埋まる
+        let row = field.row(PosY::below(HEIGHT as i8 - 2)).unwrap();
+        assert_eq!(1, row.iter().filter(|c| c.is_empty()).count());
+        assert!(row[2].is_empty());
+        let row = field.row(PosY::below(HEIGHT as i8 - 1)).unwrap();
+        assert_eq!(1, row.iter().filter(|c| c.is_empty()).count());
+        assert!(row[7].is_empty());
+    }
+
+    #[test]
+    fn test_insert_steel_garbage_rows() {
+        let mut field = Field::empty();
+        assert!(!field.insert_garbage_rows_with(&[3], Cell::Steel));
+        let expected = Field::from_str_art("@@@.@@@@@@").unwrap();
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_insert_garbage_rows_hole_out_of_range() {
+        let mut field = Field::empty();
+        assert!(!field.insert_garbage_rows(&[WIDTH, usize::MAX]));
+        // フィールドの外を指す穴は右端の列とし，挿入したラインはそろわない
+        let expected = Field::from_str_art(
+            "
+            #########.
+            #########.
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+        assert!(field.clear_filled_rows().is_empty());
+    }
+
+    #[test]
+    fn test_insert_garbage_rows_overflow() {
+        let mut field = Field::empty();
+        *field.get_mut(Pos::origin()).unwrap() = Cell::Normal;
+        assert!(field.insert_garbage_rows(&[0]));
+    }
+
+    #[test]
+    fn test_remove_rows() {
+        let mut field = Field::from_str_art(
+            "
+            #.........
+            ##########
+            .*........
+            ##########
+            ",
+        )
+        .unwrap();
+        field.remove_rows(&[PosY::below(HEIGHT as i8 - 1), PosY::below(HEIGHT as i8 - 3)]);
+
+        let expected = Field::from_str_art(
+            "
+            #.........
+            .*........
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+
+        // フィールドの外を指すラインは無視する
+        field.remove_rows(&[PosY::below(HEIGHT as i8), PosY::below(-1)]);
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_iter_pos_and_cell() {
+        let mut field = Field::empty_with_size(3, 2);
+        *field.get_mut(Pos(PosX::right(1), PosY::below(0))).unwrap() = Cell::Bomb;
+        *field.get_mut(Pos(PosX::right(2), PosY::below(1))).unwrap() = Cell::Normal;
+
+        // 最上段の左端から行優先順に列挙する
+        let pos = |x, y| Pos(PosX::right(x), PosY::below(y));
+        let expected = vec![
+            (pos(0, 0), &Cell::Empty),
+            (pos(1, 0), &Cell::Bomb),
+            (pos(2, 0), &Cell::Empty),
+            (pos(0, 1), &Cell::Empty),
+            (pos(1, 1), &Cell::Empty),
+            (pos(2, 1), &Cell::Normal),
+        ];
+        assert_eq!(expected, field.iter_pos_and_cell().collect::<Vec<_>>());
+        for (p, cell) in field.iter_pos_and_cell() {
+            assert_eq!(Some(cell), field.get(p));
+        }
+    }
+
+    #[test]
+    fn test_iter_pos_and_cell_mut() {
+        let mut field = Field::empty();
+        let positions = field
+            .iter_pos_and_cell()
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+        for (i, (p, cell)) in field.iter_pos_and_cell_mut().enumerate() {
+            assert_eq!(positions[i], p);
+            if p.y() == PosY::below(HEIGHT as i8 - 1) {
+                *cell = Cell::Normal;
+            }
+        }
+        let expected = Field::from_str_art("##########").unwrap();
+        assert_field_eq(&expected, &field);
+    }
+
+    fn o_block() -> Block {
+        Block::new(
+            QuadrupleBlockShape::O.into(),
+            Direction::Above,
+            BombTag::None,
+        )
+    }
+
+    #[test]
+    fn test_can_place_empty_field() {
+        let f = Field::empty();
+        let b = o_block();
+        let o = Pos::origin();
+        // 左上ギリギリ
+        assert!(f.can_place(&b, o + left(2) + above(1)));
+        // 上方向はみ出し
+        assert!(!f.can_place(&b, o + left(2) + above(2)));
+        // 左方向はみ出し
+        assert!(!f.can_place(&b, o + left(3) + above(1)));
+        // 右下ギリギリ
+        assert!(f.can_place(&b, o + right(6) + below(17)));
+        // 下方向はみ出し
+        assert!(!f.can_place(&b, o + right(6) + below(18)));
+        // 右方向はみ出し
+        assert!(!f.can_place(&b, o + right(7) + below(17)));
+    }
+
+    #[test]
+    fn test_can_place_non_empty_field() {
+        // 左上セルがすでに占有されているフィールド
+        let f = "
+            #.........
+            ..........
+            ..........
+            ..........
+            ..........
+        "
+        .parse::<Field>()
+        .unwrap();
+        let b = o_block();
+        let o = Pos::origin();
+        // 左上ギリギリに配置しようとすると，フィールドのセルと干渉するので配置できない
+        assert!(!f.can_place(&b, o + left(2) + above(1)));
+        // 右や下方向に1だけずらせば配置可能
+        assert!(f.can_place(&b, o + left(1) + above(1)));
+        assert!(f.can_place(&b, o + left(2) + above(0)));
+    }
+
+    #[test]
+    fn test_can_place_filled_field() {
+        // 全セルがすでに占有されているフィールド
+        let f = "##########\n".repeat(20).parse::<Field>().unwrap();
+        let b = o_block();
+        let o = Pos::origin();
+        assert!(!f.can_place(&b, o + left(2) + above(1)));
+        assert!(!f.can_place(&b, o + left(1) + above(1)));
+        assert!(!f.can_place(&b, o + left(2) + above(0)));
+        assert!(!f.can_place(&b, o + right(6) + below(17)));
+        assert_eq!(None, f.appearance_pos(&b));
+    }
+
+    #[test]
+    fn test_place_block() {
+        let mut f = Field::empty();
+        let b = o_block();
+        let o = Pos::origin();
+        assert_eq!(Ok(()), f.place_block(&b, o + right(6) + below(17)));
+        let expected = Field::from_str_art("........##\n........##").unwrap();
+        assert_field_eq(&expected, &f);
+
+        // 重なる場合は設置せず，重なったセルの位置を返す
+        let error = f.place_block(&b, o + right(5) + below(17)).unwrap_err();
+        assert_eq!(
+            PlaceError::Occupied(Pos(PosX::right(8), PosY::below(18))),
+            error
+        );
+        assert!(!error.is_above_top());
+        assert_field_eq(&expected, &f);
+    }
+
+    #[test]
+    fn test_place_block_out_of_bounds() {
+        let mut f = Field::empty();
+        let b = o_block();
+        let o = Pos::origin();
+
+        // 上端からはみ出す場合
+        let error = f.place_block(&b, o + left(2) + above(2)).unwrap_err();
+        assert_eq!(
+            PlaceError::OutOfBounds(Pos(PosX::right(0), PosY::below(-1))),
+            error
+        );
+        assert!(error.is_above_top());
+        assert_eq!("cell at (0, -1) is out of the field", error.to_string());
+        assert!(f.is_empty());
+
+        // 右端からはみ出す場合
+        let error = f.place_block(&b, o + right(7) + below(5)).unwrap_err();
+        assert_eq!(
+            PlaceError::OutOfBounds(Pos(PosX::right(10), PosY::below(6))),
+            error
+        );
+        assert!(!error.is_above_top());
+        assert!(f.is_empty());
+
+        // 緩い設置では，フィールドに収まるセルだけを設置する
+        f.place_block_lenient(&b, o + left(2) + above(2));
+        let expected = Field::from_str_art(&format!(
+            "##........\n{}",
+            "..........\n".repeat(HEIGHT - 1)
+        ))
+        .unwrap();
+        assert_field_eq(&expected, &f);
+    }
+
+    #[test]
+    fn test_appearance_pos_common_shapes() {
+        // 既定の大きさの空のフィールドでは，空でないセルの上端が最上段に揃い，
+        // テーブル中央の列がフィールドの中央の右側の列に揃う
+        let f = Field::empty();
+        let cases: Vec<(BlockShape, &[usize])> = vec![
+            (SingleBlockShape::O.into(), &[5]),
+            (QuadrupleBlockShape::O.into(), &[5, 6]),
+            (QuadrupleBlockShape::T.into(), &[4, 5, 6]),
+            (QuadrupleBlockShape::I.into(), &[4, 5, 6, 7]),
+            (QuadrupleBlockShape::L.into(), &[4, 5, 6]),
+            (QuadrupleBlockShape::S.into(), &[4, 5, 6]),
+            (QuintupleBlockShape::Star.into(), &[4, 5, 6]),
+        ];
+        for (shape, columns) in cases {
+            let b = Block::new(shape, Direction::Above, BombTag::None);
+            let pos = f.appearance_pos(&b).unwrap();
+            assert_eq!(PosX::right(3), pos.x(), "{:?}", shape);
+
+            let diff = pos - Pos::origin();
+            let cells = b
+                .iter_pos_and_occupied_cell()
+                .map(|(p, _)| p + diff)
+                .collect::<Vec<_>>();
+            let top = cells.iter().map(|p| p.y()).min().unwrap();
+            assert_eq!(PosY::origin(), top, "{:?}", shape);
+            let occupied_columns = cells
+                .iter()
+                .map(|p| p.x())
+                .sorted()
+                .dedup()
+                .collect::<Vec<_>>();
+            let expected = columns
+                .iter()
+                .map(|&x| PosX::right(x as i8))
+                .collect::<Vec<_>>();
+            assert_eq!(expected, occupied_columns, "{:?}", shape);
+        }
+    }
+
+    #[test]
+    fn test_appearance_pos_with_size() {
+        // 既定より狭いフィールドでも，中央寄りの上部に登場する
+        for &width in [4, 6, 15].iter() {
+            let f = Field::empty_with_size(width, 8);
+            let b = o_block();
+            let pos = f.appearance_pos(&b).unwrap();
+            assert!(f.can_place(&b, pos));
+            assert!(pos.x() <= PosX::right(width as i8 / 2));
+        }
+    }
+
+    #[test]
+    fn test_appearance_pos_top_aligned() {
+        // テーブルの上端まで占める縦長のブロックも，空でないセルの上端がフィールドの最上段に揃う
+        let tall = BlockShapeBuilder::new()
+            .occupy(2, 0)
+            .occupy(2, 1)
+            .occupy(2, 2)
+            .occupy(2, 3)
+            .occupy(2, 4)
+            .build()
+            .unwrap();
+        let b = Block::new(tall, Direction::Above, BombTag::None);
+        let pos = Field::empty().appearance_pos(&b).unwrap();
+        assert_eq!(PosY::origin(), pos.y());
+
+        // 最上段がふさがっている場合は，1段下に登場する
+        let f = Field::from_str_art(&format!(
+            "##########\n{}",
+            "..........\n".repeat(HEIGHT - 1)
+        ))
+        .unwrap();
+        let pos = f.appearance_pos(&b).unwrap();
+        assert_eq!(PosY::below(1), pos.y());
+
+        // 中央の列がふさがっている場合は，右，左の順に1列ずらす
+        let f = Field::from_str_art(&format!(
+            ".....#....\n{}",
+            "..........\n".repeat(HEIGHT - 1)
+        ))
+        .unwrap();
+        assert_eq!(PosX::right(4), f.appearance_pos(&b).unwrap().x());
+        let f = Field::from_str_art(&format!(
+            ".....##...\n{}",
+            "..........\n".repeat(HEIGHT - 1)
+        ))
+        .unwrap();
+        assert_eq!(PosX::right(2), f.appearance_pos(&b).unwrap().x());
+    }
+
+    #[test]
+    fn test_surface_profile_empty() {
+        let field = Field::empty();
+        assert_eq!(vec![0; WIDTH], field.column_heights());
+        assert_eq!(0, field.aggregate_height());
+        assert_eq!(0, field.bumpiness());
+    }
+
+    #[test]
+    fn test_surface_profile_full() {
+        let field = Field::from_str_art(&"##########\n".repeat(HEIGHT)).unwrap();
+        assert_eq!(vec![HEIGHT; WIDTH], field.column_heights());
+        assert_eq!(WIDTH * HEIGHT, field.aggregate_height());
+        assert_eq!(0, field.bumpiness());
+    }
+
+    #[test]
+    fn test_surface_profile_jagged() {
+        let field = Field::from_str_art(
+            "
+            ...#......
+            .BB#......
+            .BB.....@.
+            #.*.....##
+            ",
+        )
+        .unwrap();
+        // 途中の空セルも高さに含み，デカボムを構成するセルも空でないセルとして扱う
+        let heights = vec![1, 3, 3, 4, 0, 0, 0, 0, 2, 1];
+        assert_eq!(heights, field.column_heights());
+        assert_eq!(14, field.aggregate_height());
+        // |1-3|+|3-3|+|3-4|+|4-0|+0+0+0+|0-2|+|2-1|
+        assert_eq!(10, field.bumpiness());
+
+        let field = Field::empty_with_size(3, 2);
+        assert_eq!(vec![0, 0, 0], field.column_heights());
+    }
+
+    #[test]
+    fn test_holes() {
+        let field = Field::from_str_art(
+            "
+            .###......
+            .#.#....#.
+            ...#....#.
+            .#.#......
+            #..##.....
+            ",
+        )
+        .unwrap();
+        // 張り出しの下の空セルや，同じ列で空でないセルに挟まれた複数の空セルを数える
+        // 最も上の空でないセルより上の空セルは数えない
+        assert_eq!(vec![0, 2, 4, 0, 0, 0, 0, 0, 2, 0], field.holes_by_column());
+        assert_eq!(8, field.count_holes());
+
+        assert_eq!(0, Field::empty().count_holes());
+        let full = Field::from_str_art(&"##########\n".repeat(HEIGHT)).unwrap();
+        assert_eq!(0, full.count_holes());
+    }
+
+    #[test]
+    fn test_mirrored() {
+        let field = Field::from_str_art(
+            "
+            #.........
+            ##*.......
+            ###@......
+            ",
+        )
+        .unwrap();
+        let expected = Field::from_str_art(
+            "
+            .........#
+            .......*##
+            ......@###
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field.mirrored());
+        assert_field_eq(&field, &field.mirrored().mirrored());
+        assert!(field.mirrored().take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_mirrored_big_bomb() {
+        let field = Field::from_str_art(
+            "
+            BB.....HHH
+            BB.....HHH
+            ##.....HHH
+            ",
+        )
+        .unwrap();
+        let mirrored = field.mirrored();
+        let expected = Field::from_str_art(
+            "
+            HHH.....BB
+            HHH.....BB
+            HHH.....##
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &mirrored);
+
+        let top = HEIGHT as i8 - 3;
+        let cell = |x: i8, y: i8| {
+            *mirrored
+                .get(Pos(PosX::right(x), PosY::below(top + y)))
+                .unwrap()
+        };
+        assert_eq!(Cell::BigBombUpperLeft, cell(8, 0));
+        assert_eq!(Cell::BigBombUpperRight, cell(9, 0));
+        assert_eq!(Cell::BigBombLowerLeft, cell(8, 1));
+        assert_eq!(Cell::BigBombLowerRight, cell(9, 1));
+        assert_eq!(Cell::HugeBombCenter, cell(1, 1));
+    }
+
+    #[test]
+    fn test_count_cells_empty() {
+        let counts = Field::empty().count_cells();
+        let expected = CellCounts {
+            empty: WIDTH * HEIGHT,
+            ..Default::default()
+        };
+        assert_eq!(expected, counts);
+        assert_eq!(0, counts.occupied());
+        assert_eq!(WIDTH * HEIGHT, counts.total());
+        assert!(counts.is_empty_field());
+    }
+
+    #[test]
+    fn test_count_cells_mixed() {
+        let field = Field::from_str_art(
+            "
+            HHH.......
+            HHH.BB....
+            HHH.BB.*..
+            ##@@#*..##
+            ",
+        )
+        .unwrap();
+        let counts = field.count_cells();
+        let expected = CellCounts {
+            empty: WIDTH * HEIGHT - 22,
+            normal: 5,
+            steel: 2,
+            bomb: 2,
+            big_bomb_part: 4,
+            huge_bomb_part: 9,
+        };
+        assert_eq!(expected, counts);
+        assert_eq!(22, counts.occupied());
+        assert!(!counts.is_empty_field());
+    }
+
+    #[test]
+    fn test_count_cells_filled() {
+        let mut field = Field::empty_with_size(4, 3);
+        for (_, cell) in field.iter_pos_and_cell_mut() {
+            *cell = Cell::Normal;
+        }
+        let counts = field.count_cells();
+        let expected = CellCounts {
+            normal: 12,
+            ..Default::default()
+        };
+        assert_eq!(expected, counts);
+        assert_eq!(12, counts.occupied());
+        assert_eq!(12, counts.total());
+        assert!(!counts.is_empty_field());
+    }
+
+    #[test]
+    fn test_clear_filled_rows() {
+        let mut field = Field::from_str_art(
+            "
+            ##########
+            #.........
+            *###@#####
+            ####.#####
+            ",
+        )
+        .unwrap();
+        let cleared = field.clear_filled_rows();
+        let expected_ys = vec![PosY::below(HEIGHT as i8 - 4), PosY::below(HEIGHT as i8 - 2)];
+        assert_eq!(expected_ys, cleared);
+
+        // 揃ったラインだけが空になり，ほかのセルは移動しない
+        let expected = Field::from_str_art(
+            "
+            ..........
+            #.........
+            ..........
+            ####.#####
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+        assert!(field.clear_filled_rows().is_empty());
+    }
+
+    #[test]
+    fn test_remove_row() {
+        let art = "
+            .#........
+            ##.*......
+            ###.......
+            ####......
+            ";
+        let bottom = PosY::below(HEIGHT as i8 - 1);
+
+        // 最下段を取り除く
+        let mut field = Field::from_str_art(art).unwrap();
+        field.remove_row(bottom);
+        let expected = Field::from_str_art(
+            "
+            .#........
+            ##.*......
+            ###.......
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+
+        // 途中のラインを取り除くと，それより上のラインだけが下へ移動する
+        let mut field = Field::from_str_art(art).unwrap();
+        field.remove_row(bottom + above(2));
+        let expected = Field::from_str_art(
+            "
+            .#........
+            ###.......
+            ####......
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+
+        // フィールドの外を指すラインは無視する
+        field.remove_row(bottom + below(1));
+        field.remove_row(PosY::below(-1));
+        assert_field_eq(&expected, &field);
+
+        // 最上段を取り除くと，最上段は空セルで埋まり，ほかのラインは移動しない
+        let mut field = Field::from_str_art(art).unwrap();
+        *field.get_mut(Pos(PosX::right(2), PosY::origin())).unwrap() = Cell::Normal;
+        *field.get_mut(Pos(PosX::right(3), PosY::below(1))).unwrap() = Cell::Bomb;
+        let mut expected = Field::from_str_art(art).unwrap();
+        *expected
+            .get_mut(Pos(PosX::right(3), PosY::below(1)))
+            .unwrap() = Cell::Bomb;
+        field.remove_row(PosY::origin());
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_remove_row_splits_big_bomb() {
+        let mut field = Field::from_str_art(
+            "
+            .BB.BB....
+            .BB.BB....
+            ##########
+            ",
+        )
+        .unwrap();
+        // デカボムの下半分のラインを取り除くと，残った上半分はボムセルに戻る
+        field.remove_row(PosY::below(HEIGHT as i8 - 2));
+        let expected = Field::from_str_art(
+            "
+            .**.**....
+            ##########
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+        assert_eq!(Ok(field.clone()), field.to_str_art().parse::<Field>());
+    }
+
+    #[test]
+    fn test_shift_rows_down() {
+        let mut field = Field::from_str_art(
+            "
+            #.........
+            .#........
+            ..#.......
+            ...#......
+            ....#.....
+            ",
+        )
+        .unwrap();
+        let top = PosY::below(HEIGHT as i8 - 5);
+        // 上の3ラインを2ライン下げる．移動先のラインは上書きされる
+        field.shift_rows_down(top..top + below(3), 2);
+        let expected = Field::from_str_art(
+            "
+            ..........
+            ..........
+            #.........
+            .#........
+            ..#.......
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+
+        // 下端からはみ出したラインは失われる
+        field.shift_rows_down(top..PosY::below(HEIGHT as i8 + 3), 2);
+        let expected = Field::from_str_art("#.........").unwrap();
+        assert_field_eq(&expected, &field);
+
+        // 移動量が0の場合や範囲が空の場合は変化しない
+        field.shift_rows_down(PosY::origin()..PosY::below(HEIGHT as i8), 0);
+        field.shift_rows_down(top..top, 3);
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_shift_rows_down_splits_huge_bomb() {
+        let mut field = Field::from_str_art(
+            "
+            HHH.......
+            HHH.......
+            HHH.BB....
+            ....BB....
+            ",
+        )
+        .unwrap();
+        // 特大ボムの上の1ラインだけを下げると，特大ボムのセルはすべてボムセルに戻る
+        // 分断されていないデカボムはそのまま残る
+        let top = PosY::below(HEIGHT as i8 - 4);
+        field.shift_rows_down(top..top + below(1), 1);
+        let expected = Field::from_str_art(
+            "
+            ..........
+            ***.......
+            ***.BB....
+            ....BB....
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_validate_and_repair_big_bombs() {
+        let mut field = Field::from_str_art(
+            "
+            BB........
+            BB..HHH...
+            ....HHH...
+            ....HHH...
+            ",
+        )
+        .unwrap();
+        assert!(field.validate_big_bombs().is_empty());
+
+        // 爆発などでデカボムの左上と特大ボムの一部が失われた状態を作る
+        let top = HEIGHT as i8 - 4;
+        let pos = |x: i8, y: i8| Pos(PosX::right(x), PosY::below(top + y));
+        *field.get_mut(pos(0, 0)).unwrap() = Cell::Empty;
+        *field.get_mut(pos(6, 3)).unwrap() = Cell::Empty;
+        // 相方のいないデカボムの断片
+        *field.get_mut(pos(9, 3)).unwrap() = Cell::BigBombLowerRight;
+
+        let huge_bomb = (1..4).flat_map(|y| (4..7).map(move |x| pos(x, y)));
+        let expected = vec![pos(1, 0), pos(0, 1), pos(1, 1)]
+            .into_iter()
+            .chain(huge_bomb.filter(|&p| p != pos(6, 3)))
+            .chain(std::iter::once(pos(9, 3)))
+            .collect::<Vec<_>>();
+        assert_eq!(expected, field.validate_big_bombs());
+
+        field.repair_big_bombs();
+        let repaired = Field::from_str_art(
+            "
+            .*........
+            **..***...
+            ....***...
+            ....**...*
+            ",
+        )
+        .unwrap();
+        assert_field_eq(&repaired, &field);
+        assert!(field.validate_big_bombs().is_empty());
+    }
+
+    #[test]
+    fn test_insert_garbage_rows_splits_big_bomb() {
+        let mut field = Field::empty_with_size(4, 3);
+        let cells = [
+            (0, 0, Cell::BigBombUpperLeft),
+            (1, 0, Cell::BigBombUpperRight),
+            (0, 1, Cell::BigBombLowerLeft),
+            (1, 1, Cell::BigBombLowerRight),
+        ];
+        for &(x, y, cell) in cells.iter() {
+            *field.get_mut(Pos(PosX::right(x), PosY::below(y))).unwrap() = cell;
+        }
+        // デカボムの上半分がフィールド上端からはみ出る
+        assert!(field.insert_garbage_rows(&[3]));
+        let expected = "
+            **..
+            ....
+            ###.
+            "
+        .parse::<Field>()
+        .unwrap();
+        assert_field_eq(&expected, &field);
+    }
+
+    #[test]
+    fn test_insert_garbage() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        let mut field = Field::empty();
+        field.insert_garbage(5, GarbagePattern::RandomPerRow, &mut rng);
+
+        for (i, row) in field.rows().enumerate() {
+            let empty_count = row.iter().filter(|c| c.is_empty()).count();
+            if i < HEIGHT - 5 {
+                assert_eq!(WIDTH, empty_count);
+            } else {
+                // せり上がりラインの穴はちょうどひとつ
+                assert_eq!(1, empty_count);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_garbage() {
+        use rand::SeedableRng;
+
+        for seed in 0..20 {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let field = Field::random_garbage(&mut rng, 8, 3);
+
+            for (i, row) in field.rows().enumerate() {
+                let empty_count = row.iter().filter(|c| c.is_empty()).count();
+                if i < HEIGHT - 8 {
+                    assert_eq!(WIDTH, empty_count);
+                } else {
+                    assert_eq!(3, empty_count);
+                    assert!(row.iter().all(|&c| c == Cell::Empty || c == Cell::Normal));
+                }
+            }
+            assert_eq!(8 * (WIDTH - 3), field.count_cells().normal);
+        }
+
+        // 同じシード値からは同じフィールドを生成する
+        let generate = |seed| {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            Field::random_garbage(&mut rng, 8, 3)
+        };
+        assert_field_eq(&generate(5), &generate(5));
+    }
+
+    #[test]
+    fn test_random_garbage_clamps() {
+        use rand::SeedableRng;
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+        // 穴の数が0でも，各ラインに穴をひとつあける
+        let field = Field::random_garbage(&mut rng, HEIGHT + 5, 0);
+        for row in field.rows() {
+            assert_eq!(1, row.iter().filter(|c| c.is_empty()).count());
+        }
+
+        // 横方向のセル数を超える穴の数は，ライン全体を穴とする
+        let field = Field::random_garbage(&mut rng, 4, WIDTH + 1);
+        assert!(field.is_empty());
+
+        let field = Field::random_garbage(&mut rng, 0, 2);
+        assert!(field.is_empty());
+    }
 }
@@ -0,0 +1,154 @@
+use super::Field;
+use std::collections::VecDeque;
+
+/// 既定で保持するスナップショットの数．
+pub const DEFAULT_CAPACITY: usize = 64;
+
+/// フィールドのスナップショットを，指定した数まで新しいものから順に保持するスタック．
+/// 一手戻す操作や，ゲーム終了後に盤面を一手ずつ見返す場合などに利用する．
+///
+/// 保持できる数を超えてスナップショットを積んだ場合は，最も古いスナップショットを捨てる．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldHistory {
+    /// 保持しているスナップショット．先頭が最も古い．
+    snapshots: VecDeque<Field>,
+    /// 保持できるスナップショットの数．
+    capacity: usize,
+}
+
+impl FieldHistory {
+    /// 既定の数までスナップショットを保持する，空の履歴を返す．
+    pub fn new() -> FieldHistory {
+        Self::with_capacity(DEFAULT_CAPACITY).expect("default capacity is not zero")
+    }
+
+    /// 指定した数までスナップショットを保持する，空の履歴を返す．
+    /// # Returns
+    /// 保持できる数に0が指定された場合は`None`を返す．
+    pub fn with_capacity(capacity: usize) -> Option<FieldHistory> {
+        if capacity == 0 {
+            return None;
+        }
+        Some(Self {
+            snapshots: VecDeque::with_capacity(capacity),
+            capacity,
+        })
+    }
+
+    /// 保持できるスナップショットの数を返す．
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// 保持しているスナップショットの数を返す．
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// スナップショットをひとつも保持していない場合は`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// スナップショットを積む．
+    /// # Returns
+    /// 保持できる数を超えた場合は，捨てた最も古いスナップショットを`Some(field)`として返す．
+    pub fn push(&mut self, field: Field) -> Option<Field> {
+        let evicted = if self.snapshots.len() == self.capacity {
+            self.snapshots.pop_front()
+        } else {
+            None
+        };
+        self.snapshots.push_back(field);
+        evicted
+    }
+
+    /// 最も新しいスナップショットを取り出す．
+    /// # Returns
+    /// 履歴が空の場合は`None`を返す．
+    pub fn pop(&mut self) -> Option<Field> {
+        self.snapshots.pop_back()
+    }
+
+    /// 最も新しいスナップショットへの参照を返す．
+    /// # Returns
+    /// 履歴が空の場合は`None`を返す．
+    pub fn peek(&self) -> Option<&Field> {
+        self.snapshots.back()
+    }
+
+    /// すべてのスナップショットを捨てる．
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// 保持しているスナップショットを古いものから順に列挙する．
+    pub fn iter(&self) -> impl Iterator<Item = &Field> + '_ {
+        self.snapshots.iter()
+    }
+}
+
+impl Default for FieldHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 最上段の左端から`n`個のセルだけを通常のセルとしたフィールドを返す．
+    fn numbered_field(n: usize) -> Field {
+        let art = format!("{}{}", "#".repeat(n), ".".repeat(10 - n));
+        Field::from_str_art(&art).unwrap()
+    }
+
+    #[test]
+    fn test_push_pop_peek() {
+        let mut history = FieldHistory::new();
+        assert!(history.is_empty());
+        assert_eq!(None, history.peek());
+        assert_eq!(None, history.pop());
+
+        for n in 0..3 {
+            assert_eq!(None, history.push(numbered_field(n)));
+        }
+        assert_eq!(3, history.len());
+        assert_eq!(Some(&numbered_field(2)), history.peek());
+        assert_eq!(Some(numbered_field(2)), history.pop());
+        assert_eq!(Some(numbered_field(1)), history.pop());
+        assert_eq!(Some(&numbered_field(0)), history.peek());
+        assert_eq!(1, history.len());
+    }
+
+    #[test]
+    fn test_eviction_order() {
+        let mut history = FieldHistory::with_capacity(3).unwrap();
+        for n in 0..3 {
+            assert_eq!(None, history.push(numbered_field(n)));
+        }
+        // 容量を超えると，古いものから順に捨てる
+        assert_eq!(Some(numbered_field(0)), history.push(numbered_field(3)));
+        assert_eq!(Some(numbered_field(1)), history.push(numbered_field(4)));
+        assert_eq!(3, history.len());
+
+        let kept = history.iter().cloned().collect::<Vec<_>>();
+        let expected = (2..5).map(numbered_field).collect::<Vec<_>>();
+        assert_eq!(expected, kept);
+
+        // 取り出すのは新しいものから
+        assert_eq!(Some(numbered_field(4)), history.pop());
+        assert_eq!(None, history.push(numbered_field(5)));
+        assert_eq!(Some(numbered_field(2)), history.push(numbered_field(6)));
+
+        history.clear();
+        assert!(history.is_empty());
+        assert_eq!(3, history.capacity());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        assert!(FieldHistory::with_capacity(0).is_none());
+    }
+}
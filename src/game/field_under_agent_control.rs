@@ -1,9 +1,11 @@
-use super::{Block, BlockQueue, BlockSelector, Cell, Field};
-use crate::data_type::Shake;
+use super::{Block, BlockQueue, BlockSelector, Direction, Field, FramedField, Rotation};
 use crate::geometry::*;
 use crate::graphics::*;
 use crate::user::GameCommand;
 
+/// 積み上がったセルがあると危険を知らせる，フィールド上部のライン数．
+pub const DANGER_ROWS: usize = 3;
+
 /// ユーザが操作するブロックを表す．
 #[derive(Debug, Clone)]
 struct ControlledBlock {
@@ -19,7 +21,8 @@ impl ControlledBlock {
     }
 
     /// このブロックの空でないセルとその位置を返す．
-    fn iter_pos_and_occupied_cell(&self) -> impl IntoIterator<Item = (Pos, &'_ Cell)> + '_ {
+    #[cfg(test)]
+    fn iter_pos_and_occupied_cell(&self) -> impl IntoIterator<Item = (Pos, &'_ super::Cell)> + '_ {
         let diff = self.left_top - Pos::origin();
         self.block
             .iter_pos_and_occupied_cell()
@@ -35,6 +38,12 @@ pub enum GameCommandResult {
     /// ブロックの操作が確定した．次の処理に移行してくれ．
     /// このvariantはブロック設置後の`Field`と，今後のブロック操作に利用される`BlockQueue`をもつ．
     ProceedAnimation(Field, BlockQueue),
+    /// ブロックの一部がフィールド上端からはみ出した位置で，ブロックの設置位置が確定した．ゲームを終えてくれ．
+    /// このvariantは，はみ出したセルを除いてブロックを設置した後の`Field`をもつ．
+    ToppedOut(Field),
+    /// ゲームをやめる操作が入力された．
+    /// このvariantは操作を適用する前の状態をもち，フィールドやブロックは変化しない．
+    Quit(FieldUnderAgentControl),
 }
 
 /// エージェントの操作対象となるフィールドを表す．
@@ -56,7 +65,7 @@ impl FieldUnderAgentControl {
         // キューからブロックを取り出して操作ブロックとする
         let controlled_block = {
             let block = block_queue.pop_and_fill(selector);
-            let pos = find_block_appearance_pos(&field, &block)?;
+            let pos = field.appearance_pos(&block)?;
             ControlledBlock::new(block, pos)
         };
 
@@ -67,6 +76,76 @@ impl FieldUnderAgentControl {
         })
     }
 
+    /// 操作中のブロックを含まない，セルが配置されたフィールドを返す．
+    pub fn field(&self) -> &Field {
+        &self.field
+    }
+
+    /// 操作中のブロックを返す．
+    pub fn controlled_block(&self) -> &Block {
+        &self.controlled_block.block
+    }
+
+    /// 操作中のブロックのセルテーブルのうち，最も左上のセルのフィールドにおける座標を返す．
+    pub fn controlled_block_pos(&self) -> Pos {
+        self.controlled_block.left_top
+    }
+
+    /// 今後操作するブロックのキューを返す．
+    pub fn block_queue(&self) -> &BlockQueue {
+        &self.block_queue
+    }
+
+    /// 操作中のブロックを真下に落とせるマス数を返す．
+    pub fn drop_distance(&self) -> usize {
+        // フィールドに収まり，かつフィールドの他セルと干渉しない範囲内でどこまで落とせるか計算
+        let mut drop_shift = 0;
+        loop {
+            let next_pos = self.controlled_block.left_top + below(drop_shift + 1);
+            if self.field.can_place(&self.controlled_block.block, next_pos) {
+                drop_shift += 1;
+            } else {
+                break drop_shift as usize;
+            }
+        }
+    }
+
+    /// 操作中のブロックをNextブロックキューの先頭に戻し，フィールドとブロックキューを返す．
+    /// 戻すブロックの向きは，生成された直後の向きに戻す．
+    /// キューの大きさは変わらないので，キュー末尾のブロックは捨てられる．
+    pub fn suspend(self) -> (Field, BlockQueue) {
+        let block = self.controlled_block.block;
+        let block = Block::new(block.shape(), Direction::Above, block.bomb_tag());
+        let mut block_queue = self.block_queue;
+        block_queue.push_front(block);
+        (self.field, block_queue)
+    }
+
+    /// フィールドの下端にせり上がりラインを挿入する．操作中のブロックは動かない．
+    /// 挿入するラインの指定方法は`Field::insert_garbage_rows`と同様．
+    /// # Returns
+    /// 挿入後も操作中のブロックを配置可能な場合は，挿入後の状態を`Ok`で返す．
+    /// 操作中のブロックがフィールドのセルと重なるか，空でないセルがフィールド上端からはみ出た場合は，
+    /// 挿入後のフィールドを`Err`で返す．
+    pub fn insert_garbage_rows(
+        mut self,
+        hole_columns: &[usize],
+    ) -> Result<FieldUnderAgentControl, Field> {
+        let overflowed = self.field.insert_garbage_rows(hole_columns);
+        let block = &self.controlled_block;
+        if overflowed || !self.field.can_place(&block.block, block.left_top) {
+            Err(self.field)
+        } else {
+            Ok(self)
+        }
+    }
+
+    /// 操作中のブロックを自動落下させた結果を返す．
+    /// `GameCommand::Down`と同様に，これ以上落とせない場合はブロックの設置位置が確定する．
+    pub fn apply_gravity(self) -> GameCommandResult {
+        self.apply_command(GameCommand::Down)
+    }
+
     /// このフィールドに指定した操作を施した結果を返す．
     pub fn apply_command(mut self, command: GameCommand) -> GameCommandResult {
         use GameCommand::*;
@@ -81,7 +160,7 @@ impl FieldUnderAgentControl {
                     _ => panic!("should not reach here"),
                 };
                 let next_pos = self.controlled_block.left_top + shift;
-                if is_arrangeable(&self.field, &self.controlled_block.block, next_pos) {
+                if self.field.can_place(&self.controlled_block.block, next_pos) {
                     let next_state = Self {
                         controlled_block: ControlledBlock::new(
                             self.controlled_block.block,
@@ -93,8 +172,7 @@ impl FieldUnderAgentControl {
                 } else {
                     // 下移動ができなかった場合は次の状態へ移行
                     if command == Down {
-                        let field = place_block(self.controlled_block, self.field);
-                        GameCommandResult::ProceedAnimation(field, self.block_queue)
+                        place_block(self.controlled_block, self.field, self.block_queue)
                     } else {
                         GameCommandResult::WaitNextCommand(self)
                     }
@@ -102,44 +180,31 @@ impl FieldUnderAgentControl {
             }
             // ブロックを真下に落とせるだけ落とす
             Drop => {
-                // フィールドに収まり，かつフィールドの他セルと干渉しない範囲内でどこまで落とせるか計算
-                let final_pos = {
-                    let mut drop_shift = 0;
-                    loop {
-                        let next_pos = self.controlled_block.left_top + below(drop_shift + 1);
-                        if is_arrangeable(&self.field, &self.controlled_block.block, next_pos) {
-                            drop_shift += 1;
-                        } else {
-                            break self.controlled_block.left_top + below(drop_shift);
-                        }
-                    }
-                };
+                let final_pos = self.controlled_block.left_top + below(self.drop_distance() as i8);
 
                 let dropped_block = ControlledBlock::new(self.controlled_block.block, final_pos);
-                let field = place_block(dropped_block, self.field);
                 // 次の状態へ移行
-                GameCommandResult::ProceedAnimation(field, self.block_queue)
+                place_block(dropped_block, self.field, self.block_queue)
             }
             // ブロック回転
             RotateClockwise | RotateUnticlockwise => {
-                let rotated_block = if command == RotateClockwise {
-                    self.controlled_block.block.rotate_clockwise()
+                let rotation = if command == RotateClockwise {
+                    Rotation::Clockwise
                 } else {
-                    self.controlled_block.block.rotate_unticlockwise()
+                    Rotation::Unticlockwise
                 };
-                let shift_max = self.controlled_block.block.cell_table_size() as i8 / 2;
-                // 元の位置になるべく近くなるように，操作ブロックの位置を決めなおす．
-                // これにより，操作ブロックの位置変更を許さない場合と比べてユーザにとってブロックが操作しやすくなる．
-                for y in Shake::<i8>::new().take_while(|y| y.abs() <= shift_max) {
-                    for x in Shake::<i8>::new().take_while(|x| x.abs() <= shift_max) {
-                        let shifted_pos = self.controlled_block.left_top + right(x) + below(y);
-                        if is_arrangeable(&self.field, &rotated_block, shifted_pos) {
-                            let next_state = Self {
-                                controlled_block: ControlledBlock::new(rotated_block, shifted_pos),
-                                ..self
-                            };
-                            return GameCommandResult::WaitNextCommand(next_state);
-                        }
+                let block = self.controlled_block.block;
+                let rotated_block = block.rotate(rotation);
+                // 回転後の位置をそのまま置けない場合は，形状と向きごとに定められた位置補正(キック)を順に試す．
+                // これにより，壁際や隙間でもブロックを回転させられる．
+                for kick in block.kick_offsets(rotation) {
+                    let shifted_pos = self.controlled_block.left_top + kick;
+                    if self.field.can_place(&rotated_block, shifted_pos) {
+                        let next_state = Self {
+                            controlled_block: ControlledBlock::new(rotated_block, shifted_pos),
+                            ..self
+                        };
+                        return GameCommandResult::WaitNextCommand(next_state);
                     }
                 }
 
@@ -149,7 +214,7 @@ impl FieldUnderAgentControl {
             Hold => {
                 let popped_block = self.block_queue.hold_block();
                 // Holdブロックをフィールドに出現させられる場合のみ入れ替える
-                match find_block_appearance_pos(&self.field, &popped_block) {
+                match self.field.appearance_pos(&popped_block) {
                     Some(pos) => {
                         self.block_queue
                             .swap_hold_block(self.controlled_block.block);
@@ -162,6 +227,8 @@ impl FieldUnderAgentControl {
                     None => GameCommandResult::WaitNextCommand(self),
                 }
             }
+            // ゲームをやめる
+            Quit => GameCommandResult::Quit(self),
         }
     }
 }
@@ -170,8 +237,8 @@ impl Drawable for FieldUnderAgentControl {
     fn region_size(&self) -> Movement {
         use std::cmp::max;
 
-        // フィールド用
-        let field_region_size = self.field.region_size();
+        // 枠付きのフィールド用
+        let field_region_size = FramedField::new(&self.field).region_size();
         // nextブロック用
         let queue_region_size = self.block_queue.region_size();
         // フィールドの右にnextブロック列とholdブロックを表示するので，
@@ -182,151 +249,396 @@ impl Drawable for FieldUnderAgentControl {
     }
 
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        let p = Pos::origin();
-        // 左上にフィールドを描画
-        self.field.draw_on_child(p, canvas);
+        // 左上に枠付きのフィールドを描画
+        let framed_field = FramedField::new(&self.field);
+        framed_field.draw(canvas);
+        // フィールドに重ねて描画するものは，枠の内側に描画する
+        let p = FramedField::FIELD_LEFT_TOP;
+        // ゲームオーバーが近い場合は，上部の空セルを赤く塗って知らせる
+        if is_in_danger(&self.field) {
+            let danger_cell = CanvasCell::new(
+                SquareChar::new(' ', '!'),
+                canvas
+                    .theme()
+                    .inverted_color(ThemeColor::Warning)
+                    .with_attributes(Attributes::BLINK),
+            );
+            for row in self.field.rows().take(DANGER_ROWS) {
+                for cell_ref in row.cell_refs() {
+                    if cell_ref.cell().is_empty() {
+                        canvas.draw_cell(p + (cell_ref.pos() - Pos::origin()), danger_cell);
+                    }
+                }
+            }
+        }
+        // 操作中のブロックを真下に落とした位置に，薄い文字でゴーストを描画する
+        let ghost_left_top = self.controlled_block.left_top + below(self.drop_distance() as Shift);
+        for (pos, cell) in self.controlled_block.block.iter_pos_and_occupied_cell() {
+            let mut ghost_cell = cell.canvas_cell(&canvas.theme());
+            ghost_cell.color = ghost_cell.color.with_attributes(Attributes::DIM);
+            let pos = p + (ghost_left_top - Pos::origin()) + (pos - Pos::origin());
+            canvas.draw_cell(pos, ghost_cell);
+        }
         // 操作中のブロック描画
         self.controlled_block
             .block
             .draw_on_child(p + (self.controlled_block.left_top - Pos::origin()), canvas);
-        // フィールドから1マス開けて，右側にNextブロックやHoldブロックを描画していく
-        let p = p + self.field.region_size().x() + right(1);
+        // 枠から1マス開けて，右側にNextブロックやHoldブロックを描画していく
+        let p = Pos::origin() + framed_field.region_size().x() + right(1);
         self.block_queue.draw_on_child(p, canvas);
     }
 }
 
-/// 指定したブロックを指定した位置に配置可能かどうか返す．
-/// ブロックの空でないセルとがすべてフィールド内に存在し，それらがフィールドの空でないセルが干渉しない場合に配置可能であると判定する．
-pub fn is_arrangeable(field: &Field, block: &Block, block_left_top: Pos) -> bool {
-    let diff = block_left_top - Pos::origin();
-    block
-        .iter_pos_and_occupied_cell()
-        .map(|(pos, _cell)| pos + diff)
-        .all(|pos| field.get(pos).map(|c| c.is_empty()).unwrap_or(false))
-}
-
-/// 指定したブロックを操作ブロックとしてフィールドに登場させる場合，その初期位置(ブロックセル群の左上の座標)を返す．
-/// 初期位置は，そのブロックが配置可能な座標のうち，ブロックが可能な限りフィールド中央，フィールド上部に配置される位置となる．
-/// # Returns
-/// 指定したブロックが配置可能な場合，その左上座標`pos`を`Some(pos)`として返す．
-/// 配置不可能な場合，`None`を返す．
-pub fn find_block_appearance_pos(field: &Field, block: &Block) -> Option<Pos> {
-    let shift_max = block.cell_table_size() as i8 / 2;
-    for y in -shift_max..shift_max {
-        for x in Shake::<i8>::new()
-            .map(|x| x + field.width() as i8 / 2 - block.cell_table_size() as i8 / 2)
-            .take(3)
-        {
-            let pos = Pos::origin() + below(y) + right(x);
-            if is_arrangeable(field, block, pos) {
-                return Some(pos);
-            }
-        }
-    }
-    None
+/// フィールドの上から`DANGER_ROWS`ラインに空でないセルがある場合は`true`を返す．
+/// 操作中のブロックはフィールドに含まれないので，判定には影響しない．
+pub fn is_in_danger(field: &Field) -> bool {
+    field
+        .rows()
+        .take(DANGER_ROWS)
+        .any(|row| row.iter().any(|c| !c.is_empty()))
 }
 
-/// 指定したブロックをフィールドに設置する．
-/// ブロックの中にフィールドに収まらないセルが存在する場合，そのセルはフィールドに残らない．
-/// # Panics on debug build
-/// 1. 指定したブロックの空でないセルと，フィールドの空でないセルとが干渉していた場合．
-fn place_block(controlled_block: ControlledBlock, mut field: Field) -> Field {
-    for (pos, &cell) in controlled_block.iter_pos_and_occupied_cell() {
-        if let Some(c) = field.get_mut(pos) {
-            debug_assert!(c.is_empty());
-            *c = cell;
+/// 指定したブロックをフィールドに設置し，設置位置が確定した結果を返す．
+/// ブロックの一部がフィールド上端からはみ出す場合は，収まるセルだけを設置して`GameCommandResult::ToppedOut`を返す．
+/// # Panics
+/// 1. 指定したブロックがフィールドの左右の端や下端からはみ出す場合や，フィールドの空でないセルと重なる場合．
+fn place_block(
+    controlled_block: ControlledBlock,
+    mut field: Field,
+    block_queue: BlockQueue,
+) -> GameCommandResult {
+    let ControlledBlock { block, left_top } = controlled_block;
+    match field.place_block(&block, left_top) {
+        Ok(()) => GameCommandResult::ProceedAnimation(field, block_queue),
+        Err(e) if e.is_above_top() => {
+            field.place_block_lenient(&block, left_top);
+            GameCommandResult::ToppedOut(field)
         }
+        Err(e) => panic!("controlled block should fit the field: {}", e),
     }
-
-    field
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::Cell;
     use super::super::QuadrupleBlockShape::*;
-    use super::super::{BlockShape, BombTag};
+    use super::super::{BombTag, Direction, QuadrupleBlockShape};
+    use super::super::{ScriptEnd, ScriptedBlockSelector};
     use super::*;
+    use crate::game::test_util::repeating_selector;
 
-    struct QuadrupleBlockGenerator {
-        current_index: usize,
+    fn block_generator() -> ScriptedBlockSelector {
+        let blocks = QuadrupleBlockShape::all()
+            .map(|shape| (shape.into(), BombTag::None))
+            .collect();
+        ScriptedBlockSelector::new(blocks, ScriptEnd::Repeat).unwrap()
+    }
+
+    #[test]
+    fn test_apply_gravity() {
+        let mut selector = block_generator();
+        let queue = BlockQueue::new(&mut selector);
+        let mut agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let distance = agent_field.drop_distance();
+
+        // 落とせるだけ落ちた後，もう一度落下させると設置位置が確定する
+        for _ in 0..distance {
+            agent_field = match agent_field.apply_gravity() {
+                GameCommandResult::WaitNextCommand(next) => next,
+                GameCommandResult::ProceedAnimation(..) => panic!("block should not be placed yet"),
+                GameCommandResult::ToppedOut(_) => panic!("block should not top out"),
+                GameCommandResult::Quit(_) => panic!("gravity should not quit"),
+            };
+        }
+        assert_eq!(0, agent_field.drop_distance());
+        match agent_field.apply_gravity() {
+            GameCommandResult::WaitNextCommand(_) => panic!("block should be placed"),
+            GameCommandResult::ProceedAnimation(..) => {}
+            GameCommandResult::ToppedOut(_) => panic!("block should not top out"),
+            GameCommandResult::Quit(_) => panic!("gravity should not quit"),
+        }
     }
 
-    impl BlockSelector for QuadrupleBlockGenerator {
-        fn select_block_shape(&mut self) -> BlockShape {
-            let shapes = [O, J, L, Z, S, T, I];
+    #[test]
+    fn test_apply_quit() {
+        let mut selector = block_generator();
+        let queue = BlockQueue::new(&mut selector);
+        let agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let before = agent_field
+            .controlled_block
+            .iter_pos_and_occupied_cell()
+            .into_iter()
+            .map(|(pos, _)| pos)
+            .collect::<Vec<_>>();
 
-            let shape = shapes[self.current_index % shapes.len()];
-            self.current_index += 1;
-            shape.into()
+        // やめる操作では，フィールドも操作中のブロックも変化しない
+        match agent_field.apply_command(GameCommand::Quit) {
+            GameCommandResult::Quit(agent_field) => {
+                assert_eq!(Field::empty(), agent_field.field);
+                let after = agent_field
+                    .controlled_block
+                    .iter_pos_and_occupied_cell()
+                    .into_iter()
+                    .map(|(pos, _)| pos)
+                    .collect::<Vec<_>>();
+                assert_eq!(before, after);
+            }
+            _ => panic!("quit command should be surfaced"),
         }
+    }
+
+    #[test]
+    fn test_insert_garbage_rows() {
+        let mut selector = block_generator();
+        let queue = BlockQueue::new(&mut selector);
+        let agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let left_top = agent_field.controlled_block_pos();
+
+        // ブロックと重ならなければ，ブロックを動かさずにラインを挿入する
+        let agent_field = agent_field.insert_garbage_rows(&[0, 9]).unwrap();
+        assert_eq!(left_top, agent_field.controlled_block_pos());
+        let expected = Field::from_str_art(
+            "
+            .#########
+            #########.
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, agent_field.field);
 
-        fn select_bomb(&mut self, _: BlockShape) -> BombTag {
-            BombTag::None
+        // 操作中のブロックの真下まで積み上がっていると，挿入したラインに押し上げられて重なる
+        let block_bottom = agent_field
+            .controlled_block
+            .iter_pos_and_occupied_cell()
+            .into_iter()
+            .filter_map(|(pos, _)| pos.y().as_positive_index())
+            .max()
+            .unwrap();
+        let mut field = Field::empty();
+        field.insert_garbage_rows(&vec![0; field.height() - block_bottom - 1]);
+        let block = *agent_field.controlled_block();
+        let agent_field = with_controlled_block(field, block, left_top);
+        match agent_field.insert_garbage_rows(&[0]) {
+            Ok(_) => panic!("block should collide"),
+            Err(field) => {
+                let row = field.row(PosY::below(block_bottom as i8)).unwrap();
+                assert!(!row.iter().all(|c| c.is_empty()));
+            }
         }
     }
 
-    fn block_generator() -> QuadrupleBlockGenerator {
-        QuadrupleBlockGenerator { current_index: 0 }
+    #[test]
+    fn test_is_in_danger() {
+        assert!(!is_in_danger(&Field::empty()));
+
+        // 上から3ライン目にセルがあれば危険
+        let mut field = Field::empty();
+        *field.get_mut(Pos(PosX::right(9), PosY::below(2))).unwrap() = Cell::Normal;
+        assert!(is_in_danger(&field));
+
+        // 4ライン目までしか積み上がっていなければ危険ではない
+        let mut field = Field::empty();
+        for y in 3..field.height() {
+            *field
+                .get_mut(Pos(PosX::origin(), PosY::below(y as i8)))
+                .unwrap() = Cell::Normal;
+        }
+        assert!(!is_in_danger(&field));
+
+        // 出現したばかりの操作中のブロックは上部にあるが，判定には含まない
+        let mut selector = block_generator();
+        let block_queue = BlockQueue::new(&mut selector);
+        let agent_field = FieldUnderAgentControl::new(field, block_queue, &mut selector).unwrap();
+        assert!(!is_in_danger(agent_field.field()));
+
+        let render = |agent_field: &FieldUnderAgentControl| {
+            let mut canvas = RootCanvas::new();
+            agent_field.draw(&mut canvas);
+            let mut s = String::new();
+            canvas.construct_plain_string(&mut s);
+            s
+        };
+        assert!(!render(&agent_field).contains('!'));
+        let mut field = agent_field.field().clone();
+        *field.get_mut(Pos(PosX::right(9), PosY::below(2))).unwrap() = Cell::Normal;
+        let block_queue = BlockQueue::new(&mut selector);
+        let agent_field = FieldUnderAgentControl::new(field, block_queue, &mut selector).unwrap();
+        assert!(render(&agent_field).contains('!'));
     }
 
     #[test]
-    fn test_is_arrangeable_empty_field() {
-        let f = Field::empty();
-        let b = block_generator().generate_block();
-        let o = Pos::origin();
-        // 左上ギリギリ
-        assert!(is_arrangeable(&f, &b, o + left(2) + above(1)));
-        // 上方向はみ出し
-        assert!(!is_arrangeable(&f, &b, o + left(2) + above(2)));
-        // 左方向はみ出し
-        assert!(!is_arrangeable(&f, &b, o + left(3) + above(1)));
-        // 右下ギリギリ
-        assert!(is_arrangeable(&f, &b, o + right(6) + below(17)));
-        // 下方向はみ出し
-        assert!(!is_arrangeable(&f, &b, o + right(6) + below(18)));
-        // 右方向はみ出し
-        assert!(!is_arrangeable(&f, &b, o + right(7) + below(17)));
+    fn test_draw_inside_frame() {
+        let mut selector = block_generator();
+        let block_queue = BlockQueue::new(&mut selector);
+        let agent_field =
+            FieldUnderAgentControl::new(Field::empty(), block_queue, &mut selector).unwrap();
+        let mut canvas = RootCanvas::new();
+        agent_field.draw(&mut canvas);
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        let lines = s
+            .lines()
+            .map(|line| line.chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        // 枠の四隅
+        let field = agent_field.field();
+        let (right_x, bottom_y) = (2 * (field.width() + 1), field.height() + 1);
+        assert_eq!(['+', '-'], lines[0][1..3]);
+        assert_eq!(['-', '+'], lines[0][right_x - 1..right_x + 1]);
+        assert_eq!('+', lines[bottom_y][1]);
+        assert_eq!('+', lines[bottom_y][right_x]);
+
+        // 操作中のブロックは枠の内側に，フィールドの座標から1セルずらして描画する
+        for (pos, _) in agent_field.controlled_block.iter_pos_and_occupied_cell() {
+            let x = 2 * (pos.x().as_positive_index().unwrap() + 1);
+            let y = pos.y().as_positive_index().unwrap() + 1;
+            assert_eq!(['[', ']'], lines[y][x..x + 2]);
+        }
     }
 
     #[test]
-    fn test_is_arrangeable_non_empty_field() {
-        // 左上セルがすでに占有されているフィールド
-        let f = {
-            let mut field = Field::empty();
-            *field.get_mut(Pos::origin()).unwrap() = Cell::Normal;
-            field
+    fn test_draw_ghost() {
+        let mut selector = block_generator();
+        let block_queue = BlockQueue::new(&mut selector);
+        let agent_field =
+            FieldUnderAgentControl::new(Field::empty(), block_queue, &mut selector).unwrap();
+        let mut canvas = RootCanvas::new();
+        agent_field.draw(&mut canvas);
+        let cell_at = |pos: Pos| {
+            canvas
+                .iter_pos_and_cell()
+                .find(|&(p, _)| p == pos)
+                .map(|(_, cell)| *cell)
+                .unwrap()
         };
-        let b = block_generator().generate_block();
-        let o = Pos::origin();
-        // 左上ギリギリに配置しようとすると，フィールドのセルと干渉するので配置できない
-        assert!(!is_arrangeable(&f, &b, o + left(2) + above(1)));
-        // 右や下方向に1だけずらせば配置可能
-        assert!(is_arrangeable(&f, &b, o + left(1) + above(1)));
-        assert!(is_arrangeable(&f, &b, o + left(2) + above(0)));
+
+        let frame_offset = FramedField::FIELD_LEFT_TOP - Pos::origin();
+        let distance = below(agent_field.drop_distance() as Shift);
+        for (pos, cell) in agent_field.controlled_block.iter_pos_and_occupied_cell() {
+            // 操作中のブロックはそのまま，落下先のゴーストは薄い文字で描画する
+            let block_cell = cell_at(pos + frame_offset);
+            assert_eq!(cell.canvas_cell(&Theme::default()), block_cell);
+            let ghost_cell = cell_at(pos + frame_offset + distance);
+            assert_eq!(cell.canvas_cell(&Theme::default()).c, ghost_cell.c);
+            assert_eq!(Attributes::DIM, ghost_cell.color.attributes());
+        }
     }
 
     #[test]
-    fn test_is_arrangeable_filled_field() {
-        // 全セルがすでに占有されているフィールド
-        let f = {
-            let mut field = Field::empty();
-            for y in 0..field.height() {
-                for x in 0..field.width() {
-                    let p = Pos::origin() + right(x as i8) + below(y as i8);
-                    *field.get_mut(p).unwrap() = Cell::Normal;
-                }
-            }
-            field
+    fn test_suspend() {
+        let mut selector = block_generator();
+        let queue = BlockQueue::new(&mut selector);
+        let before = queue.clone();
+        let agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let agent_field = match agent_field.apply_command(GameCommand::RotateClockwise) {
+            GameCommandResult::WaitNextCommand(agent_field) => agent_field,
+            _ => panic!("block should be rotated"),
         };
-        let b = block_generator().generate_block();
-        let o = Pos::origin();
-        // 左上ギリギリに配置しようとすると，フィールドのセルと干渉するので配置できない
-        assert!(!is_arrangeable(&f, &b, o + left(2) + above(1)));
-        // 右や下方向に1だけずらしても当然配置不可能
-        assert!(!is_arrangeable(&f, &b, o + left(1) + above(1)));
-        assert!(!is_arrangeable(&f, &b, o + left(2) + above(0)));
-        // 右下ギリギリもだめ
-        assert!(!is_arrangeable(&f, &b, o + right(6) + below(17)));
+
+        // 操作中のブロックは向きを戻してキューの先頭に戻る
+        let (field, queue) = agent_field.suspend();
+        assert_eq!(Field::empty(), field);
+        assert_eq!(before, queue);
+    }
+
+    /// 指定したブロックを指定した位置で操作中のフィールドを返す．
+    fn with_controlled_block(field: Field, block: Block, left_top: Pos) -> FieldUnderAgentControl {
+        let block_queue = BlockQueue::new(&mut block_generator());
+        FieldUnderAgentControl {
+            field,
+            controlled_block: ControlledBlock::new(block, left_top),
+            block_queue,
+        }
+    }
+
+    /// 操作を適用し，操作中のブロックが占める位置を返す．
+    fn occupied_after(agent_field: FieldUnderAgentControl, command: GameCommand) -> Vec<Pos> {
+        match agent_field.apply_command(command) {
+            GameCommandResult::WaitNextCommand(agent_field) => agent_field
+                .controlled_block
+                .iter_pos_and_occupied_cell()
+                .into_iter()
+                .map(|(pos, _)| pos)
+                .collect(),
+            GameCommandResult::ProceedAnimation(..) | GameCommandResult::ToppedOut(_) => {
+                panic!("block should not be placed")
+            }
+            GameCommandResult::Quit(_) => panic!("game should not be quit"),
+        }
+    }
+
+    fn positions(cells: &[(i8, i8)]) -> Vec<Pos> {
+        cells
+            .iter()
+            .map(|&(x, y)| Pos(PosX::right(x), PosY::below(y)))
+            .collect()
+    }
+
+    #[test]
+    fn test_rotate_i_against_wall() {
+        // 左の壁に接した縦向きのIミノ
+        let block = repeating_selector(I, BombTag::None)
+            .generate_block()
+            .rotate_clockwise();
+        assert_eq!(Direction::Right, block.direction());
+        let left_top = Pos::origin() + left(2) + below(10);
+
+        // 横向きにすると壁にめり込むので，右へ1マス補正される
+        let agent_field = with_controlled_block(Field::empty(), block, left_top);
+        assert_eq!(
+            positions(&[(0, 12), (1, 12), (2, 12), (3, 12)]),
+            occupied_after(agent_field, GameCommand::RotateClockwise)
+        );
+
+        // 補正先がふさがっている場合は，斜めに移動せず回転しない
+        let mut field = Field::empty();
+        *field.get_mut(Pos(PosX::right(1), PosY::below(12))).unwrap() = Cell::Normal;
+        let agent_field = with_controlled_block(field, block, left_top);
+        assert_eq!(
+            positions(&[(0, 10), (0, 11), (0, 12), (0, 13)]),
+            occupied_after(agent_field, GameCommand::RotateClockwise)
+        );
+    }
+
+    #[test]
+    fn test_rotate_t_into_slot() {
+        // 屋根の下にあるT字型の隙間
+        let field = Field::from_str_art(
+            "
+            ....#.....
+            ##....####
+            ###...####
+            ##########
+            ",
+        )
+        .unwrap();
+        // 隙間の右上に立てた左向きのTミノ
+        let block = repeating_selector(T, BombTag::None)
+            .generate_block()
+            .rotate_unticlockwise();
+        assert_eq!(Direction::Left, block.direction());
+        let left_top = Pos::origin() + right(3) + below(15);
+        let agent_field = with_controlled_block(field, block, left_top);
+        assert_eq!(
+            positions(&[(5, 16), (4, 17), (5, 17), (5, 18)]),
+            agent_field
+                .controlled_block
+                .iter_pos_and_occupied_cell()
+                .into_iter()
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>()
+        );
+
+        // 左下へ補正され，隙間にはまる．
+        // 近い位置から探す方法では，左へ2マス移動して屋根の左に乗ってしまう．
+        assert_eq!(
+            positions(&[(4, 17), (3, 18), (4, 18), (5, 18)]),
+            occupied_after(agent_field, GameCommand::RotateClockwise)
+        );
     }
 }
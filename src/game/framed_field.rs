@@ -0,0 +1,80 @@
+use super::Field;
+use crate::geometry::*;
+use crate::graphics::*;
+
+/// フィールドの周囲に1セル幅の枠を付けて表示する描画物を表す．
+/// 暗い端末でも，フィールドの左右の壁や床の位置が分かるようにするために利用される．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FramedField<'f> {
+    field: &'f Field,
+}
+
+impl<'f> FramedField<'f> {
+    /// 枠付きの描画物の左上を原点とした，フィールドの左上の位置．
+    pub const FIELD_LEFT_TOP: Pos = Frame::INNER_LEFT_TOP;
+
+    /// 指定したフィールドに枠を付けた描画物を返す．
+    pub const fn new(field: &'f Field) -> FramedField<'f> {
+        Self { field }
+    }
+
+    /// 枠付きの描画物の左上を原点として，フィールドを描画する領域を返す．
+    /// 操作中のブロックなど，フィールドに重ねて描画するものはこの領域に描画する．
+    pub fn field_roi(&self) -> RegionOfInterest {
+        self.field.get_roi(Self::FIELD_LEFT_TOP)
+    }
+}
+
+impl Drawable for FramedField<'_> {
+    fn region_size(&self) -> Movement {
+        self.field.region_size() + right(2) + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        // 枠を含めた大きさが座標に収まらないほど大きなフィールドは，枠を描画しない
+        if let Some(frame) = Frame::around(self.field) {
+            frame.draw(canvas);
+        }
+        self.field.draw_on_child(Self::FIELD_LEFT_TOP, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Cell;
+
+    fn render(field: &Field) -> Vec<String> {
+        let mut canvas = RootCanvas::new();
+        FramedField::new(field).draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        buffer.lines().map(str::to_owned).collect()
+    }
+
+    #[test]
+    fn test_region_size() {
+        let field = Field::empty();
+        let framed = FramedField::new(&field);
+        assert_eq!(right(12) + below(22), framed.region_size());
+        assert_eq!(
+            RegionOfInterest::new(Pos(PosX::right(1), PosY::below(1)), right(10) + below(20)),
+            framed.field_roi()
+        );
+    }
+
+    #[test]
+    fn test_draw_frame() {
+        let mut field = Field::empty_with_size(3, 2);
+        *field.get_mut(Pos(PosX::origin(), PosY::below(1))).unwrap() = Cell::Normal;
+        let lines = render(&field);
+
+        assert_eq!(" +------+ ", &lines[0][..10]);
+        assert_eq!(" | . . .| ", &lines[1][..10]);
+        assert_eq!(" |[] . .| ", &lines[2][..10]);
+        assert_eq!(" +------+ ", &lines[3][..10]);
+        // 枠の外には何も描画しない
+        assert!(lines[0][10..].trim().is_empty());
+        assert!(lines[4].trim().is_empty());
+    }
+}
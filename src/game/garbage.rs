@@ -0,0 +1,106 @@
+use rand::Rng;
+
+/// せり上がりライン(お邪魔ライン)の穴の配置パターンを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GarbagePattern {
+    /// すべてのラインで同じ列に穴をあける．
+    SameHole,
+    /// `period`ラインごとに，穴の列を直前とは異なる列へ移す．
+    ShiftingHole { period: usize },
+    /// ラインごとに穴の列を独立に決める．
+    RandomPerRow,
+}
+
+impl GarbagePattern {
+    /// 指定したライン数ぶんの穴の列を，上のラインから順に返す．
+    /// # Params
+    /// 1. `rows` 生成するライン数．
+    /// 1. `width` フィールドの横方向のセル数．
+    /// 1. `rng` 穴の列を決めるための乱数生成器．
+    ///
+    /// # Panics
+    /// 1. `width`が0の場合．
+    /// 1. `ShiftingHole`の`period`が0の場合．
+    pub fn hole_columns<R: Rng>(&self, rows: usize, width: usize, rng: &mut R) -> Vec<usize> {
+        assert!(width > 0);
+
+        match *self {
+            GarbagePattern::SameHole => {
+                let column = rng.gen_range(0..width);
+                vec![column; rows]
+            }
+            GarbagePattern::ShiftingHole { period } => {
+                assert!(period > 0);
+                let mut column = rng.gen_range(0..width);
+                let mut columns = Vec::with_capacity(rows);
+                for i in 0..rows {
+                    if i > 0 && i % period == 0 && width > 1 {
+                        // 直前と同じ列を選ばないように，直前の列以外から選ぶ
+                        let next = rng.gen_range(0..width - 1);
+                        column = if next >= column { next + 1 } else { next };
+                    }
+                    columns.push(column);
+                }
+                columns
+            }
+            GarbagePattern::RandomPerRow => (0..rows).map(|_| rng.gen_range(0..width)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const WIDTH: usize = 10;
+
+    #[test]
+    fn test_same_hole() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let columns = GarbagePattern::SameHole.hole_columns(8, WIDTH, &mut rng);
+        assert_eq!(8, columns.len());
+        assert!(columns[0] < WIDTH);
+        assert!(columns.iter().all(|&c| c == columns[0]));
+    }
+
+    #[test]
+    fn test_shifting_hole() {
+        for seed in 0..20 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let pattern = GarbagePattern::ShiftingHole { period: 3 };
+            let columns = pattern.hole_columns(9, WIDTH, &mut rng);
+            assert_eq!(9, columns.len());
+            assert!(columns.iter().all(|&c| c < WIDTH));
+            for i in 1..columns.len() {
+                if i % 3 == 0 {
+                    // 周期の境目では必ず穴が移動する
+                    assert_ne!(columns[i - 1], columns[i]);
+                } else {
+                    assert_eq!(columns[i - 1], columns[i]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shifting_hole_single_column() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let pattern = GarbagePattern::ShiftingHole { period: 1 };
+        assert_eq!(vec![0, 0, 0], pattern.hole_columns(3, 1, &mut rng));
+    }
+
+    #[test]
+    fn test_random_per_row() {
+        let columns1 =
+            GarbagePattern::RandomPerRow.hole_columns(50, WIDTH, &mut StdRng::seed_from_u64(7));
+        let columns2 =
+            GarbagePattern::RandomPerRow.hole_columns(50, WIDTH, &mut StdRng::seed_from_u64(7));
+        // 同じシード値からは同じ穴の列が得られる
+        assert_eq!(columns1, columns2);
+        assert!(columns1.iter().all(|&c| c < WIDTH));
+        // 50ラインもあれば穴の列はばらつくはず
+        assert!(columns1.iter().any(|&c| c != columns1[0]));
+    }
+}
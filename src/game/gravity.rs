@@ -0,0 +1,97 @@
+mod consts {
+    /// 既定の落下間隔(フレーム数)．
+    pub const DEFAULT_INTERVAL: usize = 20;
+}
+
+use consts::*;
+
+/// 操作中のブロックを一定フレームごとに自動で落下させるタイミングを管理する．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gravity {
+    /// 落下間隔(フレーム数)．
+    interval: usize,
+    /// 前回の落下から経過したフレーム数．
+    elapsed: usize,
+}
+
+impl Gravity {
+    /// 指定したフレーム数ごとに落下させる`Gravity`を返す．
+    /// # Panics
+    /// `interval`に0を指定した場合．
+    pub fn new(interval: usize) -> Gravity {
+        assert!(interval > 0);
+        Self {
+            interval,
+            elapsed: 0,
+        }
+    }
+
+    /// 落下間隔(フレーム数)を返す．
+    pub const fn interval(&self) -> usize {
+        self.interval
+    }
+
+    /// 落下間隔を変更する．経過フレーム数は保たれる．
+    /// # Panics
+    /// `interval`に0を指定した場合．
+    pub fn set_interval(&mut self, interval: usize) {
+        assert!(interval > 0);
+        self.interval = interval;
+    }
+
+    /// 1フレーム進める．
+    /// # Returns
+    /// このフレームでブロックを落下させる場合は`true`を返す．
+    pub fn tick(&mut self) -> bool {
+        self.elapsed += 1;
+        if self.elapsed >= self.interval {
+            self.elapsed = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 経過フレーム数を0に戻す．
+    pub fn reset(&mut self) {
+        self.elapsed = 0;
+    }
+}
+
+impl Default for Gravity {
+    fn default() -> Self {
+        Self::new(DEFAULT_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tick() {
+        let mut gravity = Gravity::new(3);
+        let ticks = (0..7).map(|_| gravity.tick()).collect::<Vec<_>>();
+        assert_eq!(vec![false, false, true, false, false, true, false], ticks);
+    }
+
+    #[test]
+    fn test_set_interval() {
+        let mut gravity = Gravity::new(3);
+        assert!(!gravity.tick());
+        // 間隔を短くすると，経過フレーム数が達し次第落下する
+        gravity.set_interval(1);
+        assert_eq!(1, gravity.interval());
+        assert!(gravity.tick());
+        assert!(gravity.tick());
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut gravity = Gravity::new(2);
+        assert!(!gravity.tick());
+        gravity.reset();
+        assert!(!gravity.tick());
+        assert!(gravity.tick());
+    }
+}
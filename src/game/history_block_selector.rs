@@ -0,0 +1,181 @@
+use super::{BlockSelector, BlockShape, BombTag, QuadrupleBlockShape, QuintupleBlockShape};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+/// 履歴として覚えておく，直前に生成した形状の数．
+pub const HISTORY_LENGTH: usize = 4;
+/// 既定の引き直しの回数の上限．
+pub const DEFAULT_RETRIES: usize = 4;
+
+/// 直前に生成した形状を避けるように選ぶブロック生成ルール．
+///
+/// 候補の形状から無作為に選んだ形状が直前`HISTORY_LENGTH`個の形状のいずれかと同じ場合は，指定した回数まで選び直す．
+/// 選び直しても履歴にある形状しか出なかった場合は，最後に選んだ形状を生成する．
+/// 同じ形状が続いたり，ある形状がしばらく出なかったりすることが，純粋な無作為選択より起きにくくなる．
+/// 各ブロックの空でないセルのうち，無作為に選んだひとつがボムセルとなる．
+///
+/// 同じ候補，引き直しの回数とシード値から作った生成ルールは，ボムセルの位置を含めて同じブロック列を生成する．
+#[derive(Debug, Clone)]
+pub struct HistoryBlockSelector {
+    /// 選ぶ候補となる形状．
+    shapes: Vec<BlockShape>,
+    /// 引き直しの回数の上限．
+    retries: usize,
+    /// 直前に生成した形状．先頭が最も古い．
+    history: VecDeque<BlockShape>,
+    /// 乱数生成器の初期化に利用したシード値．
+    seed: u64,
+    rng: StdRng,
+}
+
+impl HistoryBlockSelector {
+    /// 候補の形状と引き直しの回数の上限を指定した生成ルールを，無作為なシード値で初期化して返す．
+    /// # Returns
+    /// 候補が空の場合は`None`を返す．
+    pub fn new(shapes: Vec<BlockShape>, retries: usize) -> Option<HistoryBlockSelector> {
+        Self::with_seed(shapes, retries, rand::random())
+    }
+
+    /// 候補の形状と引き直しの回数の上限を指定した生成ルールを，指定したシード値で初期化して返す．
+    /// # Returns
+    /// 候補が空の場合は`None`を返す．
+    pub fn with_seed(
+        shapes: Vec<BlockShape>,
+        retries: usize,
+        seed: u64,
+    ) -> Option<HistoryBlockSelector> {
+        if shapes.is_empty() {
+            return None;
+        }
+        Some(Self {
+            shapes,
+            retries,
+            history: VecDeque::with_capacity(HISTORY_LENGTH + 1),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// 7種類のテトリミノを候補とし，既定の回数まで引き直す生成ルールを返す．
+    pub fn tetromino(seed: u64) -> HistoryBlockSelector {
+        let shapes = QuadrupleBlockShape::all().map(Into::into).collect();
+        Self::with_seed(shapes, DEFAULT_RETRIES, seed).expect("tetrominoes are not empty")
+    }
+
+    /// 17種類のペントミノを候補とし，既定の回数まで引き直す生成ルールを返す．
+    pub fn pentomino(seed: u64) -> HistoryBlockSelector {
+        let shapes = QuintupleBlockShape::all().map(Into::into).collect();
+        Self::with_seed(shapes, DEFAULT_RETRIES, seed).expect("pentominoes are not empty")
+    }
+
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 選ぶ候補となる形状を返す．
+    pub fn shapes(&self) -> &[BlockShape] {
+        &self.shapes
+    }
+
+    /// 候補から無作為に形状をひとつ選ぶ．
+    fn roll(&mut self) -> BlockShape {
+        self.shapes[self.rng.gen_range(0..self.shapes.len())]
+    }
+}
+
+impl BlockSelector for HistoryBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        let mut shape = self.roll();
+        for _ in 0..self.retries {
+            if !self.history.contains(&shape) {
+                break;
+            }
+            shape = self.roll();
+        }
+
+        self.history.push_back(shape);
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        shape
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        BombTag::Single(self.rng.gen_range(0..shape.non_empty_cell_count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn shapes(selector: &mut HistoryBlockSelector, count: usize) -> Vec<BlockShape> {
+        (0..count)
+            .map(|_| selector.generate_block().shape())
+            .collect()
+    }
+
+    /// 直前`HISTORY_LENGTH`個のいずれかと同じ形状が選ばれた回数を返す．
+    fn count_repeats(shapes: &[BlockShape]) -> usize {
+        shapes
+            .iter()
+            .enumerate()
+            .filter(|&(i, shape)| {
+                let start = i.saturating_sub(HISTORY_LENGTH);
+                shapes[start..i].contains(shape)
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_same_seed_same_blocks() {
+        let mut a = HistoryBlockSelector::tetromino(42);
+        let mut b = HistoryBlockSelector::tetromino(42);
+        for _ in 0..100 {
+            assert_eq!(a.generate_block(), b.generate_block());
+        }
+        assert_eq!(42, a.seed());
+    }
+
+    #[test]
+    fn test_repeats_are_bounded_by_retries() {
+        const COUNT: usize = 10000;
+
+        // 引き直さない場合は純粋な無作為選択となり，半分近くが直前の形状と重なる
+        let shapes_without_retry = {
+            let pool = QuadrupleBlockShape::all().map(Into::into).collect();
+            let mut selector = HistoryBlockSelector::with_seed(pool, 0, 7).unwrap();
+            shapes(&mut selector, COUNT)
+        };
+        let repeats = count_repeats(&shapes_without_retry);
+        assert!(repeats > COUNT * 2 / 5, "{}", repeats);
+
+        // 4回まで引き直すと，重なるのは5回続けて履歴の形状を引いた場合だけとなる
+        // その確率は(4/7)^5，およそ6%なので，十分に余裕をもたせる
+        let mut selector = HistoryBlockSelector::tetromino(7);
+        let repeats = count_repeats(&shapes(&mut selector, COUNT));
+        assert!(repeats < COUNT / 10, "{}", repeats);
+
+        // 十分な回数引き直せば，履歴の形状は一度も選ばれない
+        let pool = QuadrupleBlockShape::all().map(Into::into).collect();
+        let mut selector = HistoryBlockSelector::with_seed(pool, 100, 7).unwrap();
+        assert_eq!(0, count_repeats(&shapes(&mut selector, COUNT)));
+    }
+
+    #[test]
+    fn test_pentomino_pool() {
+        let mut selector = HistoryBlockSelector::pentomino(3);
+        let shapes = shapes(&mut selector, 1000);
+        assert!(shapes.iter().all(|shape| shape.non_empty_cell_count() == 5));
+        let kinds = shapes.into_iter().collect::<HashSet<_>>();
+        assert_eq!(QuintupleBlockShape::ALL.len(), kinds.len());
+    }
+
+    #[test]
+    fn test_empty_pool() {
+        assert!(HistoryBlockSelector::new(vec![], DEFAULT_RETRIES).is_none());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::geometry::*;
+use crate::graphics::*;
+use std::time::Duration;
+
+mod consts {
+    /// レベルがひとつ上がるまでに揃えるライン数．
+    pub const ROWS_PER_LEVEL: usize = 10;
+}
+
+use consts::*;
+
+/// 各レベルでのゲームの速さを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LevelSpeed {
+    /// 自動落下の間隔(フレーム数)．
+    gravity_interval: usize,
+    /// アニメーションのフレーム間の待機時間(ミリ秒)．
+    frame_millis: u64,
+}
+
+/// レベル1から順に並べた，各レベルでのゲームの速さ．
+/// 表の最後より上のレベルでは，最後の速さを使う．
+const LEVEL_SPEEDS: [LevelSpeed; 12] = [
+    LevelSpeed {
+        gravity_interval: 20,
+        frame_millis: 50,
+    },
+    LevelSpeed {
+        gravity_interval: 17,
+        frame_millis: 48,
+    },
+    LevelSpeed {
+        gravity_interval: 14,
+        frame_millis: 46,
+    },
+    LevelSpeed {
+        gravity_interval: 12,
+        frame_millis: 44,
+    },
+    LevelSpeed {
+        gravity_interval: 10,
+        frame_millis: 42,
+    },
+    LevelSpeed {
+        gravity_interval: 8,
+        frame_millis: 40,
+    },
+    LevelSpeed {
+        gravity_interval: 6,
+        frame_millis: 38,
+    },
+    LevelSpeed {
+        gravity_interval: 5,
+        frame_millis: 36,
+    },
+    LevelSpeed {
+        gravity_interval: 4,
+        frame_millis: 34,
+    },
+    LevelSpeed {
+        gravity_interval: 3,
+        frame_millis: 32,
+    },
+    LevelSpeed {
+        gravity_interval: 2,
+        frame_millis: 30,
+    },
+    LevelSpeed {
+        gravity_interval: 1,
+        frame_millis: 30,
+    },
+];
+
+/// 揃えたライン数に応じて上がるレベルを表す．
+/// レベルは1から始まり，`ROWS_PER_LEVEL`本揃えるたびにひとつ上がる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Level {
+    /// これまでに揃えたライン数．
+    rows: usize,
+}
+
+impl Level {
+    /// まだラインを揃えていない，レベル1の状態を返す．
+    pub const fn new() -> Level {
+        Self { rows: 0 }
+    }
+
+    /// 指定した本数のラインを揃えた状態を返す．
+    pub const fn with_rows(rows: usize) -> Level {
+        Self { rows }
+    }
+
+    /// 指定した本数のラインが揃ったことを記録する．
+    /// # Returns
+    /// レベルが上がった場合は`true`を返す．
+    pub fn record_rows(&mut self, rows: usize) -> bool {
+        let previous_level = self.level();
+        self.rows += rows;
+        self.level() > previous_level
+    }
+
+    /// 現在のレベルを返す．
+    pub const fn level(&self) -> usize {
+        self.rows / ROWS_PER_LEVEL + 1
+    }
+
+    /// これまでに揃えたライン数を返す．
+    pub const fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// 次のレベルに上がるまでに揃えるライン数を返す．
+    pub const fn rows_to_next_level(&self) -> usize {
+        ROWS_PER_LEVEL - self.rows % ROWS_PER_LEVEL
+    }
+
+    /// 現在のレベルでの自動落下の間隔(フレーム数)を返す．
+    pub fn gravity_interval(&self) -> usize {
+        self.speed().gravity_interval
+    }
+
+    /// 現在のレベルでの，アニメーションのフレーム間の待機時間を返す．
+    pub fn frame_duration(&self) -> Duration {
+        Duration::from_millis(self.speed().frame_millis)
+    }
+
+    /// 描画するときの，レベルの数値を含む1行目の文字列を返す．
+    pub fn level_text(&self) -> String {
+        format!("Level {:>4}", self.level())
+    }
+
+    fn speed(&self) -> LevelSpeed {
+        let index = std::cmp::min(self.level(), LEVEL_SPEEDS.len()) - 1;
+        LEVEL_SPEEDS[index]
+    }
+}
+
+/// レベルと，次のレベルまでのライン数を2行で描画する．
+impl Drawable for Level {
+    fn region_size(&self) -> Movement {
+        right(10) + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        ColoredStr(self.level_text(), color).draw_on_child(Pos::origin(), canvas);
+        let next = format!("Next  {:>4}", self.rows_to_next_level());
+        ColoredStr(next, color).draw_on_child(Pos::origin() + below(1), canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_from_rows() {
+        // (揃えたライン数, レベル, 次のレベルまでのライン数, 落下間隔)
+        let cases = [
+            (0, 1, 10, 20),
+            (9, 1, 1, 20),
+            (10, 2, 10, 17),
+            (25, 3, 5, 14),
+            (110, 12, 10, 1),
+            (500, 51, 10, 1),
+        ];
+        for &(rows, level, rows_to_next, interval) in cases.iter() {
+            let l = Level::with_rows(rows);
+            assert_eq!(level, l.level(), "rows: {}", rows);
+            assert_eq!(rows_to_next, l.rows_to_next_level(), "rows: {}", rows);
+            assert_eq!(interval, l.gravity_interval(), "rows: {}", rows);
+        }
+    }
+
+    #[test]
+    fn test_speeds_never_slow_down() {
+        for speeds in LEVEL_SPEEDS.windows(2) {
+            assert!(speeds[0].gravity_interval >= speeds[1].gravity_interval);
+            assert!(speeds[0].frame_millis >= speeds[1].frame_millis);
+        }
+        assert_eq!(Duration::from_millis(50), Level::new().frame_duration());
+    }
+
+    #[test]
+    fn test_record_rows() {
+        let mut level = Level::new();
+        assert!(!level.record_rows(9));
+        assert!(level.record_rows(2));
+        assert_eq!(2, level.level());
+        assert_eq!(11, level.rows());
+        assert!(!level.record_rows(0));
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut canvas = RootCanvas::new();
+        Level::with_rows(13).draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let mut lines = buffer.lines();
+        assert!(lines.next().unwrap().starts_with("Level    2"));
+        assert!(lines.next().unwrap().starts_with("Next     7"));
+    }
+}
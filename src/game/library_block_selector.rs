@@ -0,0 +1,117 @@
+use super::{BlockSelector, BlockShape, BombTag, ShapeLibrary};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// 形状定義ファイルから読み込んだ形状の一覧から，無作為にブロックを選ぶブロック生成ルール．
+/// 各ブロックの空でないセルのうち，無作為に選んだひとつがボムセルとなる．
+///
+/// 同じ一覧と同じシード値から作った生成ルールは，ボムセルの位置を含めて同じブロック列を生成する．
+#[derive(Debug, Clone)]
+pub struct LibraryBlockSelector {
+    /// 選ぶ候補となる形状．
+    shapes: Vec<BlockShape>,
+    /// 乱数生成器の初期化に利用したシード値．
+    seed: u64,
+    rng: StdRng,
+}
+
+impl LibraryBlockSelector {
+    /// 無作為なシード値で初期化した生成ルールを返す．
+    /// # Returns
+    /// 一覧が形状をひとつも含まない場合は`None`を返す．
+    pub fn new(library: &ShapeLibrary) -> Option<LibraryBlockSelector> {
+        Self::with_seed(library, rand::random())
+    }
+
+    /// 指定したシード値で初期化した生成ルールを返す．
+    /// # Returns
+    /// 一覧が形状をひとつも含まない場合は`None`を返す．
+    pub fn with_seed(library: &ShapeLibrary, seed: u64) -> Option<LibraryBlockSelector> {
+        if library.is_empty() {
+            return None;
+        }
+        Some(Self {
+            shapes: library.shapes().collect(),
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl BlockSelector for LibraryBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.shapes[self.rng.gen_range(0..self.shapes.len())]
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        BombTag::Single(self.rng.gen_range(0..shape.non_empty_cell_count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{load_from_str, Block, Cell};
+    use super::*;
+    use std::collections::HashSet;
+
+    fn generate_blocks(selector: &mut LibraryBlockSelector, count: usize) -> Vec<Block> {
+        (0..count).map(|_| selector.generate_block()).collect()
+    }
+
+    #[test]
+    fn test_draws_from_library() {
+        let library = load_from_str(
+            "
+            shape Domino
+            above
+            .....
+            .....
+            ..01.
+            .....
+            .....
+            shape Tromino
+            above
+            .....
+            ..0..
+            ..12.
+            .....
+            .....
+            ",
+        )
+        .unwrap();
+        let mut selector = LibraryBlockSelector::with_seed(&library, 3).unwrap();
+        let blocks = generate_blocks(&mut selector, 100);
+
+        // 一覧のすべての形状が選ばれ，一覧にない形状は選ばれない
+        let shapes = blocks.iter().map(|b| b.shape()).collect::<HashSet<_>>();
+        assert_eq!(library.shapes().collect::<HashSet<_>>(), shapes);
+        // ボムセルはちょうどひとつ
+        for block in blocks {
+            let bombs = block
+                .iter_pos_and_occupied_cell()
+                .filter(|(_, &cell)| cell == Cell::Bomb)
+                .count();
+            assert_eq!(1, bombs);
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_blocks() {
+        let library = ShapeLibrary::builtin();
+        let mut a = LibraryBlockSelector::with_seed(library, 42).unwrap();
+        let mut b = LibraryBlockSelector::with_seed(library, 42).unwrap();
+        assert_eq!(42, a.seed());
+        assert_eq!(generate_blocks(&mut a, 100), generate_blocks(&mut b, 100));
+    }
+
+    #[test]
+    fn test_empty_library() {
+        let library = load_from_str("").unwrap();
+        assert!(LibraryBlockSelector::new(&library).is_none());
+    }
+}
@@ -0,0 +1,152 @@
+use super::Field;
+use crate::geometry::*;
+use crate::graphics::*;
+
+/// フィールドを縦横半分の解像度で表示する描画物を表す．
+/// フィールドの2x2セルがキャンバスの1セルにまとめられ，そのうち埋まっているセルの数に応じた文字で表示される．
+/// 画面幅が足りない場合に，相手のフィールドを小さく表示するために利用される．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MiniField {
+    /// 縮小後の各位置について，元の2x2セルのうち埋まっているセルの数．
+    occupied_counts: Vec<Vec<u8>>,
+}
+
+impl MiniField {
+    /// 指定したフィールドを縮小した描画物を返す．
+    pub fn new(field: &Field) -> MiniField {
+        Self::from_occupancy(field.width(), field.height(), |x, y| {
+            let pos = Pos(PosX::right(x as i8), PosY::below(y as i8));
+            field.get(pos).map(|cell| !cell.is_empty()).unwrap_or(false)
+        })
+    }
+
+    /// 指定した大きさの領域を縮小した描画物を返す．
+    /// 幅や高さが奇数の場合，範囲外のセルは空として扱う．
+    /// # Params
+    /// 1. `is_occupied` 指定した位置(x, y)のセルが埋まっているか返す関数．
+    fn from_occupancy<F>(width: usize, height: usize, is_occupied: F) -> MiniField
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let occupied_counts = (0..height.div_ceil(2))
+            .map(|mini_y| {
+                (0..width.div_ceil(2))
+                    .map(|mini_x| {
+                        let xs = mini_x * 2..std::cmp::min(mini_x * 2 + 2, width);
+                        let ys = mini_y * 2..std::cmp::min(mini_y * 2 + 2, height);
+                        ys.flat_map(|y| xs.clone().map(move |x| (x, y)))
+                            .filter(|&(x, y)| is_occupied(x, y))
+                            .count() as u8
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { occupied_counts }
+    }
+
+    /// 埋まっているセルの数に対応する表示用の文字を返す．
+    fn char_for_count(count: u8) -> char {
+        match count {
+            0 => ' ',
+            1 => '.',
+            2 => ':',
+            _ => '#',
+        }
+    }
+
+    /// 埋まっているセルの数に対応する表示用の色を返す．
+    fn color_for_count(count: u8, theme: &Theme) -> CanvasCellColor {
+        match count {
+            0 | 1 => theme.cell_color(ThemeColor::EmptyCell),
+            _ => theme.cell_color(ThemeColor::NormalCell),
+        }
+    }
+}
+
+impl Drawable for MiniField {
+    fn region_size(&self) -> Movement {
+        let width = self
+            .occupied_counts
+            .first()
+            .map(|row| row.len())
+            .unwrap_or(0);
+        let height = self.occupied_counts.len();
+        right(width as i8) + below(height as i8)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let theme = canvas.theme();
+        for (y, row) in self.occupied_counts.iter().enumerate() {
+            for (x, &count) in row.iter().enumerate() {
+                let c = Self::char_for_count(count);
+                let cell =
+                    CanvasCell::new(SquareChar::new(c, c), Self::color_for_count(count, &theme));
+                let pos = Pos(PosX::right(x as i8), PosY::below(y as i8));
+                canvas.draw_cell(pos, cell);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Cell;
+
+    /// 描画結果を，縮小後の1セルを1文字とした文字列の行として返す．
+    fn render_rows(mini_field: &MiniField) -> Vec<String> {
+        let mut canvas = RootCanvas::new();
+        mini_field.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+
+        let width = mini_field.region_size().x().as_positive_index().unwrap();
+        let height = mini_field.region_size().y().as_positive_index().unwrap();
+        buffer
+            .lines()
+            .take(height)
+            .map(|line| line.chars().step_by(2).take(width).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_downsample_field() {
+        let mut field = Field::empty();
+        let occupied = [
+            // 左上の2x2セルはすべて埋まる
+            (0, 0),
+            (1, 0),
+            (0, 1),
+            (1, 1),
+            // 次の2x2セルは1つだけ
+            (2, 0),
+            // 最下段の右端は2つ
+            (8, 19),
+            (9, 18),
+        ];
+        for &(x, y) in occupied.iter() {
+            *field.get_mut(Pos(PosX::right(x), PosY::below(y))).unwrap() = Cell::Normal;
+        }
+
+        let mini_field = MiniField::new(&field);
+        assert_eq!(right(5) + below(10), mini_field.region_size());
+
+        let rows = render_rows(&mini_field);
+        assert_eq!("#.   ", rows[0]);
+        for row in rows.iter().skip(1).take(8) {
+            assert_eq!("     ", row);
+        }
+        assert_eq!("    :", rows[9]);
+    }
+
+    #[test]
+    fn test_downsample_odd_dimensions() {
+        // 3x3の領域がすべて埋まっている場合，端の2x2セルは範囲外を空として数える
+        let mini_field = MiniField::from_occupancy(3, 3, |_, _| true);
+        assert_eq!(right(2) + below(2), mini_field.region_size());
+        assert_eq!(vec![vec![4, 2], vec![2, 1]], mini_field.occupied_counts);
+
+        let rows = render_rows(&mini_field);
+        assert_eq!(vec!["#:", ":."], rows);
+    }
+}
@@ -0,0 +1,110 @@
+use super::{BlockSelector, BlockShape, BombTag, QuintupleBlockShape};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// 17種類のペントミノから無作為にブロックを選ぶブロック生成ルール．
+/// 各ブロックの空でないセルのうち，無作為に選んだひとつがボムセルとなる．
+///
+/// 同じシード値から作った生成ルールは，ボムセルの位置を含めて同じブロック列を生成する．
+#[derive(Debug, Clone)]
+pub struct PentominoBlockSelector {
+    /// 乱数生成器の初期化に利用したシード値．
+    seed: u64,
+    rng: StdRng,
+}
+
+impl PentominoBlockSelector {
+    /// 無作為なシード値で初期化した生成ルールを返す．
+    pub fn new() -> PentominoBlockSelector {
+        Self::with_seed(rand::random())
+    }
+
+    /// 指定したシード値で初期化した生成ルールを返す．
+    pub fn with_seed(seed: u64) -> PentominoBlockSelector {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for PentominoBlockSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockSelector for PentominoBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        let shapes = QuintupleBlockShape::ALL;
+        shapes[self.rng.gen_range(0..shapes.len())].into()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        BombTag::Single(self.rng.gen_range(0..shape.non_empty_cell_count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BombPolicy, BombPolicySelector, Field};
+    use super::*;
+    use crate::game::test_util::repeating_selector;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_same_seed_same_blocks() {
+        let mut a = PentominoBlockSelector::with_seed(42);
+        let mut b = PentominoBlockSelector::with_seed(42);
+        for _ in 0..100 {
+            assert_eq!(a.generate_block(), b.generate_block());
+        }
+        assert_eq!(42, a.seed());
+    }
+
+    #[test]
+    fn test_selects_every_shape() {
+        let mut selector = PentominoBlockSelector::with_seed(0);
+        let shapes = (0..1000)
+            .map(|_| selector.select_block_shape())
+            .collect::<HashSet<_>>();
+        assert_eq!(QuintupleBlockShape::ALL.len(), shapes.len());
+        assert!(shapes.iter().all(|shape| shape.non_empty_cell_count() == 5));
+    }
+
+    #[test]
+    fn test_every_shape_and_direction_spawns() {
+        let field = Field::empty();
+        for &shape in QuintupleBlockShape::ALL.iter() {
+            let mut block = repeating_selector(shape, BombTag::Single(4)).generate_block();
+            for _ in 0..4 {
+                assert!(
+                    field.appearance_pos(&block).is_some(),
+                    "{:?} {:?}",
+                    shape,
+                    block.direction()
+                );
+                block = block.rotate_clockwise();
+            }
+        }
+    }
+
+    #[test]
+    fn test_bomb_policy_labels() {
+        for label in 0..5 {
+            let policy = BombPolicy::FixedLabel(label);
+            let mut selector =
+                BombPolicySelector::with_seed(PentominoBlockSelector::with_seed(1), policy, 1)
+                    .unwrap();
+            for _ in 0..10 {
+                let shape = selector.select_block_shape();
+                assert_eq!(BombTag::Single(label), selector.select_bomb(shape));
+            }
+        }
+    }
+}
@@ -0,0 +1,351 @@
+use super::animation::Drawer;
+use super::single_play::{self, GameEvent, GameResult, LineClearRule};
+use super::{
+    BlockShape, BombTag, Field, FramedField, QuadrupleBlockShape, ScriptEnd, ScriptedBlockSelector,
+};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::menu::ItemList;
+use crate::user::{GameCommand, MenuCommand};
+use std::cell::Cell;
+
+/// 途中まで埋まったフィールドと，使えるブロックの列からなるパズルを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Puzzle {
+    /// 一覧に表示する名前．
+    name: &'static str,
+    /// 最初のフィールド．`Field::from_str_art`の形式で表す．
+    field: &'static str,
+    /// 使えるブロックの形状とボム指定．生成される順に並ぶ．
+    blocks: &'static [(BlockShape, BombTag)],
+}
+
+impl Puzzle {
+    pub const fn new(
+        name: &'static str,
+        field: &'static str,
+        blocks: &'static [(BlockShape, BombTag)],
+    ) -> Puzzle {
+        Self {
+            name,
+            field,
+            blocks,
+        }
+    }
+
+    /// 一覧に表示する名前を返す．
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// 最初のフィールドを返す．
+    /// # Panics
+    /// フィールドの表記が`Field::from_str_art`の形式として正しくない場合．
+    pub fn field(&self) -> Field {
+        Field::from_str_art(self.field).expect("invalid puzzle field")
+    }
+
+    /// 使えるブロックの数を返す．
+    pub const fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// 使えるブロックを順に生成し，使い切ったらそれを知らせるブロック生成ルールを返す．
+    /// 最初の3つはNextブロックとHoldブロックになり，Holdブロックは3番目のブロックとなる．
+    /// # Panics
+    /// ボム指定がブロックの形状に合わない場合．
+    pub fn selector(&self) -> ScriptedBlockSelector {
+        ScriptedBlockSelector::new(self.blocks.to_vec(), ScriptEnd::Stop)
+            .expect("invalid puzzle blocks")
+    }
+}
+
+/// 組み込みのパズル．
+pub const PUZZLES: [Puzzle; 3] = [
+    Puzzle::new(
+        "Warm-up",
+        "
+        ##*#..#*##
+        ##*#..#*##
+        ",
+        &[(BlockShape::Quadruple(QuadrupleBlockShape::O), BombTag::None)],
+    ),
+    Puzzle::new(
+        "Two steps",
+        "
+        #*#....#*#
+        #*#....#*#
+        ",
+        &[
+            (BlockShape::Quadruple(QuadrupleBlockShape::O), BombTag::None),
+            (BlockShape::Quadruple(QuadrupleBlockShape::O), BombTag::None),
+        ],
+    ),
+    Puzzle::new(
+        "Pillar",
+        "
+        ##*#.##*##
+        ##*#.##*##
+        ##*#.##*##
+        ##*#.##*##
+        ",
+        &[(BlockShape::Quadruple(QuadrupleBlockShape::I), BombTag::None)],
+    ),
+];
+
+/// 描画のたびに，フィールドの下へ残りのブロック数を重ねて描画する描画機能．
+struct PuzzleDrawer<'d, 'p, D> {
+    drawer: &'d mut D,
+    /// 残りのブロック数を描画する位置．
+    pos: Pos,
+    remaining: &'p Cell<usize>,
+}
+
+impl<'d, 'p, D: Drawer> Drawer for PuzzleDrawer<'d, 'p, D> {
+    type Canvas = D::Canvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        self.drawer.canvas_mut()
+    }
+
+    fn clear(&mut self) {
+        self.drawer.clear();
+    }
+
+    fn show(&mut self) {
+        let color = self
+            .drawer
+            .canvas_mut()
+            .theme()
+            .cell_color(ThemeColor::Text);
+        let remaining = format!("Blocks {}", self.remaining.get());
+        ColoredStr(remaining, color).draw_on_child(self.pos, self.drawer.canvas_mut());
+        self.drawer.show();
+    }
+
+    fn wait_frame(&mut self) {
+        self.drawer.wait_frame();
+    }
+
+    fn set_frame_duration(&mut self, duration: std::time::Duration) {
+        self.drawer.set_frame_duration(duration);
+    }
+
+    fn render_config(&self) -> RenderConfig {
+        self.drawer.render_config()
+    }
+}
+
+/// パズルの終了時の状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleResult {
+    /// ゲーム終了時の状態．
+    pub game: GameResult,
+    /// フィールドをすべて消してゲームを終えた場合は`true`．
+    pub solved: bool,
+}
+
+/// パズルを解けたかどうか，爆発の統計と最終得点を上から順に描画する．
+impl Drawable for PuzzleResult {
+    fn region_size(&self) -> Movement {
+        self.game.region_size() + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        let outcome = if self.solved { "Cleared!" } else { "Failed" };
+        ColoredStr(outcome, color).draw_on_child(Pos::origin(), canvas);
+        self.game.draw_on_child(Pos::origin() + below(1), canvas);
+    }
+}
+
+/// パズルを実行する．
+///
+/// パズルのフィールドから始めて，パズルのブロックを順に使う．
+/// フィールドのセルがすべて消えたら解けたものとして，ブロックを使い切ったら解けなかったものとしてゲームを終える．
+/// 入力の扱いは`single_play::execute_game`と同様．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_puzzle<I, D>(puzzle: &Puzzle, input: I, drawer: &mut D) -> PuzzleResult
+where
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+{
+    execute_puzzle_with(puzzle.field(), puzzle.selector(), input, drawer, |_| {})
+}
+
+/// 指定したフィールドとブロック生成ルールでパズルを実行する．
+///
+/// 使えるブロックの数は，ゲーム開始時に`block_generator`がまだ生成していないブロックの数とする．
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_puzzle`と同様．
+pub fn execute_puzzle_with<I, D, E>(
+    field: Field,
+    block_generator: ScriptedBlockSelector,
+    mut input: I,
+    drawer: &mut D,
+    mut on_event: E,
+) -> PuzzleResult
+where
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let remaining = Cell::new(block_generator.remaining());
+    let solved = Cell::new(false);
+    let mut drawer = PuzzleDrawer {
+        drawer,
+        pos: Pos::origin() + FramedField::new(&field).region_size().y(),
+        remaining: &remaining,
+    };
+
+    let game = single_play::execute_game_from(
+        field,
+        block_generator,
+        LineClearRule::Explosion,
+        || {
+            // 解けたか，ブロックを使い切ったら，入力を終えてゲームを終了させる
+            if solved.get() || remaining.get() == 0 {
+                vec![]
+            } else {
+                input()
+            }
+        },
+        &mut drawer,
+        |event| {
+            match event {
+                GameEvent::BlockPlaced => remaining.set(remaining.get().saturating_sub(1)),
+                GameEvent::FieldCleared => solved.set(true),
+                _ => {}
+            }
+            on_event(event);
+        },
+    );
+
+    PuzzleResult {
+        game,
+        solved: solved.get(),
+    }
+}
+
+/// 組み込みのパズルの一覧を描画し，遊ぶパズルを選ばせる．
+///
+/// `MenuCommand::Up`と`MenuCommand::Down`で選択を移動し，`MenuCommand::Proceed`で決定する．
+/// # Returns
+/// 選ばれたパズル．`MenuCommand::Back`が入力されるか，入力が終了して`None`が返された場合は`None`を返す．
+pub fn select_puzzle<M, D>(mut menu_input: M, drawer: &mut D) -> Option<&'static Puzzle>
+where
+    M: FnMut() -> Option<MenuCommand>,
+    D: Drawer,
+{
+    let names = PUZZLES
+        .iter()
+        .map(|puzzle| puzzle.name().to_string())
+        .collect::<Vec<_>>();
+    let mut selected = 0;
+    loop {
+        drawer.clear();
+        ItemList::new("Puzzles", names.clone(), selected).draw(drawer.canvas_mut());
+        drawer.show();
+
+        match menu_input()? {
+            MenuCommand::Up => selected = ItemList::previous_index(selected, PUZZLES.len()),
+            MenuCommand::Down => selected = ItemList::next_index(selected, PUZZLES.len()),
+            MenuCommand::Proceed => break Some(&PUZZLES[selected]),
+            MenuCommand::Back => break None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::HeadlessDrawer;
+    use GameCommand::*;
+
+    /// 指定したパズルを，1ティックずつ指定した操作列で実行する．
+    fn play(puzzle: &Puzzle, ticks: Vec<Vec<GameCommand>>) -> (PuzzleResult, Vec<GameEvent>) {
+        let mut ticks = ticks.into_iter();
+        let mut events = vec![];
+        let result = execute_puzzle_with(
+            puzzle.field(),
+            puzzle.selector(),
+            || ticks.next().unwrap_or_default(),
+            &mut HeadlessDrawer::new(),
+            |event| events.push(event),
+        );
+        (result, events)
+    }
+
+    #[test]
+    fn test_builtin_puzzles_are_valid() {
+        for puzzle in PUZZLES.iter() {
+            assert!(!puzzle.field().is_empty(), "{}", puzzle.name());
+            assert!(puzzle.block_count() > 0, "{}", puzzle.name());
+            assert_eq!(puzzle.block_count(), puzzle.selector().remaining());
+        }
+    }
+
+    #[test]
+    fn test_solve_puzzle() {
+        // 1つ目のOブロックを左に寄せ，2つ目のOブロックはそのまま落とす
+        let (result, events) = play(&PUZZLES[1], vec![vec![Left, Left, Drop], vec![Drop]]);
+
+        assert!(result.solved);
+        assert!(result.game.field.is_empty());
+        assert_eq!(Some(&GameEvent::FieldCleared), events.last());
+        assert_eq!(
+            2,
+            events
+                .iter()
+                .filter(|&event| event == &GameEvent::BlockPlaced)
+                .count()
+        );
+    }
+
+    #[test]
+    fn test_solve_with_rotation() {
+        let (result, _) = play(&PUZZLES[2], vec![vec![RotateClockwise, Left, Drop]]);
+        assert!(result.solved);
+    }
+
+    #[test]
+    fn test_blocks_exhausted() {
+        // 穴を埋めずに落とすと，ブロックを使い切って終わる
+        let (result, events) = play(&PUZZLES[0], vec![vec![Right, Right, Drop], vec![Drop]]);
+
+        assert!(!result.solved);
+        assert!(!result.game.field.is_empty());
+        assert!(!events.contains(&GameEvent::FieldCleared));
+        assert_eq!(1, events.len());
+    }
+
+    #[test]
+    fn test_select_puzzle() {
+        let mut inputs =
+            vec![MenuCommand::Down, MenuCommand::Down, MenuCommand::Proceed].into_iter();
+        let puzzle = select_puzzle(|| inputs.next(), &mut HeadlessDrawer::new());
+        assert_eq!(Some(&PUZZLES[2]), puzzle);
+
+        let mut inputs = vec![MenuCommand::Up, MenuCommand::Back].into_iter();
+        assert_eq!(
+            None,
+            select_puzzle(|| inputs.next(), &mut HeadlessDrawer::new())
+        );
+        assert_eq!(None, select_puzzle(|| None, &mut HeadlessDrawer::new()));
+    }
+
+    #[test]
+    fn test_draw_result() {
+        let (game, _) = play(&PUZZLES[0], vec![]);
+        let result = PuzzleResult {
+            solved: true,
+            ..game
+        };
+        let mut canvas = RootCanvas::new();
+        result.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        assert!(buffer.starts_with("Cleared!"));
+    }
+}
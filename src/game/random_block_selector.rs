@@ -0,0 +1,87 @@
+use super::{BlockSelector, BlockShape, BombTag, QuadrupleBlockShape};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// 7種類のテトリミノから無作為にブロックを選ぶブロック生成ルール．
+/// 各ブロックの空でないセルのうち，無作為に選んだひとつがボムセルとなる．
+///
+/// 同じシード値から作った生成ルールは，ボムセルの位置を含めて同じブロック列を生成する．
+#[derive(Debug, Clone)]
+pub struct RandomBlockSelector {
+    /// 乱数生成器の初期化に利用したシード値．
+    seed: u64,
+    rng: StdRng,
+}
+
+impl RandomBlockSelector {
+    /// 無作為なシード値で初期化した生成ルールを返す．
+    pub fn new() -> RandomBlockSelector {
+        Self::with_seed(rand::random())
+    }
+
+    /// 指定したシード値で初期化した生成ルールを返す．
+    pub fn with_seed(seed: u64) -> RandomBlockSelector {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl Default for RandomBlockSelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockSelector for RandomBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        let shapes = QuadrupleBlockShape::ALL;
+        shapes[self.rng.gen_range(0..shapes.len())].into()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        BombTag::Single(self.rng.gen_range(0..shape.non_empty_cell_count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Block;
+
+    fn generate_blocks(selector: &mut RandomBlockSelector, count: usize) -> Vec<Block> {
+        (0..count).map(|_| selector.generate_block()).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_blocks() {
+        let mut a = RandomBlockSelector::with_seed(42);
+        let mut b = RandomBlockSelector::with_seed(42);
+        assert_eq!(generate_blocks(&mut a, 100), generate_blocks(&mut b, 100));
+    }
+
+    #[test]
+    fn test_different_seed_different_blocks() {
+        let mut a = RandomBlockSelector::with_seed(1);
+        let mut b = RandomBlockSelector::with_seed(2);
+        assert_ne!(generate_blocks(&mut a, 100), generate_blocks(&mut b, 100));
+    }
+
+    #[test]
+    fn test_seed() {
+        assert_eq!(7, RandomBlockSelector::with_seed(7).seed());
+        let selector = RandomBlockSelector::new();
+        let mut copied = RandomBlockSelector::with_seed(selector.seed());
+        let mut selector = selector;
+        assert_eq!(
+            generate_blocks(&mut selector, 10),
+            generate_blocks(&mut copied, 10)
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use super::RandomBlockSelector;
+use crate::user::GameCommand;
+use std::collections::VecDeque;
+use std::io;
+use std::path::Path;
+
+/// ゲームの入力を包み，入力された操作をティックとともに記録する．
+///
+/// ティックは，通常のゲームでは入力を読んだ回数，自動落下のあるゲームではフレーム数を表す．
+/// 自動落下はフレーム数だけで決まるので，記録した操作とブロック生成ルールのシード値があればゲームを再現できる．
+pub struct CommandRecorder<I> {
+    input: I,
+    /// 現在のティック．
+    tick: u64,
+    /// 記録した操作と，それが入力されたティック．
+    commands: Vec<(u64, GameCommand)>,
+}
+
+impl<I> CommandRecorder<I> {
+    /// 指定した入力を包んだ記録器を返す．
+    pub fn new(input: I) -> CommandRecorder<I> {
+        Self {
+            input,
+            tick: 0,
+            commands: vec![],
+        }
+    }
+
+    /// これまでに記録した操作を，入力された順に返す．
+    pub fn commands(&self) -> &[(u64, GameCommand)] {
+        &self.commands
+    }
+
+    /// 記録した操作と，指定したシード値からなるリプレイを返す．
+    pub fn into_replay(self, seed: u64) -> Replay {
+        Replay::new(seed, self.commands)
+    }
+}
+
+impl<I> CommandRecorder<I>
+where
+    I: FnMut() -> Vec<GameCommand>,
+{
+    /// 包んだ入力から1ティックぶんの操作列を読み，記録してから返す．
+    /// `single_play::execute_game`の入力として利用できる．
+    pub fn next_commands(&mut self) -> Vec<GameCommand> {
+        let commands = (self.input)();
+        let tick = self.tick;
+        self.commands
+            .extend(commands.iter().map(|&command| (tick, command)));
+        self.tick += 1;
+        commands
+    }
+}
+
+impl<I> CommandRecorder<I>
+where
+    I: FnMut() -> Option<GameCommand>,
+{
+    /// 包んだ入力から操作をひとつ読み，記録してから返す．
+    /// `None`が返されたら，そのフレームの入力は終わったものとして次のティックに進む．
+    /// `single_play::execute_gravity_game`の入力として利用できる．
+    pub fn next_command(&mut self) -> Option<GameCommand> {
+        match (self.input)() {
+            Some(command) => {
+                self.commands.push((self.tick, command));
+                Some(command)
+            }
+            None => {
+                self.tick += 1;
+                None
+            }
+        }
+    }
+}
+
+/// 記録したゲームを再現するための情報を表す．
+///
+/// ファイルには，1行目にシード値を，2行目以降に操作をティックとともに1行ずつ書き出す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// ブロック生成ルールのシード値．
+    seed: u64,
+    /// 操作と，それが入力されたティック．
+    commands: Vec<(u64, GameCommand)>,
+}
+
+impl Replay {
+    pub fn new(seed: u64, commands: Vec<(u64, GameCommand)>) -> Replay {
+        Self { seed, commands }
+    }
+
+    /// ブロック生成ルールのシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 記録された操作を，入力された順に返す．
+    pub fn commands(&self) -> &[(u64, GameCommand)] {
+        &self.commands
+    }
+
+    /// 記録時と同じブロック列を生成するブロック生成ルールを返す．
+    pub fn selector(&self) -> RandomBlockSelector {
+        RandomBlockSelector::with_seed(self.seed)
+    }
+
+    /// 記録した操作を1ティックぶんずつ返す入力を返す．
+    /// `single_play::execute_game`の入力として利用でき，記録した操作を返し終えると空の操作列を返す．
+    pub fn input(&self) -> impl FnMut() -> Vec<GameCommand> {
+        let mut commands = self.commands.iter().copied().collect::<VecDeque<_>>();
+        let mut tick = 0;
+        move || {
+            let mut tick_commands = vec![];
+            while let Some(&(_, command)) = commands.front().filter(|&&(t, _)| t == tick) {
+                tick_commands.push(command);
+                commands.pop_front();
+            }
+            tick += 1;
+            tick_commands
+        }
+    }
+
+    /// 記録した操作を，記録時と同じフレームでひとつずつ返す入力を返す．
+    /// `single_play::execute_gravity_game`の入力として利用できる．
+    /// 記録時と同じ落下間隔でゲームを実行する必要がある．
+    pub fn gravity_input(&self) -> impl FnMut() -> Option<GameCommand> {
+        let mut commands = self.commands.iter().copied().collect::<VecDeque<_>>();
+        let mut tick = 0;
+        move || match commands.front() {
+            Some(&(t, command)) if t == tick => {
+                commands.pop_front();
+                Some(command)
+            }
+            _ => {
+                tick += 1;
+                None
+            }
+        }
+    }
+
+    /// ファイルに書き出す形式の文字列を返す．
+    pub fn to_text(&self) -> String {
+        let mut text = format!("seed {}\n", self.seed);
+        for (tick, command) in self.commands.iter() {
+            text += &format!("{} {:?}\n", tick, command);
+        }
+        text
+    }
+
+    /// `to_text`で書き出した形式の文字列からリプレイを読み込む．
+    /// # Returns
+    /// 形式が正しくない場合は`None`を返す．
+    pub fn from_text(text: &str) -> Option<Replay> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let seed = lines.next()?.strip_prefix("seed ")?.parse().ok()?;
+        let commands = lines
+            .map(|line| {
+                let mut words = line.split_whitespace();
+                let tick = words.next()?.parse().ok()?;
+                let command = parse_command(words.next()?)?;
+                match words.next() {
+                    Some(_) => None,
+                    None => Some((tick, command)),
+                }
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self::new(seed, commands))
+    }
+
+    /// 指定したファイルに書き出す．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    /// 指定したファイルから読み込む．
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Replay> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_text(&text)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid replay format"))
+    }
+}
+
+/// `{:?}`で書き出した操作の名前を操作に戻す．
+fn parse_command(name: &str) -> Option<GameCommand> {
+    GameCommand::ALL
+        .iter()
+        .copied()
+        .find(|command| format!("{:?}", command) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::field::assert_field_eq;
+    use super::super::field_under_agent_control::GameCommandResult;
+    use super::super::single_play::execute_game_with;
+    use super::super::{BlockQueue, Field, FieldUnderAgentControl};
+    use super::*;
+    use crate::game::test_util::HeadlessDrawer;
+    use GameCommand::*;
+
+    fn scripted_session() -> Vec<Vec<GameCommand>> {
+        vec![
+            vec![Left, Left, Left],
+            vec![Drop],
+            vec![RotateClockwise, Right, Right],
+            vec![Right, Drop, Left],
+            vec![Hold],
+            vec![RotateUnticlockwise, Down, Down],
+            vec![Drop],
+            vec![Left],
+        ]
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let seed = 10007;
+        let mut session = scripted_session().into_iter();
+        let mut recorder = CommandRecorder::new(|| session.next().unwrap_or_default());
+        let mut drawer = HeadlessDrawer::new();
+        let result = execute_game_with(
+            RandomBlockSelector::with_seed(seed),
+            || recorder.next_commands(),
+            &mut drawer,
+            |_| {},
+        );
+        let replay = recorder.into_replay(seed);
+        assert_eq!((3, Right), replay.commands()[7]);
+
+        // 記録した操作を，ブロックを直接操作して再現する
+        let mut selector = replay.selector();
+        let queue = BlockQueue::new(&mut selector);
+        let mut agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let mut input = replay.input();
+        loop {
+            let commands = input();
+            if commands.is_empty() {
+                break;
+            }
+            for command in commands {
+                match agent_field.apply_command(command) {
+                    GameCommandResult::WaitNextCommand(next) => agent_field = next,
+                    // 設置位置が確定したら，そのティックの残りの操作は破棄される
+                    GameCommandResult::ProceedAnimation(field, queue) => {
+                        agent_field =
+                            FieldUnderAgentControl::new(field, queue, &mut selector).unwrap();
+                        break;
+                    }
+                    GameCommandResult::ToppedOut(_) => panic!("replay should not top out"),
+                    GameCommandResult::Quit(_) => panic!("replay should not contain quit"),
+                }
+            }
+        }
+
+        assert_ne!(Field::empty(), result.field);
+        assert_field_eq(&result.field, agent_field.field());
+    }
+
+    #[test]
+    fn test_gravity_recording() {
+        let mut inputs = vec![Some(Left), None, None, Some(Down), Some(Drop), None]
+            .into_iter()
+            .collect::<VecDeque<_>>();
+        let expected = inputs.iter().copied().collect::<Vec<_>>();
+        let mut recorder = CommandRecorder::new(|| inputs.pop_front().flatten());
+        let recorded = (0..6).map(|_| recorder.next_command()).collect::<Vec<_>>();
+        assert_eq!(expected, recorded);
+
+        // Noneごとにフレームが進む
+        let replay = recorder.into_replay(0);
+        assert_eq!(&[(0, Left), (2, Down), (2, Drop)], replay.commands());
+
+        let mut input = replay.gravity_input();
+        let replayed = (0..6).map(|_| input()).collect::<Vec<_>>();
+        assert_eq!(expected, replayed);
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let replay = Replay::new(42, vec![(0, Left), (0, Drop), (3, RotateUnticlockwise)]);
+        let text = replay.to_text();
+        assert_eq!("seed 42\n0 Left\n0 Drop\n3 RotateUnticlockwise\n", text);
+        assert_eq!(Some(replay), Replay::from_text(&text));
+
+        assert_eq!(None, Replay::from_text(""));
+        assert_eq!(None, Replay::from_text("seed 42\n0 Jump\n"));
+        assert_eq!(None, Replay::from_text("seed 42\nLeft\n"));
+    }
+}
@@ -0,0 +1,172 @@
+use super::{Field, MiniField};
+use crate::geometry::*;
+use crate::graphics::*;
+
+/// 対戦のひとつのラウンドの終了時の様子を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundSummary {
+    /// ラウンド終了時のフィールドの縮小表示．
+    field: MiniField,
+    /// このラウンドに勝利したか．
+    won: bool,
+    /// このラウンドで設置したブロックの数．
+    placed_blocks: u32,
+    /// このラウンドで揃えたラインの数．
+    filled_rows: u32,
+}
+
+impl RoundSummary {
+    pub fn new(field: &Field, won: bool, placed_blocks: u32, filled_rows: u32) -> RoundSummary {
+        Self {
+            field: MiniField::new(field),
+            won,
+            placed_blocks,
+            filled_rows,
+        }
+    }
+
+    /// このラウンドに勝利したか返す．
+    pub fn won(&self) -> bool {
+        self.won
+    }
+}
+
+/// 過去のラウンドの終了時の様子を横に並べて表示する描画物を表す．
+/// 保持するラウンド数には上限があり，上限を超えると古いラウンドから捨てられる．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundGallery {
+    /// 保持するラウンドの最大数．
+    capacity: usize,
+    /// 最初のラウンドから数えた，`summaries`の先頭のラウンドの番号(0始まり)．
+    first_round: usize,
+    /// 各ラウンドの様子．古いものから順に並ぶ．
+    summaries: Vec<RoundSummary>,
+}
+
+impl RoundGallery {
+    /// # Panics
+    /// `capacity`に0を指定した場合．
+    pub fn new(capacity: usize) -> RoundGallery {
+        assert!(capacity > 0);
+        Self {
+            capacity,
+            first_round: 0,
+            summaries: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// 終了したラウンドを追加する．
+    pub fn push(&mut self, summary: RoundSummary) {
+        if self.summaries.len() == self.capacity {
+            self.summaries.remove(0);
+            self.first_round += 1;
+        }
+        self.summaries.push(summary);
+    }
+
+    /// 保持しているラウンドを古いものから順に返す．
+    pub fn summaries(&self) -> &[RoundSummary] {
+        &self.summaries
+    }
+
+    /// ひとつのラウンドを表示する領域の大きさを返す．
+    fn thumbnail_size(&self) -> Movement {
+        let field_size = self
+            .summaries
+            .first()
+            .map(|summary| summary.field.region_size())
+            .unwrap_or_else(|| MiniField::new(&Field::empty()).region_size());
+        // ラウンド番号，設置数，ライン数の3行を加える
+        field_size + below(3)
+    }
+}
+
+impl Drawable for RoundGallery {
+    fn region_size(&self) -> Movement {
+        let thumbnail_size = self.thumbnail_size();
+        let count = self.summaries.len() as i8;
+        // ラウンド同士の間には1セル空ける
+        let width = (thumbnail_size.x().as_positive_index().unwrap_or(0) as i8 + 1) * count - 1;
+        right(std::cmp::max(width, 0)) + thumbnail_size.y()
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let thumbnail_width = self.thumbnail_size().x().as_positive_index().unwrap_or(0) as i8;
+        let text_color = canvas.theme().cell_color(ThemeColor::Text);
+
+        for (i, summary) in self.summaries.iter().enumerate() {
+            let left_top = Pos::origin() + right((thumbnail_width + 1) * i as i8);
+
+            // 勝ったラウンドは緑，負けたラウンドは赤で番号を表示する
+            let label_color = if summary.won {
+                canvas.theme().inverted_color(ThemeColor::Success)
+            } else {
+                canvas.theme().inverted_color(ThemeColor::Warning)
+            };
+            let label = format!("R{}", self.first_round + i + 1);
+            ColoredStr(label, label_color).draw_on_child(left_top, canvas);
+
+            let field_pos = left_top + below(1);
+            summary.field.draw_on_child(field_pos, canvas);
+
+            let stats_pos = field_pos + summary.field.region_size().y();
+            let placed = format!("B{:>4}", summary.placed_blocks);
+            ColoredStr(placed, text_color).draw_on_child(stats_pos, canvas);
+            let filled = format!("L{:>4}", summary.filled_rows);
+            ColoredStr(filled, text_color).draw_on_child(stats_pos + below(1), canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Cell;
+
+    fn field_with_bottom_row() -> Field {
+        let mut field = Field::empty();
+        let bottom = PosY::below(19);
+        for (_, cell) in field
+            .iter_pos_and_cell_mut()
+            .filter(|(p, _)| p.y() == bottom)
+        {
+            *cell = Cell::Normal;
+        }
+        field
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut gallery = RoundGallery::new(2);
+        for placed_blocks in 0..3 {
+            gallery.push(RoundSummary::new(&Field::empty(), false, placed_blocks, 0));
+        }
+        assert_eq!(2, gallery.summaries().len());
+        assert_eq!(1, gallery.summaries()[0].placed_blocks);
+        assert_eq!(1, gallery.first_round);
+    }
+
+    #[test]
+    fn test_draw_two_rounds() {
+        let mut gallery = RoundGallery::new(3);
+        gallery.push(RoundSummary::new(&field_with_bottom_row(), true, 12, 3));
+        gallery.push(RoundSummary::new(&Field::empty(), false, 120, 45));
+        assert_eq!(right(11) + below(13), gallery.region_size());
+
+        let mut canvas = RootCanvas::new();
+        gallery.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let lines = buffer
+            .lines()
+            .take(13)
+            .map(|line| line[..22].to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!("R1          R2        ", lines[0]);
+        assert_eq!("                      ", lines[1]);
+        assert_eq!("::::::::::            ", lines[10]);
+        assert_eq!("B  12       B 120     ", lines[11]);
+        assert_eq!("L   3       L  45     ", lines[12]);
+    }
+}
@@ -0,0 +1,140 @@
+use super::animation::ChainCounter;
+use crate::geometry::*;
+use crate::graphics::*;
+
+mod consts {
+    /// 揃ったライン1本あたりの得点．
+    pub const ROW_FILLED_POINTS: u64 = 100;
+    /// 爆発で消えたセル1つあたりの得点．連鎖数に応じて倍率がかかる．
+    pub const EXPLODED_CELL_POINTS: u64 = 10;
+    /// ハードドロップで落とした1マスあたりの得点．
+    pub const HARD_DROP_POINTS: u64 = 2;
+}
+
+use consts::*;
+
+/// 一人プレイの得点を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    points: u64,
+}
+
+impl Score {
+    pub const fn new() -> Score {
+        Self { points: 0 }
+    }
+
+    /// 指定した得点から始める得点を返す．
+    pub const fn with_points(points: u64) -> Score {
+        Self { points }
+    }
+
+    /// 現在の得点を返す．
+    pub const fn points(&self) -> u64 {
+        self.points
+    }
+
+    /// 指定した本数のラインが揃ったことを得点に反映する．
+    pub fn record_rows_filled(&mut self, rows: usize) {
+        self.points += ROW_FILLED_POINTS * rows as u64;
+    }
+
+    /// 爆発を得点に反映する．
+    /// 消えたセル数に，連鎖数に応じた倍率(最初の爆発は1倍，以降1ずつ増える)をかけて加算する．
+    pub fn record_explosion(&mut self, chain: &ChainCounter, cells: usize) {
+        let multiplier = chain.current_chain() as u64 + 1;
+        self.points += EXPLODED_CELL_POINTS * cells as u64 * multiplier;
+    }
+
+    /// 指定したマス数だけハードドロップしたことを得点に反映する．
+    pub fn record_hard_drop(&mut self, distance: usize) {
+        self.points += HARD_DROP_POINTS * distance as u64;
+    }
+}
+
+/// 見出しと得点を2行で描画する．
+impl Drawable for Score {
+    fn region_size(&self) -> Movement {
+        right(5) + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        ColoredStr("Score", color).draw_on_child(Pos::origin(), canvas);
+        let points = format!("{:>10}", self.points);
+        ColoredStr(points, color).draw_on_child(Pos::origin() + below(1), canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::field_under_agent_control::GameCommandResult;
+    use super::super::FieldUnderAgentControl;
+    use super::super::QuadrupleBlockShape::*;
+    use super::super::{BlockQueue, BombTag, Field};
+    use super::*;
+    use crate::game::test_util::repeating_selector;
+    use crate::user::GameCommand;
+
+    #[test]
+    fn test_record() {
+        let mut score = Score::new();
+        score.record_rows_filled(2);
+        assert_eq!(200, score.points());
+
+        // 最初の爆発は1倍
+        let chain = ChainCounter::new();
+        score.record_explosion(&chain, 5);
+        assert_eq!(250, score.points());
+        // 1連鎖目は2倍
+        score.record_explosion(&chain.next(), 5);
+        assert_eq!(350, score.points());
+
+        score.record_hard_drop(3);
+        assert_eq!(356, score.points());
+    }
+
+    #[test]
+    fn test_scripted_commands() {
+        let mut selector = repeating_selector(O, BombTag::None);
+        let queue = BlockQueue::new(&mut selector);
+        let mut agent_field =
+            FieldUnderAgentControl::new(Field::empty(), queue, &mut selector).unwrap();
+        let mut score = Score::new();
+
+        // 2マス下げてからハードドロップすると，残りの距離だけが得点になる
+        let commands = [GameCommand::Down, GameCommand::Down, GameCommand::Drop];
+        let mut placed = None;
+        for &command in commands.iter() {
+            if command == GameCommand::Drop {
+                score.record_hard_drop(agent_field.drop_distance());
+            }
+            match agent_field.apply_command(command) {
+                GameCommandResult::WaitNextCommand(next) => agent_field = next,
+                GameCommandResult::ProceedAnimation(field, _) => {
+                    placed = Some(field);
+                    break;
+                }
+                GameCommandResult::ToppedOut(_) => panic!("game should not top out"),
+                GameCommandResult::Quit(_) => panic!("game should not be quit"),
+            }
+        }
+
+        assert!(placed.is_some());
+        assert_eq!(2 * 16, score.points());
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut score = Score::new();
+        score.record_rows_filled(1);
+
+        let mut canvas = RootCanvas::new();
+        score.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let mut lines = buffer.lines();
+        assert!(lines.next().unwrap().starts_with("Score "));
+        assert!(lines.next().unwrap().starts_with("       100"));
+    }
+}
@@ -0,0 +1,145 @@
+use super::{BlockSelector, BlockShape, BombTag, SingleBlockShape};
+
+/// 指定したブロック列を使い切った後の振る舞いを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptEnd {
+    /// 列の先頭に戻り，同じ順にブロックを生成し続ける．
+    Repeat,
+    /// 使い切ったことを`ScriptedBlockSelector::is_exhausted`で知らせ，以降はボムを含まない1セルのブロックを生成し続ける．
+    /// ゲーム側で使い切ったことを確かめ，ゲームを終える．
+    Stop,
+}
+
+/// 指定した順にブロックを生成するブロック生成ルール．
+/// パズルや練習のように出現するブロックを決めておきたい場合や，テストで利用する．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptedBlockSelector {
+    /// 生成するブロックの形状とボム指定．
+    blocks: Vec<(BlockShape, BombTag)>,
+    /// 使い切った後の振る舞い．
+    end: ScriptEnd,
+    /// 次に生成するブロックのインデックス．
+    index: usize,
+}
+
+impl ScriptedBlockSelector {
+    /// 生成する順にブロックを並べた生成ルールを返す．
+    /// # Returns
+    /// ボムセルのラベルが形状の空でないセルの数以上の場合や，列が空なのに繰り返す場合は`None`を返す．
+    pub fn new(
+        blocks: Vec<(BlockShape, BombTag)>,
+        end: ScriptEnd,
+    ) -> Option<ScriptedBlockSelector> {
+        if blocks
+            .iter()
+            .any(|&(shape, bomb)| !bomb.is_valid_for(shape))
+        {
+            return None;
+        }
+        if blocks.is_empty() && end == ScriptEnd::Repeat {
+            return None;
+        }
+        Some(Self {
+            blocks,
+            end,
+            index: 0,
+        })
+    }
+
+    /// 列の末尾までに，まだ生成していないブロックの数を返す．
+    /// 繰り返す場合は，列の先頭に戻るまでに生成するブロックの数となる．
+    pub fn remaining(&self) -> usize {
+        self.blocks.len().saturating_sub(self.index)
+    }
+
+    /// 繰り返さない場合に，指定したブロックをすべて生成し終えていれば`true`を返す．
+    /// 繰り返す場合は常に`false`を返す．
+    pub fn is_exhausted(&self) -> bool {
+        self.end == ScriptEnd::Stop && self.remaining() == 0
+    }
+}
+
+impl BlockSelector for ScriptedBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        match self.blocks.get(self.index) {
+            Some(&(shape, _)) => shape,
+            None => SingleBlockShape::O.into(),
+        }
+    }
+
+    fn select_bomb(&mut self, _: BlockShape) -> BombTag {
+        let bomb = match self.blocks.get(self.index) {
+            Some(&(_, bomb)) => bomb,
+            None => BombTag::None,
+        };
+        self.index = match self.end {
+            ScriptEnd::Repeat => (self.index + 1) % self.blocks.len(),
+            ScriptEnd::Stop => std::cmp::min(self.index + 1, self.blocks.len()),
+        };
+        bomb
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::BombLabels;
+    use super::super::QuadrupleBlockShape::*;
+    use super::*;
+
+    fn shapes(selector: &mut ScriptedBlockSelector, count: usize) -> Vec<(BlockShape, BombTag)> {
+        (0..count)
+            .map(|_| {
+                let block = selector.generate_block();
+                (block.shape(), block.bomb_tag())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stop() {
+        let script = vec![(O.into(), BombTag::All), (T.into(), BombTag::Single(3))];
+        let mut selector = ScriptedBlockSelector::new(script.clone(), ScriptEnd::Stop).unwrap();
+        assert_eq!(2, selector.remaining());
+        assert!(!selector.is_exhausted());
+
+        assert_eq!(script, shapes(&mut selector, 2));
+        assert!(selector.is_exhausted());
+
+        // 使い切った後も，ボムを含まない1セルのブロックを生成できる
+        let filler = (SingleBlockShape::O.into(), BombTag::None);
+        assert_eq!(vec![filler, filler], shapes(&mut selector, 2));
+        assert_eq!(0, selector.remaining());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let two_bombs = BombTag::Many(BombLabels::new(&[1, 3]).unwrap());
+        let script = vec![
+            (I.into(), two_bombs),
+            (S.into(), BombTag::Single(0)),
+            (L.into(), BombTag::None),
+        ];
+        let mut selector = ScriptedBlockSelector::new(script.clone(), ScriptEnd::Repeat).unwrap();
+
+        let generated = shapes(&mut selector, 7);
+        assert_eq!(script[..], generated[0..3]);
+        assert_eq!(script[..], generated[3..6]);
+        assert_eq!(script[0], generated[6]);
+        assert_eq!(2, selector.remaining());
+        assert!(!selector.is_exhausted());
+    }
+
+    #[test]
+    fn test_invalid_script() {
+        // Tミノの空でないセルは4つ
+        let script = vec![(O.into(), BombTag::None), (T.into(), BombTag::Single(4))];
+        assert_eq!(None, ScriptedBlockSelector::new(script, ScriptEnd::Stop));
+        let labels = BombLabels::new(&[0, 4]).unwrap();
+        let script = vec![(T.into(), BombTag::Many(labels))];
+        assert_eq!(None, ScriptedBlockSelector::new(script, ScriptEnd::Stop));
+        assert_eq!(None, ScriptedBlockSelector::new(vec![], ScriptEnd::Repeat));
+        assert!(ScriptedBlockSelector::new(vec![], ScriptEnd::Stop)
+            .unwrap()
+            .is_exhausted());
+    }
+}
@@ -0,0 +1,400 @@
+use super::single_play::GameState;
+use super::{
+    Block, BlockQueue, BlockSelector, BlockShape, BombLabels, BombTag, Direction, Field, Level,
+    Score,
+};
+use std::io;
+use std::path::Path;
+
+mod consts {
+    /// ファイルの先頭行に書き出す，ファイルの種類を表す文字列．
+    pub const HEADER: &str = "rustetris-session";
+    /// ファイルの形式の版．形式を変えたら増やす．
+    pub const VERSION: u32 = 2;
+}
+
+use consts::*;
+
+/// 生成したブロックの数を数えるブロック生成ルール．
+/// 生成は包んだ生成ルールに任せる．
+#[derive(Debug, Clone)]
+pub struct CountingSelector<S> {
+    selector: S,
+    /// これまでに生成したブロックの数．
+    generated_blocks: usize,
+}
+
+impl<S> CountingSelector<S> {
+    pub fn new(selector: S) -> CountingSelector<S> {
+        Self {
+            selector,
+            generated_blocks: 0,
+        }
+    }
+
+    /// これまでに生成したブロックの数を返す．
+    pub const fn generated_blocks(&self) -> usize {
+        self.generated_blocks
+    }
+
+    /// 包んでいる生成ルールを返す．
+    pub fn selector(&self) -> &S {
+        &self.selector
+    }
+}
+
+impl<S: BlockSelector> BlockSelector for CountingSelector<S> {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.generated_blocks += 1;
+        self.selector.select_block_shape()
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        self.selector.select_bomb(shape)
+    }
+}
+
+/// 中断したゲームを再開するための情報を表す．
+///
+/// ブロック生成ルールの状態は，初期化に利用したシード値と，そこから生成したブロックの数で表す．
+/// ゲームのモードやブロック生成ルールの種類は，中断したゲームを始めたときの設定を表す文字列として記録する．
+/// 再開する側は，同じ設定で始めたゲームでなければ再開してはならない．
+/// ファイルには，先頭行に形式の版を書き出す．版が異なるファイルは読み込まない．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// 中断した時点の状態．
+    state: GameState,
+    /// ブロック生成ルールの初期化に利用したシード値．
+    seed: u64,
+    /// 再開するときに，ブロック生成ルールで読み飛ばすブロックの数．
+    skipped_blocks: usize,
+    /// 中断したゲームを始めたときの，モードやブロック生成ルールの設定．
+    mode: String,
+}
+
+impl Session {
+    /// 中断したゲームを再開するための情報を返す．
+    /// # Params
+    /// 1. `state` 中断した時点の状態．`GameResult::suspended`で得られる．
+    /// 1. `seed` ブロック生成ルールの初期化に利用したシード値．
+    /// 1. `generated_blocks` ゲーム開始から中断までに，ブロック生成ルールが生成したブロックの数．
+    /// 1. `mode` 中断したゲームを始めたときの，モードやブロック生成ルールの設定を表す文字列．
+    /// # Returns
+    /// `mode`が改行を含む場合は`None`を返す．
+    pub fn new(
+        state: GameState,
+        seed: u64,
+        generated_blocks: usize,
+        mode: String,
+    ) -> Option<Session> {
+        if mode.contains(['\n', '\r']) {
+            return None;
+        }
+        // 中断したときに操作中のブロックをキューへ戻し，キュー末尾のブロックを捨てている．
+        // 再開後にそのブロックを生成し直すため，ひとつ少なく読み飛ばす
+        Some(Self {
+            state,
+            seed,
+            skipped_blocks: generated_blocks.saturating_sub(1),
+            mode,
+        })
+    }
+
+    /// 中断した時点の状態を返す．
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// 中断した時点の状態を取り出す．
+    pub fn into_state(self) -> GameState {
+        self.state
+    }
+
+    /// ブロック生成ルールの初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 中断したゲームを始めたときの，モードやブロック生成ルールの設定を返す．
+    pub fn mode(&self) -> &str {
+        &self.mode
+    }
+
+    /// 指定したブロック生成ルールを，中断した時点と同じ状態まで進め，生成したブロックを数える生成ルールとして返す．
+    /// ブロック生成ルールは，`seed`で初期化した直後のものでなければならない．
+    /// 返す生成ルールは，読み飛ばしたブロックも数に含める．
+    pub fn restore_selector<S: BlockSelector>(&self, selector: S) -> CountingSelector<S> {
+        let mut selector = CountingSelector::new(selector);
+        for _ in 0..self.skipped_blocks {
+            selector.generate_block();
+        }
+        selector
+    }
+
+    /// ファイルに書き出す形式の文字列を返す．
+    pub fn to_text(&self) -> String {
+        let state = &self.state;
+        let mut text = format!("{} {}\n", HEADER, VERSION);
+        text += &format!("mode {}\n", self.mode);
+        text += &format!("seed {}\n", self.seed);
+        text += &format!("skip {}\n", self.skipped_blocks);
+        text += &format!("score {}\n", state.score.points());
+        text += &format!("rows {}\n", state.level.rows());
+        for &block in state.block_queue.next_blocks() {
+            text += &format!("next {}\n", block_to_text(block));
+        }
+        text += &format!("hold {}\n", block_to_text(state.block_queue.hold_block()));
+        text += "field\n";
+        text += &state.field.to_str_art();
+        text
+    }
+
+    /// `to_text`で書き出した形式の文字列から読み込む．
+    /// # Returns
+    /// 形式が正しくないか，形式の版が異なる場合は`None`を返す．
+    pub fn from_text(text: &str) -> Option<Session> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let version = lines
+            .next()?
+            .strip_prefix(HEADER)?
+            .trim()
+            .parse::<u32>()
+            .ok()?;
+        if version != VERSION {
+            return None;
+        }
+        let mode = lines.next()?.strip_prefix("mode")?.trim().to_string();
+        let seed = lines.next()?.strip_prefix("seed ")?.parse().ok()?;
+        let skipped_blocks = lines.next()?.strip_prefix("skip ")?.parse().ok()?;
+        let points = lines.next()?.strip_prefix("score ")?.parse().ok()?;
+        let rows = lines.next()?.strip_prefix("rows ")?.parse().ok()?;
+
+        let mut next_blocks = vec![];
+        let hold_block = loop {
+            let line = lines.next()?;
+            if let Some(block) = line.strip_prefix("next ") {
+                next_blocks.push(parse_block(block)?);
+            } else {
+                break parse_block(line.strip_prefix("hold ")?)?;
+            }
+        };
+        let block_queue = BlockQueue::with_blocks(&next_blocks, hold_block)?;
+
+        if lines.next()? != "field" {
+            return None;
+        }
+        let art = lines.collect::<Vec<_>>();
+        if art.len() != Field::empty().height() {
+            return None;
+        }
+        let field = Field::from_str_art(&art.join("\n"))?;
+
+        let state = GameState {
+            field,
+            block_queue,
+            score: Score::with_points(points),
+            level: Level::with_rows(rows),
+        };
+        Some(Self {
+            state,
+            seed,
+            skipped_blocks,
+            mode,
+        })
+    }
+
+    /// 指定したファイルに書き出す．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    /// 指定したファイルから読み込む．
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Session> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_text(&text)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid session format"))
+    }
+}
+
+/// ブロックを，形状，向き，ボムセルの位置を空白で区切った文字列に変換する．
+/// 複数のボムセルのラベルは，`Many(0,2)`のように空白を含めずに書き出す．
+fn block_to_text(block: Block) -> String {
+    let bomb_tag = match block.bomb_tag() {
+        BombTag::Many(labels) => {
+            let labels = labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+            format!("Many({})", labels.join(","))
+        }
+        bomb_tag => format!("{:?}", bomb_tag),
+    };
+    format!("{:?} {:?} {}", block.shape(), block.direction(), bomb_tag)
+}
+
+/// `block_to_text`で書き出した文字列をブロックに戻す．
+fn parse_block(text: &str) -> Option<Block> {
+    let mut words = text.split_whitespace();
+    let shape = words.next()?;
+    let shape = BlockShape::all().find(|s| format!("{:?}", s) == shape)?;
+    let direction = words.next()?;
+    let direction = Direction::ALL
+        .iter()
+        .copied()
+        .find(|d| format!("{:?}", d) == direction)?;
+    let bomb_tag = match words.next()? {
+        "None" => BombTag::None,
+        "All" => BombTag::All,
+        bomb_tag if bomb_tag.starts_with("Many(") => {
+            let labels = bomb_tag.strip_prefix("Many(")?.strip_suffix(')')?;
+            let labels = labels
+                .split(',')
+                .filter(|label| !label.is_empty())
+                .map(|label| label.parse().ok())
+                .collect::<Option<Vec<_>>>()?;
+            BombTag::Many(BombLabels::new(&labels)?)
+        }
+        bomb_tag => {
+            let label = bomb_tag
+                .strip_prefix("Single(")?
+                .strip_suffix(')')?
+                .parse()
+                .ok()?;
+            BombTag::Single(label)
+        }
+    };
+    if !bomb_tag.is_valid_for(shape) || words.next().is_some() {
+        return None;
+    }
+    Some(Block::new(shape, direction, bomb_tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::single_play::{execute_game_from_state, execute_game_with, LineClearRule};
+    use super::super::RandomBlockSelector;
+    use super::*;
+    use crate::game::test_util::HeadlessDrawer;
+    use crate::user::GameCommand;
+
+    const SEED: u64 = 7;
+    const MODE: &str = "endless --gravity";
+
+    /// ブロックを左右に振り分けて何度か落とし，回転させたところで一時停止してやめたゲームの中断情報を返す．
+    fn suspended_session() -> Session {
+        use GameCommand::*;
+
+        let mut inputs = vec![
+            vec![Left, Left, Left, Drop],
+            vec![Right, Right, Right, Drop],
+            vec![Hold],
+            vec![Drop],
+            vec![RotateClockwise, Left, Drop],
+            vec![RotateClockwise],
+            vec![Quit],
+            vec![Quit],
+        ]
+        .into_iter();
+        let mut selector = CountingSelector::new(RandomBlockSelector::with_seed(SEED));
+        let result = execute_game_with(
+            &mut selector,
+            || inputs.next().unwrap_or_default(),
+            &mut HeadlessDrawer::new(),
+            |_| {},
+        );
+        let state = result.suspended.expect("quit game should be suspended");
+        Session::new(state, SEED, selector.generated_blocks(), MODE.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let session = suspended_session();
+        assert!(!session.state().field.is_empty());
+        assert!(session.state().score.points() > 0);
+
+        let text = session.to_text();
+        assert!(text.starts_with("rustetris-session 2\nmode endless --gravity\n"));
+        assert_eq!(MODE, session.mode());
+        assert_eq!(Some(session), Session::from_text(&text));
+    }
+
+    #[test]
+    fn test_mode_with_newline_rejected() {
+        let state = suspended_session().into_state();
+        let mode = "endless\nseed 0".to_string();
+        assert_eq!(None, Session::new(state, SEED, 1, mode));
+    }
+
+    #[test]
+    fn test_restore_selector() {
+        let session = suspended_session();
+
+        // 中断しなかった場合に続けて生成されるブロックと，再開後に生成されるブロックが一致する
+        let mut original = CountingSelector::new(RandomBlockSelector::with_seed(SEED));
+        for _ in 0..session.skipped_blocks {
+            original.generate_block();
+        }
+        let mut restored = session.restore_selector(RandomBlockSelector::with_seed(SEED));
+        assert_eq!(original.generated_blocks(), restored.generated_blocks());
+        for _ in 0..10 {
+            assert_eq!(original.generate_block(), restored.generate_block());
+        }
+    }
+
+    #[test]
+    fn test_resume() {
+        let session = suspended_session();
+        let state = session.state().clone();
+        let selector = session.restore_selector(RandomBlockSelector::with_seed(SEED));
+
+        // 再開してすぐにやめると，中断した時点と同じ状態になる
+        let mut inputs = vec![vec![GameCommand::Quit, GameCommand::Quit]].into_iter();
+        let result = execute_game_from_state(
+            state.clone(),
+            selector,
+            LineClearRule::Explosion,
+            || inputs.next().unwrap_or_default(),
+            &mut HeadlessDrawer::new(),
+            |_| {},
+        );
+        assert_eq!(state.score, result.score);
+        assert_eq!(state.level, result.level);
+        assert_eq!(Some(state), result.suspended);
+    }
+
+    #[test]
+    fn test_many_bombs_round_trip() {
+        let labels = BombLabels::new(&[0, 2]).unwrap();
+        let shape = crate::game::QuadrupleBlockShape::T.into();
+        let block = Block::new(shape, Direction::Right, BombTag::Many(labels));
+        let text = block_to_text(block);
+        assert!(text.ends_with(" Right Many(0,2)"));
+        assert_eq!(Some(block), parse_block(&text));
+    }
+
+    #[test]
+    fn test_reject_invalid_text() {
+        let text = suspended_session().to_text();
+
+        // 版が異なる
+        let other_version = text.replacen("rustetris-session 2", "rustetris-session 1", 1);
+        assert_eq!(None, Session::from_text(&other_version));
+        // 別の種類のファイル
+        assert_eq!(None, Session::from_text("seed 1\n0 Left\n"));
+        assert_eq!(None, Session::from_text(""));
+        // 設定の行がない
+        let without_mode = text.replacen("mode endless --gravity\n", "", 1);
+        assert_eq!(None, Session::from_text(&without_mode));
+        // 途中で切れている
+        let truncated = text.lines().take(12).collect::<Vec<_>>().join("\n");
+        assert_eq!(None, Session::from_text(&truncated));
+        // 存在しない形状やボムセルの位置
+        let unknown_shape = text.replacen("next ", "next Hexa(X) ", 1);
+        assert_eq!(None, Session::from_text(&unknown_shape));
+        assert_eq!(None, parse_block("Quadruple(T) Above Single(4)"));
+        assert!(parse_block("Quadruple(T) Above Single(3)").is_some());
+        assert_eq!(None, parse_block("Quadruple(T) Above Many(1,1)"));
+        assert_eq!(None, parse_block("Quadruple(T) Above Many(0,4)"));
+        // フィールドに不正なセルがある
+        let broken_field = format!("{}?", text.trim_end());
+        assert_eq!(None, Session::from_text(&broken_field));
+    }
+}
@@ -0,0 +1,315 @@
+//! 描画やフレームの待機を伴わずに，ブロック設置後の処理を計算する．
+//!
+//! 処理の内容は，一人プレイのゲームループがアニメーションとともに行うものと同じである．
+
+use super::animation::{
+    connect_big_bombs, drop_floating_cells, scan_filled_rows, scan_new_big_bombs, ChainCounter,
+    ExplosionState, NewBigBomb,
+};
+use super::{Block, Field};
+use crate::geometry::*;
+
+/// ブロックの設置位置が確定した後の，一連の処理の結果を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolution {
+    /// 処理を終えたフィールド．
+    pub field: Field,
+    /// 揃ったラインを検出するたびに数えた，ライン数の合計．
+    pub rows_filled: usize,
+    /// 起きた爆発の回数．連鎖した場合は連鎖のたびに数える．
+    pub explosions: usize,
+    /// 爆発で消えた，空でないセルの数．
+    pub exploded_cells: usize,
+    /// 新しくできたデカボムの数．
+    pub big_bombs_formed: usize,
+    /// 新しくできた特大ボムの数．
+    pub huge_bombs_formed: usize,
+    /// 爆発せずに揃ったまま残ったラインのy座標．
+    /// 次にブロックを設置したときの`resolve`に渡す．
+    pub filled_row_ys: Vec<PosY>,
+}
+
+/// 指定した位置にブロックを置いたフィールドを返す．
+/// `left_top`はブロックのセルテーブルのうち，最も左上のセルのフィールドにおける座標．
+/// # Returns
+/// フィールドの外にはみ出すか，他のセルと重なる場合は`None`を返す．
+pub fn place_block(field: &Field, block: &Block, left_top: Pos) -> Option<Field> {
+    let mut field = field.clone();
+    field.place_block(block, left_top).ok()?;
+    Some(field)
+}
+
+/// 指定した位置からブロックを真下に落とした位置を返す．
+/// # Returns
+/// 指定した位置にブロックを置けない場合は`None`を返す．
+pub fn drop_position(field: &Field, block: &Block, left_top: Pos) -> Option<Pos> {
+    if !field.can_place(block, left_top) {
+        return None;
+    }
+
+    let mut pos = left_top;
+    while field.can_place(block, pos + below(1)) {
+        pos = pos + below(1);
+    }
+    Some(pos)
+}
+
+/// ブロックの設置位置が確定したフィールドについて，
+/// 揃ったラインの検出，爆発の連鎖，セルの落下，デカボムと特大ボムの生成を順に行う．
+///
+/// `previous_filled_rows`には，前回の`resolve`で揃ったまま残ったラインを渡す．
+pub fn resolve(mut field: Field, previous_filled_rows: &[PosY]) -> Resolution {
+    let mut filled_row_ys = previous_filled_rows.to_vec();
+    let mut chain = ChainCounter::new();
+    let mut rows_filled = 0;
+    let mut explosions = 0;
+    let mut exploded_cells = 0;
+
+    loop {
+        let mut ys = scan_filled_rows(&field, &filled_row_ys);
+        rows_filled += ys.len();
+        match ExplosionState::try_new(&field, &ys, chain.clone()) {
+            Some(mut state) => {
+                while state.advance(&mut field) {}
+                let (next_chain, report) = state.finish();
+                explosions += 1;
+                exploded_cells += report.steps().iter().map(|step| step.len()).sum::<usize>();
+                drop_floating_cells(&mut field);
+                // 次の連鎖が起こりうるので，揃ったラインを検出し直す
+                filled_row_ys = vec![];
+                chain = next_chain;
+            }
+            None => {
+                filled_row_ys.append(&mut ys);
+                filled_row_ys.sort();
+                filled_row_ys.dedup();
+                break;
+            }
+        }
+    }
+
+    let big_bombs = scan_new_big_bombs(&field);
+    connect_big_bombs(&mut field, &big_bombs);
+
+    Resolution {
+        field,
+        rows_filled,
+        explosions,
+        exploded_cells,
+        big_bombs_formed: big_bombs
+            .iter()
+            .filter(|bomb| matches!(bomb, NewBigBomb::Big(_)))
+            .count(),
+        huge_bombs_formed: big_bombs
+            .iter()
+            .filter(|bomb| matches!(bomb, NewBigBomb::Huge(_)))
+            .count(),
+        filled_row_ys,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::animation::{
+        Animation, AnimationField, ConnectBomb, ConnectBombInitResult, DropCell, Explosion,
+        ExplosionInitResult, FullRow,
+    };
+    use super::super::{BlockQueue, BombTag, QuadrupleBlockShape};
+    use super::*;
+    use crate::game::test_util::{repeating_selector, HeadlessDrawer};
+
+    /// 一人プレイのゲームループと同じ順でアニメーションを実行し，最終的なフィールドと揃ったまま残ったラインを返す．
+    fn animate(field: Field, previous_filled_rows: &[PosY]) -> (Field, Vec<PosY>) {
+        let mut drawer = HeadlessDrawer::new();
+        let mut filled_row_ys = previous_filled_rows.to_vec();
+        let mut animation_field = AnimationField::new(
+            field,
+            BlockQueue::new(&mut repeating_selector(
+                QuadrupleBlockShape::T,
+                BombTag::Single(0),
+            )),
+        );
+        let mut chain = ChainCounter::new();
+
+        let animation_field = loop {
+            let (field, mut ys) =
+                FullRow::new(animation_field, &filled_row_ys).execute(&mut drawer);
+            match Explosion::try_init(field, &ys, chain.clone()) {
+                ExplosionInitResult::Explodes(explosion) => {
+                    let (field, next_chain, _) = explosion.execute(&mut drawer);
+                    animation_field = DropCell::new(field).execute(&mut drawer);
+                    filled_row_ys = vec![];
+                    chain = next_chain;
+                }
+                ExplosionInitResult::Stay(field) => {
+                    filled_row_ys.append(&mut ys);
+                    filled_row_ys.sort();
+                    filled_row_ys.dedup();
+                    break field;
+                }
+            }
+        };
+        let animation_field = match ConnectBomb::new(animation_field) {
+            ConnectBombInitResult::Connects(connect_bomb) => connect_bomb.execute(&mut drawer),
+            ConnectBombInitResult::Stay(field) => field,
+        };
+
+        (animation_field.field, filled_row_ys)
+    }
+
+    /// アニメーションを伴う処理と同じ結果になることを確かめ，`resolve`の結果を返す．
+    fn assert_same_as_animation(art: &str, previous_filled_rows: &[PosY]) -> Resolution {
+        let field = Field::from_str_art(art).unwrap();
+        let resolution = resolve(field.clone(), previous_filled_rows);
+        let (animated_field, animated_filled_row_ys) = animate(field, previous_filled_rows);
+
+        assert_eq!(animated_field.to_str_art(), resolution.field.to_str_art());
+        assert_eq!(animated_filled_row_ys, resolution.filled_row_ys);
+        resolution
+    }
+
+    #[test]
+    fn test_single_bomb_row() {
+        let resolution = assert_same_as_animation(
+            "
+            ..#.......
+            ####*#####
+            ",
+            &[],
+        );
+        assert_eq!(1, resolution.rows_filled);
+        assert_eq!(1, resolution.explosions);
+        assert_eq!(7, resolution.exploded_cells);
+        assert!(resolution.filled_row_ys.is_empty());
+    }
+
+    #[test]
+    fn test_steel_survives_explosion() {
+        let resolution = assert_same_as_animation(
+            "
+            .##.......
+            #@##*##@##
+            ",
+            &[],
+        );
+        assert_eq!(1, resolution.rows_filled);
+        assert_eq!(1, resolution.explosions);
+        // 鋼鉄のセルは爆発の範囲にあっても消えず，上に載ったセルを支え続ける
+        assert_eq!(5, resolution.exploded_cells);
+        let expected = Field::from_str_art(
+            "
+            .##.......
+            #@.....@##
+            ",
+        )
+        .unwrap();
+        assert_eq!(expected, resolution.field);
+    }
+
+    #[test]
+    fn test_steel_row_filled() {
+        let resolution = assert_same_as_animation("@@@@@#####", &[]);
+        assert_eq!(1, resolution.rows_filled);
+        assert_eq!(0, resolution.explosions);
+        assert_eq!(vec![PosY::below(19)], resolution.filled_row_ys);
+    }
+
+    #[test]
+    fn test_chain() {
+        let resolution = assert_same_as_animation(
+            "
+            #.........
+            ...*......
+            ......*...
+            ########*#
+            ####*#####
+            ",
+            &[],
+        );
+        assert_eq!(2, resolution.rows_filled);
+        assert_eq!(1, resolution.explosions);
+        assert!(resolution.exploded_cells > 20);
+    }
+
+    #[test]
+    fn test_second_chain_after_drop() {
+        // 最初の爆発で消えなかったセルが落ちて，もう一度ラインが揃う
+        let resolution = assert_same_as_animation(
+            "
+            ##*##.....
+            #*########
+            ",
+            &[],
+        );
+        assert_eq!(2, resolution.rows_filled);
+        assert_eq!(2, resolution.explosions);
+        assert_eq!(11, resolution.exploded_cells);
+        assert_eq!(Field::from_str_art("......####").unwrap(), resolution.field);
+    }
+
+    #[test]
+    fn test_rows_without_bombs_remain() {
+        let art = "
+            ..........
+            ##########
+            ";
+        let resolution = assert_same_as_animation(art, &[]);
+        assert_eq!(1, resolution.rows_filled);
+        assert_eq!(0, resolution.explosions);
+        assert_eq!(vec![PosY::below(19)], resolution.filled_row_ys);
+
+        // 同じラインは再び数えない
+        let resolution = assert_same_as_animation(art, &resolution.filled_row_ys);
+        assert_eq!(0, resolution.rows_filled);
+    }
+
+    #[test]
+    fn test_big_bomb_formation() {
+        let resolution = assert_same_as_animation(
+            "
+            .**.......
+            .**.......
+            ",
+            &[],
+        );
+        assert_eq!(1, resolution.big_bombs_formed);
+        assert_eq!(0, resolution.explosions);
+    }
+
+    #[test]
+    fn test_huge_bomb_formation() {
+        let resolution = assert_same_as_animation(
+            "
+            .***......
+            .***......
+            .****.....
+            ",
+            &[],
+        );
+        assert_eq!(1, resolution.huge_bombs_formed);
+        assert_eq!(0, resolution.big_bombs_formed);
+    }
+
+    #[test]
+    fn test_place_and_drop() {
+        let field = Field::from_str_art("#.........").unwrap();
+        let mut selector = repeating_selector(QuadrupleBlockShape::T, BombTag::Single(0));
+        let block = BlockQueue::new(&mut selector).pop_and_fill(&mut selector);
+
+        let top = Pos::origin();
+        let bottom = drop_position(&field, &block, top).unwrap();
+        assert!(bottom.y() > top.y());
+        assert_eq!(None, drop_position(&field, &block, top + below(20)));
+
+        let placed = place_block(&field, &block, bottom).unwrap();
+        let occupied = |field: &Field| {
+            field
+                .rows()
+                .flat_map(|row| row.iter().copied().collect::<Vec<_>>())
+                .filter(|cell| !cell.is_empty())
+                .count()
+        };
+        assert_eq!(occupied(&field) + 4, occupied(&placed));
+        assert_eq!(None, place_block(&placed, &block, bottom));
+    }
+}
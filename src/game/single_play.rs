@@ -1,74 +1,848 @@
 use super::animation::{
-    Animation, AnimationField, ChainCounter, ConnectBomb, ConnectBombInitResult, Drawer, DropCell,
-    Explosion, ExplosionInitResult, FullRow, PlaceBlock,
+    Animation, AnimationField, ChainCounter, ClearRows, ClearRowsInitResult, ConnectBomb,
+    ConnectBombInitResult, Countdown, Drawer, DropCell, Explosion, ExplosionInitResult,
+    ExplosionReport, FullRow, GravityMode, LevelUp, PlaceBlock, ScorePopup,
 };
-use super::field_under_agent_control::FieldUnderAgentControl;
-use super::{BlockQueue, BlockSelector, BlockShape, BombTag, Field};
+use super::field_under_agent_control::{FieldUnderAgentControl, GameCommandResult};
+use super::{
+    BlockQueue, BlockSelector, BombTag, ClearStatistics, Field, FramedField, Gravity, Level,
+    QuadrupleBlockShape, Score, ScriptEnd, ScriptedBlockSelector, StatsPanel,
+};
+use crate::geometry::*;
 use crate::graphics::*;
-use crate::user::GameCommand;
+use crate::user::{Agent, GameCommand, MenuCommand};
+use itertools::Itertools;
+use std::time::{Duration, Instant};
+
+/// 7種類のテトリミノを決まった順に繰り返し生成するブロック生成ルールを返す．
+/// 各ブロックの最初のセルがボムセルとなる．
+pub(super) fn quadruple_block_generator() -> ScriptedBlockSelector {
+    let blocks = QuadrupleBlockShape::all()
+        .map(|shape| (shape.into(), BombTag::Single(0)))
+        .collect();
+    ScriptedBlockSelector::new(blocks, ScriptEnd::Repeat).expect("every tetromino has a cell")
+}
 
-struct QuadrupleBlockGenerator {
-    current_index: usize,
+/// 描画のたびに，ブロックキューの右へ得点，レベル，統計を重ねて描画する描画機能．
+struct ScoreDrawer<'d, D> {
+    drawer: &'d mut D,
+    /// 得点を描画する位置．レベルと統計はその下に順に描画する．
+    pos: Pos,
+    score: Score,
+    level: Level,
+    stats: StatsPanel,
+    /// ゲームを始めた時刻．統計の経過時間の計測に利用する．
+    started: Instant,
+    /// レベルアップのアニメーション中は，アニメーションがレベルを点滅させるので，レベルを重ねて描画しない．
+    level_flashing: bool,
 }
 
-impl QuadrupleBlockGenerator {
-    fn new() -> QuadrupleBlockGenerator {
-        Self { current_index: 0 }
+impl<'d, D> ScoreDrawer<'d, D> {
+    /// レベルを描画する位置を返す．
+    fn level_pos(&self) -> Pos {
+        self.pos + self.score.region_size().y()
     }
 }
 
-impl BlockSelector for QuadrupleBlockGenerator {
-    fn select_block_shape(&mut self) -> BlockShape {
-        use super::QuadrupleBlockShape::*;
+impl<'d, D: Drawer> Drawer for ScoreDrawer<'d, D> {
+    type Canvas = D::Canvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        self.drawer.canvas_mut()
+    }
+
+    fn clear(&mut self) {
+        self.drawer.clear();
+    }
+
+    fn show(&mut self) {
+        self.score.draw_on_child(self.pos, self.drawer.canvas_mut());
+        let level_pos = self.level_pos();
+        if !self.level_flashing {
+            self.level
+                .draw_on_child(level_pos, self.drawer.canvas_mut());
+        }
+        self.stats.set_elapsed(self.started.elapsed());
+        let stats_pos = level_pos + self.level.region_size().y();
+        let frame = Frame::around(&self.stats)
+            .expect("stats panel fits in a frame")
+            .with_title("Stats");
+        let canvas = self.drawer.canvas_mut();
+        frame.draw_with(&self.stats, &mut canvas.child(frame.get_roi(stats_pos)));
+        self.drawer.show();
+    }
+
+    fn wait_frame(&mut self) {
+        self.drawer.wait_frame();
+    }
 
-        let shapes = [O, J, L, Z, S, T, I];
+    fn set_frame_duration(&mut self, duration: Duration) {
+        self.drawer.set_frame_duration(duration);
+    }
 
-        let shape = shapes[self.current_index % shapes.len()];
-        self.current_index = (self.current_index + 1) % shapes.len();
-        shape.into()
+    fn render_config(&self) -> RenderConfig {
+        self.drawer.render_config()
     }
+}
+
+/// 揃ったラインの扱いを決めるルールを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineClearRule {
+    /// 揃ったラインにあるボムを爆発させる．ボムを含まないラインは揃ったまま残る．
+    #[default]
+    Explosion,
+    /// 揃ったラインをボムによらずそのまま消し，その上のセルを消したライン数だけ下へずらす．
+    Classic,
+    /// `Explosion`と同様にボムを爆発させる．
+    /// 爆発の後は，宙に浮いたセルを落とす代わりに，各ラインをその下で空になったライン数だけ下へずらす．
+    ExplosionWithLineGravity,
+}
 
-    fn select_bomb(&mut self, _: BlockShape) -> BombTag {
-        BombTag::Single(0)
+impl LineClearRule {
+    /// 爆発の後にセルを落とす規則を返す．
+    pub const fn gravity_mode(&self) -> GravityMode {
+        match self {
+            LineClearRule::Explosion => GravityMode::Sticky,
+            LineClearRule::Classic | LineClearRule::ExplosionWithLineGravity => GravityMode::Line,
+        }
     }
 }
 
+/// ゲームの進行中に起きた出来事を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameEvent {
+    /// 操作中のブロックの設置位置が確定した．
+    BlockPlaced,
+    /// ラインが揃った．揃ったラインのy座標を上から順に保持する．
+    RowsFilled(Vec<PosY>),
+    /// 爆発が起きた．
+    Exploded {
+        /// 爆発の連鎖数．最初の爆発は0．
+        chain: usize,
+        /// 爆発で消えた，空でないセルの数．
+        cells: usize,
+    },
+    /// 指定した数のデカボムができた．
+    BigBombFormed(usize),
+    /// 指定した数の特大ボムができた．
+    HugeBombFormed(usize),
+    /// ブロックを設置した後の処理を終えて，フィールドのセルがすべて空になった．
+    FieldCleared,
+    /// 新しいブロックを配置できなくなった．
+    ToppedOut,
+    /// プレイヤーが一時停止画面でゲームをやめる操作をした．
+    Quit,
+}
+
+/// ゲーム終了時の状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    /// ゲーム終了時のフィールド．操作中だったブロックは含まない．
+    pub field: Field,
+    /// 各行が爆発で消された回数の統計．
+    pub clear_statistics: ClearStatistics,
+    /// 最終的な得点．
+    pub score: Score,
+    /// 最終的なレベル．
+    pub level: Level,
+    /// ゲーム中に達した最大の連鎖数．連鎖が起きなかった場合は0．
+    pub max_chain: usize,
+    /// やめる操作でゲームを終えた場合は，その時点から再開するための状態．それ以外の場合は`None`．
+    pub suspended: Option<GameState>,
+}
+
+/// ゲームを途中から始めるための状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameState {
+    /// 操作中のブロックを含まないフィールド．
+    pub field: Field,
+    /// 次に操作するブロックを先頭にもつブロックキュー．
+    pub block_queue: BlockQueue,
+    /// 得点．
+    pub score: Score,
+    /// レベル．
+    pub level: Level,
+}
+
+impl GameState {
+    /// 指定したフィールドから，得点やレベルをはじめから数える状態を返す．
+    /// ブロックキューは指定したブロック生成ルールで満たす．
+    pub fn new<S: BlockSelector>(field: Field, selector: &mut S) -> GameState {
+        Self {
+            field,
+            block_queue: BlockQueue::new(selector),
+            score: Score::new(),
+            level: Level::new(),
+        }
+    }
+}
+
+/// 爆発の統計，最終得点，最大の連鎖数を上から順に描画する．
+impl Drawable for GameResult {
+    fn region_size(&self) -> Movement {
+        self.clear_statistics.region_size() + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        self.clear_statistics.draw(canvas);
+        let pos = Pos::origin() + self.clear_statistics.region_size().y();
+        let summary = format!("Final score: {}", self.score.points());
+        ColoredStr(summary, color).draw_on_child(pos, canvas);
+        let max_chain = format!("Max chain: {}", self.max_chain);
+        ColoredStr(max_chain, color).draw_on_child(pos + below(1), canvas);
+    }
+}
+
+/// ブロックの操作が終わった理由を表す．
+enum ControlResult {
+    /// ブロックの設置位置が確定した．
+    Placed(Field, BlockQueue),
+    /// 入力の終了や時間切れにより，ゲームを途中で終える．操作途中のフィールドをもつ．
+    Stopped(FieldUnderAgentControl),
+    /// プレイヤーがゲームをやめる操作をした．操作途中のフィールドをもつ．
+    Quit(FieldUnderAgentControl),
+    /// 操作中にフィールドが変わり，ブロックを置けなくなった．変わった後のフィールドをもつ．
+    ToppedOut(Field),
+}
+
 /// 一人プレイエンドレスゲームを実行する．
-pub fn execute_game<I, D>(input: I, drawer: &mut D)
+///
+/// `input`は1ティックぶんの操作列を返す．
+/// 操作列は先頭から順に適用されるが，途中でブロックの設置位置が確定した場合，残りの操作は破棄される．
+/// `input`が空の操作列を返した場合は，入力が終了したものとしてゲームを終える．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_game<I, D>(input: I, drawer: &mut D) -> GameResult
+where
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+{
+    execute_game_with(quadruple_block_generator(), input, drawer, |_| {})
+}
+
+/// 指定したブロック生成ルールで一人プレイエンドレスゲームを実行する．
+///
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_game`と同様．
+pub fn execute_game_with<S, I, D, E>(
+    block_generator: S,
+    input: I,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    execute_game_with_rule(
+        block_generator,
+        LineClearRule::default(),
+        input,
+        drawer,
+        on_event,
+    )
+}
+
+/// 指定したブロック生成ルールと揃ったラインの扱いで，一人プレイエンドレスゲームを実行する．
+/// その他は`execute_game_with`と同様．
+pub fn execute_game_with_rule<S, I, D, E>(
+    block_generator: S,
+    rule: LineClearRule,
+    input: I,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    execute_game_from(
+        Field::empty(),
+        block_generator,
+        rule,
+        input,
+        drawer,
+        on_event,
+    )
+}
+
+/// 指定したフィールドから一人プレイゲームを始める．
+/// その他は`execute_game_with_rule`と同様．
+pub(super) fn execute_game_from<S, I, D, E>(
+    field: Field,
+    mut block_generator: S,
+    rule: LineClearRule,
+    input: I,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let state = GameState::new(field, &mut block_generator);
+    execute_game_from_state(state, block_generator, rule, input, drawer, on_event)
+}
+
+/// 指定した状態から一人プレイゲームを始める．中断したゲームの再開に利用できる．
+/// 得点やレベルは，指定した状態から数え続ける．
+/// その他は`execute_game_with_rule`と同様．
+pub fn execute_game_from_state<S, I, D, E>(
+    state: GameState,
+    block_generator: S,
+    rule: LineClearRule,
+    mut input: I,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Vec<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    play(
+        state,
+        block_generator,
+        rule,
+        drawer,
+        on_event,
+        |agent_field, _| Ok(agent_field),
+        |mut agent_field, drawer| {
+            let mut paused = false;
+            loop {
+                use super::field_under_agent_control::GameCommandResult::*;
+
+                let commands = input();
+                // 入力が終了したらゲーム終了
+                if commands.is_empty() {
+                    return ControlResult::Stopped(agent_field);
+                }
+                for command in commands {
+                    // 一時停止中は，やめる操作でゲームを終え，ほかの操作で再開する
+                    if paused {
+                        if command == GameCommand::Quit {
+                            return ControlResult::Quit(agent_field);
+                        }
+                        paused = false;
+                        continue;
+                    }
+                    match apply_command(agent_field, command, &mut drawer.score) {
+                        WaitNextCommand(next_field) => agent_field = next_field,
+                        ProceedAnimation(field, block_queue) => {
+                            return ControlResult::Placed(field, block_queue)
+                        }
+                        ToppedOut(field) => return ControlResult::ToppedOut(field),
+                        Quit(next_field) => {
+                            agent_field = next_field;
+                            paused = true;
+                        }
+                    }
+                }
+                draw_control(&agent_field, paused, drawer);
+            }
+        },
+    )
+}
+
+/// キー入力の代わりにエージェントが操作する一人プレイエンドレスゲームを実行する．
+///
+/// エージェントは1ティックにつき操作をひとつ決め，操作を適用するたびに描画してフレームを待機する．
+/// ゲームはブロックを置けなくなるまで続く．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_agent_game<A, D>(agent: A, drawer: &mut D) -> GameResult
+where
+    A: Agent,
+    D: Drawer,
+{
+    execute_agent_game_with(quadruple_block_generator(), agent, drawer, |_| {})
+}
+
+/// 指定したブロック生成ルールで，エージェントが操作する一人プレイエンドレスゲームを実行する．
+///
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_agent_game`と同様．
+pub fn execute_agent_game_with<S, A, D, E>(
+    mut block_generator: S,
+    mut agent: A,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    A: Agent,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    play(
+        GameState::new(Field::empty(), &mut block_generator),
+        block_generator,
+        LineClearRule::default(),
+        drawer,
+        on_event,
+        |agent_field, _| Ok(agent_field),
+        |mut agent_field, drawer| loop {
+            use super::field_under_agent_control::GameCommandResult::*;
+
+            let command = agent.decide(
+                agent_field.field(),
+                agent_field.controlled_block(),
+                agent_field.controlled_block_pos(),
+                agent_field.block_queue(),
+            );
+            match apply_command(agent_field, command, &mut drawer.score) {
+                WaitNextCommand(next_field) => agent_field = next_field,
+                ProceedAnimation(field, block_queue) => {
+                    return ControlResult::Placed(field, block_queue)
+                }
+                ToppedOut(field) => return ControlResult::ToppedOut(field),
+                Quit(agent_field) => return ControlResult::Quit(agent_field),
+            }
+            drawer.clear();
+            agent_field.draw(drawer.canvas_mut());
+            drawer.show();
+            drawer.wait_frame();
+        },
+    )
+}
+
+/// 操作中のブロックが一定フレームごとに自動で落下する一人プレイエンドレスゲームを実行する．
+///
+/// `input`はブロックせずに操作をひとつ返し，そのフレームの操作がもうなければ`None`を返す．
+/// 毎フレーム，`input`が`None`を返すまで操作を適用した後，落下のタイミングであればブロックを1マス落とす．
+/// ただし同じフレームにユーザが`GameCommand::Down`を入力していた場合，自動落下は行わない．
+/// ゲームはブロックを置けなくなるまで続く．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_gravity_game<I, D>(input: I, gravity: Gravity, drawer: &mut D) -> GameResult
+where
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+{
+    execute_gravity_game_with(quadruple_block_generator(), input, gravity, drawer, |_| {})
+}
+
+/// 指定したブロック生成ルールで，自動落下のある一人プレイエンドレスゲームを実行する．
+///
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_gravity_game`と同様．
+pub fn execute_gravity_game_with<S, I, D, E>(
+    block_generator: S,
+    input: I,
+    gravity: Gravity,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    execute_gravity_game_with_rule(
+        block_generator,
+        LineClearRule::default(),
+        input,
+        gravity,
+        drawer,
+        on_event,
+    )
+}
+
+/// 指定したブロック生成ルールと揃ったラインの扱いで，自動落下のある一人プレイエンドレスゲームを実行する．
+/// その他は`execute_gravity_game_with`と同様．
+pub fn execute_gravity_game_with_rule<S, I, D, E>(
+    mut block_generator: S,
+    rule: LineClearRule,
+    input: I,
+    gravity: Gravity,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let state = GameState::new(Field::empty(), &mut block_generator);
+    execute_gravity_game_from_state(
+        state,
+        block_generator,
+        rule,
+        input,
+        gravity,
+        drawer,
+        on_event,
+    )
+}
+
+/// 指定した状態から，自動落下のある一人プレイゲームを始める．中断したゲームの再開に利用できる．
+/// 得点やレベルは，指定した状態から数え続ける．
+/// その他は`execute_gravity_game_with_rule`と同様．
+pub fn execute_gravity_game_from_state<S, I, D, E>(
+    state: GameState,
+    block_generator: S,
+    rule: LineClearRule,
+    input: I,
+    gravity: Gravity,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    execute_gravity_game_until(
+        state,
+        block_generator,
+        rule,
+        input,
+        gravity,
+        || false,
+        |agent_field, _| Ok(agent_field),
+        drawer,
+        on_event,
+    )
+}
+
+/// 自動落下のある一人プレイゲームを，ブロックを置けなくなるか`is_over`が`true`を返すまで実行する．
+///
+/// `is_over`はブロックの操作中，毎フレームの最初に呼ばれる．
+/// `before_control`は新しいブロックを操作し始める前に，その時点のレベルとともに呼ばれ，フィールドを変更できる．
+/// `before_control`が`Err`でフィールドを返した場合は，ブロックを置けなくなったものとしてゲームを終える．
+/// その他は`execute_gravity_game_from_state`と同様．
+#[allow(clippy::too_many_arguments)]
+pub(super) fn execute_gravity_game_until<S, I, O, B, D, E>(
+    state: GameState,
+    block_generator: S,
+    rule: LineClearRule,
+    mut input: I,
+    mut gravity: Gravity,
+    mut is_over: O,
+    before_control: B,
+    drawer: &mut D,
+    on_event: E,
+) -> GameResult
 where
-    I: Fn() -> GameCommand,
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    O: FnMut() -> bool,
+    B: FnMut(FieldUnderAgentControl, &Level) -> Result<FieldUnderAgentControl, Field>,
     D: Drawer,
+    E: FnMut(GameEvent),
 {
-    let mut block_generator = QuadrupleBlockGenerator::new();
+    let base_interval = gravity.interval();
+    play(
+        state,
+        block_generator,
+        rule,
+        drawer,
+        on_event,
+        before_control,
+        |mut agent_field, drawer| {
+            use super::field_under_agent_control::GameCommandResult::*;
 
-    let mut field = Field::empty();
-    let mut block_queue = BlockQueue::new(&mut block_generator);
-    let mut filled_row_ys = vec![];
+            // 新しいブロックは，落下間隔をはじめから数える
+            gravity.reset();
+            // レベルに応じた落下間隔の方が短ければ，そちらで落とす
+            let interval = std::cmp::min(base_interval, drawer.level.gravity_interval());
+            gravity.set_interval(interval);
+            let mut paused = false;
+            loop {
+                if is_over() {
+                    return ControlResult::Stopped(agent_field);
+                }
+                let mut moved_down = false;
+                while let Some(command) = input() {
+                    // 一時停止中は，やめる操作でゲームを終え，ほかの操作で再開する
+                    if paused {
+                        if command == GameCommand::Quit {
+                            return ControlResult::Quit(agent_field);
+                        }
+                        paused = false;
+                        continue;
+                    }
+                    moved_down |= command == GameCommand::Down;
+                    match apply_command(agent_field, command, &mut drawer.score) {
+                        WaitNextCommand(next_field) => agent_field = next_field,
+                        ProceedAnimation(field, block_queue) => {
+                            return ControlResult::Placed(field, block_queue)
+                        }
+                        ToppedOut(field) => return ControlResult::ToppedOut(field),
+                        Quit(next_field) => {
+                            agent_field = next_field;
+                            paused = true;
+                        }
+                    }
+                }
+                // 一時停止中とユーザが下移動したフレームでは，自動で落とさない
+                if gravity.tick() && !moved_down && !paused {
+                    match agent_field.apply_gravity() {
+                        WaitNextCommand(next_field) => agent_field = next_field,
+                        ProceedAnimation(field, block_queue) => {
+                            return ControlResult::Placed(field, block_queue)
+                        }
+                        ToppedOut(field) => return ControlResult::ToppedOut(field),
+                        Quit(agent_field) => return ControlResult::Quit(agent_field),
+                    }
+                }
+                draw_control(&agent_field, paused, drawer);
+                drawer.wait_frame();
+            }
+        },
+    )
+}
 
+/// ゲームオーバーのたびに続けるかを尋ね，やめるまでゲームを繰り返す．
+///
+/// 各ゲームは，`new_selector`で新しく作ったブロック生成ルールを`play`に渡して実行する．
+/// ゲームごとにフィールドやブロックキュー，連鎖などの状態ははじめからやり直しになる．
+/// ゲームオーバー後は結果と案内を描画し，`menu_input`が`MenuCommand::Proceed`を返せば次のゲームを始める．
+/// `MenuCommand::Back`を返すか，入力が終了して`None`を返した場合は繰り返しを終える．
+/// その他の操作は無視する．
+/// # Returns
+/// 実行したゲームの結果を，実行した順に返す．
+pub fn run<S, F, P, R, M, D>(
+    mut new_selector: F,
+    mut play: P,
+    mut menu_input: M,
+    drawer: &mut D,
+) -> Vec<R>
+where
+    S: BlockSelector,
+    F: FnMut() -> S,
+    P: FnMut(S, &mut D) -> R,
+    R: Drawable,
+    M: FnMut() -> Option<MenuCommand>,
+    D: Drawer,
+{
+    let mut results = vec![];
     loop {
-        let mut agent_field =
-            match FieldUnderAgentControl::new(field, block_queue, &mut block_generator) {
+        let result = play(new_selector(), drawer);
+        drawer.clear();
+        result.draw(drawer.canvas_mut());
+        let prompt = ColoredStr(
+            "z: Retry  x: Quit",
+            drawer.canvas_mut().theme().cell_color(ThemeColor::Text),
+        );
+        prompt.draw_on_child(
+            Pos::origin() + result.region_size().y(),
+            drawer.canvas_mut(),
+        );
+        drawer.show();
+        results.push(result);
+
+        let retry = loop {
+            match menu_input() {
+                Some(MenuCommand::Proceed) => break true,
+                Some(MenuCommand::Back) | None => break false,
+                Some(MenuCommand::Up) | Some(MenuCommand::Down) => {}
+            }
+        };
+        if !retry {
+            break results;
+        }
+    }
+}
+
+/// 一時停止中に，フィールドの上へ重ねて描画する画面．
+/// やめる操作で中断した状態を保存してゲームをやめ，ほかの操作で再開することを案内する．
+struct PauseScreen;
+
+impl PauseScreen {
+    /// 枠の中に描画する各行．
+    const LINES: [&'static str; 2] = ["Quit : save & quit", "Other: resume"];
+}
+
+impl Drawable for PauseScreen {
+    fn region_size(&self) -> Movement {
+        let color = CanvasCellColor::default();
+        let width = Self::LINES
+            .iter()
+            .map(|line| ColoredStr(*line, color).region_size().x())
+            .max_by_key(|width| width.as_positive_index())
+            .unwrap_or(right(0));
+        width + below(Self::LINES.len() as i8)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        for (i, line) in Self::LINES.iter().enumerate() {
+            ColoredStr(*line, color).draw_on_child(Pos::origin() + below(i as i8), canvas);
+        }
+    }
+}
+
+/// 操作中のフィールドを描画して表示する．
+/// 一時停止中は，フィールドの左端に揃えて，縦方向の中央に一時停止画面を重ねて描画する．
+fn draw_control<D: Drawer>(agent_field: &FieldUnderAgentControl, paused: bool, drawer: &mut D) {
+    drawer.clear();
+    agent_field.draw(drawer.canvas_mut());
+    if paused {
+        let frame = Frame::around(&PauseScreen)
+            .expect("pause screen fits in a frame")
+            .with_title("Paused");
+        let height = |size: Movement| size.y().as_positive_index().unwrap_or(0);
+        let field_height = height(FramedField::new(agent_field.field()).region_size());
+        let margin = field_height.saturating_sub(height(frame.region_size())) / 2;
+        let pos = Pos::origin() + below(margin as i8);
+        let canvas = drawer.canvas_mut();
+        frame.draw_with(&PauseScreen, &mut canvas.child(frame.get_roi(pos)));
+    }
+    drawer.show();
+}
+
+/// 操作を適用する．ハードドロップの場合は得点に反映する．
+fn apply_command(
+    agent_field: FieldUnderAgentControl,
+    command: GameCommand,
+    score: &mut Score,
+) -> GameCommandResult {
+    if command == GameCommand::Drop {
+        score.record_hard_drop(agent_field.drop_distance());
+    }
+    agent_field.apply_command(command)
+}
+
+/// 指定した状態からゲームを実行する．
+/// `before_control`は新しいブロックを操作し始める前に，その時点のレベルとともに呼ばれ，フィールドを変更できる．
+/// `before_control`が`Err`でフィールドを返した場合は，ブロックを置けなくなったものとしてゲームを終える．
+/// ブロックの設置位置が確定するまでの操作は`control`が行う．
+/// 揃ったラインは`rule`に従って爆発させるか，そのまま消す．
+fn play<S, D, E, B, C>(
+    state: GameState,
+    mut block_generator: S,
+    rule: LineClearRule,
+    drawer: &mut D,
+    mut on_event: E,
+    mut before_control: B,
+    mut control: C,
+) -> GameResult
+where
+    S: BlockSelector,
+    D: Drawer,
+    E: FnMut(GameEvent),
+    B: FnMut(FieldUnderAgentControl, &Level) -> Result<FieldUnderAgentControl, Field>,
+    C: FnMut(FieldUnderAgentControl, &mut ScoreDrawer<'_, D>) -> ControlResult,
+{
+    let GameState {
+        field,
+        block_queue,
+        score,
+        level,
+    } = state;
+    let mut clear_statistics = ClearStatistics::new();
+    let mut max_chain = 0;
+    // フィールドとブロックキューの右に得点，レベルと統計を表示する
+    let mut drawer = ScoreDrawer {
+        pos: Pos::origin()
+            + FramedField::new(&field).region_size().x()
+            + right(1)
+            + block_queue.region_size().x()
+            + right(1),
+        drawer,
+        score,
+        level,
+        stats: StatsPanel::new(),
+        started: Instant::now(),
+        level_flashing: false,
+    };
+    let drawer = &mut drawer;
+    // アニメーションは始めるレベルの速さにする
+    let duration = drawer.level.frame_duration();
+    drawer.set_frame_duration(duration);
+
+    // ゲーム開始前のカウントダウン
+    let countdown = Countdown::new(AnimationField::new(field, block_queue));
+    let AnimationField {
+        mut field,
+        mut block_queue,
+    } = countdown.execute(drawer);
+    // 統計の経過時間は，カウントダウンを終えてから計る
+    drawer.started = Instant::now();
+
+    loop {
+        let agent_field =
+            match FieldUnderAgentControl::new(field.clone(), block_queue, &mut block_generator) {
                 Some(field) => field,
                 // ブロックをもう置けなくなったらゲーム終了
-                None => break,
+                None => {
+                    on_event(GameEvent::ToppedOut);
+                    break;
+                }
             };
+        let agent_field = match before_control(agent_field, &drawer.level) {
+            Ok(agent_field) => agent_field,
+            Err(topped_out_field) => {
+                on_event(GameEvent::ToppedOut);
+                field = topped_out_field;
+                break;
+            }
+        };
+        // ブロックを設置する前から揃っているラインは，新たに揃ったラインとして数えない．
+        // せり上がりでラインが移動している場合があるので，操作を始める時点のフィールドから求める
+        let mut filled_row_ys = agent_field.field().filled_rows();
         // 最初の状態を描画
         drawer.clear();
         agent_field.draw(drawer.canvas_mut());
         drawer.show();
 
-        // ブロックの設置位置が確定するまでユーザからの入力を受け付ける
-        let (confirmed_field, confirmed_block_queue) = loop {
-            use super::field_under_agent_control::GameCommandResult::*;
-
-            match agent_field.apply_command(input()) {
-                WaitNextCommand(next_field) => agent_field = next_field,
-                ProceedAnimation(field, block_queue) => break (field, block_queue),
+        // ブロックの設置位置が確定するまで操作する
+        let control_result = control(agent_field, drawer);
+        if let ControlResult::Quit(_) = control_result {
+            on_event(GameEvent::Quit);
+        }
+        let (confirmed_field, confirmed_block_queue) = match control_result {
+            ControlResult::Placed(field, block_queue) => (field, block_queue),
+            // 操作中にブロックを置けなくなってもゲーム終了
+            ControlResult::ToppedOut(topped_out_field) => {
+                on_event(GameEvent::ToppedOut);
+                field = topped_out_field;
+                break;
+            }
+            // 入力の終了や時間切れでゲーム終了
+            ControlResult::Stopped(agent_field) => {
+                return GameResult {
+                    field: agent_field.field().clone(),
+                    clear_statistics,
+                    score: drawer.score,
+                    level: drawer.level,
+                    max_chain,
+                    suspended: None,
+                };
+            }
+            // やめる操作でゲーム終了．操作中のブロックをキューに戻して再開できるようにする
+            ControlResult::Quit(agent_field) => {
+                let (field, block_queue) = agent_field.suspend();
+                let suspended = GameState {
+                    field: field.clone(),
+                    block_queue,
+                    score: drawer.score,
+                    level: drawer.level,
+                };
+                return GameResult {
+                    field,
+                    clear_statistics,
+                    score: drawer.score,
+                    level: drawer.level,
+                    max_chain,
+                    suspended: Some(suspended),
+                };
             }
-            drawer.clear();
-            agent_field.draw(drawer.canvas_mut());
-            drawer.show();
         };
+        drawer.stats.record_piece();
+        on_event(GameEvent::BlockPlaced);
 
         // ブロックを設置アニメーション
         let animation_field = AnimationField::new(confirmed_field, confirmed_block_queue);
@@ -80,37 +854,209 @@ where
         let finished_animation_field = loop {
             // ラインが揃ったアニメーション
             let full_row_animation = FullRow::new(finished_animation_field, &filled_row_ys);
-            let (field_after_full_row, mut ys) = full_row_animation.execute(drawer);
+            let (mut field_after_full_row, ys) = full_row_animation.execute(drawer);
+            if !ys.is_empty() {
+                drawer.score.record_rows_filled(ys.len());
+                // 以前から揃っていたラインは，レベルの計算には数えない
+                let new_rows = ys.iter().filter(|y| !filled_row_ys.contains(y)).count();
+                drawer.stats.record_rows(new_rows);
+                if drawer.level.record_rows(new_rows) {
+                    let duration = drawer.level.frame_duration();
+                    drawer.set_frame_duration(duration);
+                    // レベルが上がったことを，サイドパネルのレベルを点滅させて知らせる
+                    let level_up =
+                        LevelUp::new(field_after_full_row, drawer.level, drawer.level_pos());
+                    drawer.level_flashing = true;
+                    field_after_full_row = level_up.execute(drawer);
+                    drawer.level_flashing = false;
+                }
+                on_event(GameEvent::RowsFilled(ys.clone()));
+            }
+            // クラシックでは揃ったラインをそのまま消す．消した後に新たなラインは揃わないので，連鎖は起こらない
+            if rule == LineClearRule::Classic {
+                break match ClearRows::try_init(field_after_full_row) {
+                    ClearRowsInitResult::Clears(clear_rows) => clear_rows.execute(drawer),
+                    ClearRowsInitResult::Stay(animation_field) => animation_field,
+                };
+            }
             // 必要なら，ラインを消すアニメーション
-            match Explosion::try_init(field_after_full_row, &ys, explosion_chain) {
+            match Explosion::try_init(field_after_full_row, &ys, explosion_chain.clone()) {
                 ExplosionInitResult::Explodes(explosion) => {
                     // アニメーション実行
-                    let (field_after_explosion, next_chain) = explosion.execute(drawer);
+                    let (field_after_explosion, next_chain, report) = explosion.execute(drawer);
+                    clear_statistics.record_explosion(&report);
+                    let cells = report.steps().iter().map(|step| step.len()).sum();
+                    let points_before = drawer.score.points();
+                    drawer.score.record_explosion(&explosion_chain, cells);
+                    on_event(GameEvent::Exploded {
+                        chain: explosion_chain.current_chain(),
+                        cells,
+                    });
+                    // 爆発した場所から，獲得した得点を浮かび上がらせる
+                    let text = format!("+{}", drawer.score.points() - points_before);
+                    let pos = score_popup_pos(&report, field_after_explosion.field.width(), &text);
+                    let score_popup = ScorePopup::new(field_after_explosion, text, pos);
+                    let field_after_popup = score_popup.execute(drawer);
                     // 爆発後にセルが落ちるアニメーション
-                    let drop_cell = DropCell::new(field_after_explosion);
+                    let drop_cell = match rule.gravity_mode() {
+                        GravityMode::Sticky => DropCell::new(field_after_popup),
+                        GravityMode::Line => {
+                            DropCell::with_cleared_rows(field_after_popup, &report.rows())
+                        }
+                    };
                     finished_animation_field = drop_cell.execute(drawer);
                     // 次の連鎖が起こりうるので，フィールドを更新
                     filled_row_ys = vec![];
                     explosion_chain = next_chain;
+                    max_chain = std::cmp::max(max_chain, explosion_chain.max_reached());
                 }
                 ExplosionInitResult::Stay(animation_field) => {
                     // 今回の操作では爆発は起こらない．
-                    // 次の操作のためにフィールドとキューを更新
-                    filled_row_ys.append(&mut ys);
-                    filled_row_ys.sort();
-                    filled_row_ys.dedup();
+                    // 揃ったまま残ったラインは，次の操作を始めるときに求め直す
                     break animation_field;
                 }
             }
         };
 
         // ここまで来たら，ブロックの設置，爆発，落下はひととおり終わっている．
-        // 最後にデカボム生成
+        // 最後にデカボムと特大ボムを生成
         let finished_animation_field = match ConnectBomb::new(finished_animation_field) {
-            ConnectBombInitResult::Connects(connect_bomb) => connect_bomb.execute(drawer),
+            ConnectBombInitResult::Connects(connect_bomb) => {
+                let huge_bomb_count = connect_bomb.new_huge_bomb_count();
+                if huge_bomb_count > 0 {
+                    on_event(GameEvent::HugeBombFormed(huge_bomb_count));
+                }
+                let big_bomb_count = connect_bomb.new_big_bomb_count();
+                if big_bomb_count > 0 {
+                    on_event(GameEvent::BigBombFormed(big_bomb_count));
+                }
+                connect_bomb.execute(drawer)
+            }
             ConnectBombInitResult::Stay(animation_field) => animation_field,
         };
         field = finished_animation_field.field;
         block_queue = finished_animation_field.block_queue;
+        if field.count_cells().is_empty_field() {
+            on_event(GameEvent::FieldCleared);
+        }
+    }
+
+    GameResult {
+        field,
+        clear_statistics,
+        score: drawer.score,
+        level: drawer.level,
+        max_chain,
+        suspended: None,
+    }
+}
+
+/// 爆発で獲得した得点を表示し始める位置を返す．
+/// 爆発で消えたセルのうち最も上の行の中央に，文字列がフィールドの幅に収まるよう配置する．
+fn score_popup_pos(report: &ExplosionReport, field_width: usize, text: &str) -> Pos {
+    let positions = report.steps().iter().flatten().collect::<Vec<_>>();
+    let top = match positions.iter().map(|pos| pos.y()).min() {
+        Some(top) => top,
+        None => return Pos::origin(),
+    };
+    let (left, right) = positions
+        .iter()
+        .filter(|pos| pos.y() == top)
+        .filter_map(|pos| pos.x().as_positive_index())
+        .minmax()
+        .into_option()
+        .unwrap_or((0, 0));
+
+    let text_width = ColoredStr(text, CanvasCellColor::default())
+        .region_size()
+        .x()
+        .as_positive_index()
+        .unwrap_or(0);
+    let center = (left + right) / 2;
+    let x = std::cmp::min(
+        center.saturating_sub(text_width / 2),
+        field_width.saturating_sub(text_width),
+    );
+    Pos(PosX::right(x as i8), top)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{t_block_selector, HeadlessDrawer};
+
+    #[test]
+    fn test_side_column_fits_in_min_canvas() {
+        let mut drawer = HeadlessDrawer::recording();
+        let mut inputs = vec![vec![GameCommand::Drop], vec![GameCommand::Quit]].into_iter();
+        execute_game_with(
+            t_block_selector(),
+            || inputs.next().unwrap_or_default(),
+            &mut drawer,
+            |_| {},
+        );
+
+        // 得点，レベル，統計の枠の下端まで，最小の大きさのキャンバスに収まる
+        let frame = drawer
+            .shown
+            .iter()
+            .find(|frame| frame.contains("Stats"))
+            .expect("stats panel is shown");
+        let lines = frame.lines().collect::<Vec<_>>();
+        assert_eq!(RootCanvas::MIN_HEIGHT, lines.len());
+        for label in ["Score", "Level", "Pieces", "PPM"] {
+            assert!(frame.contains(label), "{} is not shown", label);
+        }
+        // 統計の最終行の真下に，枠の下端が描画されている
+        let ppm_y = lines.iter().position(|line| line.contains("PPM")).unwrap();
+        let ppm_x = lines[ppm_y].chars().position(|c| c == 'P').unwrap();
+        let below_ppm = lines
+            .get(ppm_y + 1)
+            .and_then(|line| line.chars().nth(ppm_x));
+        assert!(below_ppm.is_some_and(|c| !c.is_whitespace()));
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        use GameCommand::*;
+
+        let mut drawer = HeadlessDrawer::recording();
+        let mut events = vec![];
+        let mut inputs = vec![vec![Quit], vec![Left]].into_iter();
+        let result = execute_game_with(
+            t_block_selector(),
+            || inputs.next().unwrap_or_default(),
+            &mut drawer,
+            |event| events.push(event),
+        );
+
+        // やめる操作で一時停止し，ほかの操作で再開する
+        let paused = drawer
+            .shown
+            .iter()
+            .position(|frame| frame.contains("Paused"))
+            .expect("pause screen is shown");
+        assert!(drawer.shown[paused].contains("save & quit"));
+        assert!(!drawer.shown.last().unwrap().contains("Paused"));
+        assert!(!events.contains(&GameEvent::Quit));
+        assert_eq!(None, result.suspended);
+    }
+
+    #[test]
+    fn test_quit_from_pause_screen() {
+        use GameCommand::*;
+
+        let mut events = vec![];
+        let mut inputs = vec![vec![Drop], vec![Quit], vec![Quit]].into_iter();
+        let result = execute_game_with(
+            t_block_selector(),
+            || inputs.next().unwrap_or_default(),
+            &mut HeadlessDrawer::new(),
+            |event| events.push(event),
+        );
+
+        // 一時停止画面でもう一度やめる操作をすると，再開できる状態を残してゲームを終える
+        assert_eq!(Some(&GameEvent::Quit), events.last());
+        assert!(result.suspended.is_some());
     }
 }
@@ -0,0 +1,517 @@
+use super::animation::Drawer;
+use super::single_play::{self, GameEvent, GameResult, GameState, LineClearRule};
+use super::{BlockSelector, Field, FramedField, Gravity};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::user::{Clock, GameCommand};
+use std::cell::Cell;
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// スプリントで，ゲームを終えるまでに揃えるライン数．
+pub const GOAL_ROWS: usize = 40;
+
+/// 揃ったラインを数える．
+///
+/// `FullRow`は揃っているラインをすべて検出するので，前回検出したラインは重ねて数えない．
+/// 爆発が起きるとゲームループは検出済みのラインを忘れるので，ここでも同様に忘れる．
+struct RowCounter {
+    /// 前回検出したライン．
+    previous_rows: Vec<PosY>,
+    count: usize,
+}
+
+impl RowCounter {
+    fn new() -> RowCounter {
+        Self {
+            previous_rows: vec![],
+            count: 0,
+        }
+    }
+
+    fn record(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::RowsFilled(ys) => {
+                let previous_rows = &self.previous_rows;
+                self.count += ys.iter().filter(|y| !previous_rows.contains(y)).count();
+                self.previous_rows = ys.clone();
+            }
+            GameEvent::Exploded { .. } => self.previous_rows.clear(),
+            _ => {}
+        }
+    }
+}
+
+/// 描画のたびに，フィールドの下へ経過時間と揃えたライン数を重ねて描画する描画機能．
+struct TimerDrawer<'d, 'p, D, C> {
+    drawer: &'d mut D,
+    clock: &'p C,
+    /// 経過時間を描画する位置．
+    pos: Pos,
+    /// 計測を始めた時刻．まだ計り始めていない場合は`None`．
+    started: &'p Cell<Option<Instant>>,
+    /// 目標を達成するまでにかかった時間．まだ達成していない場合は`None`．
+    finished: &'p Cell<Option<Duration>>,
+    rows: &'p Cell<usize>,
+    goal_rows: usize,
+}
+
+impl<'d, 'p, D: Drawer, C: Clock> Drawer for TimerDrawer<'d, 'p, D, C> {
+    type Canvas = D::Canvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        self.drawer.canvas_mut()
+    }
+
+    fn clear(&mut self) {
+        self.drawer.clear();
+    }
+
+    fn show(&mut self) {
+        let elapsed = match (self.finished.get(), self.started.get()) {
+            (Some(finished), _) => finished,
+            (None, Some(started)) => self.clock.now().saturating_duration_since(started),
+            (None, None) => Duration::from_secs(0),
+        };
+        let color = self
+            .drawer
+            .canvas_mut()
+            .theme()
+            .cell_color(ThemeColor::Text);
+        let time = format!("Time  {}", format_time(elapsed));
+        ColoredStr(time, color).draw_on_child(self.pos, self.drawer.canvas_mut());
+        let rows = format!("Lines {}/{}", self.rows.get(), self.goal_rows);
+        ColoredStr(rows, color).draw_on_child(self.pos + below(1), self.drawer.canvas_mut());
+        self.drawer.show();
+    }
+
+    fn wait_frame(&mut self) {
+        self.drawer.wait_frame();
+    }
+
+    fn set_frame_duration(&mut self, duration: Duration) {
+        self.drawer.set_frame_duration(duration);
+    }
+
+    fn render_config(&self) -> RenderConfig {
+        self.drawer.render_config()
+    }
+}
+
+/// スプリントの終了時の状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SprintResult {
+    /// ゲーム終了時の状態．
+    pub game: GameResult,
+    /// 目標のライン数を揃えるまでにかかった時間．達成できなかった場合は`None`．
+    pub time: Option<Duration>,
+    /// このゲームを含めた最短記録．
+    pub best: BestTime,
+}
+
+/// 今回の記録，最短記録，爆発の統計と最終得点を上から順に描画する．
+impl Drawable for SprintResult {
+    fn region_size(&self) -> Movement {
+        self.game.region_size() + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        let time = match self.time {
+            Some(time) => format!("Time  {}", format_time(time)),
+            None => "Time  --:--.--".to_string(),
+        };
+        ColoredStr(time, color).draw_on_child(Pos::origin(), canvas);
+        self.best.draw_on_child(Pos::origin() + below(1), canvas);
+        self.game.draw_on_child(Pos::origin() + below(2), canvas);
+    }
+}
+
+/// スプリントの最短記録を表す．
+///
+/// ファイルには，最短記録をミリ秒単位の整数で書き出す．記録がない場合は空のファイルになる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BestTime {
+    time: Option<Duration>,
+}
+
+impl BestTime {
+    /// 記録のない状態を返す．
+    pub const fn new() -> BestTime {
+        Self { time: None }
+    }
+
+    /// 最短記録を返す．まだ記録がない場合は`None`を返す．
+    pub const fn time(&self) -> Option<Duration> {
+        self.time
+    }
+
+    /// 指定した時間を記録する．
+    /// # Returns
+    /// 最短記録を更新した場合は`true`を返す．
+    pub fn record(&mut self, time: Duration) -> bool {
+        match self.time {
+            Some(best) if best <= time => false,
+            _ => {
+                self.time = Some(time);
+                true
+            }
+        }
+    }
+
+    /// ファイルに書き出す形式の文字列を返す．
+    pub fn to_text(&self) -> String {
+        match self.time {
+            Some(time) => format!("{}\n", time.as_millis()),
+            None => String::new(),
+        }
+    }
+
+    /// `to_text`で書き出した形式の文字列から最短記録を読み込む．
+    /// # Returns
+    /// 形式が正しくない場合は`None`を返す．
+    pub fn from_text(text: &str) -> Option<BestTime> {
+        let text = text.trim();
+        if text.is_empty() {
+            Some(Self::new())
+        } else {
+            let millis = text.parse().ok()?;
+            Some(Self {
+                time: Some(Duration::from_millis(millis)),
+            })
+        }
+    }
+
+    /// 指定したファイルに書き出す．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    /// 指定したファイルから読み込む．
+    /// ファイルが存在しない場合は，記録のない状態を返す．
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<BestTime> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid best time")),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// 見出しと最短記録を1行で描画する．
+impl Drawable for BestTime {
+    fn region_size(&self) -> Movement {
+        right(14) + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let best = match self.time {
+            Some(time) => format!("Best  {}", format_time(time)),
+            None => "Best  --:--.--".to_string(),
+        };
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        ColoredStr(best, color).draw_on_child(Pos::origin(), canvas);
+    }
+}
+
+/// 時間を`分:秒.1/100秒`の形式で表す．
+pub(super) fn format_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    format!(
+        "{:02}:{:02}.{:02}",
+        centis / 6000,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// `GOAL_ROWS`本のラインをできるだけ早く揃えるゲームを実行する．
+///
+/// ゲームは`single_play::execute_gravity_game`と同様に進み，目標のライン数を揃えると終わる．
+/// 目標を達成する前にブロックを置けなくなった場合もゲームを終える．
+/// 経過時間は`clock`で計り，ゲーム中は毎フレームフィールドの下に表示する．
+/// 経過時間は，ゲーム開始前のカウントダウンを終えて最初のブロックを操作し始めたときから計る．
+/// 目標を達成した場合は，かかった時間を`best`に記録する．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_sprint<S, I, C, D>(
+    block_generator: S,
+    input: I,
+    clock: &C,
+    best: &mut BestTime,
+    drawer: &mut D,
+) -> SprintResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    C: Clock,
+    D: Drawer,
+{
+    execute_sprint_with(
+        block_generator,
+        input,
+        Gravity::default(),
+        clock,
+        best,
+        GOAL_ROWS,
+        drawer,
+        |_| {},
+    )
+}
+
+/// 指定した落下間隔で，指定したライン数を揃えるまでのゲームを実行する．
+///
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_sprint`と同様．
+#[allow(clippy::too_many_arguments)]
+pub fn execute_sprint_with<S, I, C, D, E>(
+    mut block_generator: S,
+    input: I,
+    gravity: Gravity,
+    clock: &C,
+    best: &mut BestTime,
+    goal_rows: usize,
+    drawer: &mut D,
+    mut on_event: E,
+) -> SprintResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    C: Clock,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let started = Cell::new(None);
+    let finished = Cell::new(None);
+    let rows = Cell::new(0);
+    let mut counter = RowCounter::new();
+    let mut drawer = TimerDrawer {
+        drawer,
+        clock,
+        pos: Pos::origin() + FramedField::new(&Field::empty()).region_size().y(),
+        started: &started,
+        finished: &finished,
+        rows: &rows,
+        goal_rows,
+    };
+
+    let game = single_play::execute_gravity_game_until(
+        GameState::new(Field::empty(), &mut block_generator),
+        block_generator,
+        LineClearRule::default(),
+        input,
+        gravity,
+        || {
+            // 最初のブロックを操作し始めたときから計る
+            if started.get().is_none() {
+                started.set(Some(clock.now()));
+            }
+            // 目標を達成したら，ゲームを終了させる
+            finished.get().is_some()
+        },
+        |agent_field, _| Ok(agent_field),
+        &mut drawer,
+        |event| {
+            counter.record(&event);
+            rows.set(counter.count);
+            if counter.count >= goal_rows && finished.get().is_none() {
+                let started = started.get().unwrap_or_else(|| clock.now());
+                finished.set(Some(clock.now().saturating_duration_since(started)));
+            }
+            on_event(event);
+        },
+    );
+
+    let time = finished.get();
+    if let Some(time) = time {
+        best.record(time);
+    }
+
+    SprintResult {
+        game,
+        time,
+        best: *best,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{BombTag, SingleBlockShape};
+    use super::*;
+    use crate::game::test_util::{repeating_selector, HeadlessDrawer};
+    use std::collections::VecDeque;
+
+    /// 呼ばれるたびに1秒進む時計．
+    struct TickingClock {
+        origin: Instant,
+        seconds: Cell<u64>,
+    }
+
+    impl Clock for TickingClock {
+        fn now(&self) -> Instant {
+            let seconds = self.seconds.get();
+            self.seconds.set(seconds + 1);
+            self.origin + Duration::from_secs(seconds)
+        }
+    }
+
+    /// 出現位置(x=5)にあるブロックを指定した列まで動かし，落とす1フレームぶんの入力を返す．
+    /// フレームの終わりを表す`None`で終わる．
+    fn drop_at(x: i8) -> Vec<Option<GameCommand>> {
+        let shift = x - 5;
+        let command = if shift < 0 {
+            GameCommand::Left
+        } else {
+            GameCommand::Right
+        };
+        let mut commands = vec![Some(command); shift.unsigned_abs() as usize];
+        commands.push(Some(GameCommand::Drop));
+        commands.push(None);
+        commands
+    }
+
+    /// 入力に残っている，ブロックを落とす操作の数を返す．
+    fn count_drops(inputs: &VecDeque<Option<GameCommand>>) -> usize {
+        inputs
+            .iter()
+            .filter(|&&c| c == Some(GameCommand::Drop))
+            .count()
+    }
+
+    fn clock() -> TickingClock {
+        TickingClock {
+            origin: Instant::now(),
+            seconds: Cell::new(0),
+        }
+    }
+
+    #[test]
+    fn test_sprint_finishes_at_goal() {
+        // 最下段を左から順に埋め続ける．各ラインは揃うとすぐに爆発して消える
+        let mut inputs = (0..1000)
+            .flat_map(|i| drop_at((i % 10) as i8))
+            .collect::<VecDeque<_>>();
+        let mut drawer = HeadlessDrawer::new();
+        let mut best = BestTime::new();
+        let mut placed = 0;
+
+        let result = execute_sprint_with(
+            repeating_selector(SingleBlockShape::O, BombTag::All),
+            || inputs.pop_front().flatten(),
+            Gravity::default(),
+            &clock(),
+            &mut best,
+            GOAL_ROWS,
+            &mut drawer,
+            |event| {
+                if event == GameEvent::BlockPlaced {
+                    placed += 1;
+                }
+            },
+        );
+
+        // 目標を達成した時点で，残りの入力を使わずに終わる
+        assert_eq!(GOAL_ROWS * 10, placed);
+        assert_eq!(1000 - GOAL_ROWS * 10, count_drops(&inputs));
+        assert!(result.time.is_some());
+        assert_eq!(result.time, best.time());
+        assert_eq!(best, result.best);
+        // 10ラインごとにレベルが上がる
+        assert_eq!(GOAL_ROWS, result.game.level.rows());
+        assert_eq!(GOAL_ROWS / 10 + 1, result.game.level.level());
+
+        let mut buffer = String::new();
+        drawer.canvas.construct_plain_string(&mut buffer);
+        assert!(buffer.contains(&format!("Lines {}/{}", GOAL_ROWS, GOAL_ROWS)));
+    }
+
+    #[test]
+    fn test_sprint_without_reaching_goal() {
+        // 入力が尽きた後は，自動落下で同じ列に積み上がり，ブロックを置けなくなる
+        let mut inputs = (0..9).flat_map(drop_at).collect::<VecDeque<_>>();
+        let mut drawer = HeadlessDrawer::new();
+        let mut best = BestTime::new();
+
+        let result = execute_sprint_with(
+            repeating_selector(SingleBlockShape::O, BombTag::All),
+            || inputs.pop_front().flatten(),
+            Gravity::new(1),
+            &clock(),
+            &mut best,
+            GOAL_ROWS,
+            &mut drawer,
+            |_| {},
+        );
+
+        assert_eq!(None, result.time);
+        assert_eq!(BestTime::new(), best);
+    }
+
+    #[test]
+    fn test_timer_advances_without_input() {
+        // 操作がなくても，経過時間は毎フレーム描き直される
+        let mut drawer = HeadlessDrawer::recording();
+        let mut placed = false;
+        execute_sprint_with(
+            repeating_selector(SingleBlockShape::O, BombTag::All),
+            || None,
+            Gravity::new(1),
+            &clock(),
+            &mut BestTime::new(),
+            GOAL_ROWS,
+            &mut drawer,
+            |event| placed |= event == GameEvent::BlockPlaced,
+        );
+        assert!(placed);
+
+        let times = drawer
+            .shown
+            .iter()
+            .filter_map(|frame| frame.lines().find(|line| line.starts_with("Time")))
+            .map(str::trim_end)
+            .collect::<Vec<_>>();
+        assert!(times.contains(&"Time  00:01.00"));
+        assert!(times.contains(&"Time  00:05.00"));
+    }
+
+    #[test]
+    fn test_row_counter_ignores_previous_rows() {
+        let mut counter = RowCounter::new();
+        counter.record(&GameEvent::RowsFilled(vec![PosY::below(19)]));
+        counter.record(&GameEvent::RowsFilled(vec![
+            PosY::below(18),
+            PosY::below(19),
+        ]));
+        assert_eq!(2, counter.count);
+
+        // 爆発の後に揃ったラインは，新たに数える
+        counter.record(&GameEvent::Exploded { chain: 0, cells: 3 });
+        counter.record(&GameEvent::RowsFilled(vec![PosY::below(19)]));
+        assert_eq!(3, counter.count);
+    }
+
+    #[test]
+    fn test_best_time() {
+        let mut best = BestTime::new();
+        assert_eq!("", best.to_text());
+        assert!(best.record(Duration::from_millis(62_340)));
+        assert!(!best.record(Duration::from_millis(70_000)));
+        assert!(best.record(Duration::from_millis(61_005)));
+        assert_eq!(Some(Duration::from_millis(61_005)), best.time());
+
+        let text = best.to_text();
+        assert_eq!("61005\n", text);
+        assert_eq!(Some(best), BestTime::from_text(&text));
+        assert_eq!(Some(BestTime::new()), BestTime::from_text(""));
+        assert_eq!(None, BestTime::from_text("1:01.00"));
+    }
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!("00:00.00", format_time(Duration::from_millis(0)));
+        assert_eq!("01:01.00", format_time(Duration::from_millis(61_005)));
+        assert_eq!("10:00.99", format_time(Duration::from_millis(600_999)));
+    }
+}
@@ -0,0 +1,160 @@
+use super::sprint::format_time;
+use crate::geometry::*;
+use crate::graphics::*;
+use std::time::Duration;
+
+/// 一人プレイ中の統計を表す．
+/// 設置したブロック数，揃えたライン数，経過時間，1分あたりの設置数を描画する．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StatsPanel {
+    /// 設置位置が確定したブロックの数．
+    pieces: usize,
+    /// 揃えたラインの数．
+    lines: usize,
+    /// ゲーム開始からの経過時間．
+    elapsed: Duration,
+}
+
+impl StatsPanel {
+    pub const fn new() -> StatsPanel {
+        Self {
+            pieces: 0,
+            lines: 0,
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    /// 設置位置が確定したブロックの数を返す．
+    pub const fn pieces(&self) -> usize {
+        self.pieces
+    }
+
+    /// 揃えたラインの数を返す．
+    pub const fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// ゲーム開始からの経過時間を返す．
+    pub const fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// 1分あたりに設置したブロックの数を返す．
+    /// 経過時間が0の場合は0を返す．
+    pub fn pieces_per_minute(&self) -> f64 {
+        let minutes = self.elapsed.as_secs_f64() / 60.0;
+        if minutes > 0.0 {
+            self.pieces as f64 / minutes
+        } else {
+            0.0
+        }
+    }
+
+    /// ブロックの設置位置がひとつ確定したことを記録する．
+    pub fn record_piece(&mut self) {
+        self.pieces += 1;
+    }
+
+    /// 指定した本数のラインを新たに揃えたことを記録する．
+    pub fn record_rows(&mut self, rows: usize) {
+        self.lines += rows;
+    }
+
+    /// ゲーム開始からの経過時間を更新する．
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// 描画する各行を，上から順に返す．
+    fn lines_to_draw(&self) -> [String; 4] {
+        [
+            format!("Pieces {:>8}", self.pieces),
+            format!("Lines  {:>8}", self.lines),
+            format!("Time   {:>8}", format_time(self.elapsed)),
+            format!("PPM    {:>8.1}", self.pieces_per_minute()),
+        ]
+    }
+}
+
+/// 見出しと値を1行ずつ描画する．
+/// 幅は最も長い行に合わせる．
+impl Drawable for StatsPanel {
+    fn region_size(&self) -> Movement {
+        let color = CanvasCellColor::default();
+        let lines = self.lines_to_draw();
+        let width = lines
+            .iter()
+            .map(|line| ColoredStr(line.as_str(), color).region_size().x())
+            .max_by_key(|width| width.as_positive_index())
+            .unwrap_or(right(0));
+        width + below(lines.len() as i8)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        for (i, line) in self.lines_to_draw().iter().enumerate() {
+            ColoredStr(line.as_str(), color).draw_on_child(Pos::origin() + below(i as i8), canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(stats: &StatsPanel) -> String {
+        let mut canvas = RootCanvas::new();
+        stats.draw(&mut canvas);
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        s
+    }
+
+    #[test]
+    fn test_records() {
+        let mut stats = StatsPanel::new();
+        stats.record_piece();
+        stats.record_piece();
+        stats.record_rows(3);
+        stats.record_rows(1);
+        stats.set_elapsed(Duration::from_secs(30));
+
+        assert_eq!(2, stats.pieces());
+        assert_eq!(4, stats.lines());
+        assert_eq!(Duration::from_secs(30), stats.elapsed());
+        assert!((stats.pieces_per_minute() - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pieces_per_minute_without_time() {
+        let mut stats = StatsPanel::new();
+        stats.record_piece();
+        assert_eq!(0.0, stats.pieces_per_minute());
+    }
+
+    #[test]
+    fn test_format() {
+        let mut stats = StatsPanel::new();
+        (0..125).for_each(|_| stats.record_piece());
+        stats.record_rows(48);
+        stats.set_elapsed(Duration::from_millis(61_250));
+
+        let s = render(&stats);
+        assert!(s.contains("Pieces      125"));
+        assert!(s.contains("Lines        48"));
+        assert!(s.contains("Time   01:01.25"));
+        assert!(s.contains("PPM       122.4"));
+    }
+
+    #[test]
+    fn test_region_size() {
+        let stats = StatsPanel::new();
+        assert_eq!(right(8) + below(4), stats.region_size());
+
+        // 値の桁数が欄の幅を超えると，その分だけ広がる
+        let mut stats = StatsPanel::new();
+        stats.record_rows(123_456_789_012);
+        assert_eq!(right(10) + below(4), stats.region_size());
+        assert!(render(&stats).contains("Lines  123456789012"));
+    }
+}
@@ -0,0 +1,261 @@
+use super::animation::Drawer;
+use super::single_play::{
+    self, quadruple_block_generator, GameEvent, GameResult, GameState, LineClearRule,
+};
+use super::{BlockSelector, Field, GarbagePattern, Gravity, Level};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::user::GameCommand;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::cell::Cell;
+
+/// レベル1で，せり上がりラインをひとつ挿入するまでに設置するブロック数．
+pub const INITIAL_PIECES_PER_ROW: usize = 8;
+/// せり上がりラインをひとつ挿入するまでに設置するブロック数の最小値．
+pub const MIN_PIECES_PER_ROW: usize = 2;
+
+/// 指定したレベルで，せり上がりラインをひとつ挿入するまでに設置するブロック数を返す．
+/// レベルがひとつ上がるごとにひとつ減り，`MIN_PIECES_PER_ROW`より少なくはならない．
+pub fn pieces_per_garbage_row(level: &Level) -> usize {
+    let pieces = INITIAL_PIECES_PER_ROW.saturating_sub(level.level() - 1);
+    std::cmp::max(pieces, MIN_PIECES_PER_ROW)
+}
+
+/// サバイバルの終了時の状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SurvivalResult {
+    /// ゲーム終了時の状態．
+    pub game: GameResult,
+    /// ゲーム中に挿入されたせり上がりラインの数．
+    pub garbage_rows: usize,
+}
+
+/// せり上がったライン数，爆発の統計と最終得点を上から順に描画する．
+impl Drawable for SurvivalResult {
+    fn region_size(&self) -> Movement {
+        self.game.region_size() + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        let garbage_rows = format!("Garbage rows: {}", self.garbage_rows);
+        ColoredStr(garbage_rows, color).draw_on_child(Pos::origin(), canvas);
+        self.game.draw_on_child(Pos::origin() + below(1), canvas);
+    }
+}
+
+/// ブロックを設置するたびにフィールドの下端からラインがせり上がってくるゲームを実行する．
+///
+/// ゲームは`single_play::execute_gravity_game`と同様に進む．
+/// ブロックを`pieces_per_garbage_row`個設置するたびに，ランダムな列に穴がひとつあいたせり上がりラインを挿入する．
+/// 挿入したラインに押し上げられて操作中のブロックと重なった場合は，ブロックを置けなくなったものとしてゲームを終える．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_survival<I, D>(input: I, drawer: &mut D) -> SurvivalResult
+where
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+{
+    execute_survival_with(
+        quadruple_block_generator(),
+        rand::random(),
+        input,
+        Gravity::default(),
+        drawer,
+        |_| {},
+    )
+}
+
+/// 指定したブロック生成ルールと落下間隔でサバイバルを実行する．
+///
+/// せり上がりラインの穴の列は，`seed`で初期化した乱数で決める．
+/// ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_survival`と同様．
+pub fn execute_survival_with<S, I, D, E>(
+    mut block_generator: S,
+    seed: u64,
+    input: I,
+    gravity: Gravity,
+    drawer: &mut D,
+    mut on_event: E,
+) -> SurvivalResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    // 前回せり上がってから設置したブロック数
+    let placed_pieces = Cell::new(0);
+    let mut garbage_rows = 0;
+
+    let game = single_play::execute_gravity_game_until(
+        GameState::new(Field::empty(), &mut block_generator),
+        block_generator,
+        LineClearRule::Explosion,
+        input,
+        gravity,
+        || false,
+        |agent_field, level| {
+            if placed_pieces.get() < pieces_per_garbage_row(level) {
+                return Ok(agent_field);
+            }
+            placed_pieces.set(0);
+            garbage_rows += 1;
+            let width = agent_field.field().width();
+            let hole_columns = GarbagePattern::RandomPerRow.hole_columns(1, width, &mut rng);
+            agent_field.insert_garbage_rows(&hole_columns)
+        },
+        drawer,
+        |event| {
+            if event == GameEvent::BlockPlaced {
+                placed_pieces.set(placed_pieces.get() + 1);
+            }
+            on_event(event);
+        },
+    );
+
+    SurvivalResult { game, garbage_rows }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::{t_block_selector, HeadlessDrawer};
+    use crate::game::Cell;
+
+    /// Tミノを指定した数だけその場で落とした後，一時停止してゲームをやめる．
+    fn drop_and_quit(drops: usize) -> SurvivalResult {
+        let mut drawer = HeadlessDrawer::new();
+        let mut commands = (0..drops)
+            .map(|_| GameCommand::Drop)
+            .chain([GameCommand::Quit, GameCommand::Quit]);
+        execute_survival_with(
+            t_block_selector(),
+            1,
+            || commands.next(),
+            Gravity::default(),
+            &mut drawer,
+            |_| {},
+        )
+    }
+
+    /// 空でないセルを含む最も上のラインのインデックスを返す．
+    fn stack_top(result: &SurvivalResult) -> usize {
+        result
+            .game
+            .field
+            .rows()
+            .position(|row| row.iter().any(|c| !c.is_empty()))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_pieces_per_garbage_row() {
+        assert_eq!(8, pieces_per_garbage_row(&Level::new()));
+        assert_eq!(7, pieces_per_garbage_row(&Level::with_rows(10)));
+        assert_eq!(2, pieces_per_garbage_row(&Level::with_rows(1000)));
+    }
+
+    #[test]
+    fn test_field_rises_after_placements() {
+        // せり上がる直前では，Tミノが2ラインずつ積み上がっているだけ
+        let before = drop_and_quit(INITIAL_PIECES_PER_ROW - 1);
+        assert_eq!(0, before.garbage_rows);
+        let height = before.game.field.height();
+        assert_eq!(
+            height - 2 * (INITIAL_PIECES_PER_ROW - 1),
+            stack_top(&before)
+        );
+
+        // 規定の数だけ設置すると，穴がひとつあいたラインが最下段に挿入され，積み上がったセルが押し上げられる
+        let after = drop_and_quit(INITIAL_PIECES_PER_ROW);
+        assert_eq!(1, after.garbage_rows);
+        assert_eq!(height - 2 * INITIAL_PIECES_PER_ROW - 1, stack_top(&after));
+        let bottom = after.game.field.rows().last().unwrap();
+        assert_eq!(1, bottom.iter().filter(|c| c.is_empty()).count());
+        let normal_cells = after
+            .game
+            .field
+            .rows()
+            .map(|row| row.iter().filter(|&&c| c == Cell::Normal).count())
+            .sum::<usize>();
+        let width = after.game.field.width();
+        assert_eq!(4 * INITIAL_PIECES_PER_ROW + width - 1, normal_cells);
+    }
+
+    #[test]
+    fn test_top_out_by_garbage() {
+        let mut drawer = HeadlessDrawer::new();
+        let mut events = vec![];
+        let result = execute_survival_with(
+            t_block_selector(),
+            1,
+            || Some(GameCommand::Drop),
+            Gravity::default(),
+            &mut drawer,
+            |event| events.push(event),
+        );
+
+        assert!(result.garbage_rows > 0);
+        assert_eq!(Some(&GameEvent::ToppedOut), events.last());
+    }
+
+    #[test]
+    fn test_garbage_under_filled_row() {
+        // 最下段が揃っているがボムがないので，爆発せずに残る
+        let field = Field::from_str_art("##########").unwrap();
+        let mut drawer = HeadlessDrawer::new();
+        let mut selector = t_block_selector();
+        let mut commands = vec![
+            GameCommand::Drop,
+            GameCommand::Drop,
+            GameCommand::Quit,
+            GameCommand::Quit,
+        ]
+        .into_iter();
+        let mut placed = 0;
+        let mut events = vec![];
+        let result = single_play::execute_gravity_game_until(
+            GameState::new(field, &mut selector),
+            selector,
+            LineClearRule::Explosion,
+            || commands.next(),
+            Gravity::default(),
+            || false,
+            |agent_field, _| {
+                placed += 1;
+                // 2つめのブロックを操作し始める前に，揃ったラインの下へせり上がりラインを挿入する
+                if placed == 2 {
+                    agent_field.insert_garbage_rows(&[0])
+                } else {
+                    Ok(agent_field)
+                }
+            },
+            &mut drawer,
+            |event| events.push(event),
+        );
+
+        // せり上がりで移動した揃ったラインを，新たに揃ったラインとして数えない
+        let height = result.field.height();
+        let filled_row = result.field.row(PosY::below(height as i8 - 2)).unwrap();
+        assert!(filled_row.iter().all(|c| !c.is_empty()));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, GameEvent::RowsFilled(_))));
+        assert_eq!(0, result.level.rows());
+    }
+
+    #[test]
+    fn test_draw_result() {
+        let result = drop_and_quit(0);
+        let mut canvas = RootCanvas::new();
+        result.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        assert!(buffer.starts_with("Garbage rows: 0"));
+        assert!(buffer.contains("Final score: 0"));
+    }
+}
@@ -0,0 +1,79 @@
+use super::animation::Drawer;
+use super::{BlockShape, BombTag, QuadrupleBlockShape, ScriptEnd, ScriptedBlockSelector};
+use crate::graphics::RootCanvas;
+
+/// 指定した形状とボム指定のブロックだけを生成し続けるブロック生成ルールを返す．
+/// # Panics
+/// ボムセルのラベルが形状の空でないセルの数以上の場合．
+pub(crate) fn repeating_selector<S: Into<BlockShape>>(
+    shape: S,
+    bomb: BombTag,
+) -> ScriptedBlockSelector {
+    ScriptedBlockSelector::new(vec![(shape.into(), bomb)], ScriptEnd::Repeat)
+        .expect("bomb label fits in the shape")
+}
+
+/// ボムを含まないTミノだけを生成し続けるブロック生成ルールを返す．
+pub(crate) fn t_block_selector() -> ScriptedBlockSelector {
+    repeating_selector(QuadrupleBlockShape::T, BombTag::None)
+}
+
+/// 端末に出力せず，フレーム間の待機もしない描画機能．
+/// 表示した回数と待機した回数を数える．`recording`で生成した場合は，表示した内容も記録する．
+pub(crate) struct HeadlessDrawer {
+    pub canvas: RootCanvas,
+    /// `show`を呼んだ回数．
+    pub show_count: usize,
+    /// `wait_frame`を呼んだ回数．
+    pub waited_frames: usize,
+    /// 表示した内容を，表示した順に並べたもの．
+    pub shown: Vec<String>,
+    /// 表示した内容を記録するか．
+    recording: bool,
+}
+
+impl HeadlessDrawer {
+    /// 最小の大きさのキャンバスに描画し，表示した内容を記録しない描画機能を返す．
+    pub fn new() -> HeadlessDrawer {
+        Self {
+            canvas: RootCanvas::new(),
+            show_count: 0,
+            waited_frames: 0,
+            shown: vec![],
+            recording: false,
+        }
+    }
+
+    /// 最小の大きさのキャンバスに描画し，表示した内容を記録する描画機能を返す．
+    pub fn recording() -> HeadlessDrawer {
+        Self {
+            recording: true,
+            ..Self::new()
+        }
+    }
+}
+
+impl Drawer for HeadlessDrawer {
+    type Canvas = RootCanvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        &mut self.canvas
+    }
+
+    fn clear(&mut self) {
+        self.canvas.clear();
+    }
+
+    fn show(&mut self) {
+        self.show_count += 1;
+        if self.recording {
+            let mut s = String::new();
+            self.canvas.construct_plain_string(&mut s);
+            self.shown.push(s);
+        }
+    }
+
+    fn wait_frame(&mut self) {
+        self.waited_frames += 1;
+    }
+}
@@ -0,0 +1,257 @@
+use super::animation::Drawer;
+use super::single_play::{
+    self, quadruple_block_generator, GameEvent, GameResult, GameState, LineClearRule,
+};
+use super::sprint::format_time;
+use super::{BlockSelector, Field, FramedField, Gravity};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::user::{Clock, GameCommand, SystemClock};
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// ウルトラの既定の制限時間．
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(120);
+
+/// 描画のたびに，フィールドの下へ残り時間を重ねて描画する描画機能．
+struct CountdownDrawer<'d, 'c, D, C> {
+    drawer: &'d mut D,
+    clock: &'c C,
+    /// 残り時間を描画する位置．
+    pos: Pos,
+    /// 制限時間．
+    duration: Duration,
+    /// 制限時間が切れる時刻．まだ計り始めていない場合は`None`．
+    deadline: &'c Cell<Option<Instant>>,
+}
+
+impl<'d, 'c, D: Drawer, C: Clock> Drawer for CountdownDrawer<'d, 'c, D, C> {
+    type Canvas = D::Canvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        self.drawer.canvas_mut()
+    }
+
+    fn clear(&mut self) {
+        self.drawer.clear();
+    }
+
+    fn show(&mut self) {
+        let remaining = match self.deadline.get() {
+            Some(deadline) => deadline.saturating_duration_since(self.clock.now()),
+            None => self.duration,
+        };
+        let color = self
+            .drawer
+            .canvas_mut()
+            .theme()
+            .cell_color(ThemeColor::Text);
+        let time = format!("Left  {}", format_time(remaining));
+        ColoredStr(time, color).draw_on_child(self.pos, self.drawer.canvas_mut());
+        self.drawer.show();
+    }
+
+    fn wait_frame(&mut self) {
+        self.drawer.wait_frame();
+    }
+
+    fn set_frame_duration(&mut self, duration: Duration) {
+        self.drawer.set_frame_duration(duration);
+    }
+
+    fn render_config(&self) -> RenderConfig {
+        self.drawer.render_config()
+    }
+}
+
+/// ウルトラの終了時の状態を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UltraResult {
+    /// ゲーム終了時の状態．
+    pub game: GameResult,
+    /// 制限時間が切れてゲームを終えた場合は`true`．ブロックを置けなくなって終えた場合は`false`．
+    pub timed_out: bool,
+}
+
+/// 終了した理由，爆発の統計と最終得点を上から順に描画する．
+impl Drawable for UltraResult {
+    fn region_size(&self) -> Movement {
+        self.game.region_size() + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        let reason = if self.timed_out {
+            "Time up!"
+        } else {
+            "Topped out"
+        };
+        ColoredStr(reason, color).draw_on_child(Pos::origin(), canvas);
+        self.game.draw_on_child(Pos::origin() + below(1), canvas);
+    }
+}
+
+/// 制限時間内にできるだけ多くの得点を稼ぐゲームを実行する．
+///
+/// ゲームは`single_play::execute_gravity_game`と同様に進み，制限時間が切れるとブロックを置けるかどうかに関わらず終わる．
+/// 制限時間は，ゲーム開始前のカウントダウンを終えて最初のブロックを操作し始めたときから計る．
+/// 制限時間はブロックの操作中に毎フレーム確かめるので，アニメーションの途中では終わらない．
+/// 残り時間はゲーム中にフィールドの下に表示する．
+/// # Returns
+/// ゲーム終了時の状態．
+pub fn execute_ultra<I, D>(duration: Duration, input: I, drawer: &mut D) -> UltraResult
+where
+    I: FnMut() -> Option<GameCommand>,
+    D: Drawer,
+{
+    execute_ultra_with(
+        quadruple_block_generator(),
+        duration,
+        input,
+        Gravity::default(),
+        &SystemClock,
+        drawer,
+        |_| {},
+    )
+}
+
+/// 指定したブロック生成ルールと落下間隔でウルトラを実行する．
+///
+/// 時間は`clock`で計る．ゲーム中に起きた出来事は，起きた順に`on_event`へ渡される．
+/// その他は`execute_ultra`と同様．
+pub fn execute_ultra_with<S, I, C, D, E>(
+    mut block_generator: S,
+    duration: Duration,
+    input: I,
+    gravity: Gravity,
+    clock: &C,
+    drawer: &mut D,
+    on_event: E,
+) -> UltraResult
+where
+    S: BlockSelector,
+    I: FnMut() -> Option<GameCommand>,
+    C: Clock,
+    D: Drawer,
+    E: FnMut(GameEvent),
+{
+    let deadline = Cell::new(None);
+    let mut timed_out = false;
+    let mut drawer = CountdownDrawer {
+        drawer,
+        clock,
+        pos: Pos::origin() + FramedField::new(&Field::empty()).region_size().y(),
+        duration,
+        deadline: &deadline,
+    };
+
+    let game = single_play::execute_gravity_game_until(
+        GameState::new(Field::empty(), &mut block_generator),
+        block_generator,
+        LineClearRule::Explosion,
+        input,
+        gravity,
+        || {
+            let now = clock.now();
+            // 最初のブロックを操作し始めたときから計る
+            let end = deadline.get().unwrap_or(now + duration);
+            deadline.set(Some(end));
+            timed_out = now >= end;
+            timed_out
+        },
+        |agent_field, _| Ok(agent_field),
+        &mut drawer,
+        on_event,
+    );
+
+    UltraResult { game, timed_out }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::HeadlessDrawer;
+    use std::cell::Cell;
+
+    /// 呼ばれるたびに1秒進む時計．
+    struct TickingClock {
+        origin: Instant,
+        seconds: Cell<u64>,
+    }
+
+    impl Clock for TickingClock {
+        fn now(&self) -> Instant {
+            let seconds = self.seconds.get();
+            self.seconds.set(seconds + 1);
+            self.origin + Duration::from_secs(seconds)
+        }
+    }
+
+    #[test]
+    fn test_time_up() {
+        let clock = TickingClock {
+            origin: Instant::now(),
+            seconds: Cell::new(0),
+        };
+        let mut drawer = HeadlessDrawer::new();
+        let mut events = vec![];
+
+        let result = execute_ultra_with(
+            quadruple_block_generator(),
+            Duration::from_secs(30),
+            || Some(GameCommand::Drop),
+            Gravity::default(),
+            &clock,
+            &mut drawer,
+            |event| events.push(event),
+        );
+
+        // ブロックを置ききる前に制限時間が切れる
+        assert!(result.timed_out);
+        assert!(events.contains(&GameEvent::BlockPlaced));
+        assert!(!events.contains(&GameEvent::ToppedOut));
+        assert!(result.game.score.points() > 0);
+    }
+
+    #[test]
+    fn test_tiny_duration() {
+        let mut drawer = HeadlessDrawer::new();
+        let result = execute_ultra(Duration::from_nanos(0), || None, &mut drawer);
+
+        assert!(result.timed_out);
+        assert_eq!(Field::empty(), result.game.field);
+    }
+
+    #[test]
+    fn test_top_out_before_time_up() {
+        let mut drawer = HeadlessDrawer::new();
+        let mut events = vec![];
+
+        let result = execute_ultra_with(
+            quadruple_block_generator(),
+            Duration::from_secs(3600),
+            || None,
+            Gravity::new(1),
+            &SystemClock,
+            &mut drawer,
+            |event| events.push(event),
+        );
+
+        assert!(!result.timed_out);
+        assert_eq!(Some(&GameEvent::ToppedOut), events.last());
+    }
+
+    #[test]
+    fn test_draw_result() {
+        let result = UltraResult {
+            game: execute_ultra(Duration::from_nanos(0), || None, &mut HeadlessDrawer::new()).game,
+            timed_out: true,
+        };
+        let mut canvas = RootCanvas::new();
+        result.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        assert!(buffer.starts_with("Time up!"));
+        assert!(buffer.contains("Final score: 0"));
+    }
+}
@@ -0,0 +1,180 @@
+use super::{
+    BlockSelector, BlockShape, BombTag, DoubleBlockShape, QuadrupleBlockShape, QuintupleBlockShape,
+    SextupleBlockShape, SingleBlockShape, TripleBlockShape,
+};
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+/// 指定した重みに比例する確率でブロックの形状を選ぶブロック生成ルール．
+/// 各ブロックの空でないセルのうち，無作為に選んだひとつがボムセルとなる．
+///
+/// 同じ重みとシード値から作った生成ルールは，ボムセルの位置を含めて同じブロック列を生成する．
+#[derive(Debug, Clone)]
+pub struct WeightedShapeSelector {
+    /// 選ばれうる形状．
+    shapes: Vec<BlockShape>,
+    /// `shapes`の各形状を選ぶ重みに従う分布．
+    distribution: WeightedIndex<u32>,
+    /// 乱数生成器の初期化に利用したシード値．
+    seed: u64,
+    rng: StdRng,
+}
+
+impl WeightedShapeSelector {
+    /// 形状とその重みの組を並べた生成ルールを，無作為なシード値で初期化して返す．
+    /// # Returns
+    /// 組が空の場合や，重みがすべて0の場合は`None`を返す．
+    pub fn new(weights: Vec<(BlockShape, u32)>) -> Option<WeightedShapeSelector> {
+        Self::with_seed(weights, rand::random())
+    }
+
+    /// 形状とその重みの組を並べた生成ルールを，指定したシード値で初期化して返す．
+    /// # Returns
+    /// 組が空の場合や，重みがすべて0の場合は`None`を返す．
+    pub fn with_seed(weights: Vec<(BlockShape, u32)>, seed: u64) -> Option<WeightedShapeSelector> {
+        let distribution = WeightedIndex::new(weights.iter().map(|&(_, weight)| weight)).ok()?;
+        Some(Self {
+            shapes: weights.into_iter().map(|(shape, _)| shape).collect(),
+            distribution,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        })
+    }
+
+    /// 1セルから5セルまでのすべての大きさの形状を混ぜた生成ルールを，指定したシード値で初期化して返す．
+    /// 大きさごとの重みの合計が等しくなるよう，形状の少ない大きさほど各形状の重みを大きくする．
+    pub fn chaos(seed: u64) -> WeightedShapeSelector {
+        // 大きさごとの形状数(1, 1, 3, 7, 17)の最小公倍数
+        const TOTAL: u32 = 357;
+        let mut weights = vec![
+            (SingleBlockShape::O.into(), TOTAL),
+            (DoubleBlockShape::ShortI.into(), TOTAL),
+        ];
+        let triples = TripleBlockShape::all().map(|shape| (shape.into(), TOTAL / 3));
+        weights.extend(triples);
+        let quadruples = QuadrupleBlockShape::all().map(|shape| (shape.into(), TOTAL / 7));
+        weights.extend(quadruples);
+        let quintuples = QuintupleBlockShape::all().map(|shape| (shape.into(), TOTAL / 17));
+        weights.extend(quintuples);
+
+        Self::with_seed(weights, seed).expect("chaos weights are not empty")
+    }
+
+    /// 5セルと6セルの大きな形状だけを混ぜた生成ルールを，指定したシード値で初期化して返す．
+    /// 大きさごとの重みの合計が等しくなるよう，形状の少ない大きさほど各形状の重みを大きくする．
+    pub fn giant(seed: u64) -> WeightedShapeSelector {
+        // 大きさごとの形状数(17, 5)の最小公倍数
+        const TOTAL: u32 = 85;
+        let quintuples = QuintupleBlockShape::all().map(|shape| (shape.into(), TOTAL / 17));
+        let sextuples = SextupleBlockShape::all().map(|shape| (shape.into(), TOTAL / 5));
+        let weights = quintuples.chain(sextuples).collect();
+
+        Self::with_seed(weights, seed).expect("giant weights are not empty")
+    }
+
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    pub const fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// 選ばれうる形状を返す．
+    pub fn shapes(&self) -> &[BlockShape] {
+        &self.shapes
+    }
+}
+
+impl BlockSelector for WeightedShapeSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        self.shapes[self.distribution.sample(&mut self.rng)]
+    }
+
+    fn select_bomb(&mut self, shape: BlockShape) -> BombTag {
+        BombTag::Single(self.rng.gen_range(0..shape.non_empty_cell_count()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn shapes(selector: &mut WeightedShapeSelector, count: usize) -> Vec<BlockShape> {
+        (0..count)
+            .map(|_| {
+                let shape = selector.select_block_shape();
+                selector.select_bomb(shape);
+                shape
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rejects_empty_weights() {
+        assert!(WeightedShapeSelector::new(vec![]).is_none());
+        let zero = vec![(SingleBlockShape::O.into(), 0)];
+        assert!(WeightedShapeSelector::new(zero).is_none());
+    }
+
+    #[test]
+    fn test_concentrated_weight() {
+        let t = QuadrupleBlockShape::T.into();
+        let weights = vec![
+            (SingleBlockShape::O.into(), 0),
+            (t, 5),
+            (QuintupleBlockShape::Star.into(), 0),
+        ];
+        let mut selector = WeightedShapeSelector::with_seed(weights, 3).unwrap();
+        assert!(shapes(&mut selector, 200).iter().all(|&shape| shape == t));
+    }
+
+    #[test]
+    fn test_weights_are_respected() {
+        let single = SingleBlockShape::O.into();
+        let double = DoubleBlockShape::ShortI.into();
+        let weights = vec![(single, 1), (double, 3)];
+        let mut selector = WeightedShapeSelector::with_seed(weights, 10007).unwrap();
+
+        let mut counts = HashMap::new();
+        for shape in shapes(&mut selector, 10000) {
+            *counts.entry(shape).or_insert(0) += 1;
+        }
+        // 期待値は2500回と7500回．十分に余裕をもたせる
+        let singles = counts[&single];
+        assert!((2200..2800).contains(&singles), "{}", singles);
+        assert_eq!(10000 - singles, counts[&double]);
+    }
+
+    #[test]
+    fn test_same_seed_same_blocks() {
+        let mut a = WeightedShapeSelector::chaos(5);
+        let mut b = WeightedShapeSelector::chaos(5);
+        for _ in 0..100 {
+            assert_eq!(a.generate_block(), b.generate_block());
+        }
+        assert_eq!(5, a.seed());
+    }
+
+    #[test]
+    fn test_chaos_mixes_every_size() {
+        let mut selector = WeightedShapeSelector::chaos(0);
+        let mut sizes = shapes(&mut selector, 500)
+            .iter()
+            .map(|shape| shape.non_empty_cell_count())
+            .collect::<Vec<_>>();
+        sizes.sort_unstable();
+        sizes.dedup();
+        assert_eq!(vec![1, 2, 3, 4, 5], sizes);
+    }
+
+    #[test]
+    fn test_giant_produces_sextuples() {
+        let mut selector = WeightedShapeSelector::giant(0);
+        let mut sizes = shapes(&mut selector, 500)
+            .iter()
+            .map(|shape| shape.non_empty_cell_count())
+            .collect::<Vec<_>>();
+        sizes.sort_unstable();
+        sizes.dedup();
+        assert_eq!(vec![5, 6], sizes);
+    }
+}
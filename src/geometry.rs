@@ -2,4 +2,4 @@ mod position;
 mod roi;
 
 pub use position::*;
-pub use roi::RegionOfInterest;
+pub use roi::{RegionOfInterest, RoiPosIter};
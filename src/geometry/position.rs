@@ -1,5 +1,8 @@
-use std::convert::TryInto;
-use std::ops::{Add, Sub};
+use crate::data_type::{TableIndex, TableSize};
+use std::cmp::Ordering;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
 
 /// 座標や移動量の表現のために利用される型．
 pub type Shift = i8;
@@ -30,6 +33,20 @@ impl PosX {
     pub fn as_positive_index(&self) -> Option<usize> {
         self.right_shift.try_into().ok()
     }
+
+    /// この点から指定した距離だけ移動した点を返す．
+    /// # Returns
+    /// 移動後の座標が`Shift`で表せない場合は`None`を返す．
+    pub fn checked_add(self, rhs: MoveX) -> Option<PosX> {
+        let right_shift = self.right_shift.checked_add(rhs.0)?;
+        Some(Self { right_shift })
+    }
+}
+
+impl fmt::Display for PosX {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.right_shift)
+    }
 }
 
 impl Add<MoveX> for PosX {
@@ -76,6 +93,22 @@ impl Sub for MoveX {
     }
 }
 
+impl Neg for MoveX {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Mul<Shift> for MoveX {
+    type Output = Self;
+
+    fn mul(self, rhs: Shift) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
 /// y方向に一次元の長さをもつ格子の座標を表す．
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct PosY {
@@ -101,6 +134,20 @@ impl PosY {
     pub fn as_positive_index(&self) -> Option<usize> {
         self.below_shift.try_into().ok()
     }
+
+    /// この点から指定した距離だけ移動した点を返す．
+    /// # Returns
+    /// 移動後の座標が`Shift`で表せない場合は`None`を返す．
+    pub fn checked_add(self, rhs: MoveY) -> Option<PosY> {
+        let below_shift = self.below_shift.checked_add(rhs.0)?;
+        Some(Self { below_shift })
+    }
+}
+
+impl fmt::Display for PosY {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.below_shift)
+    }
 }
 
 impl Add<MoveY> for PosY {
@@ -147,6 +194,22 @@ impl Sub for MoveY {
     }
 }
 
+impl Neg for MoveY {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
+impl Mul<Shift> for MoveY {
+    type Output = Self;
+
+    fn mul(self, rhs: Shift) -> Self::Output {
+        Self(self.0 * rhs)
+    }
+}
+
 /// フィールドにおけるセルの位置を表す．
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Pos(pub PosX, pub PosY);
@@ -163,6 +226,75 @@ impl Pos {
     pub const fn y(&self) -> PosY {
         self.1
     }
+
+    /// この点から指定した移動量だけ移動した点を返す．
+    /// # Returns
+    /// 移動後のx座標またはy座標が`Shift`で表せない場合は`None`を返す．
+    pub fn checked_add<T: Into<Movement>>(self, rhs: T) -> Option<Pos> {
+        let rhs = rhs.into();
+        Some(Self(self.0.checked_add(rhs.0)?, self.1.checked_add(rhs.1)?))
+    }
+
+    /// この点に上下左右で隣接する4点を，上，左，右，下の順に返す．
+    /// この順は行優先順と一致する．
+    /// # Panics on debug build
+    /// 1. 隣接する点の座標が`Shift`で表せない場合．
+    pub fn orthogonal_neighbors(&self) -> [Pos; 4] {
+        [
+            *self + above(1),
+            *self + left(1),
+            *self + right(1),
+            *self + below(1),
+        ]
+    }
+
+    /// この点に斜めに隣接する4点を，左上，右上，左下，右下の順に返す．
+    /// この順は行優先順と一致する．
+    /// # Panics on debug build
+    /// 1. 隣接する点の座標が`Shift`で表せない場合．
+    pub fn diagonal_neighbors(&self) -> [Pos; 4] {
+        [
+            *self + left(1) + above(1),
+            *self + right(1) + above(1),
+            *self + left(1) + below(1),
+            *self + right(1) + below(1),
+        ]
+    }
+
+    /// この点と指定した点の，x方向とy方向の距離の和を返す．
+    pub fn manhattan_distance(&self, other: Pos) -> u32 {
+        let dx = self.x().right_shift.abs_diff(other.x().right_shift);
+        let dy = self.y().below_shift.abs_diff(other.y().below_shift);
+        u32::from(dx) + u32::from(dy)
+    }
+
+    /// この点を，原点を左上とするテーブルのインデックスに変換して返す．
+    /// # Returns
+    /// x座標またはy座標が負の場合は`None`を返す．
+    pub fn to_table_index(&self) -> Option<TableIndex> {
+        TableIndex::try_from(*self).ok()
+    }
+}
+
+/// 位置は行優先順に並ぶ．
+/// すなわち上の行の位置ほど小さく，同じ行では左の位置ほど小さい．
+impl Ord for Pos {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.y(), self.x()).cmp(&(other.y(), other.x()))
+    }
+}
+
+impl PartialOrd for Pos {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `(3, 17)`のように，x座標とy座標を並べて表示する．
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.0, self.1)
+    }
 }
 
 impl<T: Into<Movement>> Add<T> for Pos {
@@ -191,6 +323,11 @@ impl Sub for Pos {
 pub struct Movement(pub MoveX, pub MoveY);
 
 impl Movement {
+    /// x, y方向ともに移動しない移動量を返す．
+    pub const fn zero() -> Movement {
+        Self(MoveX(0), MoveY(0))
+    }
+
     pub const fn x(&self) -> MoveX {
         self.0
     }
@@ -198,6 +335,40 @@ impl Movement {
     pub const fn y(&self) -> MoveY {
         self.1
     }
+
+    /// x方向の成分だけを取り出した移動量を返す．
+    pub const fn x_component(&self) -> Movement {
+        Self(self.0, MoveY(0))
+    }
+
+    /// y方向の成分だけを取り出した移動量を返す．
+    pub const fn y_component(&self) -> Movement {
+        Self(MoveX(0), self.1)
+    }
+
+    /// 画面上で時計回りに90度回転した移動量を返す．
+    ///
+    /// この座標系ではx軸は右向き，y軸は下向きである．
+    /// そのため`(dx, dy)`は`(-dy, dx)`に写り，右向きの移動量は下向きの移動量となる．
+    /// y軸が上向きの数学の座標系とは回転の向きが逆になることに注意．
+    pub fn rotate_clockwise(&self) -> Movement {
+        Self(MoveX(-(self.1).0), MoveY((self.0).0))
+    }
+
+    /// 画面上で反時計回りに90度回転した移動量を返す．
+    ///
+    /// `(dx, dy)`は`(dy, -dx)`に写り，右向きの移動量は上向きの移動量となる．
+    /// 座標系の向きについては`rotate_clockwise`を参照．
+    pub fn rotate_anticlockwise(&self) -> Movement {
+        Self(MoveX((self.1).0), MoveY(-(self.0).0))
+    }
+}
+
+/// `(3, -2)`のように，右方向と下方向を正とした移動量を並べて表示する．
+impl fmt::Display for Movement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", (self.0).0, (self.1).0)
+    }
 }
 
 impl From<MoveX> for Movement {
@@ -239,6 +410,71 @@ impl<T: Into<Movement>> Add<T> for Movement {
     }
 }
 
+impl Neg for Movement {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0, -self.1)
+    }
+}
+
+impl Mul<Shift> for Movement {
+    type Output = Self;
+
+    fn mul(self, rhs: Shift) -> Self::Output {
+        Self(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+/// 負の座標や移動量を，テーブルのインデックスや大きさに変換しようとしたことを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NegativeComponentError;
+
+impl fmt::Display for NegativeComponentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "negative component cannot be used as a table index or size"
+        )
+    }
+}
+
+impl std::error::Error for NegativeComponentError {}
+
+impl TryFrom<Pos> for TableIndex {
+    type Error = NegativeComponentError;
+
+    fn try_from(pos: Pos) -> Result<Self, Self::Error> {
+        match (pos.x().as_positive_index(), pos.y().as_positive_index()) {
+            (Some(x), Some(y)) => Ok(TableIndex::new(x, y)),
+            _ => Err(NegativeComponentError),
+        }
+    }
+}
+
+/// テーブルの大きさを，幅と高さだけ右下へ進む移動量に変換する．
+/// `Shift`で表せない大きさは`Shift::MAX`に切り詰める．
+impl From<TableSize> for Movement {
+    fn from(size: TableSize) -> Self {
+        let clamp = |length: usize| length.try_into().unwrap_or(Shift::MAX);
+        Self(MoveX(clamp(size.x)), MoveY(clamp(size.y)))
+    }
+}
+
+impl TryFrom<Movement> for TableSize {
+    type Error = NegativeComponentError;
+
+    fn try_from(movement: Movement) -> Result<Self, Self::Error> {
+        match (
+            movement.x().as_positive_index(),
+            movement.y().as_positive_index(),
+        ) {
+            (Some(x), Some(y)) => Ok(TableSize::new(x, y)),
+            _ => Err(NegativeComponentError),
+        }
+    }
+}
+
 pub const fn right(shift: Shift) -> MoveX {
     MoveX(shift)
 }
@@ -298,6 +534,13 @@ mod tests_move_x {
     fn test_add() {
         assert_eq!(right(5), right(2) + right(3));
     }
+
+    #[test]
+    fn test_neg_mul() {
+        assert_eq!(left(2), -right(2));
+        assert_eq!(right(3), right(1) * 3);
+        assert_eq!(left(4), right(2) * -2);
+    }
 }
 
 #[cfg(test)]
@@ -343,6 +586,13 @@ mod tests_move_y {
     fn test_add() {
         assert_eq!(below(5), below(2) + below(3));
     }
+
+    #[test]
+    fn test_neg_mul() {
+        assert_eq!(above(2), -below(2));
+        assert_eq!(below(6), below(2) * 3);
+        assert_eq!(below(5), above(5) * -1);
+    }
 }
 
 #[cfg(test)]
@@ -367,6 +617,80 @@ mod tests_movement {
         let m2 = Movement(left(3), below(4));
         assert_eq!(Movement(left(4), below(6)), m1 + m2);
     }
+
+    #[test]
+    fn test_zero() {
+        let m = Movement(left(1), below(2));
+        assert_eq!(Movement(right(0), below(0)), Movement::zero());
+        assert_eq!(m, m + Movement::zero());
+        assert_eq!(Movement::zero(), m + -m);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!("(3, -2)", (right(3) + above(2)).to_string());
+        assert_eq!("(0, 0)", Movement::zero().to_string());
+    }
+
+    #[test]
+    fn test_table_size() {
+        let size = TableSize::new(10, 20);
+        assert_eq!(right(10) + below(20), Movement::from(size));
+        assert_eq!(Ok(size), TableSize::try_from(right(10) + below(20)));
+        assert_eq!(
+            Ok(TableSize::new(0, 0)),
+            TableSize::try_from(Movement::zero())
+        );
+        assert!(TableSize::try_from(left(1) + below(1)).is_err());
+        assert!(TableSize::try_from(right(1) + above(1)).is_err());
+
+        // `Shift`で表せない大きさは切り詰める
+        let large = Movement::from(TableSize::new(1000, 127));
+        assert_eq!(right(Shift::MAX) + below(127), large);
+        let large = Movement::from(TableSize::new(usize::MAX, 128));
+        assert_eq!(right(Shift::MAX) + below(Shift::MAX), large);
+    }
+
+    #[test]
+    fn test_neg() {
+        assert_eq!(Movement(right(1), above(2)), -Movement(left(1), below(2)));
+        assert_eq!(Movement::zero(), -Movement::zero());
+    }
+
+    #[test]
+    fn test_mul() {
+        let m = Movement(right(1), above(2));
+        assert_eq!(Movement(right(3), above(6)), m * 3);
+        assert_eq!(-m, m * -1);
+        assert_eq!(m + m, m * 2);
+    }
+
+    #[test]
+    fn test_component() {
+        let m = Movement(left(1), below(2));
+        assert_eq!(Movement::from(left(1)), m.x_component());
+        assert_eq!(Movement::from(below(2)), m.y_component());
+        assert_eq!(m, m.x_component() + m.y_component());
+    }
+
+    #[test]
+    fn test_rotate() {
+        // y軸は下向きなので，右向きを時計回りに回すと下向きになる
+        let unit = Movement::from(right(1));
+        assert_eq!(Movement::from(below(1)), unit.rotate_clockwise());
+        assert_eq!(Movement::from(above(1)), unit.rotate_anticlockwise());
+        assert_eq!(
+            Movement::from(left(1)),
+            unit.rotate_clockwise().rotate_clockwise()
+        );
+
+        let m = Movement(right(2), above(1));
+        assert_eq!(Movement(right(1), below(2)), m.rotate_clockwise());
+        assert_eq!(Movement(left(1), above(2)), m.rotate_anticlockwise());
+        assert_eq!(m, m.rotate_clockwise().rotate_anticlockwise());
+        let rotated = (0..4).fold(m, |m, _| m.rotate_clockwise());
+        assert_eq!(m, rotated);
+    }
 }
 
 #[cfg(test)]
@@ -388,4 +712,106 @@ mod tests_position {
         assert_eq!(PosX::right(5), p.0);
         assert_eq!(PosY::below(10), p.1);
     }
+
+    #[test]
+    fn test_checked_add() {
+        let p = Pos(PosX::right(3), PosY::below(-4));
+        assert_eq!(
+            Some(p + right(2) + above(1)),
+            p.checked_add(right(2) + above(1))
+        );
+
+        // 型の上限や下限を超える移動は`None`となる
+        let max = Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX));
+        assert_eq!(None, max.checked_add(right(1)));
+        assert_eq!(None, max.checked_add(below(1)));
+        assert_eq!(Some(max), max.checked_add(right(0) + below(0)));
+        let min = Pos(PosX::right(Shift::MIN), PosY::below(Shift::MIN));
+        assert_eq!(None, min.checked_add(left(1)));
+        assert_eq!(None, min.checked_add(above(1)));
+        assert_eq!(Some(min + right(1)), min.checked_add(right(1)));
+    }
+
+    #[test]
+    fn test_ord() {
+        let upper_right = Pos(PosX::right(5), PosY::below(0));
+        let lower_left = Pos(PosX::right(0), PosY::below(1));
+        let lower_right = Pos(PosX::right(5), PosY::below(1));
+
+        assert!(upper_right < lower_left);
+        assert!(lower_left < lower_right);
+
+        let mut positions = vec![lower_right, upper_right, lower_left];
+        positions.sort();
+        assert_eq!(vec![upper_right, lower_left, lower_right], positions);
+    }
+
+    #[test]
+    fn test_display() {
+        let p = Pos(PosX::right(3), PosY::below(17));
+        assert_eq!("(3, 17)", p.to_string());
+        assert_eq!("3", p.x().to_string());
+        assert_eq!("17", p.y().to_string());
+        assert_eq!("(-1, 0)", Pos(PosX::right(-1), PosY::origin()).to_string());
+    }
+
+    #[test]
+    fn test_orthogonal_neighbors() {
+        let p = Pos(PosX::right(3), PosY::below(5));
+        let expected = [p + above(1), p + left(1), p + right(1), p + below(1)];
+        assert_eq!(expected, p.orthogonal_neighbors());
+        assert!(p
+            .orthogonal_neighbors()
+            .iter()
+            .all(|n| p.manhattan_distance(*n) == 1));
+        // 行優先順に並ぶ
+        assert!(p.orthogonal_neighbors().windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_diagonal_neighbors() {
+        let p = Pos(PosX::origin(), PosY::origin());
+        let expected = [
+            Pos(PosX::right(-1), PosY::below(-1)),
+            Pos(PosX::right(1), PosY::below(-1)),
+            Pos(PosX::right(-1), PosY::below(1)),
+            Pos(PosX::right(1), PosY::below(1)),
+        ];
+        assert_eq!(expected, p.diagonal_neighbors());
+        assert!(p
+            .diagonal_neighbors()
+            .iter()
+            .all(|n| p.manhattan_distance(*n) == 2));
+    }
+
+    #[test]
+    fn test_manhattan_distance() {
+        let a = Pos(PosX::right(-2), PosY::below(3));
+        let b = Pos(PosX::right(4), PosY::below(-1));
+        assert_eq!(10, a.manhattan_distance(b));
+        assert_eq!(10, b.manhattan_distance(a));
+        assert_eq!(0, a.manhattan_distance(a));
+        // 座標の上限と下限の間の距離もあふれない
+        let min = Pos(PosX::right(Shift::MIN), PosY::below(Shift::MIN));
+        let max = Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX));
+        assert_eq!(255 * 2, min.manhattan_distance(max));
+    }
+
+    #[test]
+    fn test_to_table_index() {
+        let p = Pos(PosX::right(3), PosY::below(17));
+        assert_eq!(Some(TableIndex::new(3, 17)), p.to_table_index());
+        assert_eq!(
+            Ok(TableIndex::new(0, 0)),
+            TableIndex::try_from(Pos::origin())
+        );
+        let max = Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX));
+        assert_eq!(Some(TableIndex::new(127, 127)), max.to_table_index());
+        // 負の座標はインデックスにならない
+        assert_eq!(None, Pos(PosX::right(-1), PosY::below(2)).to_table_index());
+        assert_eq!(
+            Err(NegativeComponentError),
+            TableIndex::try_from(Pos(PosX::right(2), PosY::below(-1)))
+        );
+    }
 }
@@ -13,42 +13,177 @@ impl RegionOfInterest {
     /// # Panics
     /// `size`のxまたはy成分が負の場合．
     pub fn new(left_top: Pos, size: Movement) -> RegionOfInterest {
-        debug_assert!(size.x().as_positive_index().is_some());
-        debug_assert!(size.y().as_positive_index().is_some());
+        debug_assert!(
+            size.x().as_positive_index().is_some() && size.y().as_positive_index().is_some(),
+            "negative ROI size {} at {}",
+            size,
+            left_top
+        );
         Self { left_top, size }
     }
 
     /// このROIの右下の点の座標．
     pub fn right_below(&self) -> Pos {
-        self.left_top + self.size + left(1) + above(1)
+        // 先に大きさから1を引くことで，途中の計算で座標の上限を超えないようにする
+        self.left_top + (self.size + left(1) + above(1))
     }
 
     /// 指定した点がこのROIの内部に存在するか返す．
     pub fn contains(&self, pos: Pos) -> bool {
-        let right_below = self.left_top + self.size;
+        // ROIの右端や下端が座標の上限を超える場合は，その方向には際限なく広がっているものとして扱う
+        let right_end = self.left_top.x().checked_add(self.size.x());
+        let below_end = self.left_top.y().checked_add(self.size.y());
 
         pos.x() >= self.left_top.x()
-            && pos.x() < right_below.x()
+            && right_end.is_none_or(|end| pos.x() < end)
             && pos.y() >= self.left_top.y()
-            && pos.y() < right_below.y()
+            && below_end.is_none_or(|end| pos.y() < end)
     }
 
     /// このROIに含まれる格子点を列挙する．
     /// このメソッドで返される`iterator`は，まずROIの左上の座標を返し，順に右側の座標を返していく．
     /// 最上行の列挙が終わった後，続けて2行目の点を左端から右端へ順に列挙する．
     /// この操作を最下行まで繰り返す．
-    pub fn iter_pos(&self) -> impl IntoIterator<Item = Pos> {
-        let width = self.size.x().as_positive_index().unwrap();
-        let height = self.size.y().as_positive_index().unwrap();
-        let left_top = self.left_top;
-        (0..height).map(|y| below(y as i8)).flat_map(move |y| {
-            (0..width)
-                .map(|x| right(x as i8))
-                .map(move |x| left_top + x + y)
-        })
+    pub fn iter_pos(&self) -> RoiPosIter {
+        // 座標の上限を超える点は列挙しない
+        let (left, right_end) = self.span_x();
+        let (top, below_end) = self.span_y();
+        let limit = Shift::MAX as i16 + 1;
+        let width = (right_end.min(limit) - left) as usize;
+        let height = (below_end.min(limit) - top) as usize;
+        RoiPosIter {
+            left_top: self.left_top,
+            width,
+            front: 0,
+            back: width * height,
+        }
+    }
+
+    /// このROIの大きさが0の場合，すなわち格子点をひとつも含まない場合は`true`を返す．
+    pub fn is_empty(&self) -> bool {
+        self.size.x() == right(0) || self.size.y() == below(0)
+    }
+
+    /// このROIと指定したROIの両方に含まれる領域を返す．
+    /// # Returns
+    /// ふたつのROIが重ならない場合や，いずれかの大きさが0の場合は`None`を返す．
+    pub fn intersect(&self, other: &RegionOfInterest) -> Option<RegionOfInterest> {
+        let (left, right_end) = {
+            let (a, b) = (self.span_x(), other.span_x());
+            (a.0.max(b.0), a.1.min(b.1))
+        };
+        let (top, below_end) = {
+            let (a, b) = (self.span_y(), other.span_y());
+            (a.0.max(b.0), a.1.min(b.1))
+        };
+        if left >= right_end || top >= below_end {
+            return None;
+        }
+        // 重なった領域は元のROIの内側にあるので，座標も大きさも`Shift`で表せる
+        let left_top = Pos(PosX::right(left as Shift), PosY::below(top as Shift));
+        let size = right((right_end - left) as Shift) + below((below_end - top) as Shift);
+        Some(Self::new(left_top, size))
+    }
+
+    /// このROIを，指定した範囲に収まるように切り詰めて返す．
+    /// # Returns
+    /// このROIが指定した範囲と重ならない場合は`None`を返す．
+    pub fn clamped_to(&self, bounds: &RegionOfInterest) -> Option<RegionOfInterest> {
+        self.intersect(bounds)
+    }
+
+    /// このROIと指定したROIの両方を含む，最小の矩形領域を返す．
+    /// 大きさが0のROIは無視する．
+    /// 大きさが`Shift`で表せない場合は，`Shift::MAX`に切り詰める．
+    pub fn union_bounding(&self, other: &RegionOfInterest) -> RegionOfInterest {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return *other;
+        }
+        let (left, right_end) = {
+            let (a, b) = (self.span_x(), other.span_x());
+            (a.0.min(b.0), a.1.max(b.1))
+        };
+        let (top, below_end) = {
+            let (a, b) = (self.span_y(), other.span_y());
+            (a.0.min(b.0), a.1.max(b.1))
+        };
+        let clamp = |length: i16| length.min(Shift::MAX as i16) as Shift;
+        let left_top = Pos(PosX::right(left as Shift), PosY::below(top as Shift));
+        let size = right(clamp(right_end - left)) + below(clamp(below_end - top));
+        Self::new(left_top, size)
+    }
+
+    /// このROIのx方向の範囲を，左端を含み右端を含まない区間として返す．
+    /// 座標の上限を超えても計算できるよう，`Shift`より広い型で表す．
+    fn span_x(&self) -> (i16, i16) {
+        let left = self.left_top.x().right_shift as i16;
+        let width = self.size.x().as_positive_index().unwrap_or(0) as i16;
+        (left, left + width)
+    }
+
+    /// このROIのy方向の範囲を，上端を含み下端を含まない区間として返す．
+    fn span_y(&self) -> (i16, i16) {
+        let top = self.left_top.y().below_shift as i16;
+        let height = self.size.y().as_positive_index().unwrap_or(0) as i16;
+        (top, top + height)
     }
 }
 
+/// ROIに含まれる格子点を，左上から行優先の順に列挙するイテレータ．
+/// `RegionOfInterest::iter_pos`から得られる．
+#[derive(Debug, Clone)]
+pub struct RoiPosIter {
+    left_top: Pos,
+    /// 1行あたりに列挙する点の数．
+    width: usize,
+    /// 次に前から列挙する点の，行優先の通し番号．
+    front: usize,
+    /// まだ列挙していない点の，行優先の通し番号の終端(これ自身は含まない)．
+    back: usize,
+}
+
+impl RoiPosIter {
+    /// 行優先の通し番号に対応する点を返す．
+    fn pos_at(&self, index: usize) -> Pos {
+        let x = (index % self.width) as Shift;
+        let y = (index / self.width) as Shift;
+        self.left_top + right(x) + below(y)
+    }
+}
+
+impl Iterator for RoiPosIter {
+    type Item = Pos;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let pos = self.pos_at(self.front);
+        self.front += 1;
+        Some(pos)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for RoiPosIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.pos_at(self.back))
+    }
+}
+
+impl ExactSizeIterator for RoiPosIter {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -61,7 +196,7 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
+    #[should_panic(expected = "negative ROI size (-1, 1) at (2, 3)")]
     fn test_new_negative_width() {
         let left_top = Pos(PosX::right(2), PosY::below(3));
         let size = left(1) + below(1);
@@ -105,12 +240,32 @@ mod tests {
         assert!(!roi.contains(roi.right_below() + below(1)));
     }
 
+    #[test]
+    fn test_near_shift_limit() {
+        // 右下の端が座標の上限を超えるROI
+        let left_top = Pos(PosX::right(Shift::MAX - 2), PosY::below(Shift::MAX - 1));
+        let roi = RegionOfInterest::new(left_top, right(5) + below(5));
+        assert!(roi.contains(left_top));
+        assert!(roi.contains(Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX))));
+        assert!(!roi.contains(left_top + left(1)));
+        // 表せる点だけを列挙する
+        assert_eq!(3 * 2, roi.iter_pos().len());
+        assert_eq!(3 * 2, roi.iter_pos().count());
+
+        // 右下の点は座標の上限ちょうどでも求められる
+        let roi = RegionOfInterest::new(left_top, right(3) + below(2));
+        assert_eq!(
+            Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX)),
+            roi.right_below()
+        );
+    }
+
     #[test]
     fn test_iter_pos() {
         let left_top = Pos(PosX::right(4), PosY::below(5));
         let size = right(2) + below(3);
         let roi = RegionOfInterest::new(left_top, size);
-        let mut iter = roi.iter_pos().into_iter();
+        let mut iter = roi.iter_pos();
 
         assert_eq!(Some(Pos::origin() + right(4) + below(5)), iter.next());
         assert_eq!(Some(Pos::origin() + right(5) + below(5)), iter.next());
@@ -120,4 +275,118 @@ mod tests {
         assert_eq!(Some(Pos::origin() + right(5) + below(7)), iter.next());
         assert!(iter.next().is_none());
     }
+
+    #[test]
+    fn test_iter_pos_len_and_rev() {
+        let roi = roi(-1, 2, 3, 2);
+        let mut iter = roi.iter_pos();
+        assert_eq!(6, iter.len());
+        assert_eq!((6, Some(6)), iter.size_hint());
+
+        assert_eq!(Some(Pos(PosX::right(-1), PosY::below(2))), iter.next());
+        assert_eq!(Some(Pos(PosX::right(1), PosY::below(3))), iter.next_back());
+        assert_eq!(4, iter.len());
+
+        // 前後から列挙した点が重複しない
+        let forward = roi.iter_pos().collect::<Vec<_>>();
+        let mut backward = roi.iter_pos().rev().collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(forward, backward);
+        assert_eq!(forward[1..5], iter.collect::<Vec<_>>()[..]);
+    }
+
+    #[test]
+    fn test_iter_pos_empty() {
+        for empty in &[roi(3, 3, 0, 4), roi(3, 3, 4, 0), roi(3, 3, 0, 0)] {
+            let mut iter = empty.iter_pos();
+            assert_eq!(0, iter.len());
+            assert_eq!(None, iter.next());
+            assert_eq!(None, iter.next_back());
+        }
+    }
+
+    fn roi(x: Shift, y: Shift, width: Shift, height: Shift) -> RegionOfInterest {
+        RegionOfInterest::new(
+            Pos(PosX::right(x), PosY::below(y)),
+            right(width) + below(height),
+        )
+    }
+
+    #[test]
+    fn test_intersect() {
+        let a = roi(0, 0, 4, 3);
+        let b = roi(2, 1, 5, 5);
+        assert_eq!(Some(roi(2, 1, 2, 2)), a.intersect(&b));
+        assert_eq!(Some(roi(2, 1, 2, 2)), b.intersect(&a));
+        // 一方が他方を含む場合
+        assert_eq!(Some(roi(1, 1, 1, 1)), a.intersect(&roi(1, 1, 1, 1)));
+        // 辺が接しているだけの場合は重ならない
+        assert_eq!(None, a.intersect(&roi(4, 0, 2, 2)));
+        assert_eq!(None, a.intersect(&roi(0, 3, 2, 2)));
+        // 大きさが0のROIとは重ならない
+        assert_eq!(None, a.intersect(&roi(1, 1, 0, 2)));
+    }
+
+    #[test]
+    fn test_clamped_to() {
+        let bounds = roi(0, 0, 10, 8);
+        assert_eq!(Some(roi(2, 2, 3, 3)), roi(2, 2, 3, 3).clamped_to(&bounds));
+        assert_eq!(Some(roi(0, 6, 2, 2)), roi(-3, 6, 5, 5).clamped_to(&bounds));
+        assert_eq!(None, roi(10, 0, 3, 3).clamped_to(&bounds));
+    }
+
+    #[test]
+    fn test_union_bounding() {
+        let a = roi(0, 0, 2, 2);
+        let b = roi(3, -1, 1, 1);
+        assert_eq!(roi(0, -1, 4, 3), a.union_bounding(&b));
+        assert_eq!(roi(0, -1, 4, 3), b.union_bounding(&a));
+        // 大きさが0のROIは無視する
+        assert_eq!(a, a.union_bounding(&roi(10, 10, 0, 0)));
+        assert_eq!(b, roi(-5, -5, 3, 0).union_bounding(&b));
+        // 大きさは`Shift`で表せる範囲に切り詰める
+        let far = roi(Shift::MIN, 0, 1, 1).union_bounding(&roi(Shift::MAX, 0, 1, 1));
+        assert_eq!(right(Shift::MAX) + below(1), far.size);
+    }
+
+    #[test]
+    fn test_intersect_and_union_match_point_sets() {
+        let grid = roi(-3, -3, 10, 10);
+        let points = |r: &RegionOfInterest| {
+            grid.iter_pos()
+                .filter(|&p| r.contains(p))
+                .collect::<Vec<_>>()
+        };
+        let mut rois = vec![];
+        for &(x, y) in &[(-1, 0), (1, 2), (2, -1)] {
+            for &(w, h) in &[(0, 2), (1, 1), (3, 2), (2, 4)] {
+                rois.push(roi(x, y, w, h));
+            }
+        }
+
+        for a in &rois {
+            for b in &rois {
+                let (pa, pb) = (points(a), points(b));
+                let common = pa.iter().filter(|p| pb.contains(p)).copied();
+                let common = common.collect::<Vec<_>>();
+                match a.intersect(b) {
+                    Some(i) => assert_eq!(common, points(&i)),
+                    None => assert!(common.is_empty(), "{:?} {:?}", a, b),
+                }
+
+                // 外接矩形の左上と右下は，両方のROIに含まれる点の最小と最大の座標に一致する
+                let all = pa.iter().chain(pb.iter());
+                if all.clone().next().is_none() {
+                    continue;
+                }
+                let min_x = all.clone().map(|p| p.x()).min().unwrap();
+                let min_y = all.clone().map(|p| p.y()).min().unwrap();
+                let max_x = all.clone().map(|p| p.x()).max().unwrap();
+                let max_y = all.map(|p| p.y()).max().unwrap();
+                let u = a.union_bounding(b);
+                assert_eq!(Pos(min_x, min_y), u.left_top);
+                assert_eq!(Pos(max_x, max_y), u.right_below());
+            }
+        }
+    }
 }
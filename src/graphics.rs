@@ -1,7 +1,30 @@
 mod canvas;
 mod canvas_cell;
+mod canvas_diff;
 mod colored_str;
+mod colored_text;
+mod flashing;
+mod frame;
+mod render_config;
+mod theme;
 
 pub use canvas::*;
 pub use canvas_cell::*;
+pub use canvas_diff::{dirty_runs, DirtyRun};
 pub use colored_str::ColoredStr;
+pub use colored_text::ColoredText;
+pub use flashing::Flashing;
+pub use frame::{Frame, FrameChars};
+pub use render_config::RenderConfig;
+pub use theme::{ColorSupport, Theme, ThemeColor, ThemeEntry, ThemeName};
+
+/// 描画物の領域を表す型．`geometry`と同じ型を，描画側からも参照できるように再公開する．
+///
+/// ```
+/// use rustetris::{geometry, graphics};
+///
+/// let roi: graphics::RegionOfInterest =
+///     geometry::RegionOfInterest::new(geometry::Pos::origin(), geometry::right(2) + geometry::below(3));
+/// assert_eq!(6, roi.iter_pos().count());
+/// ```
+pub use crate::geometry::RegionOfInterest;
@@ -1,15 +1,8 @@
 use super::*;
+use crate::data_type::{RowMajorTable, Table, TableMut, TableSize};
 use crate::geometry::*;
-pub use console::Color;
 use itertools::Itertools;
 
-mod consts {
-    pub const CANVAS_WIDTH: usize = 40;
-    pub const CANVAS_HEIHGT: usize = 24;
-}
-
-use consts::*;
-
 /// キャンバスを表す．
 pub trait Canvas {
     /// このキャンバス上の指定した位置に，指定したセルを描画する．
@@ -22,51 +15,184 @@ pub trait Canvas {
     /// このキャンバスと子キャンバスは状態を共有する．
     /// 子キャンバス上のセルを描画すると，それに対応したこのキャンバスのセルも変更される．
     fn child(&mut self, roi: RegionOfInterest) -> ChildCanvas<'_>;
+
+    /// このキャンバスに描画する描画物が使う配色を返す．
+    fn theme(&self) -> Theme;
 }
 
 /// 画面全体を描画対象とするキャンバスを表す．
+/// 大きさは生成時に決まり，端末の大きさに合わせる場合は`RootCanvas::with_size`を利用する．
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RootCanvas {
     /// 格子状に配置されたセル．
-    cells: [[CanvasCell; CANVAS_WIDTH]; CANVAS_HEIHGT],
+    pub(super) cells: RowMajorTable<CanvasCell>,
+    /// 描画物が使う配色．
+    theme: Theme,
 }
 
 impl RootCanvas {
+    /// キャンバスの横方向のセル数の最小値．
+    /// 各画面は，少なくともこの大きさのキャンバスに収まるように配置されている．
+    pub const MIN_WIDTH: usize = 40;
+    /// キャンバスの縦方向のセル数の最小値．
+    pub const MIN_HEIGHT: usize = 24;
+    /// キャンバスの横方向のセル数の最大値．
+    /// キャンバス上の位置は`Shift`で表すため，それを超える大きさにはしない．
+    pub const MAX_WIDTH: usize = Shift::MAX as usize;
+    /// キャンバスの縦方向のセル数の最大値．
+    pub const MAX_HEIGHT: usize = Shift::MAX as usize;
+
+    /// 最小の大きさのキャンバスを返す．
     pub fn new() -> RootCanvas {
+        Self::with_size(Self::MIN_WIDTH, Self::MIN_HEIGHT)
+    }
+
+    /// 指定したセル数のキャンバスを返す．
+    /// 横と縦のセル数は，それぞれ最小値と最大値の間に切り詰める．
+    pub fn with_size(width: usize, height: usize) -> RootCanvas {
+        let width = width.clamp(Self::MIN_WIDTH, Self::MAX_WIDTH);
+        let height = height.clamp(Self::MIN_HEIGHT, Self::MAX_HEIGHT);
+        let size = TableSize::new(width, height);
         Self {
-            cells: [[CanvasCell::default(); CANVAS_WIDTH]; CANVAS_HEIHGT],
+            cells: RowMajorTable::from_fill(CanvasCell::default(), size),
+            theme: Theme::default(),
         }
     }
 
+    /// 指定した桁数と行数の端末全体に表示するキャンバスを返す．
+    /// 1セルは2桁で表示するため，横方向のセル数は桁数の半分となる．
+    pub fn for_terminal(columns: usize, rows: usize) -> RootCanvas {
+        Self::with_size(columns / 2, rows)
+    }
+
+    /// 以降に描画する描画物が使う配色を変更する．
+    /// すでに描画したセルの色は変わらない．
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// このキャンバスの大きさを返す．
+    pub fn size(&self) -> Movement {
+        Movement::from(self.cells.size())
+    }
+
+    /// このキャンバスの内容を表示するために必要な端末の大きさ(桁数, 行数)を返す．
+    pub fn output_size(&self) -> (usize, usize) {
+        (self.cells.width() * 2, self.cells.height())
+    }
+
+    /// このキャンバス全体を表すROIを返す．
+    pub fn bounds(&self) -> RegionOfInterest {
+        RegionOfInterest::new(Pos::origin(), self.size())
+    }
+
+    /// キャンバス上のすべてのセルを，その位置とともに行優先の順に列挙する．
+    pub fn iter_pos_and_cell(&self) -> impl Iterator<Item = (Pos, &CanvasCell)> + '_ {
+        let width = self.cells.width();
+        self.cells.iter_items().enumerate().map(move |(i, cell)| {
+            let pos = Pos(
+                PosX::right((i % width) as i8),
+                PosY::below((i / width) as i8),
+            );
+            (pos, cell)
+        })
+    }
+
     /// キャンバス上のすべてのセルを既定の状態にする．
     pub fn clear(&mut self) {
-        self.cells = [[CanvasCell::default(); CANVAS_WIDTH]; CANVAS_HEIHGT];
+        for cell in self.cells.as_raw_slice_mut() {
+            *cell = CanvasCell::default();
+        }
     }
 
     /// 標準出力にこのキャンバスの内容を表示するための文字列を生成する．
-    pub fn construct_output_string(&self, buffer: &mut String) {
+    /// `config.monochrome`が`true`の場合，色を指定するためのエスケープシーケンスを一切含めない．
+    /// 最後の行の後には改行を含めないので，端末の最下行まで表示しても画面はスクロールしない．
+    pub fn construct_output_string(&self, buffer: &mut String, config: &RenderConfig) {
+        if config.monochrome {
+            self.construct_plain_string(buffer);
+            return;
+        }
+
         // まずは既存の内容を全消し
         buffer.clear();
 
         // 1行ずつ
-        for row in self.cells.iter() {
-            // 行内で連続して同じ色となっているセルをまとめて書き出す．
-            // これにより，出力文字数を減らせる．
-            for (color, group) in row.iter().group_by(|cell| cell.color).into_iter() {
-                let s: String = group.flat_map(|cell| cell.c.0.iter()).collect();
-                let content = color.as_style().apply_to(s);
-                buffer.push_str(&format!("{}", content));
-            }
+        for (i, row) in self.cells.iter_row().enumerate() {
             // 次の行へ
-            buffer.push('\n');
+            if i > 0 {
+                buffer.push('\n');
+            }
+            Self::push_styled_cells(row, buffer, config);
+        }
+    }
+
+    /// 指定した並びのセルだけを表示するための文字列を生成する．
+    /// 生成した文字列はカーソルの移動を含まないので，並びの左端へ移動してから出力する．
+    /// # Returns
+    /// 並びがこのキャンバスの範囲外にはみ出す場合は`None`を返す．
+    pub fn construct_run_string(
+        &self,
+        run: &DirtyRun,
+        buffer: &mut String,
+        config: &RenderConfig,
+    ) -> Option<()> {
+        let start = self.cells.raw_index_of(run.left.to_table_index()?)?;
+        if run.left.x().as_positive_index()? + run.len > self.cells.width() {
+            return None;
+        }
+        let cells = &self.cells.as_raw_slice()[start..start + run.len];
+
+        buffer.clear();
+        if config.monochrome {
+            buffer.extend(cells.iter().flat_map(|cell| cell.c.0.iter()));
+        } else {
+            Self::push_styled_cells(cells, buffer, config);
+        }
+        Some(())
+    }
+
+    /// 連続して同じ色と表示属性となっているセルをまとめて，色を指定するエスケープシーケンスとともに書き出す．
+    /// これにより，出力文字数を減らせる．
+    /// `config.reduced_motion`が`true`の場合，点滅の属性は書き出さない．
+    fn push_styled_cells(cells: &[CanvasCell], buffer: &mut String, config: &RenderConfig) {
+        let color_of = |cell: &CanvasCell| {
+            if config.reduced_motion {
+                let attributes = cell.color.attributes().difference(Attributes::BLINK);
+                cell.color.with_attributes(attributes)
+            } else {
+                cell.color
+            }
+        };
+        for (color, group) in cells.iter().group_by(|cell| color_of(cell)).into_iter() {
+            let s: String = group.flat_map(|cell| cell.c.0.iter()).collect();
+            color.push_styled(&s, buffer);
+        }
+    }
+
+    /// このキャンバスの内容を，色の情報を含まない文字列として生成する．
+    /// 描画結果を文字列として比較したい場合に利用する．
+    pub fn construct_plain_string(&self, buffer: &mut String) {
+        buffer.clear();
+
+        for (i, row) in self.cells.iter_row().enumerate() {
+            if i > 0 {
+                buffer.push('\n');
+            }
+            buffer.extend(row.iter().flat_map(|cell| cell.c.0.iter()));
         }
     }
 }
 
+impl Default for RootCanvas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Canvas for RootCanvas {
     fn draw_cell(&mut self, pos: Pos, cell: CanvasCell) -> Option<()> {
-        let y = pos.y().as_positive_index()?;
-        let x = pos.x().as_positive_index()?;
-        let c = self.cells.get_mut(y).and_then(|row| row.get_mut(x))?;
+        let c = self.cells.get_mut(pos.to_table_index()?)?;
         *c = cell;
         Some(())
     }
@@ -74,6 +200,10 @@ impl Canvas for RootCanvas {
     fn child(&mut self, roi: RegionOfInterest) -> ChildCanvas<'_> {
         ChildCanvas::new(self, roi)
     }
+
+    fn theme(&self) -> Theme {
+        self.theme
+    }
 }
 
 /// 子キャンバスを表す．
@@ -82,19 +212,34 @@ pub struct ChildCanvas<'root> {
     root_canvas: &'root mut RootCanvas,
     /// 親キャンバスの座標系における，この子キャンバスのROI．
     roi: RegionOfInterest,
+    /// `roi`のうち，親キャンバスの範囲に収まる部分．
+    effective_roi: Option<RegionOfInterest>,
 }
 
 impl<'root> ChildCanvas<'root> {
     pub fn new(root_canvas: &'root mut RootCanvas, roi: RegionOfInterest) -> ChildCanvas<'root> {
-        Self { root_canvas, roi }
+        let effective_roi = roi.clamped_to(&root_canvas.bounds());
+        Self {
+            root_canvas,
+            roi,
+            effective_roi,
+        }
+    }
+
+    /// 親キャンバスの座標系で，この子キャンバスに実際に描画できる領域を返す．
+    /// # Returns
+    /// この子キャンバスが親キャンバスの範囲と重ならない場合は`None`を返す．
+    pub fn effective_roi(&self) -> Option<RegionOfInterest> {
+        self.effective_roi
     }
 }
 
 impl<'root> Canvas for ChildCanvas<'root> {
     fn draw_cell(&mut self, pos: Pos, cell: CanvasCell) -> Option<()> {
         let diff = pos - Pos::origin();
-        let root_canvas_pos = self.roi.left_top + diff;
-        if self.roi.contains(root_canvas_pos) {
+        // 親キャンバスの座標で表せない位置は，ROIの外として扱う
+        let root_canvas_pos = self.roi.left_top.checked_add(diff)?;
+        if self.effective_roi?.contains(root_canvas_pos) {
             self.root_canvas.draw_cell(root_canvas_pos, cell)
         } else {
             None
@@ -106,6 +251,19 @@ impl<'root> Canvas for ChildCanvas<'root> {
         let roi = RegionOfInterest::new(left_top, roi.size);
         ChildCanvas::new(self.root_canvas, roi)
     }
+
+    fn theme(&self) -> Theme {
+        self.root_canvas.theme()
+    }
+}
+
+/// 領域の中で描画物を横方向のどこに寄せるかを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    Left,
+    /// 左右の余白が等しくならない場合は，左側の余白を1セル少なくする．
+    Center,
+    Right,
 }
 
 /// 描画可能な物体を表す．
@@ -128,11 +286,30 @@ pub trait Drawable {
         let mut child_canvas = parent_canvas.child(roi);
         self.draw(&mut child_canvas);
     }
+
+    /// 指定した領域の上端に，横方向を指定した位置に寄せてこの物体を描画する．
+    /// 領域より幅の広い物体は左端から描画し，領域からはみ出す部分は描画しない．
+    fn draw_aligned<C: Canvas>(&self, alignment: Alignment, roi: RegionOfInterest, canvas: &mut C) {
+        let roi_width = roi.size.x().as_positive_index().unwrap_or(0);
+        let width = self.region_size().x().as_positive_index().unwrap_or(0);
+        let margin = roi_width.saturating_sub(width);
+        let x = match alignment {
+            Alignment::Left => 0,
+            Alignment::Center => margin / 2,
+            Alignment::Right => margin,
+        };
+        // 描画する範囲を領域の内側に限ることで，はみ出す部分を描画しない
+        let left_top = roi.left_top + right(x as i8);
+        let size = right(width.min(roi_width) as i8) + roi.size.y();
+        let mut child_canvas = canvas.child(RegionOfInterest::new(left_top, size));
+        self.draw(&mut child_canvas);
+    }
 }
 
 #[cfg(test)]
 mod tests_root_canvas {
     use super::*;
+    use crate::data_type::TableIndex;
 
     #[test]
     fn test_draw_cell() {
@@ -148,7 +325,7 @@ mod tests_root_canvas {
         // キャンバス内のあるセルを書き換え
         root_canvas.draw_cell(pos, cell);
         // ちゃんと書き換えられた?
-        assert_eq!(cell, root_canvas.cells[3][5]);
+        assert_eq!(cell, root_canvas.cells[TableIndex::new(5, 3)]);
     }
 
     #[test]
@@ -167,7 +344,87 @@ mod tests_root_canvas {
         // キャンバスを初期状態に戻す
         root_canvas.clear();
         // ちゃんと戻った?
-        assert_eq!(CanvasCell::default(), root_canvas.cells[3][5]);
+        assert_eq!(
+            CanvasCell::default(),
+            root_canvas.cells[TableIndex::new(5, 3)]
+        );
+    }
+
+    #[test]
+    fn test_construct_plain_string() {
+        let mut root_canvas = RootCanvas::new();
+        let cell = CanvasCell::new(SquareChar::new('a', 'b'), CanvasCellColor::default());
+        root_canvas.draw_cell(Pos::origin() + right(1) + below(1), cell);
+
+        let mut s = String::new();
+        root_canvas.construct_plain_string(&mut s);
+        let lines = s.lines().collect::<Vec<_>>();
+
+        assert_eq!(RootCanvas::MIN_HEIGHT, lines.len());
+        assert_eq!(" ".repeat(RootCanvas::MIN_WIDTH * 2), lines[0]);
+        assert_eq!(
+            format!("  ab{}", " ".repeat(RootCanvas::MIN_WIDTH * 2 - 4)),
+            lines[1]
+        );
+    }
+
+    #[test]
+    fn test_construct_monochrome_output_string() {
+        let mut root_canvas = RootCanvas::new();
+        let color = CanvasCellColor::new(Color::Red, Color::Blue);
+        let cell = CanvasCell::new(SquareChar::new('a', 'b'), color);
+        root_canvas.draw_cell(Pos::origin() + right(1) + below(1), cell);
+
+        let config = RenderConfig {
+            monochrome: true,
+            ..RenderConfig::default()
+        };
+        let mut s = String::new();
+        root_canvas.construct_output_string(&mut s, &config);
+
+        assert!(!s.contains('\x1b'));
+        let mut plain = String::new();
+        root_canvas.construct_plain_string(&mut plain);
+        assert_eq!(plain, s);
+    }
+
+    #[test]
+    fn test_output_string_without_trailing_newline() {
+        let root_canvas = RootCanvas::new();
+        let mut s = String::new();
+        root_canvas.construct_output_string(&mut s, &RenderConfig::default());
+
+        // 最下行の後で改行すると画面がスクロールするので，改行は行の間にだけ含める
+        assert!(!s.ends_with('\n'));
+        assert_eq!(RootCanvas::MIN_HEIGHT - 1, s.matches('\n').count());
+    }
+
+    #[test]
+    fn test_push_styled_cells() {
+        let color = CanvasCellColor::new(Color::Red, Color::Black);
+        let blink = color.with_attributes(Attributes::BLINK);
+        let c = SquareChar::new('a', 'b');
+        let cells = [
+            CanvasCell::new(c, color),
+            CanvasCell::new(c, color),
+            CanvasCell::new(c, blink),
+            CanvasCell::new(c, color),
+        ];
+
+        // 色が同じでも，表示属性が異なるセルは別々に書き出す
+        let mut s = String::new();
+        RootCanvas::push_styled_cells(&cells, &mut s, &RenderConfig::default());
+        assert_eq!(3, s.matches("\x1b[0m").count());
+        assert_eq!("abababab", console::strip_ansi_codes(&s));
+
+        // 点滅を抑える場合は，点滅の属性を除いた色でまとめる
+        let config = RenderConfig {
+            reduced_motion: true,
+            ..RenderConfig::default()
+        };
+        let mut s = String::new();
+        RootCanvas::push_styled_cells(&cells, &mut s, &config);
+        assert_eq!("\x1b[31;40mabababab\x1b[0m", s);
     }
 
     #[test]
@@ -181,11 +438,106 @@ mod tests_root_canvas {
 
         assert_eq!(roi, child.roi);
     }
+
+    #[test]
+    fn test_iter_pos_and_cell() {
+        let mut root_canvas = RootCanvas::with_size(50, 30);
+        let cell = CanvasCell::new(SquareChar::new('a', 'b'), CanvasCellColor::default());
+        let pos = Pos::origin() + right(45) + below(2);
+        root_canvas.draw_cell(pos, cell);
+
+        assert_eq!(50 * 30, root_canvas.iter_pos_and_cell().count());
+        let drawn = root_canvas
+            .iter_pos_and_cell()
+            .filter(|(_, c)| **c == cell)
+            .map(|(p, _)| p)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![pos], drawn);
+    }
+
+    #[test]
+    fn test_construct_run_string() {
+        let mut root_canvas = RootCanvas::new();
+        let color = CanvasCellColor::new(Color::Red, Color::Blue);
+        let left = Pos::origin() + right(3) + below(2);
+        root_canvas.draw_cell(left, CanvasCell::new(SquareChar::new('a', 'b'), color));
+        root_canvas.draw_cell(
+            left + right(1),
+            CanvasCell::new(SquareChar::new('c', 'd'), color),
+        );
+
+        let config = RenderConfig {
+            monochrome: true,
+            ..RenderConfig::default()
+        };
+        let run = DirtyRun { left, len: 3 };
+        let mut s = String::new();
+        assert!(root_canvas
+            .construct_run_string(&run, &mut s, &config)
+            .is_some());
+        assert_eq!("abcd  ", s);
+
+        // 色付きの場合も，表示される文字は変わらない
+        assert!(root_canvas
+            .construct_run_string(&run, &mut s, &RenderConfig::default())
+            .is_some());
+        assert_eq!("abcd  ", console::strip_ansi_codes(&s));
+
+        // キャンバスからはみ出す並びは書き出さない
+        let run = DirtyRun {
+            left: Pos::origin() + right(RootCanvas::MIN_WIDTH as i8 - 1),
+            len: 2,
+        };
+        assert!(root_canvas
+            .construct_run_string(&run, &mut s, &config)
+            .is_none());
+    }
+
+    #[test]
+    fn test_with_size() {
+        let cell = CanvasCell::new(SquareChar::new('a', 'b'), CanvasCellColor::default());
+        for &(width, height) in &[(40, 24), (60, 30), (100, 50)] {
+            let mut root_canvas = RootCanvas::with_size(width, height);
+            assert_eq!(right(width as i8) + below(height as i8), root_canvas.size());
+            assert_eq!((width * 2, height), root_canvas.output_size());
+
+            // 右下の隅には描画でき，その外側には描画できない
+            let corner = Pos::origin() + right(width as i8 - 1) + below(height as i8 - 1);
+            assert!(root_canvas.draw_cell(corner, cell).is_some());
+            assert!(root_canvas.draw_cell(corner + right(1), cell).is_none());
+            assert!(root_canvas.draw_cell(corner + below(1), cell).is_none());
+            assert!(root_canvas
+                .draw_cell(Pos::origin() + left(1), cell)
+                .is_none());
+
+            let mut s = String::new();
+            root_canvas.construct_plain_string(&mut s);
+            let lines = s.lines().collect::<Vec<_>>();
+            assert_eq!(height, lines.len());
+            assert!(lines.iter().all(|line| line.len() == width * 2));
+            assert!(lines[height - 1].ends_with("ab"));
+        }
+    }
+
+    #[test]
+    fn test_with_size_clamped() {
+        // 最小値より小さい大きさは，最小値に切り上げる
+        let root_canvas = RootCanvas::with_size(10, 5);
+        let min_size = right(RootCanvas::MIN_WIDTH as i8) + below(RootCanvas::MIN_HEIGHT as i8);
+        assert_eq!(min_size, root_canvas.size());
+        // 位置で表せない大きさは，最大値に切り詰める
+        let root_canvas = RootCanvas::with_size(500, 300);
+        assert_eq!(right(Shift::MAX) + below(Shift::MAX), root_canvas.size());
+        // 端末の2桁が1セルに対応する
+        let root_canvas = RootCanvas::for_terminal(161, 40);
+        assert_eq!(right(80) + below(40), root_canvas.size());
+    }
 }
 
 #[cfg(test)]
 mod tests_child_canvas {
     use super::*;
+    use crate::data_type::TableIndex;
 
     #[test]
     fn test_draw_cell() {
@@ -208,7 +560,7 @@ mod tests_child_canvas {
         // 描画
         child.draw_cell(pos, cell);
         // 親キャンバスのセルが書き換わっているはず
-        assert_eq!(cell, root_canvas.cells[3 + 3][2 + 5]);
+        assert_eq!(cell, root_canvas.cells[TableIndex::new(2 + 5, 3 + 3)]);
     }
 
     #[test]
@@ -235,14 +587,10 @@ mod tests_child_canvas {
         // 親キャンバスのセルは書き換わらないはず
         let cells1 = RootCanvas::new()
             .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
-            .collect::<Vec<_>>();
-        let cells2 = root_canvas
-            .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
+            .iter_items()
+            .copied()
             .collect::<Vec<_>>();
+        let cells2 = root_canvas.cells.iter_items().copied().collect::<Vec<_>>();
         assert_eq!(cells1, cells2);
     }
 
@@ -270,14 +618,10 @@ mod tests_child_canvas {
         // 親キャンバスのセルは書き換わらないはず
         let cells1 = RootCanvas::new()
             .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
-            .collect::<Vec<_>>();
-        let cells2 = root_canvas
-            .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
+            .iter_items()
+            .copied()
             .collect::<Vec<_>>();
+        let cells2 = root_canvas.cells.iter_items().copied().collect::<Vec<_>>();
         assert_eq!(cells1, cells2);
     }
 
@@ -305,14 +649,10 @@ mod tests_child_canvas {
         // 親キャンバスのセルは書き換わらないはず
         let cells1 = RootCanvas::new()
             .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
-            .collect::<Vec<_>>();
-        let cells2 = root_canvas
-            .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
+            .iter_items()
+            .copied()
             .collect::<Vec<_>>();
+        let cells2 = root_canvas.cells.iter_items().copied().collect::<Vec<_>>();
         assert_eq!(cells1, cells2);
     }
 
@@ -340,14 +680,10 @@ mod tests_child_canvas {
         // 親キャンバスのセルは書き換わらないはず
         let cells1 = RootCanvas::new()
             .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
-            .collect::<Vec<_>>();
-        let cells2 = root_canvas
-            .cells
-            .iter()
-            .flat_map(|row| row.to_vec())
+            .iter_items()
+            .copied()
             .collect::<Vec<_>>();
+        let cells2 = root_canvas.cells.iter_items().copied().collect::<Vec<_>>();
         assert_eq!(cells1, cells2);
     }
 
@@ -384,6 +720,52 @@ mod tests_child_canvas {
         grandchild.draw_cell(Pos::origin() + right(1) + below(1), cell);
 
         // 親キャンバスのセルが書き換わっているはず
-        assert_eq!(cell, root_canvas.cells[3 + 3 + 1][2 + 2 + 1]);
+        assert_eq!(
+            cell,
+            root_canvas.cells[TableIndex::new(2 + 2 + 1, 3 + 3 + 1)]
+        );
+    }
+
+    #[test]
+    fn test_child_draw_cell_near_shift_limit() {
+        let mut root_canvas = RootCanvas::new();
+        let roi =
+            RegionOfInterest::new(Pos::origin() + right(100) + below(100), right(5) + below(5));
+        let mut child = root_canvas.child(roi);
+
+        let cell = CanvasCell::new(SquareChar::new('a', 'a'), CanvasCellColor::default());
+        // 親キャンバスの座標で表せない位置には描画しない
+        let far = Pos(PosX::right(Shift::MAX), PosY::below(Shift::MAX));
+        assert!(child.draw_cell(far, cell).is_none());
+    }
+
+    #[test]
+    fn test_effective_roi() {
+        let mut root_canvas = RootCanvas::new();
+        let (width, height) = (RootCanvas::MIN_WIDTH as i8, RootCanvas::MIN_HEIGHT as i8);
+
+        // 親キャンバスに収まる場合は，ROI全体に描画できる
+        let inside =
+            RegionOfInterest::new(Pos::origin() + right(2) + below(3), right(5) + below(6));
+        assert_eq!(Some(inside), root_canvas.child(inside).effective_roi());
+
+        // 親キャンバスの右下からはみ出す場合は，収まる部分だけに描画できる
+        let overlapping = RegionOfInterest::new(
+            Pos::origin() + right(width - 2) + below(height - 1),
+            right(5) + below(5),
+        );
+        let mut child = root_canvas.child(overlapping);
+        let expected = RegionOfInterest::new(overlapping.left_top, right(2) + below(1));
+        assert_eq!(Some(expected), child.effective_roi());
+        let cell = CanvasCell::new(SquareChar::new('a', 'a'), CanvasCellColor::default());
+        assert!(child.draw_cell(Pos::origin() + right(1), cell).is_some());
+        assert!(child.draw_cell(Pos::origin() + right(2), cell).is_none());
+
+        // 親キャンバスの外にある場合は，どこにも描画できない
+        let outside =
+            RegionOfInterest::new(Pos::origin() + left(5) + below(2), right(5) + below(5));
+        let mut child = root_canvas.child(outside);
+        assert_eq!(None, child.effective_roi());
+        assert!(child.draw_cell(Pos::origin(), cell).is_none());
     }
 }
@@ -1,5 +1,6 @@
-pub use console::Color;
-use console::Style;
+use super::{Canvas, Drawable};
+use crate::geometry::*;
+use std::ops::BitOr;
 
 /// 表示用の文字を表す．
 /// これは表示した際に，正方形領域内に描画されることを保証する．
@@ -16,26 +17,150 @@ impl SquareChar {
     }
 }
 
-/// 表示する際の色を表す．
+/// 表示する色を表す．
+/// 基本の8色に加えて，256色を表示できる端末向けに色の番号でも指定できる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    /// 256色のうち，指定した番号の色．
+    Color256(u8),
+}
+
+impl Color {
+    /// この色を指定するエスケープシーケンスの引数を返す．
+    /// # Params
+    /// 1. `base` 基本の8色の最初の色を指定する値．前景色は30，背景色は40．
+    fn sgr_parameter(&self, base: u8) -> String {
+        use Color::*;
+
+        let offset = match self {
+            Black => 0,
+            Red => 1,
+            Green => 2,
+            Yellow => 3,
+            Blue => 4,
+            Magenta => 5,
+            Cyan => 6,
+            White => 7,
+            Color256(n) => return format!("{};5;{}", base + 8, n),
+        };
+        (base + offset).to_string()
+    }
+}
+
+/// 文字の太さや点滅など，色以外の表示属性の組み合わせを表す．
+/// 複数の属性は`|`で組み合わせる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Attributes(u8);
+
+impl Attributes {
+    /// 太字．
+    pub const BOLD: Attributes = Attributes(1 << 0);
+    /// 薄い文字．
+    pub const DIM: Attributes = Attributes(1 << 1);
+    /// 点滅する文字．
+    pub const BLINK: Attributes = Attributes(1 << 2);
+
+    /// 属性をひとつも含まない組み合わせを返す．
+    pub const fn empty() -> Attributes {
+        Self(0)
+    }
+
+    /// 属性をひとつも含まない場合は`true`を返す．
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// 指定した属性をすべて含む場合は`true`を返す．
+    pub const fn contains(&self, other: Attributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// 両方の属性を合わせた組み合わせを返す．
+    pub const fn union(self, other: Attributes) -> Attributes {
+        Self(self.0 | other.0)
+    }
+
+    /// 指定した属性を取り除いた組み合わせを返す．
+    pub const fn difference(self, other: Attributes) -> Attributes {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl BitOr for Attributes {
+    type Output = Attributes;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(rhs)
+    }
+}
+
+/// 表示する際の色と表示属性を表す．
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct CanvasCellColor {
     /// 前景色．
     foreground: Color,
     /// 背景色．
     background: Color,
+    /// 表示属性．
+    attributes: Attributes,
 }
 
 impl CanvasCellColor {
+    /// 表示属性を持たない色を返す．
     pub const fn new(foreground: Color, background: Color) -> CanvasCellColor {
         Self {
             foreground,
             background,
+            attributes: Attributes::empty(),
         }
     }
 
-    /// 標準出力用でこの色を反映するためのスタイルを返す．
-    pub(super) fn as_style(&self) -> Style {
-        Style::default().fg(self.foreground).bg(self.background)
+    /// 表示属性を指定したものに置き換えた色を返す．
+    pub const fn with_attributes(self, attributes: Attributes) -> CanvasCellColor {
+        Self { attributes, ..self }
+    }
+
+    /// 前景色を返す．
+    pub const fn foreground(&self) -> Color {
+        self.foreground
+    }
+
+    /// 背景色を返す．
+    pub const fn background(&self) -> Color {
+        self.background
+    }
+
+    /// 表示属性を返す．
+    pub const fn attributes(&self) -> Attributes {
+        self.attributes
+    }
+
+    /// 指定した文字列をこの色と表示属性で表示するためのエスケープシーケンスを付けて，バッファに書き出す．
+    /// 書き出した後は，色と表示属性を既定に戻す．
+    pub(super) fn push_styled(&self, s: &str, buffer: &mut String) {
+        let mut parameters = vec![
+            self.foreground.sgr_parameter(30),
+            self.background.sgr_parameter(40),
+        ];
+        let attribute_parameters = [
+            (Attributes::BOLD, "1"),
+            (Attributes::DIM, "2"),
+            (Attributes::BLINK, "5"),
+        ];
+        for (attribute, parameter) in attribute_parameters {
+            if self.attributes.contains(attribute) {
+                parameters.push(parameter.to_owned());
+            }
+        }
+        buffer.push_str(&format!("\x1b[{}m{}\x1b[0m", parameters.join(";"), s));
     }
 }
 
@@ -60,6 +185,16 @@ impl CanvasCell {
     }
 }
 
+impl Drawable for CanvasCell {
+    fn region_size(&self) -> Movement {
+        right(1) + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        canvas.draw_cell(Pos::origin(), *self);
+    }
+}
+
 impl Default for CanvasCell {
     fn default() -> Self {
         Self {
@@ -94,4 +229,51 @@ mod tests {
     fn test_square_char_invalid_right() {
         let _c = SquareChar::new('a', '\n');
     }
+
+    #[test]
+    fn test_attributes() {
+        let attributes = Attributes::BOLD | Attributes::BLINK;
+        assert!(attributes.contains(Attributes::BOLD));
+        assert!(attributes.contains(Attributes::BLINK));
+        assert!(!attributes.contains(Attributes::DIM));
+        assert!(!attributes.contains(Attributes::BOLD | Attributes::DIM));
+        assert_eq!(Attributes::BLINK, attributes.difference(Attributes::BOLD));
+        assert!(Attributes::empty().is_empty());
+        assert_eq!(Attributes::empty(), Attributes::default());
+        assert!(attributes.contains(Attributes::empty()));
+    }
+
+    #[test]
+    fn test_color_attributes() {
+        let color = CanvasCellColor::new(Color::Red, Color::Black);
+        assert!(color.attributes().is_empty());
+
+        // 表示属性だけが異なる色やセルは，異なるものとして扱う
+        let dim = color.with_attributes(Attributes::DIM);
+        assert_ne!(color, dim);
+        assert_eq!(Color::Red, dim.foreground());
+        assert_eq!(Attributes::DIM, dim.attributes());
+        let c = SquareChar::new('a', 'b');
+        assert_ne!(CanvasCell::new(c, color), CanvasCell::new(c, dim));
+    }
+
+    #[test]
+    fn test_push_styled() {
+        let render = |color: CanvasCellColor| {
+            let mut buffer = String::new();
+            color.push_styled("ab", &mut buffer);
+            buffer
+        };
+        let color = CanvasCellColor::new(Color::Red, Color::Black);
+        assert_eq!("\x1b[31;40mab\x1b[0m", render(color));
+
+        let styled =
+            render(color.with_attributes(Attributes::BOLD | Attributes::DIM | Attributes::BLINK));
+        assert_eq!("\x1b[31;40;1;2;5mab\x1b[0m", styled);
+        assert_eq!("ab", console::strip_ansi_codes(&styled));
+
+        // 256色の番号で指定した色
+        let color256 = CanvasCellColor::new(Color::Color256(196), Color::Color256(16));
+        assert_eq!("\x1b[38;5;196;48;5;16mab\x1b[0m", render(color256));
+    }
 }
@@ -0,0 +1,132 @@
+use super::*;
+use crate::geometry::*;
+
+/// 前回の表示から内容が変わった，同じ行で連続するセルの並びを表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirtyRun {
+    /// 並びの左端のセルの位置．
+    pub left: Pos,
+    /// 並びに含まれるセルの数．
+    pub len: usize,
+}
+
+/// ふたつのキャンバスを比べ，内容の異なるセルを行ごとの連続した並びとして返す．
+/// 並びは左上から行優先の順に並ぶ．
+/// # Returns
+/// ふたつのキャンバスの大きさが異なる場合は`None`を返す．
+pub fn dirty_runs(previous: &RootCanvas, current: &RootCanvas) -> Option<Vec<DirtyRun>> {
+    if previous.size() != current.size() {
+        return None;
+    }
+
+    let mut runs: Vec<DirtyRun> = vec![];
+    let changed_positions = previous
+        .iter_pos_and_cell()
+        .zip(current.iter_pos_and_cell())
+        .filter(|((_, previous_cell), (_, current_cell))| previous_cell != current_cell)
+        .map(|(_, (pos, _))| pos);
+    for pos in changed_positions {
+        match runs.last_mut() {
+            // 直前の並びの右隣であれば，その並びを伸ばす
+            Some(run)
+                if run.left.y() == pos.y() && run.left.x() + right(run.len as i8) == pos.x() =>
+            {
+                run.len += 1
+            }
+            _ => runs.push(DirtyRun { left: pos, len: 1 }),
+        }
+    }
+    Some(runs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(c: char) -> CanvasCell {
+        CanvasCell::new(SquareChar::new(c, c), CanvasCellColor::default())
+    }
+
+    fn pos(x: i8, y: i8) -> Pos {
+        Pos(PosX::right(x), PosY::below(y))
+    }
+
+    #[test]
+    fn test_no_change() {
+        let mut previous = RootCanvas::new();
+        previous.draw_cell(pos(3, 4), cell('a'));
+        let mut current = RootCanvas::new();
+        current.draw_cell(pos(3, 4), cell('a'));
+        assert_eq!(Some(vec![]), dirty_runs(&previous, &current));
+    }
+
+    #[test]
+    fn test_runs() {
+        let previous = RootCanvas::new();
+        let mut current = RootCanvas::new();
+        for x in 2..5 {
+            current.draw_cell(pos(x, 1), cell('a'));
+        }
+        current.draw_cell(pos(7, 1), cell('b'));
+        // 行末と次の行の先頭は，隣り合っていても別の並びとなる
+        let last_x = RootCanvas::MIN_WIDTH as i8 - 1;
+        current.draw_cell(pos(last_x, 2), cell('c'));
+        current.draw_cell(pos(0, 3), cell('c'));
+
+        let expected = vec![
+            DirtyRun {
+                left: pos(2, 1),
+                len: 3,
+            },
+            DirtyRun {
+                left: pos(7, 1),
+                len: 1,
+            },
+            DirtyRun {
+                left: pos(last_x, 2),
+                len: 1,
+            },
+            DirtyRun {
+                left: pos(0, 3),
+                len: 1,
+            },
+        ];
+        assert_eq!(Some(expected), dirty_runs(&previous, &current));
+    }
+
+    #[test]
+    fn test_color_change() {
+        let mut previous = RootCanvas::new();
+        previous.draw_cell(pos(0, 0), cell('a'));
+        let mut current = RootCanvas::new();
+        let color = CanvasCellColor::new(Color::Red, Color::Black);
+        current.draw_cell(pos(0, 0), CanvasCell::new(SquareChar::new('a', 'a'), color));
+
+        let expected = vec![DirtyRun {
+            left: pos(0, 0),
+            len: 1,
+        }];
+        assert_eq!(Some(expected), dirty_runs(&previous, &current));
+    }
+
+    #[test]
+    fn test_whole_row() {
+        let previous = RootCanvas::new();
+        let mut current = RootCanvas::new();
+        for x in 0..RootCanvas::MIN_WIDTH as i8 {
+            current.draw_cell(pos(x, 5), cell('a'));
+        }
+        let expected = vec![DirtyRun {
+            left: pos(0, 5),
+            len: RootCanvas::MIN_WIDTH,
+        }];
+        assert_eq!(Some(expected), dirty_runs(&previous, &current));
+    }
+
+    #[test]
+    fn test_different_size() {
+        let previous = RootCanvas::new();
+        let current = RootCanvas::with_size(50, 30);
+        assert_eq!(None, dirty_runs(&previous, &current));
+    }
+}
@@ -1,27 +1,30 @@
 use super::*;
 use crate::geometry::*;
-use itertools::Itertools;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ColoredStr<S>(pub S, pub CanvasCellColor);
 
+impl<S: AsRef<str>> ColoredStr<S> {
+    /// 指定した領域の上端の中央にこの文字列を描画する．
+    /// 領域に収まらない部分は描画しない．
+    pub fn draw_centered_in<C: Canvas>(&self, roi: RegionOfInterest, canvas: &mut C) {
+        self.draw_aligned(Alignment::Center, roi, canvas);
+    }
+}
+
 impl<S: AsRef<str>> Drawable for ColoredStr<S> {
+    /// 空の文字列は何も描画しないので，大きさも0とする．
     fn region_size(&self) -> Movement {
-        let square_char_len = (self.0.as_ref().len() + 1) / 2;
+        let square_char_len = self.0.as_ref().chars().count().div_ceil(2);
+        if square_char_len == 0 {
+            return Movement::zero();
+        }
         right(square_char_len as i8) + below(1)
     }
 
     fn draw<C: Canvas>(&self, canvas: &mut C) {
-        self.0
-            .as_ref()
-            .chars()
-            .chunks(2)
+        square_chars(self.0.as_ref())
             .into_iter()
-            .map(|mut chunk| {
-                let left = chunk.next().unwrap();
-                let right = chunk.next().unwrap_or(' ');
-                SquareChar([left, right])
-            })
             .map(|c| CanvasCell::new(c, self.1))
             .enumerate()
             .for_each(|(i, cell)| {
@@ -31,6 +34,16 @@ impl<S: AsRef<str>> Drawable for ColoredStr<S> {
     }
 }
 
+/// 文字列を先頭から2文字ずつ組にしたセルの文字を返す．
+/// 文字数が奇数の場合，最後の組の右側は空白とする．
+pub(super) fn square_chars(s: &str) -> Vec<SquareChar> {
+    let chars = s.chars().collect::<Vec<_>>();
+    chars
+        .chunks(2)
+        .map(|pair| SquareChar([pair[0], pair.get(1).copied().unwrap_or(' ')]))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -47,4 +60,67 @@ mod tests {
 
         assert_eq!(right(3) + below(1), size);
     }
+
+    #[test]
+    fn test_region_size_counts_chars() {
+        // 大きさはバイト数ではなく文字数で決まる
+        let s = ColoredStr("あいう", CanvasCellColor::default());
+        assert_eq!(right(2) + below(1), s.region_size());
+
+        let single = ColoredStr("a", CanvasCellColor::default());
+        assert_eq!(right(1) + below(1), single.region_size());
+
+        let empty = ColoredStr("", CanvasCellColor::default());
+        assert_eq!(Movement::zero(), empty.region_size());
+    }
+
+    #[test]
+    fn test_draw_odd_length() {
+        let mut canvas = RootCanvas::new();
+        ColoredStr("abc", CanvasCellColor::default()).draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        assert!(buffer.starts_with("abc   "));
+    }
+
+    /// 指定した領域に寄せて描画した結果の，1行目を返す．
+    fn render_aligned(text: &str, alignment: Alignment, x: i8, width: i8) -> String {
+        let mut canvas = RootCanvas::new();
+        let roi = RegionOfInterest::new(Pos::origin() + right(x), right(width) + below(1));
+        ColoredStr(text, CanvasCellColor::default()).draw_aligned(alignment, roi, &mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        buffer.lines().next().unwrap().trim_end().to_owned()
+    }
+
+    #[test]
+    fn test_draw_centered_in() {
+        let mut canvas = RootCanvas::new();
+        let roi = RegionOfInterest::new(Pos::origin() + right(1) + below(2), right(6) + below(1));
+        ColoredStr("abcd", CanvasCellColor::default()).draw_centered_in(roi, &mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        // 6セルの領域に2セルの文字列を置くと，左右に2セルずつ余白ができる
+        assert_eq!("      abcd", buffer.lines().nth(2).unwrap().trim_end());
+    }
+
+    #[test]
+    fn test_draw_aligned() {
+        // 余白が偶数の場合
+        assert_eq!("  abcd", render_aligned("abcd", Alignment::Center, 0, 4));
+        // 余白が奇数の場合は，左側の余白を少なくする
+        assert_eq!("  abc", render_aligned("abc", Alignment::Center, 0, 5));
+        assert_eq!("      ab", render_aligned("ab", Alignment::Center, 2, 4));
+        assert_eq!("    ab", render_aligned("ab", Alignment::Left, 2, 4));
+        assert_eq!("          ab", render_aligned("ab", Alignment::Right, 2, 4));
+    }
+
+    #[test]
+    fn test_draw_aligned_clips_wide_text() {
+        // 領域より幅の広い文字列は，左端から領域に収まる部分だけを描画する
+        let text = "abcdefghij";
+        assert_eq!("  abcdef", render_aligned(text, Alignment::Center, 1, 3));
+        assert_eq!("  abcdef", render_aligned(text, Alignment::Right, 1, 3));
+        assert_eq!("", render_aligned(text, Alignment::Center, 1, 0));
+    }
 }
@@ -0,0 +1,135 @@
+use super::colored_str::square_chars;
+use super::*;
+use crate::geometry::*;
+
+/// 複数行にわたる文字列を，同じ色で描画する描画物を表す．
+/// 文字列中の`\n`で改行し，折り返す幅を指定した場合はその幅を超える行も折り返す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColoredText<S> {
+    text: S,
+    color: CanvasCellColor,
+    /// 1行に並べるセルの最大数．`None`の場合は折り返さない．
+    wrap_width: Option<usize>,
+}
+
+impl<S: AsRef<str>> ColoredText<S> {
+    /// 折り返さずに描画する文字列を返す．
+    pub fn new(text: S, color: CanvasCellColor) -> ColoredText<S> {
+        Self {
+            text,
+            color,
+            wrap_width: None,
+        }
+    }
+
+    /// 1行あたり指定したセル数で折り返して描画する文字列を返す．
+    /// 1セルには2文字が入る．
+    /// # Returns
+    /// 幅に0が指定された場合は`None`を返す．
+    pub fn wrapped(self, width: usize) -> Option<ColoredText<S>> {
+        if width == 0 {
+            return None;
+        }
+        Some(Self {
+            wrap_width: Some(width),
+            ..self
+        })
+    }
+
+    /// 表示する行を，上から順にセルの文字の並びとして返す．
+    /// 空の文字列は1行も含まない．
+    fn lines(&self) -> Vec<Vec<SquareChar>> {
+        let text = self.text.as_ref();
+        if text.is_empty() {
+            return vec![];
+        }
+        text.split('\n')
+            .flat_map(|line| {
+                let chars = square_chars(line);
+                match self.wrap_width {
+                    // 空行も1行として数える
+                    Some(width) if !chars.is_empty() => {
+                        chars.chunks(width).map(<[_]>::to_vec).collect()
+                    }
+                    _ => vec![chars],
+                }
+            })
+            .collect()
+    }
+}
+
+impl<S: AsRef<str>> Drawable for ColoredText<S> {
+    fn region_size(&self) -> Movement {
+        let lines = self.lines();
+        let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+        right(width as i8) + below(lines.len() as i8)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        for (y, line) in self.lines().into_iter().enumerate() {
+            for (x, c) in line.into_iter().enumerate() {
+                let pos = Pos(PosX::right(x as i8), PosY::below(y as i8));
+                canvas.draw_cell(pos, CanvasCell::new(c, self.color));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render<S: AsRef<str>>(text: &ColoredText<S>) -> Vec<String> {
+        let mut canvas = RootCanvas::new();
+        text.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        buffer
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        let text = ColoredText::new("", CanvasCellColor::default());
+        assert_eq!(Movement::zero(), text.region_size());
+        assert!(render(&text).iter().all(|line| line.is_empty()));
+    }
+
+    #[test]
+    fn test_single_char() {
+        let text = ColoredText::new("a", CanvasCellColor::default());
+        assert_eq!(right(1) + below(1), text.region_size());
+        assert_eq!("a", render(&text)[0]);
+    }
+
+    #[test]
+    fn test_multi_line() {
+        let text = ColoredText::new("Score\n\n12345678", CanvasCellColor::default());
+        // 幅は最も長い行で決まり，空行も1行に数える
+        assert_eq!(right(4) + below(3), text.region_size());
+        let lines = render(&text);
+        assert_eq!(vec!["Score", "", "12345678", ""], lines[..4].to_vec());
+    }
+
+    #[test]
+    fn test_wrapped() {
+        let text = ColoredText::new("abcdefghij\nxy", CanvasCellColor::default())
+            .wrapped(2)
+            .unwrap();
+        assert_eq!(right(2) + below(4), text.region_size());
+        let lines = render(&text);
+        assert_eq!(vec!["abcd", "efgh", "ij", "xy", ""], lines[..5].to_vec());
+
+        // 折り返す幅より短い行はそのまま
+        let text = ColoredText::new("ab", CanvasCellColor::default())
+            .wrapped(5)
+            .unwrap();
+        assert_eq!(right(1) + below(1), text.region_size());
+
+        assert!(ColoredText::new("ab", CanvasCellColor::default())
+            .wrapped(0)
+            .is_none());
+    }
+}
@@ -0,0 +1,105 @@
+use super::*;
+use crate::geometry::*;
+
+/// 一定のフレーム周期で表示と非表示を繰り返す描画物を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flashing<D> {
+    /// 点滅させる描画物．
+    drawable: D,
+    /// 現在のフレーム数．
+    frame: usize,
+    /// 表示または非表示が続くフレーム数．
+    half_period: usize,
+    /// 点滅させずに常に表示するか．
+    steady: bool,
+}
+
+impl<D> Flashing<D> {
+    /// # Panics
+    /// `half_period`に0を指定した場合．
+    pub fn new(drawable: D, frame: usize, half_period: usize) -> Flashing<D> {
+        assert!(half_period > 0);
+        Self {
+            drawable,
+            frame,
+            half_period,
+            steady: false,
+        }
+    }
+
+    /// 描画設定を反映する．
+    /// `config.reduced_motion`が`true`の場合，点滅させずに常に表示する．
+    pub fn with_config(self, config: &RenderConfig) -> Flashing<D> {
+        Self {
+            steady: config.reduced_motion,
+            ..self
+        }
+    }
+
+    /// 現在のフレームで描画物が表示されるか返す．
+    /// 最初の`half_period`フレームは表示され，次の`half_period`フレームは表示されない．
+    pub fn is_visible(&self) -> bool {
+        self.steady || (self.frame / self.half_period).is_multiple_of(2)
+    }
+}
+
+impl<D: Drawable> Drawable for Flashing<D> {
+    fn region_size(&self) -> Movement {
+        self.drawable.region_size()
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        if self.is_visible() {
+            self.drawable.draw(canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_type::TableIndex;
+
+    #[test]
+    fn test_is_visible() {
+        let visibilities = (0..6)
+            .map(|frame| Flashing::new((), frame, 2).is_visible())
+            .collect::<Vec<_>>();
+        assert_eq!(vec![true, true, false, false, true, true], visibilities);
+    }
+
+    #[test]
+    fn test_is_visible_reduced_motion() {
+        let config = RenderConfig {
+            reduced_motion: true,
+            ..RenderConfig::default()
+        };
+        let visibilities = (0..6)
+            .map(|frame| {
+                Flashing::new((), frame, 2)
+                    .with_config(&config)
+                    .is_visible()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(vec![true; 6], visibilities);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_half_period() {
+        let _ = Flashing::new((), 0, 0);
+    }
+
+    #[test]
+    fn test_draw() {
+        let cell = CanvasCell::new(SquareChar::new('a', 'b'), CanvasCellColor::default());
+
+        let mut canvas = RootCanvas::new();
+        Flashing::new(cell, 0, 1).draw(&mut canvas);
+        assert_eq!(cell, canvas.cells[TableIndex::new(0, 0)]);
+
+        let mut canvas = RootCanvas::new();
+        Flashing::new(cell, 1, 1).draw(&mut canvas);
+        assert_eq!(CanvasCell::default(), canvas.cells[TableIndex::new(0, 0)]);
+    }
+}
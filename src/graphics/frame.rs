@@ -0,0 +1,281 @@
+use super::colored_str::square_chars;
+use super::*;
+use crate::geometry::*;
+
+/// 枠の角と辺に描画する文字の組を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameChars {
+    pub left_top: SquareChar,
+    pub right_top: SquareChar,
+    pub left_bottom: SquareChar,
+    pub right_bottom: SquareChar,
+    /// 上下の辺に描画する文字．
+    pub horizontal: SquareChar,
+    /// 左の辺に描画する文字．
+    pub left: SquareChar,
+    /// 右の辺に描画する文字．
+    pub right: SquareChar,
+}
+
+impl FrameChars {
+    /// 角は`+`，上下の辺は`-`，左右の辺は`|`とし，内側へ寄せた文字の組．
+    pub const ASCII: FrameChars = FrameChars {
+        left_top: SquareChar([' ', '+']),
+        right_top: SquareChar(['+', ' ']),
+        left_bottom: SquareChar([' ', '+']),
+        right_bottom: SquareChar(['+', ' ']),
+        horizontal: SquareChar(['-', '-']),
+        left: SquareChar([' ', '|']),
+        right: SquareChar(['|', ' ']),
+    };
+}
+
+impl Default for FrameChars {
+    fn default() -> Self {
+        Self::ASCII
+    }
+}
+
+/// 指定した大きさの領域を1セル幅で囲む枠を表す．
+/// 上の辺には見出しを重ねて描画できる．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'t> {
+    /// 枠の内側の大きさ．
+    inner_size: Movement,
+    chars: FrameChars,
+    /// 描画する色．`None`の場合は，キャンバスの配色の文字の色で描画する．
+    color: Option<CanvasCellColor>,
+    /// 上の辺に描画する見出し．
+    title: Option<&'t str>,
+}
+
+impl<'t> Frame<'t> {
+    /// 枠の左上を原点とした，枠の内側の左上の位置．
+    pub const INNER_LEFT_TOP: Pos = Pos(PosX { right_shift: 1 }, PosY { below_shift: 1 });
+
+    /// 指定した大きさの領域を囲む，見出しのない枠を返す．
+    /// 枠はキャンバスの配色の文字の色で描画する．
+    /// # Returns
+    /// 大きさのいずれかの成分が負の場合や，枠を含めた大きさが`Shift`に収まらない場合は`None`を返す．
+    pub fn new(inner_size: Movement) -> Option<Frame<'t>> {
+        let max_inner_len = Shift::MAX as usize - 2;
+        let is_valid = |len: Option<usize>| len.is_some_and(|len| len <= max_inner_len);
+        if !is_valid(inner_size.x().as_positive_index())
+            || !is_valid(inner_size.y().as_positive_index())
+        {
+            return None;
+        }
+        Some(Self {
+            inner_size,
+            chars: FrameChars::ASCII,
+            color: None,
+            title: None,
+        })
+    }
+
+    /// 指定した描画物をちょうど囲む枠を返す．
+    /// # Returns
+    /// 描画物が大きすぎて枠を含めた大きさが`Shift`に収まらない場合は`None`を返す．
+    pub fn around<D: Drawable>(drawable: &D) -> Option<Frame<'t>> {
+        Self::new(drawable.region_size())
+    }
+
+    /// 角と辺に描画する文字を変更した枠を返す．
+    pub fn with_chars(self, chars: FrameChars) -> Frame<'t> {
+        Self { chars, ..self }
+    }
+
+    /// 描画する色を変更した枠を返す．
+    pub fn with_color(self, color: CanvasCellColor) -> Frame<'t> {
+        Self {
+            color: Some(color),
+            ..self
+        }
+    }
+
+    /// 上の辺に見出しを付けた枠を返す．
+    /// 見出しは左上の角の右から描画し，上の辺に収まらない部分は描画しない．
+    pub fn with_title(self, title: &'t str) -> Frame<'t> {
+        Self {
+            title: Some(title),
+            ..self
+        }
+    }
+
+    /// 枠の内側の大きさを返す．
+    pub const fn inner_size(&self) -> Movement {
+        self.inner_size
+    }
+
+    /// 枠の左上を原点として，枠の内側の領域を返す．
+    pub fn inner_roi(&self) -> RegionOfInterest {
+        RegionOfInterest::new(Self::INNER_LEFT_TOP, self.inner_size)
+    }
+
+    /// 枠を描画し，その内側に指定した描画物を描画する．
+    pub fn draw_with<D: Drawable, C: Canvas>(&self, content: &D, canvas: &mut C) {
+        self.draw(canvas);
+        content.draw_on_child(Self::INNER_LEFT_TOP, canvas);
+    }
+
+    /// 枠の指定した位置に描画する文字を返す．
+    fn frame_char(
+        &self,
+        is_left: bool,
+        is_right: bool,
+        is_top: bool,
+        is_bottom: bool,
+    ) -> SquareChar {
+        let chars = &self.chars;
+        match (is_left, is_right, is_top, is_bottom) {
+            (true, _, true, _) => chars.left_top,
+            (_, true, true, _) => chars.right_top,
+            (true, _, _, true) => chars.left_bottom,
+            (_, true, _, true) => chars.right_bottom,
+            (true, _, _, _) => chars.left,
+            (_, true, _, _) => chars.right,
+            _ => chars.horizontal,
+        }
+    }
+}
+
+impl Drawable for Frame<'_> {
+    fn region_size(&self) -> Movement {
+        self.inner_size + right(2) + below(2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = self
+            .color
+            .unwrap_or_else(|| canvas.theme().cell_color(ThemeColor::Text));
+        // 大きさは生成時に検証済み
+        let width = self.inner_size.x().as_positive_index().unwrap_or(0) + 2;
+        let height = self.inner_size.y().as_positive_index().unwrap_or(0) + 2;
+        for y in 0..height {
+            let (is_top, is_bottom) = (y == 0, y == height - 1);
+            for x in 0..width {
+                let (is_left, is_right) = (x == 0, x == width - 1);
+                if !is_left && !is_right && !is_top && !is_bottom {
+                    continue;
+                }
+                let c = self.frame_char(is_left, is_right, is_top, is_bottom);
+                canvas.draw_cell(
+                    Pos(PosX::right(x as Shift), PosY::below(y as Shift)),
+                    CanvasCell::new(c, color),
+                );
+            }
+        }
+
+        if let Some(title) = self.title {
+            let inner_width = self.inner_size.x().as_positive_index().unwrap_or(0);
+            for (i, c) in square_chars(title)
+                .into_iter()
+                .take(inner_width)
+                .enumerate()
+            {
+                let pos = Pos::origin() + right(1 + i as Shift);
+                canvas.draw_cell(pos, CanvasCell::new(c, color));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(frame: &Frame) -> Vec<String> {
+        let mut canvas = RootCanvas::new();
+        frame.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        buffer
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_region_size() {
+        let frame = Frame::new(right(3) + below(2)).unwrap();
+        assert_eq!(right(5) + below(4), frame.region_size());
+        assert_eq!(
+            RegionOfInterest::new(Pos(PosX::right(1), PosY::below(1)), right(3) + below(2)),
+            frame.inner_roi()
+        );
+
+        let empty = Frame::new(Movement::zero()).unwrap();
+        assert_eq!(right(2) + below(2), empty.region_size());
+
+        let text = ColoredStr("Hello", CanvasCellColor::default());
+        assert_eq!(
+            Some(right(5) + below(3)),
+            Frame::around(&text).map(|frame| frame.region_size())
+        );
+    }
+
+    #[test]
+    fn test_invalid_size() {
+        assert!(Frame::new(left(1) + below(1)).is_none());
+        assert!(Frame::new(right(1) + above(1)).is_none());
+        assert!(Frame::new(right(Shift::MAX - 1) + below(1)).is_none());
+        assert!(Frame::new(right(Shift::MAX - 2) + below(Shift::MAX - 2)).is_some());
+    }
+
+    #[test]
+    fn test_draw() {
+        let frame = Frame::new(right(3) + below(2)).unwrap();
+        let expected = vec![" +------+", " |      |", " |      |", " +------+"];
+        assert_eq!(expected, render(&frame)[..4]);
+        assert!(render(&frame)[4..].iter().all(String::is_empty));
+    }
+
+    #[test]
+    fn test_draw_with_chars() {
+        let chars = FrameChars {
+            left_top: SquareChar::new('/', '='),
+            right_top: SquareChar::new('=', '\\'),
+            left_bottom: SquareChar::new('\\', '='),
+            right_bottom: SquareChar::new('=', '/'),
+            horizontal: SquareChar::new('=', '='),
+            left: SquareChar::new('[', ' '),
+            right: SquareChar::new(' ', ']'),
+        };
+        let frame = Frame::new(right(2) + below(1)).unwrap().with_chars(chars);
+        let expected = vec!["/======\\", "[      ]", "\\======/"];
+        assert_eq!(expected, render(&frame)[..3]);
+    }
+
+    #[test]
+    fn test_draw_title() {
+        let frame = Frame::new(right(4) + below(1)).unwrap().with_title("Next");
+        let expected = vec![" +Next----+", " |        |", " +--------+"];
+        assert_eq!(expected, render(&frame)[..3]);
+
+        // 上の辺に収まらない部分は描画せず，角を上書きしない
+        let frame = Frame::new(right(2) + below(1))
+            .unwrap()
+            .with_title("Statistics");
+        let expected = vec![" +Stat+", " |    |", " +----+"];
+        assert_eq!(expected, render(&frame)[..3]);
+
+        // 内側の幅が0の場合は見出しを描画しない
+        let frame = Frame::new(right(0) + below(1)).unwrap().with_title("Hold");
+        let expected = vec![" ++", " ||", " ++"];
+        assert_eq!(expected, render(&frame)[..3]);
+    }
+
+    #[test]
+    fn test_draw_with_content() {
+        let text = ColoredStr("Hi", CanvasCellColor::default());
+        let frame = Frame::around(&text).unwrap();
+        let mut canvas = RootCanvas::new();
+        frame.draw_with(&text, &mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let lines = buffer.lines().collect::<Vec<_>>();
+        assert_eq!(" +--+", lines[0].trim_end());
+        assert_eq!(" |Hi|", lines[1].trim_end());
+        assert_eq!(" +--+", lines[2].trim_end());
+    }
+}
@@ -0,0 +1,24 @@
+use super::{ColorSupport, Theme, ThemeName};
+
+/// 描画方法に関する設定を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderConfig {
+    /// 点滅などの明滅する演出を抑えるか．
+    /// `true`の場合，点滅する描画物は常に表示され，アニメーションは文字を切り替えずに描画される．
+    pub reduced_motion: bool,
+    /// 色を使わずに表示するか．
+    /// 色を表示できない端末で利用する．`true`の場合，セルの種類は文字だけで見分けることになる．
+    pub monochrome: bool,
+    /// 端末が表示できる色の範囲．
+    /// 描画物は，この範囲に合わせた配色で描画される．
+    pub color_support: ColorSupport,
+    /// 描画に使う組み込みの配色．
+    pub theme_name: ThemeName,
+}
+
+impl RenderConfig {
+    /// この設定で描画物が使う配色を返す．
+    pub const fn theme(&self) -> Theme {
+        Theme::new(self.theme_name, self.color_support)
+    }
+}
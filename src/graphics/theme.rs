@@ -0,0 +1,346 @@
+use super::*;
+
+/// ゲーム中で色を使い分ける，描画物の役割を表す．
+/// 実際に表示する色は，役割ごとに`Theme`が決める．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThemeColor {
+    /// 背景．
+    Background,
+    /// 文字や枠．
+    Text,
+    /// 空セル．
+    EmptyCell,
+    /// 通常のセル．
+    NormalCell,
+    /// 鋼鉄のセル．
+    SteelCell,
+    /// ボムセル．
+    Bomb,
+    /// デカボムを構成するセル．
+    BigBomb,
+    /// 特大ボムを構成するセル．
+    HugeBomb,
+    /// ゲームオーバーが近いことや失敗を知らせる表示．
+    Warning,
+    /// 成功を知らせる表示．
+    Success,
+    /// 得点や連鎖など，目立たせたい表示．
+    Highlight,
+    /// ヒートマップで，最も少ない回数を表す段階．
+    HeatLowest,
+    /// ヒートマップで，2番目に少ない回数を表す段階．
+    HeatLow,
+    /// ヒートマップで，中ほどの回数を表す段階．
+    HeatMiddle,
+    /// ヒートマップで，2番目に多い回数を表す段階．
+    HeatHigh,
+    /// ヒートマップで，最も多い回数を表す段階．
+    HeatHighest,
+}
+
+impl ThemeColor {
+    /// すべての役割．
+    pub const ALL: [ThemeColor; 16] = [
+        ThemeColor::Background,
+        ThemeColor::Text,
+        ThemeColor::EmptyCell,
+        ThemeColor::NormalCell,
+        ThemeColor::SteelCell,
+        ThemeColor::Bomb,
+        ThemeColor::BigBomb,
+        ThemeColor::HugeBomb,
+        ThemeColor::Warning,
+        ThemeColor::Success,
+        ThemeColor::Highlight,
+        ThemeColor::HeatLowest,
+        ThemeColor::HeatLow,
+        ThemeColor::HeatMiddle,
+        ThemeColor::HeatHigh,
+        ThemeColor::HeatHighest,
+    ];
+
+    /// `ALL`におけるこの役割の位置を返す．
+    const fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// 端末が表示できる色の範囲を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSupport {
+    /// 基本の8色だけを表示できる．
+    #[default]
+    Basic,
+    /// 256色を表示できる．
+    Color256,
+}
+
+impl ColorSupport {
+    /// 環境変数`TERM`と`COLORTERM`の値から，端末が表示できる色の範囲を判定する．
+    /// いずれの値からも256色以上を表示できると分からない場合は，基本の8色とみなす．
+    pub fn detect(term: Option<&str>, colorterm: Option<&str>) -> ColorSupport {
+        let is_true_color = matches!(colorterm, Some("truecolor") | Some("24bit"));
+        let is_256_color = term.is_some_and(|term| term.contains("256color"));
+        if is_true_color || is_256_color {
+            ColorSupport::Color256
+        } else {
+            ColorSupport::Basic
+        }
+    }
+
+    /// 現在の環境変数から，端末が表示できる色の範囲を判定する．
+    pub fn from_env() -> ColorSupport {
+        let term = std::env::var("TERM").ok();
+        let colorterm = std::env::var("COLORTERM").ok();
+        Self::detect(term.as_deref(), colorterm.as_deref())
+    }
+}
+
+/// 組み込みの配色の種類を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThemeName {
+    /// セルの種類や表示の役割ごとに色を使い分ける，標準の配色．
+    #[default]
+    Standard,
+    /// 黒地に白だけで表示する，コントラストの高い配色．
+    /// 色を見分けにくい場合に利用する．セルの種類は文字で見分ける．
+    HighContrast,
+}
+
+impl ThemeName {
+    /// すべての組み込みの配色．
+    pub const ALL: [ThemeName; 2] = [ThemeName::Standard, ThemeName::HighContrast];
+
+    /// 表示名を返す．
+    pub const fn label(&self) -> &'static str {
+        match self {
+            ThemeName::Standard => "Standard",
+            ThemeName::HighContrast => "High contrast",
+        }
+    }
+
+    /// 組み込みの配色のうち，次の配色を返す．最後の配色の次は最初の配色となる．
+    pub fn next(&self) -> ThemeName {
+        let i = Self::ALL.iter().position(|name| name == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// ひとつの役割に割り当てる色を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThemeEntry {
+    /// 基本の8色しか表示できない端末で使う色．
+    pub basic: Color,
+    /// 256色を表示できる端末で使う色の番号．
+    pub color256: u8,
+}
+
+impl ThemeEntry {
+    pub const fn new(basic: Color, color256: u8) -> ThemeEntry {
+        Self { basic, color256 }
+    }
+}
+
+/// 描画物の役割ごとに表示する色を決める配色を表す．
+/// 端末が256色を表示できない場合は，各役割の基本の色にフォールバックする．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// `ThemeColor::ALL`の順に並べた，各役割の色．
+    palette: [ThemeEntry; ThemeColor::ALL.len()],
+    support: ColorSupport,
+}
+
+impl Theme {
+    /// 指定した組み込みの配色を，指定した色の範囲で表示する配色を返す．
+    pub const fn new(name: ThemeName, support: ColorSupport) -> Theme {
+        match name {
+            ThemeName::Standard => Self::standard(support),
+            ThemeName::HighContrast => Self::high_contrast(support),
+        }
+    }
+
+    /// 標準の配色を，指定した色の範囲で表示する配色を返す．
+    pub const fn standard(support: ColorSupport) -> Theme {
+        use Color::*;
+
+        Self {
+            palette: [
+                ThemeEntry::new(Black, 16),
+                ThemeEntry::new(White, 252),
+                ThemeEntry::new(White, 240),
+                ThemeEntry::new(Cyan, 45),
+                ThemeEntry::new(White, 247),
+                ThemeEntry::new(Red, 196),
+                ThemeEntry::new(Magenta, 201),
+                ThemeEntry::new(Yellow, 220),
+                ThemeEntry::new(Red, 160),
+                ThemeEntry::new(Green, 40),
+                ThemeEntry::new(Yellow, 220),
+                ThemeEntry::new(Blue, 27),
+                ThemeEntry::new(Cyan, 51),
+                ThemeEntry::new(Green, 46),
+                ThemeEntry::new(Yellow, 226),
+                ThemeEntry::new(Red, 196),
+            ],
+            support,
+        }
+    }
+
+    /// 背景以外のすべての役割を白で表示する配色を，指定した色の範囲で表示する配色を返す．
+    pub const fn high_contrast(support: ColorSupport) -> Theme {
+        let white = ThemeEntry::new(Color::White, 231);
+        Self {
+            palette: [
+                ThemeEntry::new(Color::Black, 16),
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+                white,
+            ],
+            support,
+        }
+    }
+
+    /// 指定した役割の色を置き換えた配色を返す．
+    pub fn with_entry(mut self, color: ThemeColor, entry: ThemeEntry) -> Theme {
+        self.palette[color.index()] = entry;
+        self
+    }
+
+    /// 表示する色の範囲を変更した配色を返す．
+    pub fn with_support(self, support: ColorSupport) -> Theme {
+        Self { support, ..self }
+    }
+
+    /// この配色で表示する色の範囲を返す．
+    pub const fn support(&self) -> ColorSupport {
+        self.support
+    }
+
+    /// 指定した役割に割り当てた色を返す．
+    pub const fn entry(&self, color: ThemeColor) -> ThemeEntry {
+        self.palette[color.index()]
+    }
+
+    /// 指定した役割を表示する色を返す．
+    pub const fn color(&self, color: ThemeColor) -> Color {
+        let entry = self.entry(color);
+        match self.support {
+            ColorSupport::Basic => entry.basic,
+            ColorSupport::Color256 => Color::Color256(entry.color256),
+        }
+    }
+
+    /// 指定した役割の前景色を，背景の色の上に表示する色を返す．
+    pub const fn cell_color(&self, foreground: ThemeColor) -> CanvasCellColor {
+        CanvasCellColor::new(self.color(foreground), self.color(ThemeColor::Background))
+    }
+
+    /// 指定した役割の色を背景色として，背景の色の文字を表示する色を返す．
+    /// 選択中の項目や警告など，反転して目立たせる表示に利用する．
+    pub const fn inverted_color(&self, background: ThemeColor) -> CanvasCellColor {
+        CanvasCellColor::new(self.color(ThemeColor::Background), self.color(background))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::standard(ColorSupport::Basic)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index() {
+        for (i, color) in ThemeColor::ALL.iter().enumerate() {
+            assert_eq!(i, color.index());
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let theme = Theme::standard(ColorSupport::Color256);
+        assert_eq!(Color::Color256(196), theme.color(ThemeColor::Bomb));
+        assert_eq!(
+            CanvasCellColor::new(Color::Color256(45), Color::Color256(16)),
+            theme.cell_color(ThemeColor::NormalCell)
+        );
+
+        let entry = ThemeEntry::new(Color::Green, 34);
+        let theme = theme.with_entry(ThemeColor::NormalCell, entry);
+        assert_eq!(entry, theme.entry(ThemeColor::NormalCell));
+        assert_eq!(Color::Color256(34), theme.color(ThemeColor::NormalCell));
+        // 他の役割の色は変わらない
+        assert_eq!(Color::Color256(196), theme.color(ThemeColor::Bomb));
+    }
+
+    #[test]
+    fn test_fallback() {
+        let theme = Theme::standard(ColorSupport::Color256).with_support(ColorSupport::Basic);
+        assert_eq!(Theme::default(), theme);
+        // 256色を表示できない場合は，すべての役割が基本の8色で表示される
+        for color in ThemeColor::ALL {
+            let basic = theme.color(color);
+            assert!(!matches!(basic, Color::Color256(_)), "{:?}", color);
+            assert_eq!(theme.entry(color).basic, basic);
+        }
+        assert_eq!(
+            CanvasCellColor::new(Color::Red, Color::Black),
+            theme.cell_color(ThemeColor::Bomb)
+        );
+    }
+
+    #[test]
+    fn test_high_contrast() {
+        let theme = Theme::new(ThemeName::HighContrast, ColorSupport::Basic);
+        assert_eq!(Theme::high_contrast(ColorSupport::Basic), theme);
+        for color in ThemeColor::ALL {
+            let expected = match color {
+                ThemeColor::Background => Color::Black,
+                _ => Color::White,
+            };
+            assert_eq!(expected, theme.color(color), "{:?}", color);
+        }
+        assert_eq!(
+            CanvasCellColor::new(Color::Black, Color::White),
+            theme.inverted_color(ThemeColor::Warning)
+        );
+        assert_ne!(Theme::new(ThemeName::Standard, ColorSupport::Basic), theme);
+    }
+
+    #[test]
+    fn test_theme_name() {
+        assert_eq!(ThemeName::HighContrast, ThemeName::Standard.next());
+        assert_eq!(ThemeName::Standard, ThemeName::HighContrast.next());
+        assert_eq!("High contrast", ThemeName::HighContrast.label());
+    }
+
+    #[test]
+    fn test_detect() {
+        use ColorSupport::*;
+
+        assert_eq!(Color256, ColorSupport::detect(Some("xterm-256color"), None));
+        assert_eq!(
+            Color256,
+            ColorSupport::detect(Some("xterm"), Some("truecolor"))
+        );
+        assert_eq!(Color256, ColorSupport::detect(None, Some("24bit")));
+        assert_eq!(Basic, ColorSupport::detect(Some("xterm"), None));
+        assert_eq!(Basic, ColorSupport::detect(Some("linux"), Some("yes")));
+        assert_eq!(Basic, ColorSupport::detect(None, None));
+    }
+}
@@ -0,0 +1,11 @@
+pub mod data_type;
+pub mod game;
+pub mod geometry;
+pub mod graphics;
+pub mod menu;
+pub mod self_test;
+pub mod ui;
+pub mod user;
+
+/// 外部のクレートからこのクレートを利用する際に必要となる型やトレイトをまとめたモジュール．
+pub mod prelude;
@@ -1,36 +1,671 @@
-mod data_type;
-mod game;
-mod geometry;
-mod graphics;
-mod user;
+use rustetris::game;
+use rustetris::game::animation::Drawer;
+use rustetris::geometry::*;
+use rustetris::graphics::*;
+use rustetris::menu;
+use rustetris::ui;
+use rustetris::user::{self, Clock, InputMapper};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::rc::Rc;
 
-use game::animation::Drawer;
-use graphics::*;
+/// スプリントの最短記録を保存するファイル．
+const SPRINT_BEST_TIME_PATH: &str = "sprint_best_time.txt";
+/// 設定画面で変更したキー配置を保存するファイル．
+const KEY_BINDINGS_PATH: &str = "key_bindings.txt";
+/// 途中でやめたエンドレスのゲームを保存するファイル．
+const SESSION_PATH: &str = "session.txt";
+
+/// エンドレスのゲームで利用するブロック生成ルール．
+type Selector = game::BombPolicySelector<ShapeSelector>;
+
+/// 起動時に選んだ種類のブロックを生成するブロック生成ルール．
+enum ShapeSelector {
+    /// テトリミノを生成する．
+    Tetromino(game::RandomBlockSelector),
+    /// ペントミノを生成する(Pentris)．
+    Pentomino(game::PentominoBlockSelector),
+    /// 1セルから5セルまでの形状を混ぜて生成する．
+    Chaos(game::WeightedShapeSelector),
+    /// 5セルと6セルの大きな形状を混ぜて生成する．
+    Giant(game::WeightedShapeSelector),
+    /// 直前に生成した形状を避けながら，テトリミノかペントミノを生成する．
+    History(game::HistoryBlockSelector),
+}
+
+impl ShapeSelector {
+    /// 乱数生成器の初期化に利用したシード値を返す．
+    fn seed(&self) -> u64 {
+        match self {
+            ShapeSelector::Tetromino(selector) => selector.seed(),
+            ShapeSelector::Pentomino(selector) => selector.seed(),
+            ShapeSelector::Chaos(selector) => selector.seed(),
+            ShapeSelector::Giant(selector) => selector.seed(),
+            ShapeSelector::History(selector) => selector.seed(),
+        }
+    }
+
+    /// 生成しうる形状を返す．
+    fn shapes(&self) -> Vec<game::BlockShape> {
+        match self {
+            ShapeSelector::Tetromino(_) => {
+                game::QuadrupleBlockShape::all().map(Into::into).collect()
+            }
+            ShapeSelector::Pentomino(_) => {
+                game::QuintupleBlockShape::all().map(Into::into).collect()
+            }
+            ShapeSelector::Chaos(selector) => selector.shapes().to_vec(),
+            ShapeSelector::Giant(selector) => selector.shapes().to_vec(),
+            ShapeSelector::History(selector) => selector.shapes().to_vec(),
+        }
+    }
+}
+
+impl game::BlockSelector for ShapeSelector {
+    fn select_block_shape(&mut self) -> game::BlockShape {
+        match self {
+            ShapeSelector::Tetromino(selector) => selector.select_block_shape(),
+            ShapeSelector::Pentomino(selector) => selector.select_block_shape(),
+            ShapeSelector::Chaos(selector) => selector.select_block_shape(),
+            ShapeSelector::Giant(selector) => selector.select_block_shape(),
+            ShapeSelector::History(selector) => selector.select_block_shape(),
+        }
+    }
+
+    fn select_bomb(&mut self, shape: game::BlockShape) -> game::BombTag {
+        match self {
+            ShapeSelector::Tetromino(selector) => selector.select_bomb(shape),
+            ShapeSelector::Pentomino(selector) => selector.select_bomb(shape),
+            ShapeSelector::Chaos(selector) => selector.select_bomb(shape),
+            ShapeSelector::Giant(selector) => selector.select_bomb(shape),
+            ShapeSelector::History(selector) => selector.select_bomb(shape),
+        }
+    }
+}
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--self-test") {
+        let code = if self_test() { 0 } else { 1 };
+        std::process::exit(code);
+    }
+
+    let args = std::env::args().collect::<Vec<_>>();
+
+    // シード値が指定された場合は，設定画面で変更しない限り毎回同じブロック列でゲームを行う
+    let seed = match args.iter().position(|arg| arg == "--seed") {
+        Some(i) => match args.get(i + 1).map(|seed| seed.parse::<u64>()) {
+            Some(Ok(seed)) => Some(seed),
+            Some(Err(_)) | None => {
+                eprintln!("error: --seed requires a non-negative integer");
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    // 指定された場合は，ブロックのボムセルをポリシーに従って決める
+    let bomb_policy = match args.iter().position(|arg| arg == "--bombs") {
+        Some(i) => match args
+            .get(i + 1)
+            .and_then(|policy| game::BombPolicy::parse(policy))
+        {
+            Some(policy) => Some(policy),
+            None => {
+                eprintln!(
+                    "error: --bombs requires one of never, all, fixed:<label>, random:<0.0-1.0>"
+                );
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    // 指定された場合は，ゲームを終えるたびに操作をリプレイとして書き出す
+    let record_path = match args.iter().position(|arg| arg == "--record") {
+        Some(i) => match args.get(i + 1) {
+            Some(path) => Some(path.clone()),
+            None => {
+                eprintln!("error: --record requires a file path");
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    // Pentrisでは，テトリミノの代わりにペントミノを落とす
+    let pentris_enabled = std::env::args().any(|arg| arg == "--pentris");
+    // カオスでは，1セルから5セルまでの大きさのブロックが混ざって落ちる
+    let chaos_enabled = std::env::args().any(|arg| arg == "--chaos");
+    if chaos_enabled && pentris_enabled {
+        eprintln!("error: --chaos cannot be combined with --pentris");
+        std::process::exit(2);
+    }
+    // ジャイアントでは，5セルと6セルの大きなブロックだけが落ちる
+    let giant_enabled = std::env::args().any(|arg| arg == "--giant");
+    if giant_enabled && (pentris_enabled || chaos_enabled) {
+        eprintln!("error: --giant cannot be combined with --pentris or --chaos");
+        std::process::exit(2);
+    }
+    // 履歴付きの選択では，直前に出た形状が続けて出にくくなる
+    // テトリミノかペントミノ(--pentris)の選択にだけ使える
+    let history_enabled = std::env::args().any(|arg| arg == "--history");
+    if history_enabled && (chaos_enabled || giant_enabled) {
+        eprintln!("error: --history cannot be combined with --chaos or --giant");
+        std::process::exit(2);
+    }
+    // リプレイは無作為に選んだテトリミノのブロック列しか再現できない
+    if (pentris_enabled || chaos_enabled || giant_enabled || history_enabled)
+        && record_path.is_some()
+    {
+        eprintln!(
+            "error: --pentris, --chaos, --giant and --history cannot be combined with --record"
+        );
+        std::process::exit(2);
+    }
+    // リプレイはボムセルのポリシーを記録しないので，ポリシーを変えたブロック列を再現できない
+    if bomb_policy.is_some() && record_path.is_some() {
+        eprintln!("error: --bombs cannot be combined with --record");
+        std::process::exit(2);
+    }
+
+    // 同じシード値が指定されていれば，やり直すたびに同じ順でブロックが出現する
+    let new_shape_selector = |seed| {
+        if history_enabled && pentris_enabled {
+            ShapeSelector::History(game::HistoryBlockSelector::pentomino(seed))
+        } else if history_enabled {
+            ShapeSelector::History(game::HistoryBlockSelector::tetromino(seed))
+        } else if pentris_enabled {
+            ShapeSelector::Pentomino(game::PentominoBlockSelector::with_seed(seed))
+        } else if chaos_enabled {
+            ShapeSelector::Chaos(game::WeightedShapeSelector::chaos(seed))
+        } else if giant_enabled {
+            ShapeSelector::Giant(game::WeightedShapeSelector::giant(seed))
+        } else {
+            ShapeSelector::Tetromino(game::RandomBlockSelector::with_seed(seed))
+        }
+    };
+    // ボムセルのラベルは，生成しうるすべての形状に収まらなければならない
+    if let Some(policy) = bomb_policy {
+        let shapes = new_shape_selector(0).shapes();
+        if !shapes.iter().all(|&shape| policy.is_valid_for(shape)) {
+            eprintln!(
+                "error: --bombs fixed:<label> must be smaller than the cell count of every block"
+            );
+            std::process::exit(2);
+        }
+    }
+
     let terminal = console::Term::buffered_stdout();
 
+    let settings = Rc::new(RefCell::new(menu::Settings {
+        seed,
+        ..menu::Settings::default()
+    }));
+    // キー配置が読めない場合は，既定のキー配置を使う
+    settings.borrow_mut().key_bindings = match user::KeyBindings::load(KEY_BINDINGS_PATH) {
+        Ok(key_bindings) => key_bindings,
+        Err(e) => {
+            eprintln!(
+                "error: failed to load key bindings from {}: {}",
+                KEY_BINDINGS_PATH, e
+            );
+            user::KeyBindings::DEFAULT
+        }
+    };
+    settings.borrow_mut().render_config.reduced_motion =
+        std::env::args().any(|arg| arg == "--reduced-motion");
+    // 色を表示できない端末では，色を使わずに表示する
+    settings.borrow_mut().render_config.monochrome =
+        !terminal.features().colors_supported() || std::env::args().any(|arg| arg == "--no-color");
+    // 256色を表示できない端末では，基本の8色の配色にフォールバックする
+    settings.borrow_mut().render_config.color_support = ColorSupport::from_env();
+    if std::env::args().any(|arg| arg == "--high-contrast") {
+        settings.borrow_mut().render_config.theme_name = ThemeName::HighContrast;
+    }
+
+    // 入力遅延の計測は，指定された場合だけ行う
+    let latency_monitor = if std::env::args().any(|arg| arg == "--latency") {
+        Some(Rc::new(RefCell::new(user::LatencyMonitor::new(20))))
+    } else {
+        None
+    };
+
+    // 操作中のブロックの自動落下は，指定された場合だけ行う
+    let gravity_enabled = std::env::args().any(|arg| arg == "--gravity");
+    // スプリントでは，40ラインを揃えるまでの時間を競う
+    let sprint_enabled = std::env::args().any(|arg| arg == "--sprint");
+    if sprint_enabled && gravity_enabled {
+        eprintln!("error: --sprint cannot be combined with --gravity");
+        std::process::exit(2);
+    }
+    // ウルトラでは，制限時間内に稼いだ得点を競う．ブロックは常に自動で落下する
+    let ultra_enabled = std::env::args().any(|arg| arg == "--ultra");
+    if ultra_enabled && sprint_enabled {
+        eprintln!("error: --ultra cannot be combined with --sprint");
+        std::process::exit(2);
+    }
+    // パズルでは，一覧から選んだ盤面をすべて消すことを目指す
+    let puzzle_enabled = std::env::args().any(|arg| arg == "--puzzle");
+    if puzzle_enabled && (gravity_enabled || sprint_enabled || ultra_enabled) {
+        eprintln!("error: --puzzle cannot be combined with --gravity, --sprint or --ultra");
+        std::process::exit(2);
+    }
+
+    // サバイバルでは，ブロックを置くたびにせり上がってくるラインに耐え続ける．ブロックは常に自動で落下する
+    let survival_enabled = std::env::args().any(|arg| arg == "--survival");
+    if survival_enabled && (sprint_enabled || ultra_enabled || puzzle_enabled) {
+        eprintln!("error: --survival cannot be combined with --sprint, --ultra or --puzzle");
+        std::process::exit(2);
+    }
+
+    // クラシックでは，揃ったラインをボムによらずそのまま消す．ブロックはボムを含まない
+    let classic_enabled = std::env::args().any(|arg| arg == "--classic");
+    if classic_enabled && (sprint_enabled || ultra_enabled || puzzle_enabled || survival_enabled) {
+        eprintln!(
+            "error: --classic cannot be combined with --sprint, --ultra, --puzzle or --survival"
+        );
+        std::process::exit(2);
+    }
+    if classic_enabled && (bomb_policy.is_some() || record_path.is_some()) {
+        eprintln!("error: --classic cannot be combined with --bombs or --record");
+        std::process::exit(2);
+    }
+    // 爆発の後に，宙に浮いたセルを落とす代わりに，空になったライン数だけ各ラインを下へずらす
+    let line_gravity_enabled = std::env::args().any(|arg| arg == "--line-gravity");
+    if line_gravity_enabled
+        && (classic_enabled
+            || sprint_enabled
+            || ultra_enabled
+            || puzzle_enabled
+            || survival_enabled)
+    {
+        eprintln!("error: --line-gravity cannot be combined with --classic, --sprint, --ultra, --puzzle or --survival");
+        std::process::exit(2);
+    }
+    if line_gravity_enabled && record_path.is_some() {
+        eprintln!("error: --line-gravity cannot be combined with --record");
+        std::process::exit(2);
+    }
+    let (line_clear_rule, bomb_policy) = if classic_enabled {
+        (
+            game::single_play::LineClearRule::Classic,
+            Some(game::BombPolicy::Never),
+        )
+    } else if line_gravity_enabled {
+        (
+            game::single_play::LineClearRule::ExplosionWithLineGravity,
+            bomb_policy,
+        )
+    } else {
+        (game::single_play::LineClearRule::Explosion, bomb_policy)
+    };
+
+    // 代替画面に切り替えて描画し，終了時やパニック時に元の画面へ戻す
+    let mut terminal = ui::TerminalGuard::enter(terminal).unwrap();
+    ui::install_panic_hook();
+
     let mut drawer = StdoutDrawer {
-        terminal: &terminal,
-        root_canvas: RootCanvas::new(),
+        terminal: terminal.terminal(),
+        root_canvas: {
+            // 端末の大きさを取得できない場合は，最小の大きさで表示する
+            let (rows, columns) = terminal.terminal().size();
+            let mut canvas = RootCanvas::for_terminal(columns as usize, rows as usize);
+            canvas.set_theme(settings.borrow().render_config.theme());
+            canvas
+        },
+        previous_canvas: None,
+        render_config: settings.borrow().render_config,
+        latency_monitor: latency_monitor.clone(),
+        frame_duration: std::time::Duration::from_millis(50),
+    };
+
+    let input_source = user::TerminalInputSource::spawn();
+    // ゲーム中にやめる操作をしたら，結果画面を待たずに終了する
+    let quit_requested = Cell::new(false);
+    let on_event = |event: game::single_play::GameEvent| {
+        if let game::single_play::GameEvent::Quit = event {
+            quit_requested.set(true);
+        }
     };
 
-    let input_mapper = user::SinglePlayerInputMapper;
+    // エンドレスのゲームだけを中断して再開できる
+    let endless_enabled = !(sprint_enabled || ultra_enabled || puzzle_enabled || survival_enabled);
+    // 中断したゲームは，同じ設定で始めたゲームでだけ再開する
+    let session_mode = session_mode(&args);
+    // メインメニューで再開が選ばれた場合に，次のゲームで再開する中断情報
+    let pending_session = RefCell::new(None);
 
-    let input = || loop {
-        if let Ok(key) = terminal.read_key() {
-            if let Some(command) = input_mapper.map(key) {
-                break command;
+    loop {
+        // メインメニューでゲーム開始か終了が選ばれるまで画面遷移を繰り返す
+        let choice = Rc::new(Cell::new(None));
+        let can_continue = endless_enabled && std::path::Path::new(SESSION_PATH).exists();
+        let main_menu = menu::MainMenu::new(choice.clone(), settings.clone(), can_continue);
+        let mut screen_stack = ui::ScreenStack::new(Box::new(main_menu));
+        let key_bindings = settings.borrow().key_bindings;
+        ui::ScreenDriver::new(&mut drawer, || input_source.read_keys()).run(&mut screen_stack);
+        // 設定画面でキー配置が変わっていれば保存する
+        if settings.borrow().key_bindings != key_bindings {
+            if let Err(e) = settings.borrow().key_bindings.save(KEY_BINDINGS_PATH) {
+                eprintln!(
+                    "error: failed to save key bindings to {}: {}",
+                    KEY_BINDINGS_PATH, e
+                );
             }
         }
-    };
 
-    game::single_play::execute_game(input, &mut drawer);
+        match choice.get() {
+            Some(menu::MainMenuChoice::Continue) => {
+                let session = game::Session::load(SESSION_PATH);
+                // 別の設定で始めたゲームの中断情報は，その設定で起動し直せば再開できるように残してメインメニューへ戻る
+                if let Ok(session) = session.as_ref() {
+                    if session.mode() != session_mode {
+                        eprintln!(
+                            "error: {} was saved with different options ({}); restart with them to continue",
+                            SESSION_PATH,
+                            session.mode()
+                        );
+                        continue;
+                    }
+                }
+                // 読めない中断情報は，再開できないので捨ててメインメニューへ戻る
+                if let Err(e) = std::fs::remove_file(SESSION_PATH) {
+                    eprintln!("error: failed to remove {}: {}", SESSION_PATH, e);
+                }
+                match session {
+                    Ok(session) => *pending_session.borrow_mut() = Some(session),
+                    Err(e) => {
+                        eprintln!("error: failed to load session from {}: {}", SESSION_PATH, e);
+                        continue;
+                    }
+                }
+            }
+            Some(menu::MainMenuChoice::Play) => {}
+            Some(menu::MainMenuChoice::Quit) | None => break,
+        }
+
+        let settings = *settings.borrow();
+        drawer.render_config = settings.render_config;
+        drawer.root_canvas.set_theme(settings.render_config.theme());
+        // 色の設定が変わった場合に備えて，次のフレームは画面全体を描き直す
+        drawer.previous_canvas = None;
+        let record_latency = |timestamps: Vec<std::time::Instant>| {
+            if let Some(monitor) = latency_monitor.as_ref() {
+                let mut monitor = monitor.borrow_mut();
+                timestamps.into_iter().for_each(|t| monitor.record_input(t));
+            }
+        };
+
+        // ボムセルのポリシーにも同じシード値を使い，同じポリシーなら同じブロック列になるようにする
+        let new_selector_with_seed = |seed| {
+            let selector = new_shape_selector(seed);
+            match bomb_policy {
+                Some(policy) => game::BombPolicySelector::with_seed(selector, policy, seed)
+                    .expect("bomb policy is validated while parsing"),
+                None => game::BombPolicySelector::inherit(selector),
+            }
+        };
+        let new_selector = || new_selector_with_seed(settings.seed.unwrap_or_else(rand::random));
+        // ゲームオーバー画面では，やり直すかメインメニューへ戻るかを選ぶ
+        let menu_input = || loop {
+            if quit_requested.get() {
+                break None;
+            }
+            if let Some(command) = input_source
+                .read_keys()
+                .into_iter()
+                .find_map(|key| user::MenuInputMapper.map(key))
+            {
+                break Some(command);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        };
+        let wait_input = || {
+            let (commands, timestamps) =
+                input_source.wait_available_keys_with_timestamps(&settings.key_bindings);
+            record_latency(timestamps);
+            commands
+        };
+        // 自動落下を待たずに操作できるよう，入力はブロックせずに読む
+        let poll_input = |pending: &mut VecDeque<_>| {
+            if pending.is_empty() {
+                let (commands, timestamps) =
+                    input_source.read_available_keys_with_timestamps(&settings.key_bindings);
+                record_latency(timestamps);
+                pending.extend(commands);
+            }
+            pending.pop_front()
+        };
+        let save_replay = |replay: game::Replay| {
+            if let Some(path) = record_path.as_ref() {
+                if let Err(e) = replay.save(path) {
+                    eprintln!("error: failed to save replay to {}: {}", path, e);
+                }
+            }
+        };
+
+        if puzzle_enabled {
+            // 一覧で戻るを選んだら，メインメニューへ戻る
+            if let Some(puzzle) = game::puzzle::select_puzzle(menu_input, &mut drawer) {
+                let play = |selector, drawer: &mut StdoutDrawer| {
+                    game::puzzle::execute_puzzle_with(
+                        puzzle.field(),
+                        selector,
+                        wait_input,
+                        drawer,
+                        on_event,
+                    )
+                };
+                game::single_play::run(|| puzzle.selector(), play, menu_input, &mut drawer);
+            }
+            if quit_requested.get() {
+                break;
+            }
+            continue;
+        }
+
+        if sprint_enabled {
+            let play = |selector: Selector, drawer: &mut StdoutDrawer| {
+                let replay_seed = selector.selector().seed();
+                // 最短記録が読めない場合は，記録がないものとして扱う
+                let mut best =
+                    game::sprint::BestTime::load(SPRINT_BEST_TIME_PATH).unwrap_or_default();
+                let mut pending = VecDeque::new();
+                let mut recorder = game::CommandRecorder::new(|| poll_input(&mut pending));
+                let result = game::sprint::execute_sprint_with(
+                    selector,
+                    || recorder.next_command(),
+                    game::Gravity::default(),
+                    &user::SystemClock,
+                    &mut best,
+                    game::sprint::GOAL_ROWS,
+                    drawer,
+                    on_event,
+                );
+                if let Err(e) = best.save(SPRINT_BEST_TIME_PATH) {
+                    eprintln!(
+                        "error: failed to save best time to {}: {}",
+                        SPRINT_BEST_TIME_PATH, e
+                    );
+                }
+                save_replay(recorder.into_replay(replay_seed));
+                result
+            };
+            game::single_play::run(new_selector, play, menu_input, &mut drawer);
+            if quit_requested.get() {
+                break;
+            }
+            continue;
+        }
+
+        if ultra_enabled {
+            let play = |selector: Selector, drawer: &mut StdoutDrawer| {
+                let replay_seed = selector.selector().seed();
+                let mut pending = VecDeque::new();
+                let mut recorder = game::CommandRecorder::new(|| poll_input(&mut pending));
+                let result = game::ultra::execute_ultra_with(
+                    selector,
+                    game::ultra::DEFAULT_DURATION,
+                    || recorder.next_command(),
+                    game::Gravity::default(),
+                    &user::SystemClock,
+                    drawer,
+                    on_event,
+                );
+                save_replay(recorder.into_replay(replay_seed));
+                result
+            };
+            game::single_play::run(new_selector, play, menu_input, &mut drawer);
+            if quit_requested.get() {
+                break;
+            }
+            continue;
+        }
+
+        if survival_enabled {
+            let play = |selector: Selector, drawer: &mut StdoutDrawer| {
+                let replay_seed = selector.selector().seed();
+                let mut pending = VecDeque::new();
+                let mut recorder = game::CommandRecorder::new(|| poll_input(&mut pending));
+                // せり上がりラインの穴の列も，ブロックと同じシード値で決める
+                let result = game::survival::execute_survival_with(
+                    selector,
+                    replay_seed,
+                    || recorder.next_command(),
+                    game::Gravity::default(),
+                    drawer,
+                    on_event,
+                );
+                save_replay(recorder.into_replay(replay_seed));
+                result
+            };
+            game::single_play::run(new_selector, play, menu_input, &mut drawer);
+            if quit_requested.get() {
+                break;
+            }
+            continue;
+        }
+
+        // 再開する場合は，ブロック生成ルールを中断した時点まで進め，中断した時点の状態から始める
+        // 途中でやめたときに保存できるよう，生成したブロックの数を数える
+        let resumed_state = RefCell::new(None);
+        let new_counting_selector = || match pending_session.borrow_mut().take() {
+            Some(session) => {
+                let selector = session.restore_selector(new_selector_with_seed(session.seed()));
+                *resumed_state.borrow_mut() = Some(session.into_state());
+                selector
+            }
+            None => game::CountingSelector::new(new_selector()),
+        };
+        let play = |mut selector: game::CountingSelector<Selector>, drawer: &mut StdoutDrawer| {
+            let replay_seed = selector.selector().selector().seed();
+            let resumed_state = resumed_state.borrow_mut().take();
+            let is_resumed = resumed_state.is_some();
+            let state = resumed_state.unwrap_or_else(|| {
+                game::single_play::GameState::new(game::Field::empty(), &mut selector)
+            });
+            let (result, replay) = if gravity_enabled {
+                let mut pending = VecDeque::new();
+                let mut recorder = game::CommandRecorder::new(|| poll_input(&mut pending));
+                let gravity = game::Gravity::default();
+                let result = game::single_play::execute_gravity_game_from_state(
+                    state,
+                    &mut selector,
+                    line_clear_rule,
+                    || recorder.next_command(),
+                    gravity,
+                    drawer,
+                    on_event,
+                );
+                (result, recorder.into_replay(replay_seed))
+            } else {
+                let mut recorder = game::CommandRecorder::new(wait_input);
+                let result = game::single_play::execute_game_from_state(
+                    state,
+                    &mut selector,
+                    line_clear_rule,
+                    || recorder.next_commands(),
+                    drawer,
+                    on_event,
+                );
+                (result, recorder.into_replay(replay_seed))
+            };
+            if let Some(state) = result.suspended.clone() {
+                let generated_blocks = selector.generated_blocks();
+                let session =
+                    game::Session::new(state, replay_seed, generated_blocks, session_mode.clone())
+                        .expect("command line arguments contain no newline");
+                if let Err(e) = session.save(SESSION_PATH) {
+                    eprintln!("error: failed to save session to {}: {}", SESSION_PATH, e);
+                }
+            }
+            // 再開したゲームの操作は途中からしか記録されず，リプレイとして再現できない
+            if !is_resumed {
+                save_replay(replay);
+            }
+            result
+        };
+        game::single_play::run(new_counting_selector, play, menu_input, &mut drawer);
+        if quit_requested.get() {
+            break;
+        }
+    }
+
+    // 終了前に元の画面へ戻し，カーソルを戻す
+    terminal.restore().unwrap();
+}
+
+/// 中断したゲームを再開するときに一致を確かめる，ゲームのモードやブロック生成ルールの設定を表す文字列を返す．
+/// 設定に関わるコマンドライン引数を，決まった順に空白で区切って並べる．
+fn session_mode(args: &[String]) -> String {
+    const FLAGS: [&str; 7] = [
+        "--pentris",
+        "--chaos",
+        "--giant",
+        "--history",
+        "--gravity",
+        "--classic",
+        "--line-gravity",
+    ];
+    let mut mode = vec!["endless".to_string()];
+    mode.extend(
+        FLAGS
+            .iter()
+            .filter(|&&flag| args.iter().any(|arg| arg == flag))
+            .map(|flag| flag.to_string()),
+    );
+    if let Some(i) = args.iter().position(|arg| arg == "--bombs") {
+        mode.push(format!("--bombs {}", args[i + 1]));
+    }
+    mode.join(" ")
+}
+
+/// 自己診断を実行し，結果を標準出力に表示する．
+/// # Returns
+/// すべての検査に合格した場合は`true`を返す．
+fn self_test() -> bool {
+    let terminal = console::Term::stdout();
+    let colors_supported = terminal.features().colors_supported();
+    let results = rustetris::self_test::run_all(colors_supported, terminal.size_checked());
+
+    for result in results.iter() {
+        let status = if result.passed { "PASS" } else { "FAIL" };
+        println!("[{}] {}: {}", status, result.name, result.message);
+    }
+
+    results.iter().all(|result| result.passed)
 }
 
 struct StdoutDrawer<'t> {
     terminal: &'t console::Term,
     root_canvas: RootCanvas,
+    /// 直前に端末へ表示したキャンバス．まだ何も表示していない場合は`None`．
+    /// 次のフレームでは，これと異なるセルだけを書き換える．
+    previous_canvas: Option<RootCanvas>,
+    render_config: RenderConfig,
+    /// 入力遅延の計測器．計測しない場合は`None`．
+    latency_monitor: Option<Rc<RefCell<user::LatencyMonitor>>>,
+    /// アニメーションのフレーム間の待機時間．
+    frame_duration: std::time::Duration,
 }
 
 impl<'t> Drawer for StdoutDrawer<'t> {
@@ -40,15 +675,67 @@ impl<'t> Drawer for StdoutDrawer<'t> {
         &mut self.root_canvas
     }
 
+    /// 端末の表示は消去せず，次の`show`で変わったセルだけを書き換える．
+    /// 画面全体を消去すると，遅い端末や遠隔接続ではちらつくため．
     fn clear(&mut self) {
         self.root_canvas.clear();
-        self.terminal.clear_screen().unwrap();
     }
 
     fn show(&mut self) {
+        // 計測中なら，画面右下に入力遅延を重ねて表示する
+        // 枠付きのフィールドの下に表示される文字と重ならないよう，フィールドより右側に寄せる
+        if let Some(monitor) = self.latency_monitor.as_ref() {
+            let bottom = PosY::origin() + self.root_canvas.size().y() + above(1);
+            let pos = Pos(PosX::right(13), bottom);
+            monitor.borrow().draw_on_child(pos, &mut self.root_canvas);
+        }
+
         let mut buffer = String::new();
-        self.root_canvas.construct_output_string(&mut buffer);
-        self.terminal.write_str(&buffer).unwrap();
+        let runs = self
+            .previous_canvas
+            .as_ref()
+            .and_then(|previous| dirty_runs(previous, &self.root_canvas));
+        let (width, height) = self.root_canvas.output_size();
+        let cell_count = width / 2 * height;
+        match runs {
+            // 変わったセルが半分以下であれば，その部分だけを書き換える
+            Some(runs) if runs.iter().map(|run| run.len).sum::<usize>() * 2 <= cell_count => {
+                for run in runs.iter() {
+                    let x = run.left.x().as_positive_index().unwrap() * 2;
+                    let y = run.left.y().as_positive_index().unwrap();
+                    self.root_canvas
+                        .construct_run_string(run, &mut buffer, &self.render_config)
+                        .unwrap();
+                    self.terminal.move_cursor_to(x, y).unwrap();
+                    self.terminal.write_str(&buffer).unwrap();
+                }
+            }
+            // 最初のフレームや大きく変わった場合は，画面全体を描き直す
+            _ => {
+                self.root_canvas
+                    .construct_output_string(&mut buffer, &self.render_config);
+                self.terminal.clear_screen().unwrap();
+                self.terminal.write_str(&buffer).unwrap();
+            }
+        }
         self.terminal.flush().unwrap();
+        self.previous_canvas = Some(self.root_canvas.clone());
+
+        // 表示が終わった時点で，この表示に反映された入力の遅延を記録する
+        if let Some(monitor) = self.latency_monitor.as_ref() {
+            monitor.borrow_mut().record_drawn(user::SystemClock.now());
+        }
+    }
+
+    fn wait_frame(&mut self) {
+        std::thread::sleep(self.frame_duration);
+    }
+
+    fn set_frame_duration(&mut self, duration: std::time::Duration) {
+        self.frame_duration = duration;
+    }
+
+    fn render_config(&self) -> RenderConfig {
+        self.render_config
     }
 }
@@ -0,0 +1,15 @@
+mod item_list;
+mod key_config;
+mod key_test;
+mod main_menu;
+mod seed_entry;
+mod settings;
+mod text_input;
+
+pub use item_list::ItemList;
+pub use key_config::KeyConfig;
+pub use key_test::KeyTest;
+pub use main_menu::{MainMenu, MainMenuChoice};
+pub use seed_entry::SeedEntry;
+pub use settings::{Settings, SettingsMenu};
+pub use text_input::TextInput;
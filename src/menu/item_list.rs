@@ -0,0 +1,92 @@
+use crate::geometry::*;
+use crate::graphics::*;
+
+/// 見出しと，ひとつだけ選択された項目の一覧を表示する描画物を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ItemList<'a> {
+    /// 見出し．
+    title: &'a str,
+    /// 各項目の表示名．
+    items: Vec<String>,
+    /// 選択中の項目のインデックス．
+    selected: usize,
+}
+
+impl<'a> ItemList<'a> {
+    pub fn new(title: &'a str, items: Vec<String>, selected: usize) -> ItemList<'a> {
+        Self {
+            title,
+            items,
+            selected,
+        }
+    }
+
+    /// 一覧の長さが`len`のとき，選択位置をひとつ上へ移動させた位置を返す．
+    /// 先頭の項目から上へ移動すると，末尾の項目へ戻る．
+    pub fn previous_index(selected: usize, len: usize) -> usize {
+        (selected + len - 1) % len
+    }
+
+    /// 一覧の長さが`len`のとき，選択位置をひとつ下へ移動させた位置を返す．
+    /// 末尾の項目から下へ移動すると，先頭の項目へ戻る．
+    pub fn next_index(selected: usize, len: usize) -> usize {
+        (selected + 1) % len
+    }
+}
+
+impl<'a> Drawable for ItemList<'a> {
+    fn region_size(&self) -> Movement {
+        let width = std::iter::once(self.title.len())
+            .chain(self.items.iter().map(|item| item.len() + 2))
+            .max()
+            .unwrap_or(0);
+        // 見出しと空行のぶん2行を加える
+        right(width.div_ceil(2) as i8) + below(self.items.len() as i8 + 2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let normal = canvas.theme().cell_color(ThemeColor::Text);
+        let selected = canvas.theme().inverted_color(ThemeColor::Text);
+
+        ColoredStr(self.title, normal).draw_on_child(Pos::origin(), canvas);
+
+        for (i, item) in self.items.iter().enumerate() {
+            let pos = Pos::origin() + below(i as i8 + 2);
+            if i == self.selected {
+                ColoredStr(format!("> {}", item), selected).draw_on_child(pos, canvas);
+            } else {
+                ColoredStr(format!("  {}", item), normal).draw_on_child(pos, canvas);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_wraps() {
+        assert_eq!(2, ItemList::previous_index(0, 3));
+        assert_eq!(0, ItemList::previous_index(1, 3));
+        assert_eq!(1, ItemList::next_index(0, 3));
+        assert_eq!(0, ItemList::next_index(2, 3));
+    }
+
+    #[test]
+    fn test_draw() {
+        let list = ItemList::new("Menu", vec!["Play".to_string(), "Quit".to_string()], 1);
+        assert_eq!(right(3) + below(4), list.region_size());
+
+        let mut canvas = RootCanvas::new();
+        list.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let lines = buffer
+            .lines()
+            .map(|line| line.trim_end())
+            .collect::<Vec<_>>();
+
+        assert_eq!(vec!["Menu", "", "  Play", "> Quit"], lines[..4].to_vec());
+    }
+}
@@ -0,0 +1,192 @@
+use super::{ItemList, KeyTest, Settings};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::ui::{Screen, ScreenTransition};
+use crate::user::{
+    key_label, key_name, GameCommand, InputMapper, Key, MenuCommand, MenuInputMapper,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// 各操作に割り当てるキーを変更する画面を表す．
+///
+/// 操作を選んだ後に押したキーを，その操作に割り当てる．
+/// 他の操作に割り当て済みのキーを押した場合は，その操作と割り当てを入れ替えて知らせる．
+pub struct KeyConfig {
+    /// 変更対象の設定．
+    settings: Rc<RefCell<Settings>>,
+    /// 選択中の項目のインデックス．末尾の項目は戻る．
+    selected: usize,
+    /// 新しいキーの入力を待っているかどうか．
+    waiting: bool,
+    /// 一覧の下に表示する案内．
+    message: Option<String>,
+}
+
+impl KeyConfig {
+    pub fn new(settings: Rc<RefCell<Settings>>) -> KeyConfig {
+        Self {
+            settings,
+            selected: 0,
+            waiting: false,
+            message: None,
+        }
+    }
+
+    /// 項目の数を返す．
+    fn item_count() -> usize {
+        GameCommand::ALL.len() + 1
+    }
+
+    /// 選択中の操作を返す．戻るを選択中の場合は`None`を返す．
+    fn selected_command(&self) -> Option<GameCommand> {
+        GameCommand::ALL.get(self.selected).copied()
+    }
+
+    /// 入力を待っている操作に，押されたキーを割り当てる．
+    fn assign(&mut self, command: GameCommand, key: Key) {
+        self.waiting = false;
+        if key == Key::Escape {
+            self.message = None;
+            return;
+        }
+        if key_name(key).is_none() {
+            self.message = Some("This key cannot be used".to_string());
+            return;
+        }
+        let swapped = self.settings.borrow_mut().key_bindings.assign(command, key);
+        self.message = swapped.map(|other| {
+            format!(
+                "Swapped with {}",
+                KeyTest::command_label(other).to_lowercase()
+            )
+        });
+    }
+}
+
+impl Screen for KeyConfig {
+    fn handle_key(&mut self, key: Key) -> ScreenTransition {
+        if self.waiting {
+            if let Some(command) = self.selected_command() {
+                self.assign(command, key);
+            }
+            return ScreenTransition::Stay;
+        }
+
+        match MenuInputMapper.map(key) {
+            Some(MenuCommand::Up) => {
+                self.selected = ItemList::previous_index(self.selected, Self::item_count());
+            }
+            Some(MenuCommand::Down) => {
+                self.selected = ItemList::next_index(self.selected, Self::item_count());
+            }
+            Some(MenuCommand::Proceed) => match self.selected_command() {
+                Some(command) => {
+                    self.waiting = true;
+                    self.message = Some(format!(
+                        "Press a key for {} (Esc to cancel)",
+                        KeyTest::command_label(command).to_lowercase()
+                    ));
+                }
+                None => return ScreenTransition::Pop,
+            },
+            Some(MenuCommand::Back) => return ScreenTransition::Pop,
+            None => {}
+        }
+        ScreenTransition::Stay
+    }
+
+    fn draw(&self, canvas: &mut RootCanvas) {
+        let bindings = self.settings.borrow().key_bindings;
+        let items = GameCommand::ALL
+            .iter()
+            .map(|&command| {
+                format!(
+                    "{:<14}{}",
+                    KeyTest::command_label(command),
+                    key_label(bindings.key(command))
+                )
+            })
+            .chain(std::iter::once("Back".to_string()))
+            .collect();
+        let list = ItemList::new("Customize keys", items, self.selected);
+        list.draw(canvas);
+
+        if let Some(message) = self.message.as_ref() {
+            let color = canvas.theme().cell_color(ThemeColor::Highlight);
+            let pos = Pos::origin() + list.region_size().y() + below(1);
+            ColoredStr(message.as_str(), color).draw_on_child(pos, canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::KeyBindings;
+
+    fn key_config() -> (KeyConfig, Rc<RefCell<Settings>>) {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        (KeyConfig::new(settings.clone()), settings)
+    }
+
+    #[test]
+    fn test_assign_key() {
+        let (mut config, settings) = key_config();
+
+        // 左移動を選んでから押したキーを割り当てる
+        config.handle_key(Key::Char('z'));
+        assert!(config.waiting);
+        config.handle_key(Key::Char('h'));
+        assert!(!config.waiting);
+        assert_eq!(Key::Char('h'), settings.borrow().key_bindings.left);
+        assert_eq!(None, config.message);
+
+        // 入力待ちの間は，メニュー操作のキーも割り当てる
+        config.handle_key(Key::Char('z'));
+        config.handle_key(Key::Char('x'));
+        assert_eq!(Key::Char('x'), settings.borrow().key_bindings.left);
+        assert_eq!(
+            Key::Char('h'),
+            settings.borrow().key_bindings.rotate_clockwise
+        );
+        assert_eq!(
+            Some("Swapped with rotate right".to_string()),
+            config.message
+        );
+        assert!(settings.borrow().key_bindings.conflicts().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_and_unusable_key() {
+        let (mut config, settings) = key_config();
+
+        config.handle_key(Key::Char('z'));
+        config.handle_key(Key::Escape);
+        assert!(!config.waiting);
+        assert_eq!(KeyBindings::DEFAULT, settings.borrow().key_bindings);
+
+        config.handle_key(Key::Char('z'));
+        config.handle_key(Key::Unknown);
+        assert_eq!(KeyBindings::DEFAULT, settings.borrow().key_bindings);
+        assert_eq!(Some("This key cannot be used".to_string()), config.message);
+    }
+
+    #[test]
+    fn test_back() {
+        let (mut config, _) = key_config();
+
+        config.handle_key(Key::ArrowUp);
+        assert_eq!(None, config.selected_command());
+        assert!(matches!(
+            config.handle_key(Key::Char('z')),
+            ScreenTransition::Pop
+        ));
+
+        let (mut config, _) = key_config();
+        assert!(matches!(
+            config.handle_key(Key::Char('x')),
+            ScreenTransition::Pop
+        ));
+    }
+}
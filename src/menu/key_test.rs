@@ -0,0 +1,186 @@
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::ui::{Screen, ScreenTransition};
+use crate::user::{key_label, GameCommand, Key, KeyBindings};
+
+mod consts {
+    /// 押されたキーに対応する操作を強調表示し続けるフレーム数．
+    pub const HIGHLIGHT_FRAMES: usize = 10;
+}
+
+use consts::*;
+
+/// キー配置を確認するための画面を表す．
+/// 押されたキーに割り当てられた操作を，数フレームの間だけ強調表示する．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyTest {
+    /// 確認対象のキー配置．
+    bindings: KeyBindings,
+    /// 強調表示中の操作と，強調表示が終わるまでの残りフレーム数．
+    highlighted: Option<(GameCommand, usize)>,
+}
+
+impl KeyTest {
+    pub fn new(bindings: KeyBindings) -> KeyTest {
+        Self {
+            bindings,
+            highlighted: None,
+        }
+    }
+
+    /// 現在強調表示中の操作を返す．
+    pub fn highlighted(&self) -> Option<GameCommand> {
+        self.highlighted.map(|(command, _)| command)
+    }
+
+    /// 押されたキーを処理する．
+    /// # Returns
+    /// 画面を閉じるキー(`Escape`)が押された場合は`false`を返す．
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        if key == Key::Escape {
+            return false;
+        }
+        if let Some(command) = self.bindings.command(key) {
+            self.highlighted = Some((command, HIGHLIGHT_FRAMES));
+        }
+        true
+    }
+
+    /// 強調表示のためのフレームを進める．
+    pub fn tick(&mut self) {
+        self.highlighted = match self.highlighted {
+            Some((command, frames)) if frames > 1 => Some((command, frames - 1)),
+            _ => None,
+        };
+    }
+
+    /// 指定した操作の行を表示するときの色を返す．
+    fn row_color(&self, theme: &Theme, command: GameCommand) -> CanvasCellColor {
+        if self.highlighted() == Some(command) {
+            theme.inverted_color(ThemeColor::Highlight)
+        } else {
+            theme.cell_color(ThemeColor::Text)
+        }
+    }
+
+    /// 操作の表示名を返す．
+    pub(super) fn command_label(command: GameCommand) -> &'static str {
+        use GameCommand::*;
+
+        match command {
+            Left => "Move left",
+            Right => "Move right",
+            Down => "Soft drop",
+            Drop => "Hard drop",
+            RotateClockwise => "Rotate right",
+            RotateUnticlockwise => "Rotate left",
+            Hold => "Hold",
+            Quit => "Quit",
+        }
+    }
+}
+
+impl Drawable for KeyTest {
+    fn region_size(&self) -> Movement {
+        // 見出し1行と空行1行，各操作1行
+        right(16) + below(GameCommand::ALL.len() as i8 + 2)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let theme = canvas.theme();
+        let title = ColoredStr(
+            "Press keys (Esc to exit)",
+            theme.cell_color(ThemeColor::Text),
+        );
+        title.draw_on_child(Pos::origin(), canvas);
+
+        for (i, &command) in GameCommand::ALL.iter().enumerate() {
+            let color = self.row_color(&theme, command);
+            let line = format!(
+                "{:<14}{:<18}",
+                Self::command_label(command),
+                key_label(self.bindings.key(command))
+            );
+            let pos = Pos::origin() + below(i as i8 + 2);
+            ColoredStr(line, color).draw_on_child(pos, canvas);
+        }
+    }
+}
+
+impl Screen for KeyTest {
+    fn handle_key(&mut self, key: Key) -> ScreenTransition {
+        if KeyTest::handle_key(self, key) {
+            ScreenTransition::Stay
+        } else {
+            ScreenTransition::Pop
+        }
+    }
+
+    fn tick(&mut self) {
+        KeyTest::tick(self);
+    }
+
+    fn draw(&self, canvas: &mut RootCanvas) {
+        Drawable::draw(self, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let mut key_test = KeyTest::new(KeyBindings::LEFT_HANDED);
+        assert_eq!(None, key_test.highlighted());
+
+        assert!(key_test.handle_key(Key::Char('a')));
+        assert_eq!(Some(GameCommand::Left), key_test.highlighted());
+
+        // 割り当てのないキーは強調表示を変えない
+        assert!(key_test.handle_key(Key::Char('z')));
+        assert_eq!(Some(GameCommand::Left), key_test.highlighted());
+
+        for _ in 0..HIGHLIGHT_FRAMES - 1 {
+            key_test.tick();
+            assert_eq!(Some(GameCommand::Left), key_test.highlighted());
+        }
+        key_test.tick();
+        assert_eq!(None, key_test.highlighted());
+    }
+
+    #[test]
+    fn test_escape() {
+        let mut key_test = KeyTest::new(KeyBindings::DEFAULT);
+        assert!(!key_test.handle_key(Key::Escape));
+    }
+
+    #[test]
+    fn test_draw_highlighted_row() {
+        let mut key_test = KeyTest::new(KeyBindings::DEFAULT);
+        key_test.handle_key(Key::Char('c'));
+
+        let theme = Theme::default();
+        for &command in GameCommand::ALL.iter() {
+            let expected = if command == GameCommand::Hold {
+                theme.color(ThemeColor::Highlight)
+            } else {
+                theme.color(ThemeColor::Background)
+            };
+            assert_eq!(expected, key_test.row_color(&theme, command).background());
+        }
+
+        let mut canvas = RootCanvas::new();
+        Drawable::draw(&key_test, &mut canvas);
+
+        let hold_index = GameCommand::ALL
+            .iter()
+            .position(|&command| command == GameCommand::Hold)
+            .unwrap();
+        let hold_row = 2 + hold_index;
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        let line = buffer.lines().nth(hold_row).unwrap();
+        assert!(line.starts_with("Hold          c"));
+    }
+}
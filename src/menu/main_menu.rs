@@ -0,0 +1,156 @@
+use super::{ItemList, Settings, SettingsMenu};
+use crate::graphics::*;
+use crate::ui::{Screen, ScreenTransition};
+use crate::user::{InputMapper, Key, MenuCommand, MenuInputMapper};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+/// メインメニューで最終的に選ばれた行動を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainMenuChoice {
+    /// 中断したゲームを再開する．
+    Continue,
+    /// ゲームを開始する．
+    Play,
+    /// ゲームを終了する．
+    Quit,
+}
+
+/// メインメニューの項目．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MainMenuItem {
+    Continue,
+    Play,
+    Settings,
+    Quit,
+}
+
+const ITEMS: [MainMenuItem; 4] = [
+    MainMenuItem::Continue,
+    MainMenuItem::Play,
+    MainMenuItem::Settings,
+    MainMenuItem::Quit,
+];
+
+/// メインメニュー画面を表す．
+/// ゲーム開始か終了が選ばれると画面を閉じ，選ばれた行動を`choice`に書き込む．
+pub struct MainMenu {
+    /// 選ばれた行動の書き込み先．
+    choice: Rc<Cell<Option<MainMenuChoice>>>,
+    /// 設定画面へ渡す設定．
+    settings: Rc<RefCell<Settings>>,
+    /// 表示する項目．
+    items: Vec<MainMenuItem>,
+    /// 選択中の項目のインデックス．
+    selected: usize,
+}
+
+impl MainMenu {
+    /// メインメニュー画面を返す．
+    /// `can_continue`が`false`の場合は，中断したゲームを再開する項目を表示しない．
+    pub fn new(
+        choice: Rc<Cell<Option<MainMenuChoice>>>,
+        settings: Rc<RefCell<Settings>>,
+        can_continue: bool,
+    ) -> MainMenu {
+        let items = ITEMS
+            .iter()
+            .copied()
+            .filter(|&item| can_continue || item != MainMenuItem::Continue)
+            .collect();
+        Self {
+            choice,
+            settings,
+            items,
+            selected: 0,
+        }
+    }
+
+    /// 行動を決定し，画面を閉じる．
+    fn choose(&self, choice: MainMenuChoice) -> ScreenTransition {
+        self.choice.set(Some(choice));
+        ScreenTransition::Pop
+    }
+}
+
+impl Screen for MainMenu {
+    fn handle_key(&mut self, key: Key) -> ScreenTransition {
+        match MenuInputMapper.map(key) {
+            Some(MenuCommand::Up) => {
+                self.selected = ItemList::previous_index(self.selected, self.items.len());
+                ScreenTransition::Stay
+            }
+            Some(MenuCommand::Down) => {
+                self.selected = ItemList::next_index(self.selected, self.items.len());
+                ScreenTransition::Stay
+            }
+            Some(MenuCommand::Proceed) => match self.items[self.selected] {
+                MainMenuItem::Continue => self.choose(MainMenuChoice::Continue),
+                MainMenuItem::Play => self.choose(MainMenuChoice::Play),
+                MainMenuItem::Settings => {
+                    let settings_menu = SettingsMenu::new(self.settings.clone());
+                    ScreenTransition::Push(Box::new(settings_menu))
+                }
+                MainMenuItem::Quit => self.choose(MainMenuChoice::Quit),
+            },
+            Some(MenuCommand::Back) => self.choose(MainMenuChoice::Quit),
+            None => ScreenTransition::Stay,
+        }
+    }
+
+    fn draw(&self, canvas: &mut RootCanvas) {
+        let items = self
+            .items
+            .iter()
+            .map(|item| format!("{:?}", item))
+            .collect();
+        ItemList::new("RUSTETRIS", items, self.selected).draw(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ScreenStack;
+
+    #[test]
+    fn test_navigation() {
+        let choice = Rc::new(Cell::new(None));
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let main_menu = MainMenu::new(choice.clone(), settings, false);
+        let mut stack = ScreenStack::new(Box::new(main_menu));
+
+        // 設定画面を開いて戻る
+        stack.handle_key(Key::ArrowDown);
+        stack.handle_key(Key::Char('z'));
+        assert_eq!(2, stack.len());
+        stack.handle_key(Key::Char('x'));
+        assert_eq!(1, stack.len());
+        assert_eq!(None, choice.get());
+
+        // ゲーム開始を選ぶ
+        stack.handle_key(Key::ArrowUp);
+        stack.handle_key(Key::Char('z'));
+        assert!(stack.is_empty());
+        assert_eq!(Some(MainMenuChoice::Play), choice.get());
+    }
+
+    #[test]
+    fn test_continue() {
+        let choice = Rc::new(Cell::new(None));
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let main_menu = MainMenu::new(choice.clone(), settings, true);
+
+        // 再開の項目が先頭に表示される
+        let mut canvas = RootCanvas::new();
+        main_menu.draw(&mut canvas);
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        assert!(s.find("Continue").unwrap() < s.find("Play").unwrap());
+
+        let mut stack = ScreenStack::new(Box::new(main_menu));
+        stack.handle_key(Key::Char('z'));
+        assert!(stack.is_empty());
+        assert_eq!(Some(MainMenuChoice::Continue), choice.get());
+    }
+}
@@ -0,0 +1,197 @@
+use super::{Settings, TextInput};
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::ui::{Screen, ScreenTransition};
+use crate::user::Key;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+mod consts {
+    /// `u64`の最大値の桁数．
+    pub const MAX_SEED_DIGITS: usize = 20;
+}
+
+use consts::*;
+
+/// ブロック列のシード値を入力する画面を表す．
+///
+/// 文字キーはそのまま入力欄の編集に使うため，Enterで決定し，Escで変更せずに閉じる．
+/// 空のまま決定すると，ゲームごとにランダムなシード値を使う設定に戻す．
+pub struct SeedEntry {
+    /// 変更対象の設定．
+    settings: Rc<RefCell<Settings>>,
+    /// シード値の入力欄．
+    input: TextInput,
+    /// 入力欄の下に表示する案内．
+    message: Option<String>,
+}
+
+impl SeedEntry {
+    /// 現在のシード値を入力済みの状態で画面を返す．
+    pub fn new(settings: Rc<RefCell<Settings>>) -> SeedEntry {
+        let mut input = TextInput::numeric(MAX_SEED_DIGITS);
+        if let Some(seed) = settings.borrow().seed {
+            seed.to_string().chars().for_each(|c| {
+                input.insert(c);
+            });
+        }
+        Self {
+            settings,
+            input,
+            message: None,
+        }
+    }
+
+    /// 入力欄の内容をシード値として設定する．
+    /// # Returns
+    /// 設定できた場合は`true`を返す．
+    /// 入力欄の内容が`u64`で表せない場合は設定を変えずに案内を表示し，`false`を返す．
+    fn confirm(&mut self) -> bool {
+        let text = self.input.text();
+        let seed = if text.is_empty() {
+            None
+        } else {
+            match text.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(_) => {
+                    self.message = Some(format!("Seed must be at most {}", u64::MAX));
+                    return false;
+                }
+            }
+        };
+        self.settings.borrow_mut().seed = seed;
+        true
+    }
+}
+
+impl Screen for SeedEntry {
+    fn handle_key(&mut self, key: Key) -> ScreenTransition {
+        if self.input.handle_key(key) {
+            self.message = None;
+            return ScreenTransition::Stay;
+        }
+
+        match key {
+            Key::Enter if self.confirm() => ScreenTransition::Pop,
+            Key::Escape => ScreenTransition::Pop,
+            _ => ScreenTransition::Stay,
+        }
+    }
+
+    fn tick(&mut self) {
+        self.input.tick();
+    }
+
+    fn draw(&self, canvas: &mut RootCanvas) {
+        let theme = canvas.theme();
+        let title = ColoredStr(
+            "Seed (empty for random)",
+            theme.cell_color(ThemeColor::Text),
+        );
+        title.draw_on_child(Pos::origin(), canvas);
+        let hint = ColoredStr(
+            "Enter to set, Esc to cancel",
+            theme.cell_color(ThemeColor::Text),
+        );
+        hint.draw_on_child(Pos::origin() + below(1), canvas);
+
+        self.input.draw_on_child(Pos::origin() + below(3), canvas);
+
+        if let Some(message) = self.message.as_ref() {
+            let color = theme.cell_color(ThemeColor::Warning);
+            ColoredStr(message.as_str(), color).draw_on_child(Pos::origin() + below(5), canvas);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ScreenStack;
+
+    fn seed_entry(seed: Option<u64>) -> (ScreenStack, Rc<RefCell<Settings>>) {
+        let settings = Rc::new(RefCell::new(Settings {
+            seed,
+            ..Settings::default()
+        }));
+        let stack = ScreenStack::new(Box::new(SeedEntry::new(settings.clone())));
+        (stack, settings)
+    }
+
+    fn type_keys(stack: &mut ScreenStack, keys: &[Key]) {
+        keys.iter().for_each(|&key| stack.handle_key(key));
+    }
+
+    #[test]
+    fn test_enter_seed() {
+        let (mut stack, settings) = seed_entry(None);
+
+        // メニュー操作に使う文字キーを押しても画面は閉じず，数字以外は入力されない
+        type_keys(
+            &mut stack,
+            &[
+                Key::Char('4'),
+                Key::Char('z'),
+                Key::Char('x'),
+                Key::Char('2'),
+            ],
+        );
+        assert_eq!(1, stack.len());
+        stack.handle_key(Key::Enter);
+        assert!(stack.is_empty());
+        assert_eq!(Some(42), settings.borrow().seed);
+    }
+
+    #[test]
+    fn test_edit_current_seed() {
+        let (mut stack, settings) = seed_entry(Some(123));
+
+        type_keys(&mut stack, &[Key::Backspace, Key::Char('9'), Key::Enter]);
+        assert_eq!(Some(129), settings.borrow().seed);
+    }
+
+    #[test]
+    fn test_clear_seed() {
+        let (mut stack, settings) = seed_entry(Some(7));
+
+        type_keys(&mut stack, &[Key::Backspace, Key::Enter]);
+        assert!(stack.is_empty());
+        assert_eq!(None, settings.borrow().seed);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let (mut stack, settings) = seed_entry(Some(7));
+
+        type_keys(&mut stack, &[Key::Char('1'), Key::Escape]);
+        assert!(stack.is_empty());
+        assert_eq!(Some(7), settings.borrow().seed);
+    }
+
+    #[test]
+    fn test_reject_too_large_seed() {
+        let (mut stack, settings) = seed_entry(None);
+
+        // u64::MAXより1大きい値
+        let keys = "18446744073709551616"
+            .chars()
+            .map(Key::Char)
+            .collect::<Vec<_>>();
+        type_keys(&mut stack, &keys);
+        stack.handle_key(Key::Enter);
+        assert_eq!(1, stack.len());
+        assert_eq!(None, settings.borrow().seed);
+
+        let mut canvas = RootCanvas::new();
+        stack.draw(&mut canvas);
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        assert!(s.contains("18446744073709551616"));
+        assert!(s.contains("Seed must be at most 18446744073709551615"));
+
+        // 編集すると案内は消え，決定できる
+        type_keys(&mut stack, &[Key::Backspace, Key::Enter]);
+        assert!(stack.is_empty());
+        assert_eq!(Some(1844674407370955161), settings.borrow().seed);
+    }
+}
@@ -0,0 +1,269 @@
+use super::{ItemList, KeyConfig, KeyTest, SeedEntry};
+use crate::graphics::*;
+use crate::ui::{Screen, ScreenTransition};
+use crate::user::{InputMapper, Key, KeyBindings, MenuCommand, MenuInputMapper};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// ゲーム全体の設定を表す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Settings {
+    /// ゲームプレイ画面のキー配置．
+    pub key_bindings: KeyBindings,
+    /// 描画方法に関する設定．
+    pub render_config: RenderConfig,
+    /// ブロック列のシード値．
+    /// `None`の場合はゲームごとにランダムに決める．
+    pub seed: Option<u64>,
+}
+
+/// 選択可能なキー配置と，その表示名．
+const KEY_BINDING_PRESETS: [(&str, KeyBindings); 3] = [
+    ("Default", KeyBindings::DEFAULT),
+    ("Left-handed", KeyBindings::LEFT_HANDED),
+    ("Single hand", KeyBindings::SINGLE_HAND),
+];
+
+/// 設定画面の項目．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SettingsItem {
+    KeyBindings,
+    KeyConfig,
+    ReducedMotion,
+    Theme,
+    Seed,
+    KeyTest,
+    Back,
+}
+
+const ITEMS: [SettingsItem; 7] = [
+    SettingsItem::KeyBindings,
+    SettingsItem::KeyConfig,
+    SettingsItem::ReducedMotion,
+    SettingsItem::Theme,
+    SettingsItem::Seed,
+    SettingsItem::KeyTest,
+    SettingsItem::Back,
+];
+
+/// 設定を変更する画面を表す．
+pub struct SettingsMenu {
+    /// 変更対象の設定．
+    settings: Rc<RefCell<Settings>>,
+    /// 選択中の項目のインデックス．
+    selected: usize,
+}
+
+impl SettingsMenu {
+    pub fn new(settings: Rc<RefCell<Settings>>) -> SettingsMenu {
+        Self {
+            settings,
+            selected: 0,
+        }
+    }
+
+    /// 指定した項目の表示名を返す．
+    fn label(&self, item: SettingsItem) -> String {
+        let settings = self.settings.borrow();
+        match item {
+            SettingsItem::KeyBindings => {
+                let name = KEY_BINDING_PRESETS
+                    .iter()
+                    .find(|(_, preset)| *preset == settings.key_bindings)
+                    .map(|(name, _)| *name)
+                    .unwrap_or("Custom");
+                format!("Keys: {}", name)
+            }
+            SettingsItem::ReducedMotion => {
+                let state = if settings.render_config.reduced_motion {
+                    "On"
+                } else {
+                    "Off"
+                };
+                format!("Reduced motion: {}", state)
+            }
+            SettingsItem::Theme => {
+                format!("Theme: {}", settings.render_config.theme_name.label())
+            }
+            SettingsItem::Seed => match settings.seed {
+                Some(seed) => format!("Seed: {}", seed),
+                None => "Seed: Random".to_string(),
+            },
+            SettingsItem::KeyConfig => "Customize keys".to_string(),
+            SettingsItem::KeyTest => "Key test".to_string(),
+            SettingsItem::Back => "Back".to_string(),
+        }
+    }
+
+    /// キー配置を次のプリセットに切り替える．
+    fn cycle_key_bindings(&mut self) {
+        let mut settings = self.settings.borrow_mut();
+        let next_index = KEY_BINDING_PRESETS
+            .iter()
+            .position(|(_, preset)| *preset == settings.key_bindings)
+            .map(|i| (i + 1) % KEY_BINDING_PRESETS.len())
+            .unwrap_or(0);
+        settings.key_bindings = KEY_BINDING_PRESETS[next_index].1;
+    }
+}
+
+impl Screen for SettingsMenu {
+    fn handle_key(&mut self, key: Key) -> ScreenTransition {
+        match MenuInputMapper.map(key) {
+            Some(MenuCommand::Up) => {
+                self.selected = ItemList::previous_index(self.selected, ITEMS.len());
+            }
+            Some(MenuCommand::Down) => {
+                self.selected = ItemList::next_index(self.selected, ITEMS.len());
+            }
+            Some(MenuCommand::Proceed) => match ITEMS[self.selected] {
+                SettingsItem::KeyBindings => self.cycle_key_bindings(),
+                SettingsItem::ReducedMotion => {
+                    let mut settings = self.settings.borrow_mut();
+                    let reduced_motion = &mut settings.render_config.reduced_motion;
+                    *reduced_motion = !*reduced_motion;
+                }
+                SettingsItem::Theme => {
+                    let mut settings = self.settings.borrow_mut();
+                    let theme_name = &mut settings.render_config.theme_name;
+                    *theme_name = theme_name.next();
+                }
+                SettingsItem::KeyConfig => {
+                    let key_config = KeyConfig::new(self.settings.clone());
+                    return ScreenTransition::Push(Box::new(key_config));
+                }
+                SettingsItem::Seed => {
+                    let seed_entry = SeedEntry::new(self.settings.clone());
+                    return ScreenTransition::Push(Box::new(seed_entry));
+                }
+                SettingsItem::KeyTest => {
+                    let key_bindings = self.settings.borrow().key_bindings;
+                    return ScreenTransition::Push(Box::new(KeyTest::new(key_bindings)));
+                }
+                SettingsItem::Back => return ScreenTransition::Pop,
+            },
+            Some(MenuCommand::Back) => return ScreenTransition::Pop,
+            None => {}
+        }
+        ScreenTransition::Stay
+    }
+
+    fn draw(&self, canvas: &mut RootCanvas) {
+        let items = ITEMS.iter().map(|&item| self.label(item)).collect();
+        ItemList::new("Settings", items, self.selected).draw(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::ScreenStack;
+
+    fn settings_menu() -> (SettingsMenu, Rc<RefCell<Settings>>) {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        (SettingsMenu::new(settings.clone()), settings)
+    }
+
+    #[test]
+    fn test_cycle_key_bindings() {
+        let (mut menu, settings) = settings_menu();
+
+        assert!(matches!(
+            menu.handle_key(Key::Char('z')),
+            ScreenTransition::Stay
+        ));
+        assert_eq!(KeyBindings::LEFT_HANDED, settings.borrow().key_bindings);
+        menu.handle_key(Key::Char('z'));
+        menu.handle_key(Key::Char('z'));
+        assert_eq!(KeyBindings::DEFAULT, settings.borrow().key_bindings);
+    }
+
+    #[test]
+    fn test_toggle_reduced_motion() {
+        let (mut menu, settings) = settings_menu();
+
+        menu.handle_key(Key::ArrowDown);
+        menu.handle_key(Key::ArrowDown);
+        menu.handle_key(Key::Char('z'));
+        assert!(settings.borrow().render_config.reduced_motion);
+        assert_eq!(
+            "Reduced motion: On",
+            menu.label(SettingsItem::ReducedMotion)
+        );
+    }
+
+    #[test]
+    fn test_cycle_theme() {
+        let (mut menu, settings) = settings_menu();
+
+        (0..3).for_each(|_| {
+            menu.handle_key(Key::ArrowDown);
+        });
+        menu.handle_key(Key::Char('z'));
+        assert_eq!(
+            ThemeName::HighContrast,
+            settings.borrow().render_config.theme_name
+        );
+        assert_eq!("Theme: High contrast", menu.label(SettingsItem::Theme));
+        menu.handle_key(Key::Char('z'));
+        assert_eq!(
+            ThemeName::Standard,
+            settings.borrow().render_config.theme_name
+        );
+    }
+
+    #[test]
+    fn test_customize_keys() {
+        let (mut menu, settings) = settings_menu();
+
+        menu.handle_key(Key::ArrowDown);
+        assert!(matches!(
+            menu.handle_key(Key::Char('z')),
+            ScreenTransition::Push(_)
+        ));
+        settings
+            .borrow_mut()
+            .key_bindings
+            .assign(crate::user::GameCommand::Hold, Key::Char('v'));
+        assert_eq!("Keys: Custom", menu.label(SettingsItem::KeyBindings));
+    }
+
+    #[test]
+    fn test_enter_seed() {
+        let settings = Rc::new(RefCell::new(Settings::default()));
+        let menu = SettingsMenu::new(settings.clone());
+        assert_eq!("Seed: Random", menu.label(SettingsItem::Seed));
+
+        let mut stack = ScreenStack::new(Box::new(menu));
+        (0..4).for_each(|_| stack.handle_key(Key::ArrowDown));
+        stack.handle_key(Key::Char('z'));
+        assert_eq!(2, stack.len());
+        [Key::Char('1'), Key::Char('2'), Key::Enter]
+            .iter()
+            .for_each(|&key| stack.handle_key(key));
+        assert_eq!(1, stack.len());
+        assert_eq!(Some(12), settings.borrow().seed);
+
+        let mut canvas = RootCanvas::new();
+        stack.draw(&mut canvas);
+        let mut s = String::new();
+        canvas.construct_plain_string(&mut s);
+        assert!(s.contains("Seed: 12"));
+    }
+
+    #[test]
+    fn test_open_key_test_and_back() {
+        let (mut menu, _) = settings_menu();
+
+        menu.handle_key(Key::ArrowUp);
+        menu.handle_key(Key::ArrowUp);
+        assert!(matches!(
+            menu.handle_key(Key::Char('z')),
+            ScreenTransition::Push(_)
+        ));
+        assert!(matches!(
+            menu.handle_key(Key::Char('x')),
+            ScreenTransition::Pop
+        ));
+    }
+}
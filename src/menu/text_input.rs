@@ -0,0 +1,339 @@
+use crate::geometry::*;
+use crate::graphics::*;
+use crate::user::Key;
+
+mod consts {
+    /// カーソルの表示または非表示が続くフレーム数．
+    pub const CURSOR_FLASH_HALF_PERIOD: usize = 5;
+}
+
+use consts::*;
+
+/// 1行のテキスト入力欄を表す．
+/// シード値やパズルファイルのパス，プロフィール名の入力に利用される．
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    /// 入力済みの文字列．
+    text: Vec<char>,
+    /// カーソル位置．
+    /// `text`の何文字目の直前にカーソルがあるかを表し，`text.len()`の場合は末尾を表す．
+    cursor: usize,
+    /// 入力可能な最大文字数．
+    max_len: usize,
+    /// 入力を許可する文字であるかを判定する関数．
+    filter: fn(char) -> bool,
+    /// カーソルの点滅に利用するフレーム数．
+    frame: usize,
+}
+
+impl TextInput {
+    /// 空の入力欄を返す．
+    /// ただし`filter`が`true`を返す場合でも，表示できない文字(半角英数字記号以外)は入力できない．
+    pub fn new(max_len: usize, filter: fn(char) -> bool) -> TextInput {
+        Self {
+            text: vec![],
+            cursor: 0,
+            max_len,
+            filter,
+            frame: 0,
+        }
+    }
+
+    /// 数字だけを入力できる空の入力欄を返す．
+    pub fn numeric(max_len: usize) -> TextInput {
+        Self::new(max_len, |c| c.is_ascii_digit())
+    }
+
+    /// 表示可能なすべての文字を入力できる空の入力欄を返す．
+    pub fn any_printable(max_len: usize) -> TextInput {
+        Self::new(max_len, |_| true)
+    }
+
+    /// 入力済みの文字列を返す．
+    pub fn text(&self) -> String {
+        self.text.iter().collect()
+    }
+
+    /// 現在のカーソル位置を返す．
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// カーソル位置に文字を挿入し，カーソルを1文字ぶん右へ進める．
+    /// # Returns
+    /// 文字を挿入できた場合は`true`を返す．
+    /// 許可されていない文字であるか，最大文字数に達している場合は何もせず`false`を返す．
+    pub fn insert(&mut self, c: char) -> bool {
+        let is_printable = c.is_ascii() && !c.is_ascii_control();
+        if !is_printable || !(self.filter)(c) || self.text.len() >= self.max_len {
+            return false;
+        }
+        self.text.insert(self.cursor, c);
+        self.cursor += 1;
+        true
+    }
+
+    /// カーソルの直前の文字を削除する．
+    /// # Returns
+    /// 削除できた場合は`true`を返す．
+    pub fn backspace(&mut self) -> bool {
+        if self.cursor == 0 {
+            false
+        } else {
+            self.cursor -= 1;
+            self.text.remove(self.cursor);
+            true
+        }
+    }
+
+    /// カーソル位置の文字を削除する．
+    /// # Returns
+    /// 削除できた場合は`true`を返す．
+    pub fn delete(&mut self) -> bool {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// カーソルを1文字ぶん左へ移動させる．
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// カーソルを1文字ぶん右へ移動させる．
+    pub fn move_right(&mut self) {
+        self.cursor = std::cmp::min(self.cursor + 1, self.text.len());
+    }
+
+    /// 指定したキー入力を編集操作として適用する．
+    /// # Returns
+    /// キー入力が編集操作として解釈された場合は`true`を返す．
+    /// 決定やキャンセルなど，編集操作に対応しないキーの場合は`false`を返す．
+    /// このとき，キーの処理は呼び出し元に委ねられる．
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        match key {
+            Key::Char(c) => {
+                self.insert(c);
+                true
+            }
+            Key::Backspace => {
+                self.backspace();
+                true
+            }
+            Key::Del => {
+                self.delete();
+                true
+            }
+            Key::ArrowLeft => {
+                self.move_left();
+                true
+            }
+            Key::ArrowRight => {
+                self.move_right();
+                true
+            }
+            Key::Home => {
+                self.cursor = 0;
+                true
+            }
+            Key::End => {
+                self.cursor = self.text.len();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// カーソル点滅のためのフレームを進める．
+    pub fn tick(&mut self) {
+        self.frame = self.frame.wrapping_add(1);
+    }
+
+    /// 文字列を表示するときの色．
+    fn color(theme: &Theme) -> CanvasCellColor {
+        theme.cell_color(ThemeColor::Text)
+    }
+
+    /// カーソルを表示するときの色．文字列の色を反転した色．
+    fn cursor_color(theme: &Theme) -> CanvasCellColor {
+        theme.inverted_color(ThemeColor::Text)
+    }
+
+    /// カーソルが存在するキャンバスセルを，カーソルを表示した状態で返す．
+    /// カーソル位置の文字を隠さないよう，文字はそのままに色を反転して表示する．
+    /// 色はキャンバスセル単位で指定するため，同じセルのもう1文字も反転して表示される．
+    fn cursor_cell(&self, theme: &Theme) -> CanvasCell {
+        let square_index = self.cursor / 2;
+        let mut chars = [' ', ' '];
+        for (i, c) in chars.iter_mut().enumerate() {
+            if let Some(&t) = self.text.get(square_index * 2 + i) {
+                *c = t;
+            }
+        }
+        CanvasCell::new(
+            SquareChar::new(chars[0], chars[1]),
+            Self::cursor_color(theme),
+        )
+    }
+}
+
+impl Drawable for TextInput {
+    fn region_size(&self) -> Movement {
+        // 末尾にカーソルを表示するための1文字ぶんを含める
+        let square_char_len = (self.max_len + 1).div_ceil(2);
+        right(square_char_len as i8) + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let theme = canvas.theme();
+        ColoredStr(self.text(), Self::color(&theme)).draw(canvas);
+
+        let cursor_pos = Pos::origin() + right((self.cursor / 2) as i8);
+        let cursor = Flashing::new(
+            self.cursor_cell(&theme),
+            self.frame,
+            CURSOR_FLASH_HALF_PERIOD,
+        );
+        cursor.draw_on_child(cursor_pos, canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_with(text: &str) -> TextInput {
+        let mut input = TextInput::any_printable(16);
+        for c in text.chars() {
+            assert!(input.insert(c));
+        }
+        input
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut input = input_with("ac");
+        input.move_left();
+        assert!(input.insert('b'));
+        assert_eq!("abc", input.text());
+        assert_eq!(2, input.cursor());
+    }
+
+    #[test]
+    fn test_insert_max_len() {
+        let mut input = TextInput::any_printable(2);
+        assert!(input.insert('a'));
+        assert!(input.insert('b'));
+        assert!(!input.insert('c'));
+        assert_eq!("ab", input.text());
+    }
+
+    #[test]
+    fn test_backspace() {
+        let mut input = input_with("abc");
+        input.move_left();
+        assert!(input.backspace());
+        assert_eq!("ac", input.text());
+        assert_eq!(1, input.cursor());
+
+        input.move_left();
+        // 先頭では何も消せない
+        assert!(!input.backspace());
+        assert_eq!("ac", input.text());
+    }
+
+    #[test]
+    fn test_delete() {
+        let mut input = input_with("abc");
+        // 末尾では何も消せない
+        assert!(!input.delete());
+
+        input.move_left();
+        input.move_left();
+        assert!(input.delete());
+        assert_eq!("ac", input.text());
+        assert_eq!(1, input.cursor());
+    }
+
+    #[test]
+    fn test_move_cursor_is_clamped() {
+        let mut input = input_with("ab");
+        input.move_right();
+        assert_eq!(2, input.cursor());
+        input.move_left();
+        input.move_left();
+        input.move_left();
+        assert_eq!(0, input.cursor());
+    }
+
+    #[test]
+    fn test_filter() {
+        let mut input = TextInput::numeric(8);
+        assert!(input.insert('4'));
+        assert!(!input.insert('a'));
+        assert!(input.insert('2'));
+        assert_eq!("42", input.text());
+
+        // 表示できない文字はどの入力欄にも入力できない
+        let mut input = TextInput::any_printable(8);
+        assert!(!input.insert('\n'));
+        assert!(!input.insert('あ'));
+        assert_eq!("", input.text());
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut input = TextInput::any_printable(8);
+        assert!(input.handle_key(Key::Char('a')));
+        assert!(input.handle_key(Key::Char('b')));
+        assert!(input.handle_key(Key::Home));
+        assert!(input.handle_key(Key::Del));
+        assert_eq!("b", input.text());
+        // 決定やキャンセルは呼び出し元が処理する
+        assert!(!input.handle_key(Key::Enter));
+        assert!(!input.handle_key(Key::Escape));
+    }
+
+    #[test]
+    fn test_draw_cursor_mid_string() {
+        let mut input = input_with("12345");
+        input.move_left();
+        input.move_left();
+        input.move_left();
+
+        let render = |input: &TextInput| {
+            let mut canvas = RootCanvas::new();
+            input.draw_on_child(Pos::origin(), &mut canvas);
+            let mut s = String::new();
+            canvas.construct_plain_string(&mut s);
+            let colors = canvas
+                .iter_pos_and_cell()
+                .filter(|(pos, _)| pos.y() == PosY::origin())
+                .take(3)
+                .map(|(_, cell)| cell.color)
+                .collect::<Vec<_>>();
+            (s.lines().next().unwrap().trim_end().to_owned(), colors)
+        };
+
+        // カーソル位置の文字は隠さず，カーソルのあるセルの色だけを反転する
+        let (text, colors) = render(&input);
+        assert_eq!("12345", text);
+        let theme = Theme::default();
+        let normal = TextInput::color(&theme);
+        assert_eq!(
+            vec![normal, TextInput::cursor_color(&theme), normal],
+            colors
+        );
+
+        // カーソルが非表示のフレームでは文字列だけが表示される
+        for _ in 0..CURSOR_FLASH_HALF_PERIOD {
+            input.tick();
+        }
+        let (text, colors) = render(&input);
+        assert_eq!("12345", text);
+        assert_eq!(vec![normal; 3], colors);
+    }
+}
@@ -0,0 +1,10 @@
+pub use crate::game::animation::Drawer;
+pub use crate::game::field_under_agent_control::GameCommandResult;
+pub use crate::game::{
+    Block, BlockQueue, BlockSelector, BlockShape, BombTag, Cell, DoubleBlockShape, Field,
+    FieldUnderAgentControl, QuadrupleBlockShape, QuintupleBlockShape, SextupleBlockShape,
+    SingleBlockShape, TripleBlockShape,
+};
+pub use crate::geometry::*;
+pub use crate::graphics::{Canvas, Drawable, RenderConfig, RootCanvas};
+pub use crate::user::GameCommand;
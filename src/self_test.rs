@@ -0,0 +1,237 @@
+use crate::game::Field;
+use crate::game::{
+    validate_templates, BlockQueue, BlockSelector, BlockShape, BombTag, Cell,
+    FieldUnderAgentControl, QuadrupleBlockShape,
+};
+use crate::graphics::*;
+use itertools::Itertools;
+
+/// 自己診断のひとつの検査項目の結果を表す．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// 検査項目の名前．
+    pub name: &'static str,
+    /// 検査に合格したか．
+    pub passed: bool,
+    /// 検査結果の説明．
+    pub message: String,
+}
+
+impl CheckResult {
+    fn pass(name: &'static str, message: impl Into<String>) -> CheckResult {
+        Self {
+            name,
+            passed: true,
+            message: message.into(),
+        }
+    }
+
+    fn fail(name: &'static str, message: impl Into<String>) -> CheckResult {
+        Self {
+            name,
+            passed: false,
+            message: message.into(),
+        }
+    }
+}
+
+/// 検査対象となるすべての種類のセル．
+const ALL_CELLS: [Cell; 10] = [
+    Cell::Empty,
+    Cell::Normal,
+    Cell::Steel,
+    Cell::Bomb,
+    Cell::BigBombUpperLeft,
+    Cell::BigBombUpperRight,
+    Cell::BigBombLowerLeft,
+    Cell::BigBombLowerRight,
+    Cell::HugeBombCenter,
+    Cell::HugeBombPart,
+];
+
+/// すべてのブロック形状テンプレートに不備がないか検査する．
+pub fn check_block_templates() -> CheckResult {
+    const NAME: &str = "block templates";
+
+    let errors = validate_templates();
+    if errors.is_empty() {
+        CheckResult::pass(NAME, "all shape tables are valid")
+    } else {
+        let message = errors.iter().map(|e| format!("{:?}", e)).join(", ");
+        CheckResult::fail(NAME, message)
+    }
+}
+
+/// すべての種類のセルが，端末の色表示能力の範囲で見分けられる状態で描画されるか検査する．
+/// # Params
+/// 1. `colors_supported` 端末が色を表示できるか．
+///    色を表示できない端末では，セルの種類を文字だけで見分けられる必要がある．
+pub fn check_cell_rendering(colors_supported: bool) -> CheckResult {
+    const NAME: &str = "cell rendering";
+
+    // 空セル以外は，前景色と背景色が異なっていないと見えない
+    // 組み込みの配色と端末が表示できる色の範囲によって色が変わるので，すべての組み合わせで確かめる
+    let themes = ThemeName::ALL
+        .iter()
+        .cartesian_product([ColorSupport::Basic, ColorSupport::Color256])
+        .map(|(&name, support)| Theme::new(name, support))
+        .collect::<Vec<_>>();
+    let invisible_cells = ALL_CELLS
+        .iter()
+        .filter(|cell| !cell.is_empty())
+        .filter(|cell| {
+            themes.iter().any(|theme| {
+                let color = cell.canvas_cell(theme).color;
+                color.foreground() == color.background()
+            })
+        })
+        .collect::<Vec<_>>();
+    if !invisible_cells.is_empty() {
+        return CheckResult::fail(NAME, format!("invisible cells: {:?}", invisible_cells));
+    }
+
+    if colors_supported {
+        return CheckResult::pass(NAME, "all cells are rendered in colors");
+    }
+
+    // 色を表示できない場合，同じ文字で描画されるセル同士は見分けられない
+    let indistinguishable_cells = ALL_CELLS
+        .iter()
+        .tuple_combinations()
+        .filter(|(a, b)| a.canvas_cell(&themes[0]).c == b.canvas_cell(&themes[0]).c)
+        .collect::<Vec<_>>();
+    if indistinguishable_cells.is_empty() {
+        CheckResult::pass(NAME, "all cells are distinguishable without colors")
+    } else {
+        CheckResult::fail(
+            NAME,
+            format!(
+                "no color support and cells share glyphs: {:?}",
+                indistinguishable_cells
+            ),
+        )
+    }
+}
+
+/// 端末がゲーム画面を表示できる大きさであるか検査する．
+/// # Params
+/// 1. `terminal_size` 端末の大きさ(行数, 桁数)．大きさを取得できなかった場合は`None`．
+pub fn check_terminal_size(terminal_size: Option<(u16, u16)>) -> CheckResult {
+    const NAME: &str = "terminal size";
+
+    let (min_columns, min_rows) = RootCanvas::new().output_size();
+    match terminal_size {
+        Some((rows, columns)) if rows as usize >= min_rows && columns as usize >= min_columns => {
+            CheckResult::pass(NAME, format!("{}x{}", columns, rows))
+        }
+        Some((rows, columns)) => CheckResult::fail(
+            NAME,
+            format!(
+                "{}x{} is smaller than {}x{}",
+                columns, rows, min_columns, min_rows
+            ),
+        ),
+        None => CheckResult::fail(NAME, "could not detect terminal size"),
+    }
+}
+
+/// サンプル画面を描画し，出力文字列の生成とキャンバスの消去が正しく行えるか検査する．
+pub fn check_sample_frame() -> CheckResult {
+    const NAME: &str = "sample frame";
+
+    let mut selector = SampleBlockSelector;
+    let queue = BlockQueue::new(&mut selector);
+    let agent_field = match FieldUnderAgentControl::new(Field::empty(), queue, &mut selector) {
+        Some(agent_field) => agent_field,
+        None => return CheckResult::fail(NAME, "could not spawn a block on an empty field"),
+    };
+
+    let mut canvas = RootCanvas::new();
+    agent_field.draw(&mut canvas);
+
+    let (_, rows) = canvas.output_size();
+    let mut buffer = String::new();
+    canvas.construct_output_string(&mut buffer, &RenderConfig::default());
+    if buffer.lines().count() != rows {
+        return CheckResult::fail(NAME, format!("rendered {} lines", buffer.lines().count()));
+    }
+    canvas.construct_plain_string(&mut buffer);
+    if buffer.chars().all(|c| c.is_whitespace()) {
+        return CheckResult::fail(NAME, "nothing was rendered");
+    }
+
+    canvas.clear();
+    canvas.construct_plain_string(&mut buffer);
+    if !buffer.chars().all(|c| c.is_whitespace()) {
+        return CheckResult::fail(NAME, "canvas was not restored by clear");
+    }
+
+    CheckResult::pass(NAME, "rendered and restored")
+}
+
+/// すべての検査を実行する．
+/// # Params
+/// 1. `colors_supported` 端末が色を表示できるか．
+/// 1. `terminal_size` 端末の大きさ(行数, 桁数)．
+pub fn run_all(colors_supported: bool, terminal_size: Option<(u16, u16)>) -> Vec<CheckResult> {
+    vec![
+        check_block_templates(),
+        check_cell_rendering(colors_supported),
+        check_terminal_size(terminal_size),
+        check_sample_frame(),
+    ]
+}
+
+/// サンプル画面の描画に利用するブロック生成ルール．
+struct SampleBlockSelector;
+
+impl BlockSelector for SampleBlockSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        QuadrupleBlockShape::T.into()
+    }
+
+    fn select_bomb(&mut self, _: BlockShape) -> BombTag {
+        BombTag::Single(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_block_templates() {
+        assert!(check_block_templates().passed);
+    }
+
+    #[test]
+    fn test_check_cell_rendering_with_colors() {
+        assert!(check_cell_rendering(true).passed);
+    }
+
+    #[test]
+    fn test_check_cell_rendering_without_colors() {
+        assert!(check_cell_rendering(false).passed);
+    }
+
+    #[test]
+    fn test_check_terminal_size() {
+        assert!(check_terminal_size(Some((24, 80))).passed);
+        assert!(check_terminal_size(Some((50, 200))).passed);
+        assert!(!check_terminal_size(Some((23, 80))).passed);
+        assert!(!check_terminal_size(Some((24, 79))).passed);
+        assert!(!check_terminal_size(None).passed);
+    }
+
+    #[test]
+    fn test_check_sample_frame() {
+        assert!(check_sample_frame().passed);
+    }
+
+    #[test]
+    fn test_run_all() {
+        let results = run_all(true, Some((24, 80)));
+        assert_eq!(4, results.len());
+        assert!(results.iter().all(|r| r.passed));
+    }
+}
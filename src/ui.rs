@@ -0,0 +1,5 @@
+mod screen_stack;
+mod terminal_guard;
+
+pub use screen_stack::{Screen, ScreenDriver, ScreenStack, ScreenTransition};
+pub use terminal_guard::{install_panic_hook, TerminalGuard};
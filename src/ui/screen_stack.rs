@@ -0,0 +1,255 @@
+use crate::game::animation::Drawer;
+use crate::graphics::RootCanvas;
+use crate::user::Key;
+
+mod consts {
+    /// 1フレームの長さ(ミリ秒)．
+    pub const FRAME_MILLIS: u64 = 50;
+}
+
+use consts::*;
+
+/// キー入力を処理した後の画面遷移を表す．
+pub enum ScreenTransition {
+    /// 現在の画面に留まる．
+    Stay,
+    /// 指定した画面を現在の画面の上に重ね，そちらへ遷移する．
+    Push(Box<dyn Screen>),
+    /// 現在の画面を閉じ，ひとつ下の画面へ戻る．
+    Pop,
+    /// 現在の画面を閉じ，代わりに指定した画面へ遷移する．
+    Replace(Box<dyn Screen>),
+}
+
+/// 画面スタックに積まれるひとつの画面を表す．
+pub trait Screen {
+    /// キー入力を処理し，その後の画面遷移を返す．
+    fn handle_key(&mut self, key: Key) -> ScreenTransition;
+
+    /// フレームを1つ進める．
+    /// 点滅などのアニメーションをもつ画面で利用する．
+    fn tick(&mut self) {}
+
+    /// 画面の内容を描画する．
+    fn draw(&self, canvas: &mut RootCanvas);
+}
+
+/// 画面の遷移履歴を管理するスタックを表す．
+/// 最上段の画面だけがキー入力を受け取り，描画される．
+pub struct ScreenStack {
+    screens: Vec<Box<dyn Screen>>,
+}
+
+impl ScreenStack {
+    /// 指定した画面だけが積まれたスタックを返す．
+    pub fn new(root: Box<dyn Screen>) -> ScreenStack {
+        Self {
+            screens: vec![root],
+        }
+    }
+
+    /// 積まれている画面の数を返す．
+    pub fn len(&self) -> usize {
+        self.screens.len()
+    }
+
+    /// すべての画面が閉じられたか返す．
+    pub fn is_empty(&self) -> bool {
+        self.screens.is_empty()
+    }
+
+    /// 最上段の画面にキー入力を渡し，返された画面遷移を適用する．
+    /// スタックが空の場合は何もしない．
+    pub fn handle_key(&mut self, key: Key) {
+        let transition = match self.screens.last_mut() {
+            Some(screen) => screen.handle_key(key),
+            None => return,
+        };
+        self.apply(transition);
+    }
+
+    /// 最上段の画面のフレームを進める．
+    pub fn tick(&mut self) {
+        if let Some(screen) = self.screens.last_mut() {
+            screen.tick();
+        }
+    }
+
+    /// 最上段の画面を描画する．
+    pub fn draw(&self, canvas: &mut RootCanvas) {
+        if let Some(screen) = self.screens.last() {
+            screen.draw(canvas);
+        }
+    }
+
+    /// 画面遷移を適用する．
+    fn apply(&mut self, transition: ScreenTransition) {
+        match transition {
+            ScreenTransition::Stay => {}
+            ScreenTransition::Push(screen) => self.screens.push(screen),
+            ScreenTransition::Pop => {
+                self.screens.pop();
+            }
+            ScreenTransition::Replace(screen) => {
+                self.screens.pop();
+                self.screens.push(screen);
+            }
+        }
+    }
+}
+
+/// 画面スタックを，描画先と入力源を使って実行する．
+pub struct ScreenDriver<'d, D, I> {
+    /// 描画先．
+    drawer: &'d mut D,
+    /// 1フレームの間に押されたキーの列を返す入力源．
+    /// 入力を待たずに返す必要がある．
+    input: I,
+}
+
+impl<'d, D, I> ScreenDriver<'d, D, I>
+where
+    D: Drawer<Canvas = RootCanvas>,
+    I: FnMut() -> Vec<Key>,
+{
+    pub fn new(drawer: &'d mut D, input: I) -> ScreenDriver<'d, D, I> {
+        Self { drawer, input }
+    }
+
+    /// すべての画面が閉じられるまで，入力の処理と描画を繰り返す．
+    pub fn run(&mut self, stack: &mut ScreenStack) {
+        while !stack.is_empty() {
+            for key in (self.input)() {
+                stack.handle_key(key);
+                if stack.is_empty() {
+                    return;
+                }
+            }
+            stack.tick();
+
+            self.drawer.clear();
+            stack.draw(self.drawer.canvas_mut());
+            self.drawer.show();
+
+            std::thread::sleep(std::time::Duration::from_millis(FRAME_MILLIS));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::test_util::HeadlessDrawer;
+    use crate::graphics::{CanvasCell, CanvasCellColor, Drawable, SquareChar};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// 押されたキーに応じて決まった遷移を返し，受け取ったキーを記録する画面．
+    struct MockScreen {
+        name: char,
+        log: Rc<RefCell<Vec<(char, Key)>>>,
+    }
+
+    impl MockScreen {
+        fn boxed(name: char, log: &Rc<RefCell<Vec<(char, Key)>>>) -> Box<dyn Screen> {
+            Box::new(Self {
+                name,
+                log: log.clone(),
+            })
+        }
+    }
+
+    impl Screen for MockScreen {
+        fn handle_key(&mut self, key: Key) -> ScreenTransition {
+            self.log.borrow_mut().push((self.name, key));
+            match key {
+                Key::Char(c) if c.is_ascii_lowercase() => {
+                    ScreenTransition::Push(MockScreen::boxed(c, &self.log))
+                }
+                Key::Char(c) if c.is_ascii_uppercase() => {
+                    ScreenTransition::Replace(MockScreen::boxed(c, &self.log))
+                }
+                Key::Escape => ScreenTransition::Pop,
+                _ => ScreenTransition::Stay,
+            }
+        }
+
+        fn draw(&self, canvas: &mut RootCanvas) {
+            let cell = CanvasCell::new(SquareChar::new(self.name, ' '), CanvasCellColor::default());
+            cell.draw(canvas);
+        }
+    }
+
+    fn drawn_name(stack: &ScreenStack) -> char {
+        let mut canvas = RootCanvas::new();
+        stack.draw(&mut canvas);
+        let mut buffer = String::new();
+        canvas.construct_plain_string(&mut buffer);
+        buffer.chars().next().unwrap()
+    }
+
+    #[test]
+    fn test_transitions() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut stack = ScreenStack::new(MockScreen::boxed('m', &log));
+        assert_eq!(1, stack.len());
+        assert_eq!('m', drawn_name(&stack));
+
+        // Stay
+        stack.handle_key(Key::Enter);
+        assert_eq!(1, stack.len());
+
+        // Push
+        stack.handle_key(Key::Char('s'));
+        assert_eq!(2, stack.len());
+        assert_eq!('s', drawn_name(&stack));
+
+        // Replace
+        stack.handle_key(Key::Char('K'));
+        assert_eq!(2, stack.len());
+        assert_eq!('K', drawn_name(&stack));
+
+        // Pop
+        stack.handle_key(Key::Escape);
+        assert_eq!(1, stack.len());
+        assert_eq!('m', drawn_name(&stack));
+
+        stack.handle_key(Key::Escape);
+        assert!(stack.is_empty());
+        assert_eq!(' ', drawn_name(&stack));
+
+        // 空のスタックへの入力は無視される
+        stack.handle_key(Key::Escape);
+        assert!(stack.is_empty());
+
+        // キーは常に最上段の画面に渡されている
+        let expected = vec![
+            ('m', Key::Enter),
+            ('m', Key::Char('s')),
+            ('s', Key::Char('K')),
+            ('K', Key::Escape),
+            ('m', Key::Escape),
+        ];
+        assert_eq!(expected, *log.borrow());
+    }
+
+    #[test]
+    fn test_driver_runs_until_empty() {
+        let log = Rc::new(RefCell::new(vec![]));
+        let mut stack = ScreenStack::new(MockScreen::boxed('m', &log));
+        let mut drawer = HeadlessDrawer::new();
+        let mut frames = vec![
+            vec![],
+            vec![Key::Char('s')],
+            vec![Key::Escape, Key::Escape, Key::Char('x')],
+        ]
+        .into_iter();
+
+        ScreenDriver::new(&mut drawer, || frames.next().unwrap_or_default()).run(&mut stack);
+
+        assert!(stack.is_empty());
+        assert_eq!(2, drawer.show_count);
+        // スタックが空になった後のキーは処理されない
+        assert_eq!(3, log.borrow().len());
+    }
+}
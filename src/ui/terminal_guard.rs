@@ -0,0 +1,153 @@
+use std::io::{self, Write};
+use std::panic;
+
+/// 代替画面へ切り替え，カーソルを隠すエスケープシーケンス．
+const ENTER_SEQUENCE: &str = "\x1b[?1049h\x1b[?25l";
+/// 文字の装飾を戻し，カーソルを表示し，元の画面へ戻すエスケープシーケンス．
+const RESTORE_SEQUENCE: &str = "\x1b[0m\x1b[?25h\x1b[?1049l";
+
+/// ゲームの表示中だけ端末を代替画面へ切り替え，終了時に元の状態へ戻すガード．
+///
+/// 代替画面に描画するので，利用者のシェルの履歴を上書きしない．
+/// ガードを破棄すると端末の状態を戻す．パニックした場合に備えて`install_panic_hook`も併せて利用する．
+pub struct TerminalGuard<W: Write> {
+    terminal: W,
+    /// まだ端末の状態を戻していない場合は`true`．
+    active: bool,
+}
+
+impl<W: Write> TerminalGuard<W> {
+    /// 指定した端末を代替画面へ切り替え，カーソルを隠す．
+    pub fn enter(mut terminal: W) -> io::Result<TerminalGuard<W>> {
+        terminal.write_all(ENTER_SEQUENCE.as_bytes())?;
+        terminal.flush()?;
+        Ok(Self {
+            terminal,
+            active: true,
+        })
+    }
+
+    /// 描画に利用する端末を返す．
+    pub fn terminal(&self) -> &W {
+        &self.terminal
+    }
+
+    /// 文字の装飾を戻し，カーソルを表示して元の画面へ戻す．
+    /// 既に戻している場合は何もしない．
+    pub fn restore(&mut self) -> io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+        self.active = false;
+        self.terminal.write_all(RESTORE_SEQUENCE.as_bytes())?;
+        self.terminal.flush()
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        // 破棄の途中で失敗しても，これ以上できることはない
+        let _ = self.restore();
+    }
+}
+
+/// パニックしたときに標準出力の端末の状態を戻してから，それまでのフックを呼び出すフックを設定する．
+/// パニックのメッセージが，元の画面に表示されるようになる．
+pub fn install_panic_hook() {
+    install_panic_hook_with(|| {
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(RESTORE_SEQUENCE.as_bytes());
+        let _ = stdout.flush();
+    });
+}
+
+/// パニックしたときに`restore`を呼び出してから，それまでのフックを呼び出すフックを設定する．
+fn install_panic_hook_with<F>(restore: F)
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore();
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_enter_and_restore() {
+        let mut guard = TerminalGuard::enter(vec![]).unwrap();
+        assert_eq!(ENTER_SEQUENCE.as_bytes(), &guard.terminal()[..]);
+
+        guard.restore().unwrap();
+        let expected = format!("{}{}", ENTER_SEQUENCE, RESTORE_SEQUENCE);
+        assert_eq!(expected.as_bytes(), &guard.terminal()[..]);
+        // 何度戻しても，書き出すのは一度だけ
+        guard.restore().unwrap();
+        assert_eq!(expected.as_bytes(), &guard.terminal()[..]);
+    }
+
+    /// 書き出した内容を共有するバッファ．ガードを破棄した後に内容を確かめるために利用する．
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_restore_on_drop() {
+        let buffer = SharedBuffer::default();
+        let mut guard = TerminalGuard::enter(buffer.clone()).unwrap();
+        guard.restore().unwrap();
+        drop(guard);
+        let expected = format!("{}{}", ENTER_SEQUENCE, RESTORE_SEQUENCE);
+        assert_eq!(expected.as_bytes(), &buffer.0.lock().unwrap()[..]);
+
+        let buffer = SharedBuffer::default();
+        drop(TerminalGuard::enter(buffer.clone()).unwrap());
+        assert_eq!(expected.as_bytes(), &buffer.0.lock().unwrap()[..]);
+    }
+
+    #[test]
+    fn test_panic_hook_forwards_to_previous_hook() {
+        const MESSAGE: &str = "terminal guard hook test";
+
+        let original = panic::take_hook();
+        // 他のテストのパニックでも呼び出されうるので，このテストのパニックだけを数える
+        let forwarded = Arc::new(AtomicUsize::new(0));
+        let restored = Arc::new(AtomicUsize::new(0));
+        {
+            let forwarded = forwarded.clone();
+            panic::set_hook(Box::new(move |info| {
+                if info.payload().downcast_ref::<&str>() == Some(&MESSAGE) {
+                    forwarded.fetch_add(1, Ordering::SeqCst);
+                }
+            }));
+        }
+        {
+            let restored = restored.clone();
+            install_panic_hook_with(move || {
+                restored.fetch_add(1, Ordering::SeqCst);
+            });
+        }
+
+        let result = panic::catch_unwind(|| panic::panic_any(MESSAGE));
+        panic::set_hook(original);
+
+        assert!(result.is_err());
+        assert!(restored.load(Ordering::SeqCst) >= 1);
+        assert_eq!(1, forwarded.load(Ordering::SeqCst));
+    }
+}
@@ -1,3 +1,13 @@
+mod agent;
+mod clock;
 mod input;
+mod key_bindings;
+mod latency;
+mod terminal_input;
 
+pub use agent::{Agent, GreedyAgent};
+pub use clock::{Clock, SystemClock};
 pub use input::*;
+pub use key_bindings::{key_label, key_name, KeyBindings};
+pub use latency::LatencyMonitor;
+pub use terminal_input::TerminalInputSource;
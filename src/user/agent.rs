@@ -0,0 +1,42 @@
+use super::GameCommand;
+use crate::game::{Block, BlockQueue, Field};
+use crate::geometry::*;
+
+/// キー入力の代わりにゲームを操作するエージェントを表す．
+pub trait Agent {
+    /// 現在の状態から，次に施す操作を決める．
+    ///
+    /// `field`は操作中のブロックを含まないフィールド，`current`は操作中のブロック，
+    /// `current_pos`は操作中のブロックのセルテーブルのうち最も左上のセルのフィールドにおける座標，
+    /// `queue`は今後操作するブロックのキューである．
+    fn decide(
+        &mut self,
+        field: &Field,
+        current: &Block,
+        current_pos: Pos,
+        queue: &BlockQueue,
+    ) -> GameCommand;
+}
+
+impl<A: Agent + ?Sized> Agent for &mut A {
+    fn decide(
+        &mut self,
+        field: &Field,
+        current: &Block,
+        current_pos: Pos,
+        queue: &BlockQueue,
+    ) -> GameCommand {
+        (**self).decide(field, current, current_pos, queue)
+    }
+}
+
+/// 常にその場でブロックをハードドロップするエージェント．
+/// エージェントを実装する際の参考となる最小限の実装．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GreedyAgent;
+
+impl Agent for GreedyAgent {
+    fn decide(&mut self, _: &Field, _: &Block, _: Pos, _: &BlockQueue) -> GameCommand {
+        GameCommand::Drop
+    }
+}
@@ -0,0 +1,18 @@
+use std::time::Instant;
+
+/// 現在時刻を取得する手段を表す．
+/// テストなどで時刻を差し替えられるように，時刻の取得はこのトレイトを経由して行う．
+pub trait Clock {
+    /// 現在時刻を返す．
+    fn now(&self) -> Instant;
+}
+
+/// システムの単調増加時計を利用する`Clock`．
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
@@ -1,3 +1,4 @@
+use super::KeyBindings;
 pub use console::Key;
 
 /// メニュー画面で使用可能な操作を表す．
@@ -31,12 +32,41 @@ pub enum GameCommand {
     /// ホールド操作．
     /// 現在操作中のブロックとホールドブロックを交換する．
     Hold,
+    /// ゲームを一時停止する．一時停止中にもう一度入力すると，ゲームを途中でやめる．
+    Quit,
+}
+
+impl GameCommand {
+    /// すべてのゲーム操作．
+    pub const ALL: [GameCommand; 8] = [
+        GameCommand::Left,
+        GameCommand::Right,
+        GameCommand::Down,
+        GameCommand::Drop,
+        GameCommand::RotateClockwise,
+        GameCommand::RotateUnticlockwise,
+        GameCommand::Hold,
+        GameCommand::Quit,
+    ];
+}
+
+/// キー入力を操作に変換する規則を表す．
+pub trait InputMapper {
+    /// 変換後の操作．
+    type Command;
+
+    /// 指定したキーに対応する操作を返す．
+    /// # Returns
+    /// キーに操作が割り当てられていない場合は`None`を返す．
+    fn map(&self, key: Key) -> Option<Self::Command>;
 }
 
 pub struct MenuInputMapper;
 
-impl MenuInputMapper {
-    pub fn map(&self, key: Key) -> Option<MenuCommand> {
+impl InputMapper for MenuInputMapper {
+    type Command = MenuCommand;
+
+    fn map(&self, key: Key) -> Option<MenuCommand> {
         use Key::*;
         use MenuCommand::*;
 
@@ -50,23 +80,14 @@ impl MenuInputMapper {
     }
 }
 
+/// 既定のキー配置(`KeyBindings::DEFAULT`)でキー入力を操作に変換する．
 pub struct SinglePlayerInputMapper;
 
-impl SinglePlayerInputMapper {
-    pub fn map(&self, key: Key) -> Option<GameCommand> {
-        use GameCommand::*;
-        use Key::*;
+impl InputMapper for SinglePlayerInputMapper {
+    type Command = GameCommand;
 
-        match key {
-            Char('z') => Some(RotateUnticlockwise),
-            Char('x') => Some(RotateClockwise),
-            Char('c') => Some(Hold),
-            ArrowLeft => Some(Left),
-            ArrowRight => Some(Right),
-            ArrowUp => Some(Drop),
-            ArrowDown => Some(Down),
-            _ => None,
-        }
+    fn map(&self, key: Key) -> Option<GameCommand> {
+        KeyBindings::DEFAULT.map(key)
     }
 }
 
@@ -77,3 +98,104 @@ impl DoublePlayerInputMapper {
         unimplemented!()
     }
 }
+
+/// 1ティック内に入力された操作列を，ゲームループに適用する操作列へとまとめる．
+///
+/// 連続した移動操作(`Left`, `Right`, `Down`)は軸ごとにまとめられる．
+/// 横方向の移動は左右の移動量を相殺した正味の移動量だけ残し，その後に`Down`を並べる．
+/// 移動以外の操作(回転など)は入力順を保ったまま残し，その前後の移動操作同士はまとめない．
+///
+/// # Example
+/// `Left, Left, Right`は`Left`ひとつにまとめられる．
+pub fn coalesce_commands<I>(commands: I) -> Vec<GameCommand>
+where
+    I: IntoIterator<Item = GameCommand>,
+{
+    let mut coalesced = vec![];
+    // 現在まとめている移動操作の，右方向を正とした横方向の正味の移動量
+    let mut horizontal = 0isize;
+    // 現在まとめている移動操作のうち，下方向への移動回数
+    let mut down = 0usize;
+
+    fn flush(coalesced: &mut Vec<GameCommand>, horizontal: &mut isize, down: &mut usize) {
+        let command = if *horizontal > 0 {
+            GameCommand::Right
+        } else {
+            GameCommand::Left
+        };
+        coalesced.extend(std::iter::repeat_n(command, horizontal.unsigned_abs()));
+        coalesced.extend(std::iter::repeat_n(GameCommand::Down, *down));
+        *horizontal = 0;
+        *down = 0;
+    }
+
+    for command in commands {
+        match command {
+            GameCommand::Left => horizontal -= 1,
+            GameCommand::Right => horizontal += 1,
+            GameCommand::Down => down += 1,
+            _ => {
+                flush(&mut coalesced, &mut horizontal, &mut down);
+                coalesced.push(command);
+            }
+        }
+    }
+    flush(&mut coalesced, &mut horizontal, &mut down);
+
+    coalesced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GameCommand::*;
+    use super::*;
+
+    #[test]
+    fn test_coalesce_empty() {
+        assert_eq!(Vec::<GameCommand>::new(), coalesce_commands(vec![]));
+    }
+
+    #[test]
+    fn test_coalesce_opposite_moves() {
+        assert_eq!(vec![Left], coalesce_commands(vec![Left, Left, Right]));
+        assert_eq!(vec![Right], coalesce_commands(vec![Right, Left, Right]));
+        // 完全に相殺される場合は何も残らない
+        assert_eq!(
+            Vec::<GameCommand>::new(),
+            coalesce_commands(vec![Left, Right, Right, Left])
+        );
+    }
+
+    #[test]
+    fn test_coalesce_same_direction_moves() {
+        assert_eq!(vec![Left, Left], coalesce_commands(vec![Left, Left]));
+    }
+
+    #[test]
+    fn test_coalesce_per_axis() {
+        // 横方向の移動が先，下方向の移動が後にまとめられる
+        assert_eq!(
+            vec![Right, Down, Down],
+            coalesce_commands(vec![Down, Right, Down, Right, Left])
+        );
+    }
+
+    #[test]
+    fn test_coalesce_keeps_order_around_other_commands() {
+        // 回転をまたいだ移動操作はまとめない
+        assert_eq!(
+            vec![Left, RotateClockwise, Right, Drop],
+            coalesce_commands(vec![Left, RotateClockwise, Left, Right, Right, Drop])
+        );
+    }
+
+    #[test]
+    fn test_map_single_player() {
+        let mapper = SinglePlayerInputMapper;
+        assert_eq!(Some(Left), mapper.map(Key::ArrowLeft));
+        assert_eq!(Some(Hold), mapper.map(Key::Char('c')));
+        assert_eq!(Some(Quit), mapper.map(Key::Char('q')));
+        assert_eq!(Some(Quit), mapper.map(Key::Escape));
+        assert_eq!(None, mapper.map(Key::Char('a')));
+    }
+}
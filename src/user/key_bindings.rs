@@ -0,0 +1,412 @@
+use super::{GameCommand, InputMapper, Key};
+use std::io;
+use std::path::Path;
+
+/// ゲームプレイ画面の各操作に割り当てるキーを表す．
+///
+/// ファイルには，1行にひとつずつ操作の名前と割り当てたキーの名前を書き出す．
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBindings {
+    pub left: Key,
+    pub right: Key,
+    pub down: Key,
+    pub drop: Key,
+    pub rotate_clockwise: Key,
+    pub rotate_unticlockwise: Key,
+    pub hold: Key,
+    pub quit: Key,
+}
+
+impl KeyBindings {
+    /// 既定のキー配置．
+    /// 矢印キーで移動し，`z`, `x`で回転，`c`でホールド，`q`でゲームをやめる．
+    pub const DEFAULT: KeyBindings = KeyBindings {
+        left: Key::ArrowLeft,
+        right: Key::ArrowRight,
+        down: Key::ArrowDown,
+        drop: Key::ArrowUp,
+        rotate_clockwise: Key::Char('x'),
+        rotate_unticlockwise: Key::Char('z'),
+        hold: Key::Char('c'),
+        quit: Key::Char('q'),
+    };
+
+    /// 左利き向けのキー配置．
+    /// WASDで移動し，矢印キーで回転とホールドを行う．`q`でゲームをやめる．
+    pub const LEFT_HANDED: KeyBindings = KeyBindings {
+        left: Key::Char('a'),
+        right: Key::Char('d'),
+        down: Key::Char('s'),
+        drop: Key::Char('w'),
+        rotate_clockwise: Key::ArrowRight,
+        rotate_unticlockwise: Key::ArrowLeft,
+        hold: Key::ArrowUp,
+        quit: Key::Char('q'),
+    };
+
+    /// 片手だけで操作するためのキー配置．
+    /// すべての操作をキーボード左側のWASD周辺に集める．
+    /// `q`は回転に使うので，`Esc`でゲームをやめる．
+    pub const SINGLE_HAND: KeyBindings = KeyBindings {
+        left: Key::Char('a'),
+        right: Key::Char('d'),
+        down: Key::Char('s'),
+        drop: Key::Char('w'),
+        rotate_clockwise: Key::Char('e'),
+        rotate_unticlockwise: Key::Char('q'),
+        hold: Key::Char('f'),
+        quit: Key::Escape,
+    };
+
+    /// 指定した操作に割り当てられたキーを返す．
+    pub fn key(&self, command: GameCommand) -> Key {
+        use GameCommand::*;
+
+        match command {
+            Left => self.left,
+            Right => self.right,
+            Down => self.down,
+            Drop => self.drop,
+            RotateClockwise => self.rotate_clockwise,
+            RotateUnticlockwise => self.rotate_unticlockwise,
+            Hold => self.hold,
+            Quit => self.quit,
+        }
+    }
+
+    /// 指定した操作にキーを割り当てる．
+    /// 他の操作に同じキーが割り当てられていた場合は，その操作と割り当てを入れ替える．
+    /// # Returns
+    /// 割り当てを入れ替えた操作．入れ替えなかった場合は`None`．
+    pub fn assign(&mut self, command: GameCommand, key: Key) -> Option<GameCommand> {
+        let previous_key = self.key(command);
+        let swapped = GameCommand::ALL
+            .iter()
+            .copied()
+            .find(|&other| other != command && self.key(other) == key);
+        if let Some(other) = swapped {
+            *self.key_mut(other) = previous_key;
+        }
+        *self.key_mut(command) = key;
+        swapped
+    }
+
+    /// 指定した操作に割り当てられたキーへの参照を返す．
+    fn key_mut(&mut self, command: GameCommand) -> &mut Key {
+        use GameCommand::*;
+
+        match command {
+            Left => &mut self.left,
+            Right => &mut self.right,
+            Down => &mut self.down,
+            Drop => &mut self.drop,
+            RotateClockwise => &mut self.rotate_clockwise,
+            RotateUnticlockwise => &mut self.rotate_unticlockwise,
+            Hold => &mut self.hold,
+            Quit => &mut self.quit,
+        }
+    }
+
+    /// 指定したキーが割り当てられた操作を返す．
+    /// # Returns
+    /// キーに操作が割り当てられていない場合は`None`を返す．
+    /// 複数の操作に同じキーが割り当てられている場合は，`GameCommand::ALL`で先に現れる操作を返す．
+    pub fn command(&self, key: Key) -> Option<GameCommand> {
+        GameCommand::ALL
+            .iter()
+            .copied()
+            .find(|&command| self.key(command) == key)
+    }
+
+    /// 同じキーが割り当てられている操作の組をすべて返す．
+    pub fn conflicts(&self) -> Vec<(GameCommand, GameCommand)> {
+        let mut conflicts = vec![];
+        for (i, &a) in GameCommand::ALL.iter().enumerate() {
+            for &b in GameCommand::ALL.iter().skip(i + 1) {
+                if self.key(a) == self.key(b) {
+                    conflicts.push((a, b));
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// ファイルに書き出す形式の文字列を返す．
+    /// # Returns
+    /// ファイルに書き出せないキーが割り当てられている場合は`None`を返す．
+    pub fn to_text(&self) -> Option<String> {
+        let mut text = String::new();
+        for &command in GameCommand::ALL.iter() {
+            text += &format!("{:?} {}\n", command, key_name(self.key(command))?);
+        }
+        Some(text)
+    }
+
+    /// `to_text`で書き出した形式の文字列からキー配置を読み込む．
+    /// 記述のない操作には，既定のキー配置(`DEFAULT`)のキーを割り当てる．
+    /// # Returns
+    /// 形式が正しくない場合や，同じキーが複数の操作に割り当てられる場合(`conflicts`を参照)は`None`を返す．
+    pub fn from_text(text: &str) -> Option<KeyBindings> {
+        let mut bindings = Self::DEFAULT;
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut words = line.split_whitespace();
+            let command = parse_command(words.next()?)?;
+            let key = parse_key(words.next()?)?;
+            if words.next().is_some() {
+                return None;
+            }
+            *bindings.key_mut(command) = key;
+        }
+        if !bindings.conflicts().is_empty() {
+            return None;
+        }
+        Some(bindings)
+    }
+
+    /// 指定したファイルに書き出す．
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = self.to_text().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "key cannot be written to file")
+        })?;
+        std::fs::write(path, text)
+    }
+
+    /// 指定したファイルから読み込む．
+    /// ファイルが存在しない場合は，既定のキー配置を返す．
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<KeyBindings> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_text(&text)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid key bindings")),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::DEFAULT),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl InputMapper for KeyBindings {
+    type Command = GameCommand;
+
+    /// キー配置によらず，`Esc`は常にゲームをやめる操作に対応する．
+    fn map(&self, key: Key) -> Option<GameCommand> {
+        match self.command(key) {
+            None if key == Key::Escape => Some(GameCommand::Quit),
+            command => command,
+        }
+    }
+}
+
+/// キーをファイルに書き出す名前に変換する．
+/// # Returns
+/// ファイルに書き出せないキーの場合は`None`を返す．
+pub fn key_name(key: Key) -> Option<String> {
+    let name = match key {
+        Key::ArrowLeft => "ArrowLeft",
+        Key::ArrowRight => "ArrowRight",
+        Key::ArrowUp => "ArrowUp",
+        Key::ArrowDown => "ArrowDown",
+        Key::Enter => "Enter",
+        Key::Escape => "Escape",
+        Key::Backspace => "Backspace",
+        Key::Home => "Home",
+        Key::End => "End",
+        Key::Tab => "Tab",
+        Key::Del => "Del",
+        Key::Char(' ') => "Space",
+        Key::Char(c) if !c.is_whitespace() => return Some(c.to_string()),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// `key_name`で書き出した名前をキーに戻す．
+fn parse_key(name: &str) -> Option<Key> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(Key::Char(c));
+    }
+    [
+        Key::ArrowLeft,
+        Key::ArrowRight,
+        Key::ArrowUp,
+        Key::ArrowDown,
+        Key::Enter,
+        Key::Escape,
+        Key::Backspace,
+        Key::Home,
+        Key::End,
+        Key::Tab,
+        Key::Del,
+        Key::Char(' '),
+    ]
+    .iter()
+    .cloned()
+    .find(|&key| key_name(key).as_deref() == Some(name))
+}
+
+/// `{:?}`で書き出した操作の名前を操作に戻す．
+fn parse_command(name: &str) -> Option<GameCommand> {
+    GameCommand::ALL
+        .iter()
+        .copied()
+        .find(|command| format!("{:?}", command) == name)
+}
+
+/// キーを表示用の文字列に変換する．
+pub fn key_label(key: Key) -> String {
+    match key {
+        Key::ArrowLeft => "Left".to_string(),
+        Key::ArrowRight => "Right".to_string(),
+        Key::ArrowUp => "Up".to_string(),
+        Key::ArrowDown => "Down".to_string(),
+        Key::Enter => "Enter".to_string(),
+        Key::Escape => "Esc".to_string(),
+        Key::Backspace => "Backspace".to_string(),
+        Key::Home => "Home".to_string(),
+        Key::End => "End".to_string(),
+        Key::Tab => "Tab".to_string(),
+        Key::Del => "Del".to_string(),
+        Key::Char(' ') => "Space".to_string(),
+        Key::Char(c) => c.to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRESETS: [KeyBindings; 3] = [
+        KeyBindings::DEFAULT,
+        KeyBindings::LEFT_HANDED,
+        KeyBindings::SINGLE_HAND,
+    ];
+
+    #[test]
+    fn test_presets_have_no_conflicts() {
+        for preset in PRESETS.iter() {
+            assert_eq!(Vec::<(GameCommand, GameCommand)>::new(), preset.conflicts());
+        }
+    }
+
+    #[test]
+    fn test_presets_cover_every_command() {
+        for preset in PRESETS.iter() {
+            for &command in GameCommand::ALL.iter() {
+                assert_eq!(Some(command), preset.command(preset.key(command)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_default_matches_single_player_mapper() {
+        use super::super::SinglePlayerInputMapper;
+
+        let keys = [
+            Key::ArrowLeft,
+            Key::ArrowRight,
+            Key::ArrowUp,
+            Key::ArrowDown,
+            Key::Char('z'),
+            Key::Char('x'),
+            Key::Char('c'),
+            Key::Char('q'),
+            Key::Escape,
+            Key::Char('a'),
+            Key::Enter,
+        ];
+        for &key in keys.iter() {
+            assert_eq!(
+                SinglePlayerInputMapper.map(key),
+                KeyBindings::DEFAULT.map(key)
+            );
+        }
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut bindings = KeyBindings::DEFAULT;
+        bindings.hold = bindings.left;
+
+        assert_eq!(
+            vec![(GameCommand::Left, GameCommand::Hold)],
+            bindings.conflicts()
+        );
+        assert_eq!(Some(GameCommand::Left), bindings.command(Key::ArrowLeft));
+    }
+
+    #[test]
+    fn test_assign_swaps_conflicting_key() {
+        let mut bindings = KeyBindings::DEFAULT;
+
+        // 空いているキーはそのまま割り当てる
+        assert_eq!(None, bindings.assign(GameCommand::Hold, Key::Char(' ')));
+        assert_eq!(Key::Char(' '), bindings.hold);
+
+        // 使用中のキーを割り当てると，元の操作と割り当てを入れ替える
+        assert_eq!(
+            Some(GameCommand::RotateClockwise),
+            bindings.assign(GameCommand::Hold, Key::Char('x'))
+        );
+        assert_eq!(Key::Char('x'), bindings.hold);
+        assert_eq!(Key::Char(' '), bindings.rotate_clockwise);
+        assert!(bindings.conflicts().is_empty());
+
+        // 同じキーを割り当て直しても変わらない
+        assert_eq!(None, bindings.assign(GameCommand::Hold, Key::Char('x')));
+        assert_eq!(Key::Char('x'), bindings.hold);
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        for preset in PRESETS.iter() {
+            let text = preset.to_text().unwrap();
+            assert_eq!(Some(*preset), KeyBindings::from_text(&text));
+        }
+
+        let mut bindings = KeyBindings::DEFAULT;
+        bindings.assign(GameCommand::Drop, Key::Char(' '));
+        bindings.assign(GameCommand::Hold, Key::Tab);
+        let text = bindings.to_text().unwrap();
+        assert!(text.contains("Drop Space\n"));
+        assert!(text.contains("Hold Tab\n"));
+        assert_eq!(Some(bindings), KeyBindings::from_text(&text));
+    }
+
+    #[test]
+    fn test_from_text() {
+        // 記述のない操作は既定のキーになる
+        let mut expected = KeyBindings::DEFAULT;
+        expected.left = Key::Char('h');
+        assert_eq!(Some(expected), KeyBindings::from_text("Left h\n"));
+        assert_eq!(Some(KeyBindings::DEFAULT), KeyBindings::from_text(""));
+
+        assert_eq!(None, KeyBindings::from_text("Jump Space\n"));
+        assert_eq!(None, KeyBindings::from_text("Left Unknown\n"));
+        assert_eq!(None, KeyBindings::from_text("Left\n"));
+        assert_eq!(None, KeyBindings::from_text("Left a b\n"));
+
+        // 同じキーを複数の操作に割り当てる場合は読み込まない
+        assert_eq!(None, KeyBindings::from_text("Left h\nRight h\n"));
+        // 記述のない操作の既定のキーと重なる場合も同様
+        let rotate = key_name(KeyBindings::DEFAULT.key(GameCommand::RotateClockwise)).unwrap();
+        assert_eq!(None, KeyBindings::from_text(&format!("Left {}\n", rotate)));
+
+        // 書き出せないキーを含む場合は書き出さない
+        let mut bindings = KeyBindings::DEFAULT;
+        bindings.left = Key::Unknown;
+        assert_eq!(None, bindings.to_text());
+    }
+
+    #[test]
+    fn test_key_label() {
+        assert_eq!("Left", key_label(Key::ArrowLeft));
+        assert_eq!("z", key_label(Key::Char('z')));
+        assert_eq!("Space", key_label(Key::Char(' ')));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::geometry::*;
+use crate::graphics::*;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// キー入力から，その結果が画面に表示されるまでの遅延を計測する．
+///
+/// 入力されたキーの押下時刻を`record_input`で登録しておき，
+/// 画面の表示が終わった時点で`record_drawn`を呼ぶと，登録済みの各入力について遅延が記録される．
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyMonitor {
+    /// まだ結果が表示されていない入力の押下時刻．
+    pending: Vec<Instant>,
+    /// 直近の遅延．古いものから順に並ぶ．
+    samples: VecDeque<Duration>,
+    /// 統計に利用する直近の遅延の数．
+    window: usize,
+}
+
+impl LatencyMonitor {
+    /// 直近`window`個の遅延から統計を求める計測器を返す．
+    /// # Panics
+    /// `window`に0を指定した場合．
+    pub fn new(window: usize) -> LatencyMonitor {
+        assert!(window > 0);
+        Self {
+            pending: vec![],
+            samples: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// 結果がまだ表示されていない入力の押下時刻を登録する．
+    pub fn record_input(&mut self, pressed_at: Instant) {
+        self.pending.push(pressed_at);
+    }
+
+    /// 画面の表示が終わったことを通知し，登録済みの各入力について遅延を記録する．
+    /// 表示時刻が押下時刻より前の場合，遅延は0として扱う．
+    pub fn record_drawn(&mut self, drawn_at: Instant) {
+        for pressed_at in self.pending.drain(..) {
+            let latency = drawn_at
+                .checked_duration_since(pressed_at)
+                .unwrap_or_default();
+            if self.samples.len() == self.window {
+                self.samples.pop_front();
+            }
+            self.samples.push_back(latency);
+        }
+    }
+
+    /// 直近の遅延の平均を返す．
+    /// # Returns
+    /// まだ遅延が記録されていない場合は`None`を返す．
+    pub fn average(&self) -> Option<Duration> {
+        if self.samples.is_empty() {
+            None
+        } else {
+            let total = self.samples.iter().sum::<Duration>();
+            Some(total / self.samples.len() as u32)
+        }
+    }
+
+    /// 直近の遅延の最大値を返す．
+    /// # Returns
+    /// まだ遅延が記録されていない場合は`None`を返す．
+    pub fn worst(&self) -> Option<Duration> {
+        self.samples.iter().max().copied()
+    }
+
+    /// 表示用の文字列を返す．
+    fn label(&self) -> String {
+        match (self.average(), self.worst()) {
+            (Some(average), Some(worst)) => format!(
+                "lag avg {:>3}ms max {:>3}ms",
+                average.as_millis(),
+                worst.as_millis()
+            ),
+            _ => "lag avg   -ms max   -ms".to_string(),
+        }
+    }
+}
+
+impl Drawable for LatencyMonitor {
+    fn region_size(&self) -> Movement {
+        let len = self.label().len();
+        right(len.div_ceil(2) as i8) + below(1)
+    }
+
+    fn draw<C: Canvas>(&self, canvas: &mut C) {
+        let color = canvas.theme().cell_color(ThemeColor::Text);
+        ColoredStr(self.label(), color).draw(canvas);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_no_samples() {
+        let monitor = LatencyMonitor::new(4);
+        assert_eq!(None, monitor.average());
+        assert_eq!(None, monitor.worst());
+    }
+
+    #[test]
+    fn test_correlate_inputs_with_drawn_frame() {
+        let base = Instant::now();
+        let mut monitor = LatencyMonitor::new(4);
+
+        // 同じフレームで処理された2つの入力
+        monitor.record_input(base);
+        monitor.record_input(base + ms(10));
+        monitor.record_drawn(base + ms(30));
+        assert_eq!(Some(ms(25)), monitor.average());
+        assert_eq!(Some(ms(30)), monitor.worst());
+
+        // 入力のないフレームの表示は統計を変えない
+        monitor.record_drawn(base + ms(80));
+        assert_eq!(Some(ms(25)), monitor.average());
+
+        monitor.record_input(base + ms(100));
+        monitor.record_drawn(base + ms(170));
+        assert_eq!(Some(ms(40)), monitor.average());
+        assert_eq!(Some(ms(70)), monitor.worst());
+    }
+
+    #[test]
+    fn test_rolling_window() {
+        let base = Instant::now();
+        let mut monitor = LatencyMonitor::new(2);
+
+        for &latency in [100, 10, 20].iter() {
+            monitor.record_input(base);
+            monitor.record_drawn(base + ms(latency));
+        }
+        // 最も古い100msは統計から外れる
+        assert_eq!(Some(ms(15)), monitor.average());
+        assert_eq!(Some(ms(20)), monitor.worst());
+    }
+
+    #[test]
+    fn test_drawn_before_pressed() {
+        let base = Instant::now();
+        let mut monitor = LatencyMonitor::new(2);
+
+        monitor.record_input(base + ms(10));
+        monitor.record_drawn(base);
+        assert_eq!(Some(ms(0)), monitor.worst());
+    }
+
+    #[test]
+    fn test_label() {
+        let base = Instant::now();
+        let mut monitor = LatencyMonitor::new(2);
+        assert_eq!("lag avg   -ms max   -ms", monitor.label());
+
+        monitor.record_input(base);
+        monitor.record_drawn(base + ms(12));
+        monitor.record_input(base);
+        monitor.record_drawn(base + ms(40));
+        assert_eq!("lag avg  26ms max  40ms", monitor.label());
+    }
+}
@@ -0,0 +1,123 @@
+use super::{coalesce_commands, Clock, GameCommand, InputMapper, Key, SystemClock};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::Instant;
+
+/// 端末からのキー入力を受け付ける入力源を表す．
+///
+/// `console::Term::read_key()`は1回の呼び出しでひとつのキーしか返さないので，
+/// 別スレッドでキーを読み続け，読んだキーをバッファしておく．
+pub struct TerminalInputSource {
+    /// キー読み取りスレッドから送られてくるキーと，その押下時刻．
+    receiver: Receiver<(Key, Instant)>,
+}
+
+impl TerminalInputSource {
+    /// キー読み取りスレッドを起動し，入力源を返す．
+    pub fn spawn() -> TerminalInputSource {
+        Self::spawn_with_clock(SystemClock)
+    }
+
+    /// キー読み取りスレッドを起動し，入力源を返す．
+    /// キーの押下時刻は指定した時計で記録される．
+    pub fn spawn_with_clock<C>(clock: C) -> TerminalInputSource
+    where
+        C: Clock + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let terminal = console::Term::stdout();
+            while let Ok(key) = terminal.read_key() {
+                // 受信側が破棄されたら読み取りを終える
+                if sender.send((key, clock.now())).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self { receiver }
+    }
+
+    /// 現在バッファされているキーをすべて取り出し，ゲーム操作列に変換して返す．
+    /// このメソッドはブロックしない．
+    /// 返される操作列は`coalesce_commands`によりまとめられている．
+    pub fn read_available_keys<M>(&self, mapper: &M) -> Vec<GameCommand>
+    where
+        M: InputMapper<Command = GameCommand>,
+    {
+        self.read_available_keys_with_timestamps(mapper).0
+    }
+
+    /// `read_available_keys`と同様に操作列を返す．
+    /// さらに，操作に対応したキーそれぞれの押下時刻を入力順に返す．
+    pub fn read_available_keys_with_timestamps<M>(
+        &self,
+        mapper: &M,
+    ) -> (Vec<GameCommand>, Vec<Instant>)
+    where
+        M: InputMapper<Command = GameCommand>,
+    {
+        let (commands, timestamps): (Vec<_>, Vec<_>) = self
+            .drain_keys()
+            .into_iter()
+            .filter_map(|(key, pressed_at)| mapper.map(key).map(|c| (c, pressed_at)))
+            .unzip();
+        (coalesce_commands(commands), timestamps)
+    }
+
+    /// 現在バッファされているキーを，操作に変換せずにすべて取り出す．
+    /// このメソッドはブロックしない．
+    pub fn read_keys(&self) -> Vec<Key> {
+        self.drain_keys().into_iter().map(|(key, _)| key).collect()
+    }
+
+    /// 操作に対応するキーが少なくともひとつ入力されるまで待機し，
+    /// その後バッファされているキーをすべて取り出してゲーム操作列として返す．
+    /// # Returns
+    /// 空でない操作列．ただしキー読み取りスレッドが終了している場合は空の操作列を返す．
+    pub fn wait_available_keys<M>(&self, mapper: &M) -> Vec<GameCommand>
+    where
+        M: InputMapper<Command = GameCommand>,
+    {
+        self.wait_available_keys_with_timestamps(mapper).0
+    }
+
+    /// `wait_available_keys`と同様に操作列を返す．
+    /// さらに，操作に対応したキーそれぞれの押下時刻を入力順に返す．
+    pub fn wait_available_keys_with_timestamps<M>(
+        &self,
+        mapper: &M,
+    ) -> (Vec<GameCommand>, Vec<Instant>)
+    where
+        M: InputMapper<Command = GameCommand>,
+    {
+        loop {
+            let mut keys = match self.receiver.recv() {
+                Ok(key) => vec![key],
+                Err(_) => return (vec![], vec![]),
+            };
+            keys.append(&mut self.drain_keys());
+
+            let (commands, timestamps): (Vec<_>, Vec<_>) = keys
+                .into_iter()
+                .filter_map(|(key, pressed_at)| mapper.map(key).map(|c| (c, pressed_at)))
+                .unzip();
+            let commands = coalesce_commands(commands);
+            if !commands.is_empty() {
+                break (commands, timestamps);
+            }
+        }
+    }
+
+    /// 現在バッファされているキーをすべて取り出す．
+    fn drain_keys(&self) -> Vec<(Key, Instant)> {
+        let mut keys = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(key) => keys.push(key),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break keys,
+            }
+        }
+    }
+}
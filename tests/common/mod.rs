@@ -0,0 +1,37 @@
+use rustetris::prelude::*;
+
+/// 端末に出力せず，フレーム間の待機もしない描画機能．
+/// 表示した回数を数える．
+pub struct HeadlessDrawer {
+    pub canvas: RootCanvas,
+    /// `show`を呼んだ回数．
+    pub show_count: usize,
+}
+
+impl HeadlessDrawer {
+    /// 最小の大きさのキャンバスに描画する描画機能を返す．
+    pub fn new() -> HeadlessDrawer {
+        Self {
+            canvas: RootCanvas::new(),
+            show_count: 0,
+        }
+    }
+}
+
+impl Drawer for HeadlessDrawer {
+    type Canvas = RootCanvas;
+
+    fn canvas_mut(&mut self) -> &mut Self::Canvas {
+        &mut self.canvas
+    }
+
+    fn clear(&mut self) {
+        self.canvas.clear();
+    }
+
+    fn show(&mut self) {
+        self.show_count += 1;
+    }
+
+    fn wait_frame(&mut self) {}
+}
@@ -0,0 +1,354 @@
+mod common;
+
+use common::HeadlessDrawer;
+use rustetris::game::single_play::{
+    execute_agent_game_with, execute_game_with, execute_game_with_rule, execute_gravity_game_with,
+    run, GameEvent, GameResult, LineClearRule,
+};
+use rustetris::game::{Gravity, RandomBlockSelector, WeightedShapeSelector};
+use rustetris::prelude::*;
+use rustetris::user::{GreedyAgent, MenuCommand};
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+/// 1セルのブロックだけを，指定した順のボム指定で生成するブロック生成ルール．
+/// 指定を使い切った後は，ボムを含まないブロックを生成する．
+struct ScriptedSelector {
+    bombs: VecDeque<BombTag>,
+}
+
+impl ScriptedSelector {
+    /// 設置される順にボム指定を並べた生成ルールを返す．
+    fn new(bombs: Vec<BombTag>) -> ScriptedSelector {
+        let mut bombs = bombs.into_iter().collect::<VecDeque<_>>();
+        // 3番目に生成されるブロックはHoldブロックになるので，ボムを含まないブロックを差し込む
+        if bombs.len() >= 2 {
+            bombs.insert(2, BombTag::None);
+        }
+        Self { bombs }
+    }
+}
+
+impl BlockSelector for ScriptedSelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        SingleBlockShape::O.into()
+    }
+
+    fn select_bomb(&mut self, _: BlockShape) -> BombTag {
+        self.bombs.pop_front().unwrap_or(BombTag::None)
+    }
+}
+
+/// 出現位置(x=5)にあるブロックを指定した列まで動かし，落とす操作列を返す．
+fn drop_at(x: i8) -> Vec<GameCommand> {
+    let shift = x - 5;
+    let command = if shift < 0 {
+        GameCommand::Left
+    } else {
+        GameCommand::Right
+    };
+    let mut commands = vec![command; shift.unsigned_abs() as usize];
+    commands.push(GameCommand::Drop);
+    commands
+}
+
+/// 指定した列へ順にブロックを落とすゲームを，入力が尽きるまで実行する．
+fn play(bombs: Vec<BombTag>, columns: &[i8]) -> (GameResult, Vec<GameEvent>) {
+    let mut inputs = columns.iter().map(|&x| drop_at(x)).collect::<VecDeque<_>>();
+    let mut drawer = HeadlessDrawer::new();
+    let mut events = vec![];
+
+    let result = execute_game_with(
+        ScriptedSelector::new(bombs),
+        || inputs.pop_front().unwrap_or_default(),
+        &mut drawer,
+        |event| events.push(event),
+    );
+
+    (result, events)
+}
+
+/// 指定したボム指定を，指定した位置に並べたボム指定列を返す．
+/// その他のブロックはボムを含まない．
+fn bombs_at(count: usize, bombs: &[(usize, BombTag)]) -> Vec<BombTag> {
+    let mut tags = vec![BombTag::None; count];
+    for &(i, tag) in bombs {
+        tags[i] = tag;
+    }
+    tags
+}
+
+fn placed(count: usize) -> Vec<GameEvent> {
+    vec![GameEvent::BlockPlaced; count]
+}
+
+#[test]
+fn test_single_bomb_clears_row() {
+    let columns = [0, 1, 2, 3, 5, 6, 7, 8, 9, 4];
+    let bombs = bombs_at(columns.len(), &[(9, BombTag::Single(0))]);
+    let (result, events) = play(bombs, &columns);
+
+    let expected = Field::from_str_art("#.......##").unwrap();
+    assert_eq!(expected.to_str_art(), result.field.to_str_art());
+
+    let mut expected_events = placed(10);
+    expected_events.push(GameEvent::RowsFilled(vec![PosY::below(19)]));
+    expected_events.push(GameEvent::Exploded { chain: 0, cells: 7 });
+    assert_eq!(expected_events, events);
+    assert_eq!(1, result.clear_statistics.counts()[19]);
+}
+
+#[test]
+fn test_two_chain() {
+    // 最下段の左半分(x=1がボム)，その上の左半分(x=2がボム)，最下段の右半分の順に置く
+    let columns = [0, 1, 2, 3, 4, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    let bombs = bombs_at(
+        columns.len(),
+        &[(1, BombTag::Single(0)), (7, BombTag::Single(0))],
+    );
+    let (result, events) = play(bombs, &columns);
+
+    let expected = Field::from_str_art("......####").unwrap();
+    assert_eq!(expected.to_str_art(), result.field.to_str_art());
+
+    let mut expected_events = placed(15);
+    expected_events.push(GameEvent::RowsFilled(vec![PosY::below(19)]));
+    expected_events.push(GameEvent::Exploded { chain: 0, cells: 5 });
+    expected_events.push(GameEvent::RowsFilled(vec![PosY::below(19)]));
+    expected_events.push(GameEvent::Exploded { chain: 1, cells: 6 });
+    assert_eq!(expected_events, events);
+    assert_eq!(1, result.max_chain);
+}
+
+#[test]
+fn test_classic_rule_clears_row_without_explosion() {
+    // 左端に2つ積んでから最下段を埋める．最下段にはボムもあるが，爆発せずにラインごと消える
+    let columns = [0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+    let bombs = bombs_at(columns.len(), &[(4, BombTag::Single(0))]);
+    let mut inputs = columns.iter().map(|&x| drop_at(x)).collect::<VecDeque<_>>();
+    let mut drawer = HeadlessDrawer::new();
+    let mut events = vec![];
+
+    let result = execute_game_with_rule(
+        ScriptedSelector::new(bombs),
+        LineClearRule::Classic,
+        || inputs.pop_front().unwrap_or_default(),
+        &mut drawer,
+        |event| events.push(event),
+    );
+
+    // 消えたラインの上にあったセルが1ライン下へずれる
+    let expected = Field::from_str_art("#.........").unwrap();
+    assert_eq!(expected.to_str_art(), result.field.to_str_art());
+
+    let mut expected_events = placed(11);
+    expected_events.push(GameEvent::RowsFilled(vec![PosY::below(19)]));
+    assert_eq!(expected_events, events);
+}
+
+#[test]
+fn test_big_bomb_formation_and_detonation() {
+    // 左下の2x2にボムを置いてデカボムを作り，その右を2ライン埋める
+    let mut columns = vec![0, 1, 0, 1];
+    columns.extend(2..10);
+    columns.extend(2..10);
+    let bombs = bombs_at(
+        columns.len(),
+        &[
+            (0, BombTag::All),
+            (1, BombTag::All),
+            (2, BombTag::All),
+            (3, BombTag::All),
+        ],
+    );
+    let (result, events) = play(bombs, &columns);
+
+    let expected = Field::from_str_art(
+        "
+        .....#####
+        .....#####
+        ",
+    )
+    .unwrap();
+    assert_eq!(expected.to_str_art(), result.field.to_str_art());
+
+    let mut expected_events = placed(4);
+    expected_events.push(GameEvent::BigBombFormed(1));
+    expected_events.append(&mut placed(8));
+    // デカボムの下半分は爆発しないので，ラインが揃っても消えない
+    expected_events.push(GameEvent::RowsFilled(vec![PosY::below(19)]));
+    expected_events.append(&mut placed(8));
+    expected_events.push(GameEvent::RowsFilled(vec![
+        PosY::below(18),
+        PosY::below(19),
+    ]));
+    expected_events.push(GameEvent::Exploded {
+        chain: 0,
+        cells: 10,
+    });
+    assert_eq!(expected_events, events);
+}
+
+#[test]
+fn test_top_out() {
+    // 出現位置に落とし続ける
+    let columns = [5; 100];
+    let (result, events) = play(vec![], &columns);
+
+    // 出現位置の候補である3列が埋まったら終了
+    for row in result.field.to_str_art().lines() {
+        assert_eq!("....###...", row);
+    }
+    let mut expected_events = placed(60);
+    expected_events.push(GameEvent::ToppedOut);
+    assert_eq!(expected_events, events);
+}
+
+/// 自動落下のあるゲームを実行し，最初のブロックが設置されるまでのフレーム数を返す．
+/// `down_every_frame`が`true`の場合，毎フレーム`Down`を1回入力する．
+fn frames_until_first_placement(interval: usize, down_every_frame: bool) -> usize {
+    let frames = Cell::new(0);
+    let first_placement = Cell::new(None);
+    let mut drawer = HeadlessDrawer::new();
+    // 各フレームの入力は，Down(指定した場合)の後にNoneを返す
+    let mut pending_down = down_every_frame;
+
+    execute_gravity_game_with(
+        ScriptedSelector::new(vec![]),
+        || {
+            if pending_down {
+                pending_down = false;
+                Some(GameCommand::Down)
+            } else {
+                pending_down = down_every_frame;
+                frames.set(frames.get() + 1);
+                None
+            }
+        },
+        Gravity::new(interval),
+        &mut drawer,
+        |event| {
+            if event == GameEvent::BlockPlaced && first_placement.get().is_none() {
+                first_placement.set(Some(frames.get()));
+            }
+        },
+    );
+
+    first_placement.get().unwrap()
+}
+
+#[test]
+fn test_gravity_without_input() {
+    // 最上段から最下段まで19マス落ち，次の落下で設置される
+    assert_eq!(20 * 2, frames_until_first_placement(2, false));
+}
+
+#[test]
+fn test_gravity_does_not_double_move_with_down() {
+    // 毎フレームDownを入力しても，自動落下と合わせて2マス落ちることはない
+    // 設置が確定するフレームでは，入力したDownで設置されるので自動落下までのフレームを数えない
+    assert_eq!(19, frames_until_first_placement(1, true));
+}
+
+#[test]
+fn test_gravity_tops_out() {
+    let mut drawer = HeadlessDrawer::new();
+    let mut events = vec![];
+    execute_gravity_game_with(
+        ScriptedSelector::new(vec![]),
+        || None,
+        Gravity::new(1),
+        &mut drawer,
+        |event| events.push(event),
+    );
+
+    let mut expected_events = placed(60);
+    expected_events.push(GameEvent::ToppedOut);
+    assert_eq!(expected_events, events);
+}
+
+#[test]
+fn test_restart_after_top_out() {
+    let mut drawer = HeadlessDrawer::new();
+    let mut events = vec![];
+    // 1回目のゲームオーバー後はやり直し，2回目の後はやめる
+    let mut menu_inputs = vec![MenuCommand::Down, MenuCommand::Proceed, MenuCommand::Back]
+        .into_iter()
+        .collect::<VecDeque<_>>();
+
+    let results = run(
+        || ScriptedSelector::new(bombs_at(4, &[(0, BombTag::Single(0))])),
+        |selector, drawer| {
+            let mut inputs = vec![drop_at(5); 100].into_iter().collect::<VecDeque<_>>();
+            let mut game_events = vec![];
+            let result = execute_game_with(
+                selector,
+                || inputs.pop_front().unwrap_or_default(),
+                drawer,
+                |event| game_events.push(event),
+            );
+            events.push(game_events);
+            result
+        },
+        || menu_inputs.pop_front(),
+        &mut drawer,
+    );
+
+    // やり直したゲームも，フィールドや生成ルールをはじめからやり直すので同じ結果になる
+    assert_eq!(2, results.len());
+    assert_eq!(results[0], results[1]);
+    assert_eq!(events[0], events[1]);
+    assert_eq!(Some(&GameEvent::ToppedOut), events[0].last());
+    assert!(menu_inputs.is_empty());
+
+    // 最後に描画されるのは2回目のゲームの結果
+    let mut buffer = String::new();
+    drawer.canvas.construct_plain_string(&mut buffer);
+    assert!(buffer.contains(&format!("Final score: {}", results[1].score.points())));
+}
+
+#[test]
+fn test_greedy_agent_plays_50_placements() {
+    let mut drawer = HeadlessDrawer::new();
+    let placements = Cell::new(0);
+
+    // ブロックを置けなくなったら，50個置くまでやり直す
+    let results = run(
+        || RandomBlockSelector::with_seed(0),
+        |selector, drawer| {
+            execute_agent_game_with(selector, GreedyAgent, drawer, |event| {
+                if event == GameEvent::BlockPlaced {
+                    placements.set(placements.get() + 1);
+                }
+            })
+        },
+        || {
+            if placements.get() < 50 {
+                Some(MenuCommand::Proceed)
+            } else {
+                Some(MenuCommand::Back)
+            }
+        },
+        &mut drawer,
+    );
+
+    assert!(placements.get() >= 50);
+    assert!(!results.is_empty());
+}
+
+#[test]
+fn test_weighted_shape_selector_as_generator() {
+    let mut drawer = HeadlessDrawer::new();
+    let mut events = vec![];
+
+    // 大きさの異なるブロックを，出現位置に落とし続ける
+    execute_game_with(
+        WeightedShapeSelector::chaos(7),
+        || vec![GameCommand::Drop],
+        &mut drawer,
+        |event| events.push(event),
+    );
+
+    assert_eq!(Some(&GameEvent::ToppedOut), events.last());
+    assert!(events.contains(&GameEvent::BlockPlaced));
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use common::HeadlessDrawer;
+use rustetris::prelude::*;
+
+/// Tブロックだけを生成し続けるブロック生成ルール．
+struct TOnlySelector;
+
+impl BlockSelector for TOnlySelector {
+    fn select_block_shape(&mut self) -> BlockShape {
+        QuadrupleBlockShape::T.into()
+    }
+
+    fn select_bomb(&mut self, _: BlockShape) -> BombTag {
+        BombTag::Single(0)
+    }
+}
+
+#[test]
+fn test_play_with_prelude_only() {
+    let mut selector = TOnlySelector;
+    let field = Field::empty();
+    let queue = BlockQueue::new(&mut selector);
+    let agent_field = FieldUnderAgentControl::new(field, queue, &mut selector).unwrap();
+
+    // 描画できる
+    let mut drawer = HeadlessDrawer::new();
+    drawer.clear();
+    agent_field.draw(drawer.canvas_mut());
+    drawer.show();
+    assert_eq!(1, drawer.show_count);
+
+    // 操作できる
+    let agent_field = match agent_field.apply_command(GameCommand::Left) {
+        GameCommandResult::WaitNextCommand(agent_field) => agent_field,
+        GameCommandResult::ProceedAnimation(..) => panic!("block should not be placed yet"),
+        GameCommandResult::ToppedOut(_) => panic!("game should not top out"),
+        GameCommandResult::Quit(_) => panic!("game should not be quit"),
+    };
+    let field = match agent_field.apply_command(GameCommand::Drop) {
+        GameCommandResult::WaitNextCommand(_) => panic!("block should be placed"),
+        GameCommandResult::ProceedAnimation(field, _) => field,
+        GameCommandResult::ToppedOut(_) => panic!("game should not top out"),
+        GameCommandResult::Quit(_) => panic!("game should not be quit"),
+    };
+
+    // 最下段にブロックが設置されている
+    let bottom = Pos::origin() + below(field.height() as i8 - 1);
+    let bottom_row = field.row(bottom.y()).unwrap();
+    assert_eq!(3, bottom_row.iter().filter(|c| !c.is_empty()).count());
+    // 設置したブロックのボムセルはひとつだけ
+    let bomb_count = field
+        .rows()
+        .flat_map(|row| row.iter().copied().collect::<Vec<_>>())
+        .filter(|&c| c == Cell::Bomb)
+        .count();
+    assert_eq!(1, bomb_count);
+}